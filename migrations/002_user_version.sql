@@ -0,0 +1,3 @@
+-- Optimistic concurrency control for users
+-- Incremented on every update so clients can detect concurrent modifications
+ALTER TABLE users ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
@@ -0,0 +1,15 @@
+-- Full-text and fuzzy search over users
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Weighted document: name matches rank above email matches
+ALTER TABLE users ADD COLUMN search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(email, '')), 'B')
+    ) STORED;
+
+CREATE INDEX idx_users_search_vector ON users USING GIN (search_vector);
+
+-- Trigram indexes for partial and misspelled matches (similarity, ILIKE)
+CREATE INDEX idx_users_name_trgm ON users USING GIN (name gin_trgm_ops);
+CREATE INDEX idx_users_email_trgm ON users USING GIN (email gin_trgm_ops);
@@ -0,0 +1,5 @@
+-- Custom per-user attributes owned by product teams
+ALTER TABLE users ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+-- Containment filters (metadata @> '{"plan": "pro"}')
+CREATE INDEX idx_users_metadata ON users USING GIN (metadata jsonb_path_ops);
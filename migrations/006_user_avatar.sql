@@ -0,0 +1,3 @@
+-- Profile pictures stored in the configured blob store
+ALTER TABLE users ADD COLUMN avatar_key TEXT;
+ALTER TABLE users ADD COLUMN avatar_url TEXT;
@@ -0,0 +1,12 @@
+-- Record of GDPR erasure requests; the user row itself is anonymized, not deleted
+CREATE TABLE user_erasure_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reason TEXT,
+    -- Rows anonymized per data source
+    affected_rows JSONB NOT NULL DEFAULT '{}'::jsonb,
+    completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_user_erasure_requests_user_id ON user_erasure_requests(user_id);
@@ -0,0 +1,20 @@
+-- Email changes held until the new address confirms them
+CREATE TABLE user_email_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    old_email VARCHAR(255) NOT NULL,
+    new_email VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'confirmed', 'reverted', 'cancelled', 'expired')),
+    -- SHA-256 of the tokens mailed out; the tokens themselves are never stored
+    confirm_token_hash CHAR(64) NOT NULL UNIQUE,
+    revert_token_hash CHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revert_expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    confirmed_at TIMESTAMPTZ,
+    reverted_at TIMESTAMPTZ
+);
+
+-- At most one change in flight per user
+CREATE UNIQUE INDEX idx_user_email_changes_pending ON user_email_changes(user_id) WHERE status = 'pending';
@@ -0,0 +1,15 @@
+-- Responses to POST requests sent with an Idempotency-Key, replayed on retry
+CREATE TABLE idempotency_keys (
+    idempotency_key VARCHAR(255) PRIMARY KEY,
+    -- SHA-256 of the method, path and body of the first request under the key
+    fingerprint CHAR(64) NOT NULL,
+    -- NULL while the first request is still being processed
+    response_status SMALLINT,
+    response_headers JSONB,
+    response_body BYTEA,
+    locked_until TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
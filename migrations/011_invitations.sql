@@ -0,0 +1,30 @@
+-- Invitations to join, accepted by the invitee setting a password
+CREATE TABLE invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    email VARCHAR(255) NOT NULL,
+    name VARCHAR(255),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'expired', 'revoked')),
+    -- SHA-256 of the token mailed out; the token itself is never stored
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- The account created when the invitation was accepted
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    accepted_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+-- At most one open invitation per address
+CREATE UNIQUE INDEX idx_invitations_pending_email ON invitations(LOWER(email)) WHERE status = 'pending';
+CREATE INDEX idx_invitations_created_at ON invitations(created_at);
+
+-- Groups the invitee joins on acceptance
+CREATE TABLE invitation_groups (
+    invitation_id UUID NOT NULL REFERENCES invitations(id) ON DELETE CASCADE,
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    role VARCHAR(20) NOT NULL DEFAULT 'member'
+        CHECK (role IN ('owner', 'member')),
+    PRIMARY KEY (invitation_id, group_id)
+);
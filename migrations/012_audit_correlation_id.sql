@@ -0,0 +1,4 @@
+-- Correlation ID of the request that caused each audited change
+ALTER TABLE audit_logs ADD COLUMN correlation_id VARCHAR(64);
+
+CREATE INDEX idx_audit_logs_correlation_id ON audit_logs(correlation_id);
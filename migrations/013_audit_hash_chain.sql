@@ -0,0 +1,29 @@
+-- Tamper-evident hash chain over audit_logs
+-- Rows written before this migration are left out of the chain
+ALTER TABLE audit_logs
+    ADD COLUMN sequence BIGINT UNIQUE,
+    ADD COLUMN metadata_hash CHAR(64),
+    ADD COLUMN previous_hash CHAR(64),
+    ADD COLUMN hash CHAR(64),
+    ADD COLUMN redacted_at TIMESTAMPTZ;
+
+-- End of the chain; every audit write locks this single row, which
+-- serializes sequence numbers
+CREATE TABLE audit_chain_head (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    sequence BIGINT NOT NULL,
+    hash CHAR(64) NOT NULL
+);
+
+INSERT INTO audit_chain_head (sequence, hash) VALUES (0, REPEAT('0', 64));
+
+-- Signed snapshots of the chain head
+CREATE TABLE audit_checkpoints (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    sequence BIGINT NOT NULL,
+    hash CHAR(64) NOT NULL,
+    signature CHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_checkpoints_sequence ON audit_checkpoints(sequence);
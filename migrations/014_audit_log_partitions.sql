@@ -0,0 +1,74 @@
+-- Range-partition audit_logs by month of created_at
+-- Rebuilds the table, so existing entries are copied into monthly partitions
+ALTER TABLE audit_logs RENAME TO audit_logs_unpartitioned;
+ALTER INDEX audit_logs_pkey RENAME TO audit_logs_unpartitioned_pkey;
+ALTER INDEX audit_logs_sequence_key RENAME TO audit_logs_unpartitioned_sequence_key;
+DROP INDEX idx_audit_logs_user_id;
+DROP INDEX idx_audit_logs_created_at;
+DROP INDEX idx_audit_logs_correlation_id;
+
+CREATE TABLE audit_logs (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users(id),
+    action VARCHAR(100) NOT NULL,
+    resource_type VARCHAR(100) NOT NULL,
+    resource_id VARCHAR(255),
+    metadata JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    correlation_id VARCHAR(64),
+    sequence BIGINT,
+    metadata_hash CHAR(64),
+    previous_hash CHAR(64),
+    hash CHAR(64),
+    redacted_at TIMESTAMPTZ,
+    -- Unique constraints on a partitioned table must include the partition key
+    PRIMARY KEY (id, created_at)
+) PARTITION BY RANGE (created_at);
+
+-- One partition per month from the oldest entry to three months ahead;
+-- the application creates later ones as time passes
+DO $$
+DECLARE
+    month TIMESTAMP := date_trunc('month', COALESCE(
+        (SELECT MIN(created_at) FROM audit_logs_unpartitioned), NOW()
+    ) AT TIME ZONE 'UTC');
+    last_month TIMESTAMP := date_trunc('month', NOW() AT TIME ZONE 'UTC') + INTERVAL '3 months';
+BEGIN
+    WHILE month <= last_month LOOP
+        EXECUTE format(
+            'CREATE TABLE %I PARTITION OF audit_logs FOR VALUES FROM (%L) TO (%L)',
+            'audit_logs_' || to_char(month, 'YYYY_MM'),
+            month AT TIME ZONE 'UTC',
+            (month + INTERVAL '1 month') AT TIME ZONE 'UTC'
+        );
+        month := month + INTERVAL '1 month';
+    END LOOP;
+END $$;
+
+INSERT INTO audit_logs (id, user_id, action, resource_type, resource_id, metadata, created_at, correlation_id,
+                        sequence, metadata_hash, previous_hash, hash, redacted_at)
+SELECT id, user_id, action, resource_type, resource_id, metadata, COALESCE(created_at, NOW()), correlation_id,
+       sequence, metadata_hash, previous_hash, hash, redacted_at
+FROM audit_logs_unpartitioned;
+
+DROP TABLE audit_logs_unpartitioned;
+
+CREATE INDEX idx_audit_logs_user_id ON audit_logs(user_id);
+CREATE INDEX idx_audit_logs_created_at ON audit_logs(created_at);
+CREATE INDEX idx_audit_logs_correlation_id ON audit_logs(correlation_id);
+-- Sequence numbers stay unique through the audit_chain_head lock
+CREATE INDEX idx_audit_logs_sequence ON audit_logs(sequence);
+
+-- Partitions detached by retention, and where the hash chain stood at their end
+CREATE TABLE audit_log_archives (
+    partition_name VARCHAR(63) PRIMARY KEY,
+    range_start TIMESTAMPTZ NOT NULL,
+    range_end TIMESTAMPTZ NOT NULL,
+    entries BIGINT NOT NULL,
+    last_sequence BIGINT,
+    last_hash CHAR(64),
+    archive_prefix VARCHAR(255),
+    archive_parts INTEGER,
+    detached_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    dropped_at TIMESTAMPTZ
+);
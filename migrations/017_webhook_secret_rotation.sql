@@ -0,0 +1,4 @@
+-- Secret replaced by a rotation, which keeps signing deliveries until it expires
+ALTER TABLE webhook_subscriptions
+    ADD COLUMN previous_secret VARCHAR(255),
+    ADD COLUMN previous_secret_expires_at TIMESTAMPTZ;
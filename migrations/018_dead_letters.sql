@@ -0,0 +1,20 @@
+-- Events whose delivery was given up on, kept for inspection and replay
+CREATE TABLE dead_letters (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID NOT NULL,
+    event_type VARCHAR(100) NOT NULL,
+    -- Subscription that did not accept the event; NULL when it could not be dispatched at all
+    subscription_id UUID REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    response_status INTEGER,
+    response_body TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_dead_letters_event_subscription
+    ON dead_letters(event_id, COALESCE(subscription_id, '00000000-0000-0000-0000-000000000000'));
+CREATE INDEX idx_dead_letters_subscription_id ON dead_letters(subscription_id);
+CREATE INDEX idx_dead_letters_created_at ON dead_letters(created_at);
@@ -0,0 +1,7 @@
+-- Last sign of life of an unfinished import job, so jobs whose instance
+-- stopped without finishing them can be marked failed
+ALTER TABLE user_import_jobs
+    ADD COLUMN heartbeat_at TIMESTAMPTZ;
+
+CREATE INDEX idx_user_import_jobs_unfinished ON user_import_jobs(status)
+    WHERE status IN ('queued', 'running');
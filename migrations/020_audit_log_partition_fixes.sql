@@ -0,0 +1,24 @@
+-- Restore what partitioning audit_logs left behind, and catch entries that
+-- fall outside the monthly partitions
+CREATE INDEX idx_audit_logs_resource ON audit_logs(resource_type, resource_id);
+
+-- Entries with no monthly partition land here instead of failing; the
+-- partition maintenance task reports them and moves them into the monthly
+-- partition once it is created
+CREATE TABLE audit_logs_default PARTITION OF audit_logs DEFAULT;
+
+-- Unique indexes on audit_logs itself would have to include created_at, so
+-- each partition gets its own unique index on the sequence number instead
+DO $$
+DECLARE
+    partition_name TEXT;
+BEGIN
+    FOR partition_name IN
+        SELECT child.relname
+        FROM pg_inherits
+        JOIN pg_class child ON child.oid = pg_inherits.inhrelid
+        WHERE pg_inherits.inhparent = 'audit_logs'::regclass
+    LOOP
+        EXECUTE format('CREATE UNIQUE INDEX %I ON %I (sequence)', partition_name || '_sequence_key', partition_name);
+    END LOOP;
+END $$;
@@ -0,0 +1,7 @@
+-- Keys are scoped to the caller that sent them, so clients cannot collide or
+-- read each other's responses by reusing a key
+ALTER TABLE idempotency_keys ADD COLUMN scope VARCHAR(100) NOT NULL DEFAULT 'anonymous';
+ALTER TABLE idempotency_keys ALTER COLUMN scope DROP DEFAULT;
+
+ALTER TABLE idempotency_keys DROP CONSTRAINT idempotency_keys_pkey;
+ALTER TABLE idempotency_keys ADD PRIMARY KEY (scope, idempotency_key);
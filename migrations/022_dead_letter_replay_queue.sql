@@ -0,0 +1,8 @@
+-- Bulk replays queue dead letters for a background task instead of
+-- redelivering them within the request
+ALTER TABLE dead_letters ADD COLUMN replay_requested_at TIMESTAMPTZ;
+-- Hides a queued letter from other instances while it is being replayed
+ALTER TABLE dead_letters ADD COLUMN replay_locked_until TIMESTAMPTZ;
+
+CREATE INDEX idx_dead_letters_replay_requested_at ON dead_letters(replay_requested_at)
+    WHERE replay_requested_at IS NOT NULL;
@@ -18,6 +18,34 @@ pub enum ConfigValidationError {
     Vault(String),
     #[error("Invalid external service configuration: {0}")]
     ExternalService(String),
+    #[error("Invalid cache configuration: {0}")]
+    Cache(String),
+    #[error("Invalid user metadata configuration: {0}")]
+    UserMetadata(String),
+    #[error("Invalid user import configuration: {0}")]
+    UserImport(String),
+    #[error("Invalid avatar configuration: {0}")]
+    Avatar(String),
+    #[error("Invalid mailer configuration: {0}")]
+    Mailer(String),
+    #[error("Invalid email change configuration: {0}")]
+    EmailChange(String),
+    #[error("Invalid idempotency configuration: {0}")]
+    Idempotency(String),
+    #[error("Invalid invitation configuration: {0}")]
+    Invitation(String),
+    #[error("Invalid audit configuration: {0}")]
+    Audit(String),
+    #[error("Invalid outbox configuration: {0}")]
+    Outbox(String),
+    #[error("Invalid webhook configuration: {0}")]
+    Webhook(String),
+    #[error("Invalid event bus configuration: {0}")]
+    EventBus(String),
+    #[error("Invalid event stream configuration: {0}")]
+    EventStream(String),
+    #[error("Invalid WebSocket configuration: {0}")]
+    WebSocket(String),
 }
 
 /// Main application configuration
@@ -30,6 +58,36 @@ pub struct AppConfig {
     pub vault: Option<VaultConfig>,
     pub external_service: ExternalServiceConfig,
     #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub admin: AdminConfig,
+    #[serde(default)]
+    pub user_metadata: UserMetadataConfig,
+    #[serde(default)]
+    pub user_import: UserImportConfig,
+    #[serde(default)]
+    pub avatar: AvatarConfig,
+    #[serde(default)]
+    pub mailer: MailerConfig,
+    #[serde(default)]
+    pub email_change: EmailChangeConfig,
+    #[serde(default)]
+    pub idempotency: IdempotencyConfig,
+    #[serde(default)]
+    pub invitation: InvitationConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub outbox: OutboxConfig,
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    #[serde(default)]
+    pub event_bus: EventBusConfig,
+    #[serde(default)]
+    pub event_stream: EventStreamConfig,
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
+    #[serde(default)]
     pub environment: String,
 }
 
@@ -41,6 +99,20 @@ impl AppConfig {
         self.logging.validate()?;
         self.sentry.validate()?;
         self.external_service.validate()?;
+        self.cache.validate()?;
+        self.user_metadata.validate()?;
+        self.user_import.validate()?;
+        self.avatar.validate()?;
+        self.mailer.validate()?;
+        self.email_change.validate()?;
+        self.idempotency.validate()?;
+        self.invitation.validate()?;
+        self.audit.validate()?;
+        self.outbox.validate()?;
+        self.webhooks.validate()?;
+        self.event_bus.validate()?;
+        self.event_stream.validate()?;
+        self.websocket.validate()?;
 
         if let Some(vault) = &self.vault {
             vault.validate()?;
@@ -78,6 +150,9 @@ pub struct ServerConfig {
     pub connection_drain_timeout_seconds: u64,
     #[serde(default = "default_resource_cleanup_timeout")]
     pub resource_cleanup_timeout_seconds: u64,
+    /// Reject updates and deletes that do not send an `If-Match` header
+    #[serde(default)]
+    pub require_if_match: bool,
 }
 
 impl ServerConfig {
@@ -479,6 +554,1023 @@ impl Default for ExternalServiceConfig {
     }
 }
 
+/// Administrator access configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdminConfig {
+    /// Group whose members may use the administrative endpoints; nobody may when unset
+    #[serde(default)]
+    pub group_id: Option<uuid::Uuid>,
+}
+
+/// HTTP caching configuration for read endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    #[serde(default = "default_cache_enabled")]
+    pub enabled: bool,
+    /// `Cache-Control` sent when no route policy matches
+    #[serde(default = "default_cache_control")]
+    pub default_cache_control: String,
+    /// Per-route `Cache-Control` policies, keyed by route pattern
+    #[serde(default)]
+    pub routes: Vec<RouteCacheConfig>,
+    /// Largest response body that will be hashed for an `ETag`
+    #[serde(default = "default_cache_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+/// `Cache-Control` policy for a single route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteCacheConfig {
+    /// Route pattern as registered with the router, e.g. `/api/v1/users/:id`
+    pub path: String,
+    pub cache_control: String,
+}
+
+impl CacheConfig {
+    /// Validate cache configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.max_body_bytes == 0 {
+            return Err(ConfigValidationError::Cache("Max body bytes must be greater than 0".to_string()));
+        }
+
+        let policies = std::iter::once(&self.default_cache_control)
+            .chain(self.routes.iter().map(|route| &route.cache_control));
+        for policy in policies {
+            if policy.trim().is_empty() || !policy.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+                return Err(ConfigValidationError::Cache(format!("Invalid Cache-Control policy: {:?}", policy)));
+            }
+        }
+
+        if let Some(route) = self.routes.iter().find(|route| !route.path.starts_with('/')) {
+            return Err(ConfigValidationError::Cache(format!("Route path must start with '/': {}", route.path)));
+        }
+
+        Ok(())
+    }
+
+    /// Get the `Cache-Control` policy for a route pattern
+    pub fn cache_control_for(&self, path: &str) -> &str {
+        self.routes
+            .iter()
+            .find(|route| route.path == path)
+            .map(|route| route.cache_control.as_str())
+            .unwrap_or(&self.default_cache_control)
+    }
+}
+
+fn default_cache_enabled() -> bool {
+    true
+}
+
+fn default_cache_control() -> String {
+    "private, no-cache".to_string()
+}
+
+fn default_cache_max_body_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_cache_enabled(),
+            default_cache_control: default_cache_control(),
+            routes: Vec::new(),
+            max_body_bytes: default_cache_max_body_bytes(),
+        }
+    }
+}
+
+/// Custom per-user metadata configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserMetadataConfig {
+    /// Path to a JSON Schema file that user metadata must satisfy
+    #[serde(default)]
+    pub schema_path: Option<String>,
+}
+
+impl UserMetadataConfig {
+    /// Validate user metadata configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if let Some(path) = &self.schema_path {
+            if path.trim().is_empty() {
+                return Err(ConfigValidationError::UserMetadata("Schema path cannot be empty".to_string()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Bulk user import configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserImportConfig {
+    /// Rows written per database transaction
+    #[serde(default = "default_import_batch_size")]
+    pub batch_size: usize,
+    /// Imports with more rows than this run as background jobs
+    #[serde(default = "default_import_background_threshold")]
+    pub background_threshold_rows: usize,
+    /// Largest number of rows accepted in one file
+    #[serde(default = "default_import_max_rows")]
+    pub max_rows: usize,
+    /// Largest upload accepted, in bytes
+    #[serde(default = "default_import_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Longest an import below the threshold is waited for before the
+    /// request is answered with the job to poll; must stay under the 30
+    /// second request timeout
+    #[serde(default = "default_import_inline_timeout")]
+    pub inline_timeout_seconds: u64,
+    /// Unfinished jobs without progress for this long are marked failed at startup
+    #[serde(default = "default_import_stale_job_minutes")]
+    pub stale_job_minutes: u64,
+}
+
+impl UserImportConfig {
+    /// Validate user import configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.batch_size == 0 {
+            return Err(ConfigValidationError::UserImport("Batch size must be greater than 0".to_string()));
+        }
+
+        if self.max_rows == 0 {
+            return Err(ConfigValidationError::UserImport("Max rows must be greater than 0".to_string()));
+        }
+
+        if self.max_body_bytes == 0 {
+            return Err(ConfigValidationError::UserImport("Max body bytes must be greater than 0".to_string()));
+        }
+
+        if self.inline_timeout_seconds == 0 || self.inline_timeout_seconds >= 30 {
+            return Err(ConfigValidationError::UserImport(
+                "Inline timeout must be between 1 and 29 seconds".to_string(),
+            ));
+        }
+
+        if self.stale_job_minutes == 0 {
+            return Err(ConfigValidationError::UserImport("Stale job minutes must be greater than 0".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_import_batch_size() -> usize {
+    500
+}
+
+fn default_import_background_threshold() -> usize {
+    1000
+}
+
+fn default_import_max_rows() -> usize {
+    100_000
+}
+
+fn default_import_max_body_bytes() -> usize {
+    32 * 1024 * 1024
+}
+
+fn default_import_inline_timeout() -> u64 {
+    20
+}
+
+fn default_import_stale_job_minutes() -> u64 {
+    15
+}
+
+impl Default for UserImportConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: default_import_batch_size(),
+            background_threshold_rows: default_import_background_threshold(),
+            max_rows: default_import_max_rows(),
+            max_body_bytes: default_import_max_body_bytes(),
+            inline_timeout_seconds: default_import_inline_timeout(),
+            stale_job_minutes: default_import_stale_job_minutes(),
+        }
+    }
+}
+
+/// User avatar configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvatarConfig {
+    /// Largest accepted image, in bytes
+    #[serde(default = "default_avatar_max_bytes")]
+    pub max_bytes: usize,
+    /// Smallest accepted width and height, in pixels
+    #[serde(default = "default_avatar_min_dimension")]
+    pub min_dimension: u32,
+    /// Largest accepted width and height, in pixels
+    #[serde(default = "default_avatar_max_dimension")]
+    pub max_dimension: u32,
+    #[serde(default)]
+    pub storage: BlobStoreConfig,
+}
+
+/// Where uploaded files are stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum BlobStoreConfig {
+    /// Files on the local filesystem, served by the API under `public_base_url`
+    Local {
+        root: String,
+        #[serde(default = "default_local_public_base_url")]
+        public_base_url: String,
+    },
+    /// An S3-compatible object store such as AWS S3 or MinIO
+    S3 {
+        /// Endpoint URL, e.g. `https://s3.us-east-1.amazonaws.com` or `http://localhost:9000`
+        endpoint: String,
+        bucket: String,
+        #[serde(default = "default_s3_region")]
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+        /// Base URL objects are served from; defaults to `<endpoint>/<bucket>`
+        #[serde(default)]
+        public_base_url: Option<String>,
+    },
+}
+
+impl AvatarConfig {
+    /// Validate avatar configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.max_bytes == 0 {
+            return Err(ConfigValidationError::Avatar("Max bytes must be greater than 0".to_string()));
+        }
+
+        if self.min_dimension == 0 || self.min_dimension > self.max_dimension {
+            return Err(ConfigValidationError::Avatar(
+                "Dimensions must satisfy 0 < min_dimension <= max_dimension".to_string(),
+            ));
+        }
+
+        match &self.storage {
+            BlobStoreConfig::Local { root, public_base_url } => {
+                if root.trim().is_empty() {
+                    return Err(ConfigValidationError::Avatar("Local storage root cannot be empty".to_string()));
+                }
+                if !public_base_url.starts_with('/') && Url::parse(public_base_url).is_err() {
+                    return Err(ConfigValidationError::Avatar(format!(
+                        "Public base URL must be a path or an absolute URL: {}",
+                        public_base_url
+                    )));
+                }
+            }
+            BlobStoreConfig::S3 { endpoint, bucket, access_key_id, secret_access_key, .. } => {
+                Url::parse(endpoint)
+                    .map_err(|e| ConfigValidationError::Avatar(format!("Invalid S3 endpoint: {}", e)))?;
+                if bucket.is_empty() || access_key_id.is_empty() || secret_access_key.is_empty() {
+                    return Err(ConfigValidationError::Avatar(
+                        "S3 bucket and credentials cannot be empty".to_string(),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn default_avatar_max_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_avatar_min_dimension() -> u32 {
+    32
+}
+
+fn default_avatar_max_dimension() -> u32 {
+    4096
+}
+
+fn default_local_public_base_url() -> String {
+    "/avatars".to_string()
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl Default for AvatarConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_avatar_max_bytes(),
+            min_dimension: default_avatar_min_dimension(),
+            max_dimension: default_avatar_max_dimension(),
+            storage: BlobStoreConfig::default(),
+        }
+    }
+}
+
+impl Default for BlobStoreConfig {
+    fn default() -> Self {
+        BlobStoreConfig::Local {
+            root: "data/avatars".to_string(),
+            public_base_url: default_local_public_base_url(),
+        }
+    }
+}
+
+/// How outgoing email is delivered
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "transport", rename_all = "lowercase")]
+pub enum MailerConfig {
+    /// Write messages to the log instead of sending them (development)
+    #[default]
+    Log,
+    /// POST messages as JSON to a mail relay or provider API
+    Http {
+        endpoint: String,
+        #[serde(default = "default_mail_from")]
+        from: String,
+    },
+}
+
+impl MailerConfig {
+    /// Validate mailer configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if let MailerConfig::Http { endpoint, from } = self {
+            Url::parse(endpoint)
+                .map_err(|e| ConfigValidationError::Mailer(format!("Invalid mail endpoint: {}", e)))?;
+            if !from.contains('@') {
+                return Err(ConfigValidationError::Mailer(format!("Invalid sender address: {}", from)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn default_mail_from() -> String {
+    "no-reply@example.com".to_string()
+}
+
+/// Email change confirmation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailChangeConfig {
+    /// How long the new address has to confirm a change
+    #[serde(default = "default_email_change_expiry_minutes")]
+    pub expiry_minutes: u64,
+    /// How long the old address can revert a change, counted from its expiry
+    #[serde(default = "default_email_change_revert_window_hours")]
+    pub revert_window_hours: u64,
+    /// Link mailed to the new address; `{token}` is replaced with the confirmation token
+    #[serde(default = "default_email_change_confirm_url")]
+    pub confirm_url: String,
+    /// Link mailed to the old address; `{token}` is replaced with the revert token
+    #[serde(default = "default_email_change_revert_url")]
+    pub revert_url: String,
+}
+
+impl EmailChangeConfig {
+    /// Validate email change configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.expiry_minutes == 0 {
+            return Err(ConfigValidationError::EmailChange("Expiry must be greater than 0".to_string()));
+        }
+
+        for url in [&self.confirm_url, &self.revert_url] {
+            if !url.contains("{token}") {
+                return Err(ConfigValidationError::EmailChange(format!(
+                    "Link must contain a {{token}} placeholder: {}",
+                    url
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn default_email_change_expiry_minutes() -> u64 {
+    24 * 60
+}
+
+fn default_email_change_revert_window_hours() -> u64 {
+    7 * 24
+}
+
+fn default_email_change_confirm_url() -> String {
+    "http://localhost:8080/email-change/confirm?token={token}".to_string()
+}
+
+fn default_email_change_revert_url() -> String {
+    "http://localhost:8080/email-change/revert?token={token}".to_string()
+}
+
+impl Default for EmailChangeConfig {
+    fn default() -> Self {
+        Self {
+            expiry_minutes: default_email_change_expiry_minutes(),
+            revert_window_hours: default_email_change_revert_window_hours(),
+            confirm_url: default_email_change_confirm_url(),
+            revert_url: default_email_change_revert_url(),
+        }
+    }
+}
+
+/// `Idempotency-Key` handling for POST requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyConfig {
+    #[serde(default = "default_idempotency_enabled")]
+    pub enabled: bool,
+    /// How long a response is kept for replay
+    #[serde(default = "default_idempotency_ttl_hours")]
+    pub ttl_hours: u64,
+    /// How long a request holds its key before a retry may take it over
+    #[serde(default = "default_idempotency_lock_timeout_seconds")]
+    pub lock_timeout_seconds: u64,
+    /// Largest request or response body handled under a key
+    #[serde(default = "default_idempotency_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+impl IdempotencyConfig {
+    /// Validate idempotency configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.ttl_hours == 0 {
+            return Err(ConfigValidationError::Idempotency("TTL must be greater than 0".to_string()));
+        }
+
+        if self.lock_timeout_seconds == 0 {
+            return Err(ConfigValidationError::Idempotency("Lock timeout must be greater than 0".to_string()));
+        }
+
+        if self.max_body_bytes == 0 {
+            return Err(ConfigValidationError::Idempotency("Max body bytes must be greater than 0".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_idempotency_enabled() -> bool {
+    true
+}
+
+fn default_idempotency_ttl_hours() -> u64 {
+    24
+}
+
+fn default_idempotency_lock_timeout_seconds() -> u64 {
+    60
+}
+
+fn default_idempotency_max_body_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_idempotency_enabled(),
+            ttl_hours: default_idempotency_ttl_hours(),
+            lock_timeout_seconds: default_idempotency_lock_timeout_seconds(),
+            max_body_bytes: default_idempotency_max_body_bytes(),
+        }
+    }
+}
+
+/// User invitation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvitationConfig {
+    /// How long an invitation can be accepted
+    #[serde(default = "default_invitation_expiry_hours")]
+    pub expiry_hours: u64,
+    /// Link mailed to the invitee; `{token}` is replaced with the invitation token
+    #[serde(default = "default_invitation_accept_url")]
+    pub accept_url: String,
+}
+
+impl InvitationConfig {
+    /// Validate invitation configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.expiry_hours == 0 {
+            return Err(ConfigValidationError::Invitation("Expiry must be greater than 0".to_string()));
+        }
+
+        if !self.accept_url.contains("{token}") {
+            return Err(ConfigValidationError::Invitation(format!(
+                "Link must contain a {{token}} placeholder: {}",
+                self.accept_url
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_invitation_expiry_hours() -> u64 {
+    72
+}
+
+fn default_invitation_accept_url() -> String {
+    "http://localhost:8080/invitations/accept?token={token}".to_string()
+}
+
+impl Default for InvitationConfig {
+    fn default() -> Self {
+        Self {
+            expiry_hours: default_invitation_expiry_hours(),
+            accept_url: default_invitation_accept_url(),
+        }
+    }
+}
+
+/// What happens to a change when its audit log entry cannot be written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditFailureMode {
+    /// Roll the change back and report the error
+    #[default]
+    Closed,
+    /// Keep the change and log a warning
+    Open,
+}
+
+/// Audit logging configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    #[serde(default)]
+    pub failure_mode: AuditFailureMode,
+    /// Members of this group may search and export the audit log; nobody can when unset
+    #[serde(default)]
+    pub auditor_group_id: Option<uuid::Uuid>,
+    /// Key signing chain checkpoints; no checkpoints are taken when unset
+    #[serde(default)]
+    pub checkpoint_secret: Option<String>,
+    #[serde(default = "default_audit_checkpoint_interval_minutes")]
+    pub checkpoint_interval_minutes: u64,
+    /// Monthly partitions created ahead of the current month
+    #[serde(default = "default_audit_partition_premake_months")]
+    pub partition_premake_months: u32,
+    #[serde(default = "default_audit_partition_maintenance_interval_hours")]
+    pub partition_maintenance_interval_hours: u64,
+    /// Whole months entries are kept in the database; kept forever when unset
+    #[serde(default)]
+    pub retention_months: Option<u32>,
+    /// Where expired partitions are archived as NDJSON before being dropped;
+    /// without it they are only detached. Archives are exempt from erasure
+    #[serde(default)]
+    pub archive_storage: Option<BlobStoreConfig>,
+}
+
+impl AuditConfig {
+    /// Validate audit configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.checkpoint_interval_minutes == 0 {
+            return Err(ConfigValidationError::Audit("Checkpoint interval must be greater than 0".to_string()));
+        }
+
+        if let Some(secret) = &self.checkpoint_secret {
+            if secret.len() < 32 {
+                return Err(ConfigValidationError::Audit(
+                    "Checkpoint secret must be at least 32 characters long".to_string(),
+                ));
+            }
+        }
+
+        if self.partition_premake_months == 0 {
+            return Err(ConfigValidationError::Audit("Partition premake months must be greater than 0".to_string()));
+        }
+
+        if self.partition_maintenance_interval_hours == 0 {
+            return Err(ConfigValidationError::Audit(
+                "Partition maintenance interval must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.retention_months == Some(0) {
+            return Err(ConfigValidationError::Audit("Retention months must be greater than 0".to_string()));
+        }
+
+        if let Some(BlobStoreConfig::Local { root, .. }) = &self.archive_storage {
+            if root.trim().is_empty() {
+                return Err(ConfigValidationError::Audit("Local archive storage root cannot be empty".to_string()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn default_audit_checkpoint_interval_minutes() -> u64 {
+    60
+}
+
+fn default_audit_partition_premake_months() -> u32 {
+    3
+}
+
+fn default_audit_partition_maintenance_interval_hours() -> u64 {
+    24
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            failure_mode: AuditFailureMode::default(),
+            auditor_group_id: None,
+            checkpoint_secret: None,
+            checkpoint_interval_minutes: default_audit_checkpoint_interval_minutes(),
+            partition_premake_months: default_audit_partition_premake_months(),
+            partition_maintenance_interval_hours: default_audit_partition_maintenance_interval_hours(),
+            retention_months: None,
+            archive_storage: None,
+        }
+    }
+}
+
+/// Transactional outbox dispatcher configuration
+///
+/// Attempts and the base retry delay come from `external_service`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxConfig {
+    /// How often the dispatcher looks for due events
+    #[serde(default = "default_outbox_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Events claimed per poll
+    #[serde(default = "default_outbox_batch_size")]
+    pub batch_size: i64,
+    /// Deliveries in flight at once
+    #[serde(default = "default_outbox_concurrency")]
+    pub concurrency: usize,
+    /// How long a claimed event is hidden from other dispatchers
+    #[serde(default = "default_outbox_lease_seconds")]
+    pub lease_seconds: u64,
+    /// Upper bound on the delay between attempts
+    #[serde(default = "default_outbox_max_backoff_seconds")]
+    pub max_backoff_seconds: u64,
+    /// How long delivered events are kept before being purged
+    #[serde(default = "default_outbox_delivered_retention_hours")]
+    pub delivered_retention_hours: u64,
+    /// How long dead letters are kept before being purged, replayed or not
+    #[serde(default = "default_outbox_dead_letter_retention_days")]
+    pub dead_letter_retention_days: u32,
+}
+
+impl OutboxConfig {
+    /// Validate outbox configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.poll_interval_ms == 0 {
+            return Err(ConfigValidationError::Outbox("Poll interval must be greater than 0".to_string()));
+        }
+
+        if self.batch_size <= 0 || self.concurrency == 0 {
+            return Err(ConfigValidationError::Outbox(
+                "Batch size and concurrency must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.lease_seconds == 0
+            || self.max_backoff_seconds == 0
+            || self.delivered_retention_hours == 0
+            || self.dead_letter_retention_days == 0
+        {
+            return Err(ConfigValidationError::Outbox(
+                "Lease, maximum backoff and retention must be greater than 0".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_outbox_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_outbox_batch_size() -> i64 {
+    100
+}
+
+fn default_outbox_concurrency() -> usize {
+    8
+}
+
+fn default_outbox_lease_seconds() -> u64 {
+    60
+}
+
+fn default_outbox_max_backoff_seconds() -> u64 {
+    3600
+}
+
+fn default_outbox_delivered_retention_hours() -> u64 {
+    168
+}
+
+fn default_outbox_dead_letter_retention_days() -> u32 {
+    30
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: default_outbox_poll_interval_ms(),
+            batch_size: default_outbox_batch_size(),
+            concurrency: default_outbox_concurrency(),
+            lease_seconds: default_outbox_lease_seconds(),
+            max_backoff_seconds: default_outbox_max_backoff_seconds(),
+            delivered_retention_hours: default_outbox_delivered_retention_hours(),
+            dead_letter_retention_days: default_outbox_dead_letter_retention_days(),
+        }
+    }
+}
+
+/// Webhook subscription configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Group whose members may manage webhook subscriptions; nobody may when unset
+    #[serde(default)]
+    pub manager_group_id: Option<uuid::Uuid>,
+    /// Reject subscription URLs that are not HTTPS
+    #[serde(default = "default_webhook_require_https")]
+    pub require_https: bool,
+    /// Let subscription URLs lead to loopback, private and other non-public
+    /// addresses; for local development only
+    #[serde(default)]
+    pub allow_private_networks: bool,
+    /// How long delivery attempts are kept in the delivery log
+    #[serde(default = "default_webhook_delivery_log_retention_days")]
+    pub delivery_log_retention_days: u32,
+    /// How long a rotated-out secret keeps signing deliveries, unless the rotation says otherwise
+    #[serde(default = "default_webhook_secret_rotation_overlap_hours")]
+    pub secret_rotation_overlap_hours: u32,
+}
+
+impl WebhookConfig {
+    /// Validate webhook configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.delivery_log_retention_days == 0 {
+            return Err(ConfigValidationError::Webhook(
+                "Delivery log retention must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.secret_rotation_overlap_hours > 168 {
+            return Err(ConfigValidationError::Webhook(
+                "Secret rotation overlap must not exceed 168 hours".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_webhook_require_https() -> bool {
+    true
+}
+
+fn default_webhook_delivery_log_retention_days() -> u32 {
+    30
+}
+
+fn default_webhook_secret_rotation_overlap_hours() -> u32 {
+    24
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            manager_group_id: None,
+            require_https: default_webhook_require_https(),
+            allow_private_networks: false,
+            delivery_log_retention_days: default_webhook_delivery_log_retention_days(),
+            secret_rotation_overlap_hours: default_webhook_secret_rotation_overlap_hours(),
+        }
+    }
+}
+
+/// In-process domain event bus configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventBusConfig {
+    /// Events each handler may have waiting; further events are dropped for that handler
+    #[serde(default = "default_event_bus_queue_capacity")]
+    pub queue_capacity: usize,
+    /// How long a handler may take over one event before it counts as failed
+    #[serde(default = "default_event_bus_handler_timeout_seconds")]
+    pub handler_timeout_seconds: u64,
+}
+
+impl EventBusConfig {
+    /// Validate event bus configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.queue_capacity == 0 {
+            return Err(ConfigValidationError::EventBus(
+                "Queue capacity must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.handler_timeout_seconds == 0 {
+            return Err(ConfigValidationError::EventBus(
+                "Handler timeout must be greater than 0".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_event_bus_queue_capacity() -> usize {
+    1024
+}
+
+fn default_event_bus_handler_timeout_seconds() -> u64 {
+    30
+}
+
+impl Default for EventBusConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: default_event_bus_queue_capacity(),
+            handler_timeout_seconds: default_event_bus_handler_timeout_seconds(),
+        }
+    }
+}
+
+/// Live event stream configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventStreamConfig {
+    /// Group whose members may subscribe to the stream; nobody may when unset
+    #[serde(default)]
+    pub subscriber_group_id: Option<uuid::Uuid>,
+    /// Postgres NOTIFY channel the instances share events on
+    #[serde(default = "default_event_stream_channel")]
+    pub channel: String,
+    /// Recent events kept for clients resuming with `Last-Event-ID`
+    #[serde(default = "default_event_stream_replay_buffer_size")]
+    pub replay_buffer_size: usize,
+    /// Interval between heartbeats on idle connections
+    #[serde(default = "default_event_stream_heartbeat_seconds")]
+    pub heartbeat_seconds: u64,
+}
+
+impl EventStreamConfig {
+    /// Validate event stream configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        // Postgres truncates identifiers longer than 63 bytes
+        if self.channel.is_empty() || self.channel.len() > 63 {
+            return Err(ConfigValidationError::EventStream(
+                "Channel must be between 1 and 63 bytes long".to_string(),
+            ));
+        }
+
+        if self.replay_buffer_size == 0 {
+            return Err(ConfigValidationError::EventStream(
+                "Replay buffer size must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.heartbeat_seconds == 0 {
+            return Err(ConfigValidationError::EventStream(
+                "Heartbeat interval must be greater than 0".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_event_stream_channel() -> String {
+    "domain_events".to_string()
+}
+
+fn default_event_stream_replay_buffer_size() -> usize {
+    1000
+}
+
+fn default_event_stream_heartbeat_seconds() -> u64 {
+    15
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            subscriber_group_id: None,
+            channel: default_event_stream_channel(),
+            replay_buffer_size: default_event_stream_replay_buffer_size(),
+            heartbeat_seconds: default_event_stream_heartbeat_seconds(),
+        }
+    }
+}
+
+/// WebSocket endpoint configuration
+///
+/// Who may connect is decided by `event_stream.subscriber_group_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketConfig {
+    /// Open sockets allowed per instance
+    #[serde(default = "default_websocket_max_connections")]
+    pub max_connections: usize,
+    /// Subscriptions allowed per socket
+    #[serde(default = "default_websocket_max_subscriptions")]
+    pub max_subscriptions: usize,
+    /// Largest message accepted from clients
+    #[serde(default = "default_websocket_max_message_bytes")]
+    pub max_message_bytes: usize,
+    /// Messages waiting to be sent on a socket before it is closed as too slow
+    #[serde(default = "default_websocket_send_queue_capacity")]
+    pub send_queue_capacity: usize,
+    /// Interval between pings on open sockets
+    #[serde(default = "default_websocket_ping_interval_seconds")]
+    pub ping_interval_seconds: u64,
+    /// How long a client gets to answer a ping before its socket is closed
+    #[serde(default = "default_websocket_pong_timeout_seconds")]
+    pub pong_timeout_seconds: u64,
+    /// How long sockets get to close during shutdown
+    #[serde(default = "default_websocket_close_timeout_seconds")]
+    pub close_timeout_seconds: u64,
+}
+
+impl WebSocketConfig {
+    /// Validate WebSocket configuration
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        if self.max_connections == 0 {
+            return Err(ConfigValidationError::WebSocket(
+                "Max connections must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.max_subscriptions == 0 {
+            return Err(ConfigValidationError::WebSocket(
+                "Max subscriptions must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.max_message_bytes < 1024 {
+            return Err(ConfigValidationError::WebSocket(
+                "Max message size must be at least 1024 bytes".to_string(),
+            ));
+        }
+
+        if self.send_queue_capacity == 0 {
+            return Err(ConfigValidationError::WebSocket(
+                "Send queue capacity must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.ping_interval_seconds == 0 {
+            return Err(ConfigValidationError::WebSocket(
+                "Ping interval must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.pong_timeout_seconds == 0 {
+            return Err(ConfigValidationError::WebSocket(
+                "Pong timeout must be greater than 0".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_websocket_max_connections() -> usize {
+    1000
+}
+
+fn default_websocket_max_subscriptions() -> usize {
+    16
+}
+
+fn default_websocket_max_message_bytes() -> usize {
+    16384
+}
+
+fn default_websocket_send_queue_capacity() -> usize {
+    256
+}
+
+fn default_websocket_ping_interval_seconds() -> u64 {
+    30
+}
+
+fn default_websocket_pong_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_websocket_close_timeout_seconds() -> u64 {
+    5
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: default_websocket_max_connections(),
+            max_subscriptions: default_websocket_max_subscriptions(),
+            max_message_bytes: default_websocket_max_message_bytes(),
+            send_queue_capacity: default_websocket_send_queue_capacity(),
+            ping_interval_seconds: default_websocket_ping_interval_seconds(),
+            pong_timeout_seconds: default_websocket_pong_timeout_seconds(),
+            close_timeout_seconds: default_websocket_close_timeout_seconds(),
+        }
+    }
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
@@ -489,6 +1581,7 @@ impl Default for ServerConfig {
             graceful_shutdown_timeout_seconds: default_graceful_shutdown_timeout(),
             connection_drain_timeout_seconds: default_connection_drain_timeout(),
             resource_cleanup_timeout_seconds: default_resource_cleanup_timeout(),
+            require_if_match: false,
         }
     }
 }
@@ -542,6 +1635,21 @@ impl Default for AppConfig {
             sentry: SentryConfig::default(),
             vault: None,
             external_service: ExternalServiceConfig::default(),
+            cache: CacheConfig::default(),
+            admin: AdminConfig::default(),
+            user_metadata: UserMetadataConfig::default(),
+            user_import: UserImportConfig::default(),
+            avatar: AvatarConfig::default(),
+            mailer: MailerConfig::default(),
+            email_change: EmailChangeConfig::default(),
+            idempotency: IdempotencyConfig::default(),
+            invitation: InvitationConfig::default(),
+            audit: AuditConfig::default(),
+            outbox: OutboxConfig::default(),
+            webhooks: WebhookConfig::default(),
+            event_bus: EventBusConfig::default(),
+            event_stream: EventStreamConfig::default(),
+            websocket: WebSocketConfig::default(),
             environment: "development".to_string(),
         }
     }
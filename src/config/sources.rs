@@ -208,6 +208,8 @@ server:
   max_connections: 1000
   # Graceful shutdown timeout in seconds
   graceful_shutdown_timeout_seconds: 30
+  # Require If-Match on user updates and deletes (428 when missing)
+  require_if_match: false
 
 # Database configuration
 database:
@@ -257,6 +259,192 @@ sentry:
   # Enable debug mode for Sentry SDK
   debug: false
 
+# HTTP caching for read endpoints (ETag, Last-Modified, 304 responses)
+cache:
+  # Enable conditional GET handling and Cache-Control headers
+  enabled: true
+  # Cache-Control policy for routes without an explicit entry below
+  default_cache_control: "private, no-cache"
+  # Largest response body hashed into a weak ETag (bytes)
+  max_body_bytes: 1048576
+  # Per-route policies, keyed by the route pattern
+  routes:
+    - path: "/api/v1/users/:id"
+      cache_control: "private, max-age=0, must-revalidate"
+    - path: "/api/v1/users"
+      cache_control: "private, max-age=5, must-revalidate"
+
+# Administrators: bulk export and import, privacy requests and invitations
+admin:
+  # Members of this group are administrators; nobody is when unset
+  # group_id: "00000000-0000-0000-0000-000000000000"
+
+# Custom per-user metadata
+user_metadata:
+  # Optional JSON Schema file that user metadata must satisfy
+  # schema_path: "config/user_metadata.schema.json"
+
+# Bulk user import
+user_import:
+  # Rows written per database transaction
+  batch_size: 500
+  # Imports with more rows run as background jobs
+  background_threshold_rows: 1000
+  # Largest number of rows accepted in one file
+  max_rows: 100000
+  # Largest upload accepted (bytes)
+  max_body_bytes: 33554432
+  # Longest an import is run inline before answering with the job to poll
+  inline_timeout_seconds: 20
+  # Unfinished jobs without progress for this long are failed at startup
+  stale_job_minutes: 15
+
+# User avatars
+avatar:
+  # Largest accepted image (bytes)
+  max_bytes: 2097152
+  # Accepted width/height range (pixels)
+  min_dimension: 32
+  max_dimension: 4096
+  # Local filesystem storage, served by the API under public_base_url
+  storage:
+    backend: local
+    root: "data/avatars"
+    public_base_url: "/avatars"
+  # S3-compatible storage (AWS S3, MinIO, ...)
+  # storage:
+  #   backend: s3
+  #   endpoint: "http://localhost:9000"
+  #   bucket: "avatars"
+  #   region: "us-east-1"
+  #   access_key_id: "minioadmin"
+  #   secret_access_key: "minioadmin"
+  #   public_base_url: "http://localhost:9000/avatars"
+
+# Outgoing email
+mailer:
+  # Log messages instead of sending them (development)
+  transport: log
+  # POST messages as JSON to a mail relay or provider API
+  # transport: http
+  # endpoint: "https://mail.example.com/send"
+  # from: "no-reply@example.com"
+
+# Confirmed email changes
+email_change:
+  # Time the new address has to confirm (minutes)
+  expiry_minutes: 1440
+  # Time the old address can revert the change after it expires (hours)
+  revert_window_hours: 168
+  # Links mailed out; {token} is replaced with the token
+  confirm_url: "http://localhost:8080/email-change/confirm?token={token}"
+  revert_url: "http://localhost:8080/email-change/revert?token={token}"
+
+# User invitations
+invitation:
+  # Time the invitee has to accept (hours)
+  expiry_hours: 72
+  # Link mailed out; {token} is replaced with the token
+  accept_url: "http://localhost:8080/invitations/accept?token={token}"
+
+# Audit logging of user changes
+audit:
+  # closed: roll a change back when its audit entry cannot be written
+  # open: keep the change and log a warning
+  failure_mode: closed
+  # Members of this group may search and export the audit log
+  # auditor_group_id: "00000000-0000-0000-0000-000000000000"
+  # Key signing periodic checkpoints of the hash chain (at least 32 characters)
+  # checkpoint_secret: "change-me-to-a-random-32-character-secret"
+  # How often a checkpoint is signed (minutes)
+  checkpoint_interval_minutes: 60
+  # Monthly partitions of audit_logs created ahead of the current month
+  partition_premake_months: 3
+  # How often partitions are created and retention applied (hours)
+  partition_maintenance_interval_hours: 24
+  # Whole months entries are kept in the database; forever when unset
+  # retention_months: 24
+  # Expired partitions are written here as NDJSON and then dropped;
+  # without it they are only detached (same options as avatar.storage).
+  # Archived files are exempt from erasure and keep the PII they contain
+  # archive_storage:
+  #   backend: local
+  #   root: "data/audit-archives"
+
+# Delivery of user events written to the transactional outbox
+# Attempts and the base retry delay come from external_service
+outbox:
+  # How often due events are looked for (milliseconds)
+  poll_interval_ms: 1000
+  # Events claimed per poll, and deliveries in flight at once
+  batch_size: 100
+  concurrency: 8
+  # How long a claimed event is hidden from other instances (seconds)
+  lease_seconds: 60
+  # Upper bound on the delay between attempts (seconds)
+  max_backoff_seconds: 3600
+  # How long delivered events are kept (hours)
+  delivered_retention_hours: 168
+  # How long dead letters are kept, replayed or not (days)
+  dead_letter_retention_days: 30
+
+# Webhook subscriptions that outbox events are delivered to
+webhooks:
+  # Members of this group may manage subscriptions; nobody may when unset
+  # manager_group_id: "00000000-0000-0000-0000-000000000000"
+  # Reject subscription URLs that are not HTTPS
+  require_https: true
+  # Allow URLs leading to loopback, private and other non-public addresses (local development only)
+  allow_private_networks: false
+  # How long delivery attempts are kept in the delivery log (days)
+  delivery_log_retention_days: 30
+  # How long a rotated-out secret keeps signing deliveries (hours, at most 168)
+  secret_rotation_overlap_hours: 24
+
+# In-process bus that hands user changes to registered event handlers
+event_bus:
+  # Events each handler may have waiting; further events are dropped for it
+  queue_capacity: 1024
+  # How long a handler may take over one event (seconds)
+  handler_timeout_seconds: 30
+
+# Live stream of user changes at /api/v1/events/stream, shared between instances
+event_stream:
+  # Members of this group may subscribe; nobody may when unset
+  # subscriber_group_id: "00000000-0000-0000-0000-000000000000"
+  # Postgres NOTIFY channel the instances share events on
+  channel: "domain_events"
+  # Recent events kept for clients resuming with Last-Event-ID
+  replay_buffer_size: 1000
+  # Interval between heartbeats on idle connections (seconds)
+  heartbeat_seconds: 15
+
+# Realtime messaging at /api/v1/ws; subscribers are the event_stream subscriber group
+websocket:
+  # Open sockets allowed per instance
+  max_connections: 1000
+  # Subscriptions allowed per socket
+  max_subscriptions: 16
+  # Largest message accepted from clients (bytes)
+  max_message_bytes: 16384
+  # Messages waiting to be sent before a slow socket is closed
+  send_queue_capacity: 256
+  # Interval between pings on open sockets (seconds)
+  ping_interval_seconds: 30
+  # How long a client gets to answer a ping before its socket is closed (seconds)
+  pong_timeout_seconds: 10
+  # How long sockets get to close during shutdown (seconds)
+  close_timeout_seconds: 5
+
+# Idempotency-Key handling for POST requests
+idempotency:
+  enabled: true
+  # How long responses are kept for replay (hours)
+  ttl_hours: 24
+  # How long a request holds its key before a retry may take over (seconds)
+  lock_timeout_seconds: 60
+  max_body_bytes: 1048576
+
 # HashiCorp Vault configuration (optional)
 # Uncomment and configure if using Vault for secrets management
 # vault:
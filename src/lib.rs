@@ -5,6 +5,7 @@ pub mod models;
 pub mod repository;
 pub mod services;
 pub mod shutdown;
+pub mod storage;
 pub mod tracing;
 pub mod utils;
 pub mod web;
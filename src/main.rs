@@ -3,7 +3,7 @@ use rust_api::{
     config, 
     database::Database,
     services::container::ServiceContainer,
-    shutdown::{GracefulShutdown, ShutdownCoordinator, HttpServerShutdown, DatabaseShutdown, ExternalServiceShutdown, TracingShutdown, GeneralResourceCleanup},
+    shutdown::{GracefulShutdown, ShutdownCoordinator, HttpServerShutdown, DatabaseShutdown, ExternalServiceShutdown, TracingShutdown, GeneralResourceCleanup, BackgroundTask, EventBusShutdown, EventStreamShutdown, WebSocketShutdown, UserImportShutdown},
     tracing as app_tracing, 
     web::{handlers::health_handlers, router::{create_router, AppState}},
 };
@@ -50,13 +50,136 @@ async fn main() -> Result<()> {
     info!("Database connection pool initialized and migrations completed");
 
     // Create service container with dependencies
-    let services = ServiceContainer::new(
-        database.pool_cloned(), 
-        config.external_service.timeout_seconds.unwrap_or(30)
-    );
+    let services = ServiceContainer::from_config(database.pool_cloned(), &config)?;
 
     // Clone services for shutdown coordinator before moving to app state
     let external_service_for_shutdown = services.external_service();
+    let event_bus_for_shutdown = services.event_bus();
+
+    // Fail import jobs left running by instances that stopped without finishing them
+    let user_import_service = services.user_import_service();
+    if let Err(e) = user_import_service.fail_stale_jobs().await {
+        error!("Failed to mark stale user import jobs as failed: {}", e);
+    }
+
+    // Share domain events between instances for event stream clients
+    let event_stream = services.event_stream();
+    services.register_event_handler(event_stream.clone());
+    event_stream.start();
+    let websocket_sessions = services.websocket_sessions();
+
+    // Keep audit log partitions ahead of time and apply retention
+    let audit_retention_service = services.audit_retention_service();
+    if let Err(e) = audit_retention_service.create_partitions().await {
+        error!("Failed to create audit log partitions: {}", e);
+    }
+    let audit_partitions = BackgroundTask::spawn_periodic(
+        "Audit Log Partitions",
+        Duration::from_secs(config.audit.partition_maintenance_interval_hours * 3600),
+        move || {
+            let audit_retention_service = audit_retention_service.clone();
+            async move {
+                if let Err(e) = audit_retention_service.create_partitions().await {
+                    error!("Failed to create audit log partitions: {}", e);
+                }
+                if let Err(e) = audit_retention_service.prune_partitions().await {
+                    error!("Failed to apply audit log retention: {}", e);
+                }
+            }
+        },
+    )
+    .with_timeout(Duration::from_secs(30));
+
+    // Deliver events from the transactional outbox
+    let outbox_service = services.outbox_service();
+    let outbox_dispatcher = BackgroundTask::spawn_periodic(
+        "Outbox Dispatcher",
+        Duration::from_millis(config.outbox.poll_interval_ms),
+        move || {
+            let outbox_service = outbox_service.clone();
+            async move {
+                if let Err(e) = outbox_service.dispatch_due().await {
+                    error!("Failed to dispatch outbox events: {}", e);
+                }
+            }
+        },
+    )
+    .with_timeout(Duration::from_secs(config.external_service.timeout_seconds.unwrap_or(30) + 5));
+    // Replay dead letters queued by bulk replays
+    let dead_letter_service = services.dead_letter_service();
+    let dead_letter_replay = BackgroundTask::spawn_periodic(
+        "Dead Letter Replay",
+        Duration::from_millis(config.outbox.poll_interval_ms),
+        move || {
+            let dead_letter_service = dead_letter_service.clone();
+            async move {
+                if let Err(e) = dead_letter_service.replay_queued().await {
+                    error!("Failed to replay queued dead letters: {}", e);
+                }
+            }
+        },
+    )
+    .with_timeout(Duration::from_secs(config.external_service.timeout_seconds.unwrap_or(30) + 5));
+    let outbox_service = services.outbox_service();
+    let webhook_subscription_service = services.webhook_subscription_service();
+    let dead_letter_service = services.dead_letter_service();
+    let outbox_purge = BackgroundTask::spawn_periodic("Outbox Purge", Duration::from_secs(3600), move || {
+        let outbox_service = outbox_service.clone();
+        let webhook_subscription_service = webhook_subscription_service.clone();
+        let dead_letter_service = dead_letter_service.clone();
+        async move {
+            if let Err(e) = outbox_service.purge_delivered().await {
+                error!("Failed to purge delivered outbox events: {}", e);
+            }
+            if let Err(e) = webhook_subscription_service.purge_deliveries().await {
+                error!("Failed to purge the webhook delivery log: {}", e);
+            }
+            if let Err(e) = dead_letter_service.purge_expired().await {
+                error!("Failed to purge expired dead letters: {}", e);
+            }
+        }
+    });
+
+    // Forget idempotency keys once their responses are no longer replayed
+    let idempotency_repository = services.idempotency_repository();
+    let idempotency_purge = BackgroundTask::spawn_periodic("Idempotency Key Purge", Duration::from_secs(3600), move || {
+        let idempotency_repository = idempotency_repository.clone();
+        async move {
+            match idempotency_repository.purge_expired().await {
+                Ok(0) => {}
+                Ok(purged) => info!("Purged {} expired idempotency keys", purged),
+                Err(e) => error!("Failed to purge expired idempotency keys: {}", e),
+            }
+        }
+    });
+
+    // Sign audit chain checkpoints periodically when a signing key is configured
+    let audit_checkpoints = config.audit.checkpoint_secret.is_some().then(|| {
+        let audit_service = services.audit_service();
+        BackgroundTask::spawn_periodic(
+            "Audit Checkpoints",
+            Duration::from_secs(config.audit.checkpoint_interval_minutes * 60),
+            move || {
+                let audit_service = audit_service.clone();
+                async move {
+                    if let Err(e) = audit_service.create_checkpoint().await {
+                        error!("Failed to sign audit chain checkpoint: {}", e);
+                    }
+                }
+            },
+        )
+    });
+
+    // Walk the whole audit chain daily, which takes too long for a request
+    let audit_service = services.audit_service();
+    let audit_verification = BackgroundTask::spawn_periodic("Audit Chain Verification", Duration::from_secs(86400), move || {
+        let audit_service = audit_service.clone();
+        async move {
+            if let Err(e) = audit_service.verify_chain(true).await {
+                error!("Failed to verify the audit chain: {}", e);
+            }
+        }
+    });
 
     // Create application state
     let app_state = AppState::new(config.clone(), services);
@@ -86,10 +209,33 @@ async fn main() -> Result<()> {
     let mut shutdown_coordinator = ShutdownCoordinator::new();
     
     // Register shutdown components in reverse order of startup with configurable timeouts
+    // End open event streams and sockets, which would otherwise keep their connections from draining
+    shutdown_coordinator.register(
+        WebSocketShutdown::new(websocket_sessions)
+            .with_timeout(Duration::from_secs(config.websocket.close_timeout_seconds))
+    );
+    shutdown_coordinator.register(EventStreamShutdown::new(event_stream));
     shutdown_coordinator.register(
         HttpServerShutdown::new(handle)
             .with_timeout(Duration::from_secs(config.server.connection_drain_timeout_seconds))
     );
+    // Interrupt background imports once no request is waiting on one
+    shutdown_coordinator.register(UserImportShutdown::new(user_import_service));
+    // Stop the dispatcher once requests have drained, letting in-flight deliveries finish
+    shutdown_coordinator.register(outbox_dispatcher);
+    shutdown_coordinator.register(dead_letter_replay);
+    // Requests no longer publish events, so handlers can finish what is queued
+    shutdown_coordinator.register(
+        EventBusShutdown::new(event_bus_for_shutdown)
+            .with_timeout(Duration::from_secs(config.event_bus.handler_timeout_seconds))
+    );
+    shutdown_coordinator.register(outbox_purge);
+    shutdown_coordinator.register(idempotency_purge);
+    shutdown_coordinator.register(audit_partitions);
+    if let Some(audit_checkpoints) = audit_checkpoints {
+        shutdown_coordinator.register(audit_checkpoints);
+    }
+    shutdown_coordinator.register(audit_verification);
     shutdown_coordinator.register(
         ExternalServiceShutdown::new(external_service_for_shutdown)
             .with_timeout(Duration::from_secs(config.external_service.timeout_seconds.unwrap_or(5)))
@@ -1,9 +1,11 @@
 use prometheus::{
-    Gauge, Histogram, HistogramOpts, IntCounter, IntGauge, Opts, Registry,
+    Gauge, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
 };
 use std::sync::Arc;
 use tracing::{info, warn};
 
+use crate::services::EventBusStats;
+
 /// Application metrics collector
 #[derive(Clone)]
 pub struct AppMetrics {
@@ -27,6 +29,14 @@ pub struct AppMetrics {
     pub external_errors_total: IntCounter,
     pub circuit_breaker_state: IntGauge,
 
+    // Webhook metrics
+    pub webhook_dead_letters: IntGauge,
+
+    // Domain event metrics
+    pub domain_events_published_total: IntCounter,
+    pub domain_event_handler_events_total: IntCounterVec,
+    pub domain_event_handler_queue_depth: IntGaugeVec,
+
     // Application metrics
     pub application_info: IntGauge,
     pub application_uptime_seconds: Gauge,
@@ -105,6 +115,28 @@ impl AppMetrics {
             "Circuit breaker state (0=closed, 1=open, 2=half-open)"
         ).const_label("service", "rust-api"))?;
 
+        // Webhook metrics
+        let webhook_dead_letters = IntGauge::with_opts(Opts::new(
+            "webhook_dead_letters",
+            "Number of event deliveries waiting in the dead-letter queue"
+        ).const_label("service", "rust-api"))?;
+
+        // Domain event metrics
+        let domain_events_published_total = IntCounter::with_opts(Opts::new(
+            "domain_events_published_total",
+            "Total number of domain events published on the event bus"
+        ).const_label("service", "rust-api"))?;
+
+        let domain_event_handler_events_total = IntCounterVec::new(Opts::new(
+            "domain_event_handler_events_total",
+            "Domain events per handler by outcome (handled, failed, dropped)"
+        ).const_label("service", "rust-api"), &["handler", "outcome"])?;
+
+        let domain_event_handler_queue_depth = IntGaugeVec::new(Opts::new(
+            "domain_event_handler_queue_depth",
+            "Domain events waiting in each handler's queue"
+        ).const_label("service", "rust-api"), &["handler"])?;
+
         // Application metrics
         let application_info = IntGauge::with_opts(Opts::new(
             "application_info",
@@ -142,6 +174,10 @@ impl AppMetrics {
         registry.register(Box::new(external_request_duration_seconds.clone()))?;
         registry.register(Box::new(external_errors_total.clone()))?;
         registry.register(Box::new(circuit_breaker_state.clone()))?;
+        registry.register(Box::new(webhook_dead_letters.clone()))?;
+        registry.register(Box::new(domain_events_published_total.clone()))?;
+        registry.register(Box::new(domain_event_handler_events_total.clone()))?;
+        registry.register(Box::new(domain_event_handler_queue_depth.clone()))?;
         registry.register(Box::new(application_info.clone()))?;
         registry.register(Box::new(application_uptime_seconds.clone()))?;
         registry.register(Box::new(memory_usage_bytes.clone()))?;
@@ -166,6 +202,10 @@ impl AppMetrics {
             external_request_duration_seconds,
             external_errors_total,
             circuit_breaker_state,
+            webhook_dead_letters,
+            domain_events_published_total,
+            domain_event_handler_events_total,
+            domain_event_handler_queue_depth,
             application_info,
             application_uptime_seconds,
             memory_usage_bytes,
@@ -224,6 +264,30 @@ impl AppMetrics {
         self.circuit_breaker_state.set(state);
     }
 
+    /// Update the number of dead letters
+    pub fn update_dead_letter_metrics(&self, count: i64) {
+        self.webhook_dead_letters.set(count);
+    }
+
+    /// Update domain event metrics from the event bus's counters
+    pub fn update_event_bus_metrics(&self, stats: &EventBusStats) {
+        // The bus counts on its own, so counters are brought up to its totals
+        let advance = |counter: &IntCounter, total: u64| counter.inc_by(total.saturating_sub(counter.get()));
+
+        advance(&self.domain_events_published_total, stats.published);
+        for handler in &stats.handlers {
+            for (outcome, total) in [("handled", handler.handled), ("failed", handler.failed), ("dropped", handler.dropped)] {
+                advance(
+                    &self.domain_event_handler_events_total.with_label_values(&[&handler.name, outcome]),
+                    total,
+                );
+            }
+            self.domain_event_handler_queue_depth
+                .with_label_values(&[&handler.name])
+                .set(handler.queued as i64);
+        }
+    }
+
     /// Get metrics as Prometheus text format
     pub fn gather(&self) -> String {
         let encoder = prometheus::TextEncoder::new();
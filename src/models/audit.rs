@@ -0,0 +1,276 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::common::UserId;
+use super::export::csv_record;
+
+/// Audit log entry
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditLog {
+    pub id: Uuid,
+    /// User who performed the action, if known
+    pub user_id: Option<UserId>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    /// Correlation ID of the request that caused the action
+    #[sqlx(default)]
+    pub correlation_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Position in the hash chain; `None` for entries written before it existed
+    #[sqlx(default)]
+    pub sequence: Option<i64>,
+    #[sqlx(default)]
+    pub metadata_hash: Option<String>,
+    #[sqlx(default)]
+    pub previous_hash: Option<String>,
+    #[sqlx(default)]
+    pub hash: Option<String>,
+    /// When an erasure redacted the metadata, which then no longer matches `metadata_hash`
+    #[sqlx(default)]
+    pub redacted_at: Option<DateTime<Utc>>,
+}
+
+/// Audit log entry for database insertion
+#[derive(Debug, Clone)]
+pub struct NewAuditLog {
+    pub user_id: Option<UserId>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub correlation_id: Option<String>,
+}
+
+/// Who caused a change, recorded with every audit log entry it produces
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditContext {
+    /// Authenticated user making the request; `None` for anonymous or system actions
+    pub actor: Option<UserId>,
+    /// Correlation ID of the request
+    pub correlation_id: Option<String>,
+}
+
+impl AuditContext {
+    pub fn new(actor: Option<UserId>, correlation_id: Option<String>) -> Self {
+        Self { actor, correlation_id }
+    }
+
+    /// Context for changes not made on behalf of a request
+    pub fn system() -> Self {
+        Self::default()
+    }
+}
+
+impl NewAuditLog {
+    pub fn new<A: Into<String>, R: Into<String>>(action: A, resource_type: R) -> Self {
+        Self {
+            user_id: None,
+            action: action.into(),
+            resource_type: resource_type.into(),
+            resource_id: None,
+            metadata: None,
+            correlation_id: None,
+        }
+    }
+
+    /// Set the user who performed the action
+    pub fn with_actor(mut self, user_id: Option<UserId>) -> Self {
+        self.user_id = user_id;
+        self
+    }
+
+    /// Set the identifier of the affected resource
+    pub fn with_resource_id<S: Into<String>>(mut self, resource_id: S) -> Self {
+        self.resource_id = Some(resource_id.into());
+        self
+    }
+
+    /// Set the actor and correlation ID from the request context
+    pub fn with_context(mut self, context: &AuditContext) -> Self {
+        self.user_id = context.actor;
+        self.correlation_id = context.correlation_id.clone();
+        self
+    }
+
+    /// Attach structured details about the action
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Add one detail to the metadata object, creating it if needed
+    pub fn with_detail<K: Into<String>>(mut self, key: K, value: serde_json::Value) -> Self {
+        match &mut self.metadata {
+            Some(serde_json::Value::Object(object)) => {
+                object.insert(key.into(), value);
+            }
+            metadata => {
+                let mut object = serde_json::Map::new();
+                object.insert(key.into(), value);
+                *metadata = Some(serde_json::Value::Object(object));
+            }
+        }
+        self
+    }
+
+    /// Attach the fields that changed as `{"before": {...}, "after": {...}}`
+    ///
+    /// Only top-level fields whose values differ are kept. A missing side,
+    /// as for creations and hard deletions, is recorded as `null`.
+    pub fn with_changes<T: Serialize>(self, before: Option<&T>, after: Option<&T>) -> Self {
+        let to_object = |value: Option<&T>| match value.map(serde_json::to_value) {
+            Some(Ok(serde_json::Value::Object(object))) => Some(object),
+            _ => None,
+        };
+        let (before, after) = (to_object(before), to_object(after));
+
+        let changes = match (before, after) {
+            (Some(mut before), Some(mut after)) => {
+                let unchanged: Vec<String> = before
+                    .iter()
+                    .filter(|(key, value)| after.get(*key) == Some(*value))
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                for key in unchanged {
+                    before.remove(&key);
+                    after.remove(&key);
+                }
+                serde_json::json!({ "before": before, "after": after })
+            }
+            (before, after) => serde_json::json!({ "before": before, "after": after }),
+        };
+
+        self.with_metadata(changes)
+    }
+}
+
+/// Criteria for searching the audit log; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditLogFilter {
+    /// User who performed the action
+    pub actor: Option<UserId>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub action: Option<String>,
+    /// Inclusive lower bound on `created_at`
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Position after the last entry of a page, newest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl AuditCursor {
+    /// Cursor continuing after the given entry
+    pub fn after(log: &AuditLog) -> Self {
+        Self {
+            created_at: log.created_at,
+            id: log.id,
+        }
+    }
+
+    /// Encode as an opaque token for clients
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.created_at.timestamp_micros(), self.id.simple())
+    }
+
+    /// Decode a token produced by [`AuditCursor::encode`]
+    pub fn decode(token: &str) -> Option<Self> {
+        let (micros, id) = token.split_once('_')?;
+        Some(Self {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
+/// One page of audit log entries, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogPage {
+    pub items: Vec<AuditLog>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl AuditLog {
+    /// Columns of the CSV export, in order
+    pub const CSV_COLUMNS: &'static [&'static str] = &[
+        "id", "created_at", "user_id", "action", "resource_type", "resource_id", "correlation_id", "metadata",
+    ];
+
+    /// Encode the CSV header row
+    pub fn csv_header() -> Result<Vec<u8>, csv::Error> {
+        csv_record(Self::CSV_COLUMNS.iter().map(|column| column.to_string()))
+    }
+
+    /// Encode this entry as one CSV row, with the metadata as JSON
+    pub fn to_csv_row(&self) -> Result<Vec<u8>, csv::Error> {
+        csv_record([
+            self.id.to_string(),
+            self.created_at.to_rfc3339(),
+            self.user_id.map(|id| id.to_string()).unwrap_or_default(),
+            self.action.clone(),
+            self.resource_type.clone(),
+            self.resource_id.clone().unwrap_or_default(),
+            self.correlation_id.clone().unwrap_or_default(),
+            self.metadata.as_ref().map(|metadata| metadata.to_string()).unwrap_or_default(),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_changes_keep_only_differing_fields() {
+        let before = json!({ "name": "Jane", "email": "jane@example.com", "version": 1 });
+        let after = json!({ "name": "Jane", "email": "jane@example.org", "version": 2 });
+
+        let entry = NewAuditLog::new("user.update", "user").with_changes(Some(&before), Some(&after));
+        assert_eq!(
+            entry.metadata,
+            Some(json!({
+                "before": { "email": "jane@example.com", "version": 1 },
+                "after": { "email": "jane@example.org", "version": 2 }
+            }))
+        );
+    }
+
+    #[test]
+    fn test_changes_for_creation_and_context() {
+        let after = json!({ "name": "Jane" });
+        let context = AuditContext::new(Some(Uuid::nil()), Some("req-123".to_string()));
+
+        let entry = NewAuditLog::new("user.create", "user")
+            .with_context(&context)
+            .with_changes(None, Some(&after));
+        assert_eq!(entry.metadata, Some(json!({ "before": null, "after": { "name": "Jane" } })));
+        assert_eq!(entry.user_id, Some(Uuid::nil()));
+        assert_eq!(entry.correlation_id.as_deref(), Some("req-123"));
+
+        let entry = entry.with_detail("reason", json!("spam"));
+        assert_eq!(entry.metadata.unwrap()["reason"], json!("spam"));
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = AuditCursor {
+            created_at: DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+
+        assert_eq!(AuditCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(AuditCursor::decode("not-a-cursor"), None);
+        assert_eq!(AuditCursor::decode("123_nope"), None);
+    }
+}
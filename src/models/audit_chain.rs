@@ -0,0 +1,548 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::audit::{AuditLog, NewAuditLog};
+
+/// `previous_hash` of the first entry in the chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Action of the chained entry recording that an erasure redacted an earlier entry
+pub const AUDIT_REDACTION_ACTION: &str = "audit.redact";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decode hex text; `None` when malformed, including an odd number of digits
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Serialize JSON with object keys sorted, so equal values always hash the same
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", serde_json::Value::String(key.clone()), canonical_json(&object[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            format!("[{}]", items.iter().map(canonical_json).collect::<Vec<_>>().join(","))
+        }
+        scalar => scalar.to_string(),
+    }
+}
+
+/// Hash of an entry's metadata, kept apart so erasure can redact the
+/// metadata without breaking the chain
+pub fn metadata_hash(metadata: Option<&serde_json::Value>) -> String {
+    let canonical = metadata.map(canonical_json).unwrap_or_else(|| "null".to_string());
+    hex(&Sha256::digest(canonical.as_bytes()))
+}
+
+/// Hash of an entry's canonicalized content, including the previous entry's hash
+fn link_hash(log: &AuditLog, sequence: i64, metadata_hash: &str, previous_hash: &str) -> String {
+    let content = serde_json::json!({
+        "sequence": sequence,
+        "previous_hash": previous_hash,
+        "id": log.id,
+        "created_at": log.created_at.timestamp_micros(),
+        "user_id": log.user_id,
+        "action": log.action,
+        "resource_type": log.resource_type,
+        "resource_id": log.resource_id,
+        "correlation_id": log.correlation_id,
+        "metadata_hash": metadata_hash,
+    });
+    hex(&Sha256::digest(canonical_json(&content).as_bytes()))
+}
+
+impl NewAuditLog {
+    /// Build the stored entry that follows `previous_hash` at `sequence`
+    ///
+    /// The ID and timestamp are assigned here, at database precision, because
+    /// they are part of the hash.
+    pub fn seal(&self, sequence: i64, previous_hash: &str) -> AuditLog {
+        let now = Utc::now();
+        let mut log = AuditLog {
+            id: Uuid::new_v4(),
+            user_id: self.user_id,
+            action: self.action.clone(),
+            resource_type: self.resource_type.clone(),
+            resource_id: self.resource_id.clone(),
+            metadata: self.metadata.clone(),
+            correlation_id: self.correlation_id.clone(),
+            created_at: DateTime::from_timestamp_micros(now.timestamp_micros()).unwrap_or(now),
+            sequence: Some(sequence),
+            metadata_hash: None,
+            previous_hash: Some(previous_hash.to_string()),
+            hash: None,
+            redacted_at: None,
+        };
+
+        let metadata_hash = metadata_hash(log.metadata.as_ref());
+        log.hash = Some(link_hash(&log, sequence, &metadata_hash, previous_hash));
+        log.metadata_hash = Some(metadata_hash);
+        log
+    }
+}
+
+/// Metadata of an entry replaced by an erasure
+///
+/// Each redaction is appended to the chain as its own entry, so the
+/// redacted entry's metadata can still be checked against the chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRedaction {
+    /// Sequence number of the redacted entry
+    pub sequence: i64,
+    /// Hash of the metadata the redaction replaced
+    pub replaced_metadata_hash: String,
+    /// Hash of the metadata left in its place
+    pub metadata_hash: String,
+}
+
+impl AuditRedaction {
+    pub fn new(sequence: i64, replaced: &serde_json::Value, redacted: &serde_json::Value) -> Self {
+        Self {
+            sequence,
+            replaced_metadata_hash: metadata_hash(Some(replaced)),
+            metadata_hash: metadata_hash(Some(redacted)),
+        }
+    }
+
+    /// Chained entry recording the redaction of entry `id`
+    pub fn to_entry(&self, id: Uuid) -> NewAuditLog {
+        NewAuditLog::new(AUDIT_REDACTION_ACTION, "audit_log")
+            .with_resource_id(id.to_string())
+            .with_metadata(serde_json::to_value(self).unwrap_or_default())
+    }
+
+    fn from_entry(log: &AuditLog) -> Option<Self> {
+        if log.action != AUDIT_REDACTION_ACTION {
+            return None;
+        }
+        serde_json::from_value(log.metadata.clone()?).ok()
+    }
+}
+
+/// Metadata history of a redacted entry, as recorded by its redactions
+struct RedactionTrail {
+    /// Metadata hash before the first redaction
+    original: String,
+    /// Metadata hash after the latest redaction
+    current: String,
+}
+
+/// Last entry of the chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditChainHead {
+    pub sequence: i64,
+    pub hash: String,
+}
+
+/// Signed snapshot of the chain head, for comparing against an exported copy
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditCheckpoint {
+    pub id: Uuid,
+    pub sequence: i64,
+    pub hash: String,
+    /// Hex HMAC-SHA256 over the sequence, hash and creation time
+    pub signature: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditCheckpoint {
+    /// Sign the given chain head
+    pub fn sign(head: &AuditChainHead, secret: &str) -> Self {
+        let now = Utc::now();
+        let mut checkpoint = Self {
+            id: Uuid::new_v4(),
+            sequence: head.sequence,
+            hash: head.hash.clone(),
+            signature: String::new(),
+            created_at: DateTime::from_timestamp_micros(now.timestamp_micros()).unwrap_or(now),
+        };
+        checkpoint.signature = checkpoint.expected_signature(secret);
+        checkpoint
+    }
+
+    /// Whether the signature was made with `secret`, compared in constant time
+    pub fn verify(&self, secret: &str) -> bool {
+        match unhex(&self.signature) {
+            Some(signature) => self.mac(secret).verify_slice(&signature).is_ok(),
+            None => false,
+        }
+    }
+
+    fn expected_signature(&self, secret: &str) -> String {
+        hex(&self.mac(secret).finalize().into_bytes())
+    }
+
+    fn mac(&self, secret: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}:{}:{}", self.sequence, self.hash, self.created_at.timestamp_micros()).as_bytes());
+        mac
+    }
+}
+
+/// First point at which the chain no longer matches its content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrokenLink {
+    /// Sequence number at which the chain breaks
+    pub sequence: i64,
+    /// Entry at that position, if there is one
+    pub id: Option<Uuid>,
+    pub reason: String,
+}
+
+/// Outcome of walking the audit chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditChainReport {
+    /// Entries checked before the walk stopped
+    pub entries_checked: i64,
+    /// Checkpoints whose hash was compared against the chain
+    pub checkpoints_checked: i64,
+    pub head: AuditChainHead,
+    /// Last entry moved out of the database by retention; the walk starts after it
+    pub archived_through: Option<i64>,
+    /// Signed checkpoint the walk started after, unless the whole chain was walked
+    pub verified_from_checkpoint: Option<i64>,
+    /// Entries whose metadata was redacted by an erasure, matching the recorded redactions
+    pub redacted_entries: i64,
+    pub first_broken: Option<BrokenLink>,
+}
+
+impl AuditChainReport {
+    pub fn is_intact(&self) -> bool {
+        self.first_broken.is_none()
+    }
+}
+
+/// Checks chained entries one at a time, in sequence order
+pub struct AuditChainVerifier {
+    first_sequence: i64,
+    next_sequence: i64,
+    previous_hash: String,
+    /// Signed hashes expected at given sequence numbers
+    checkpoints: HashMap<i64, String>,
+    /// Walked entries whose metadata no longer matches its hash, with the
+    /// entry ID, stored hash and hash of the current metadata
+    mismatched: BTreeMap<i64, (Uuid, String, String)>,
+    /// Redactions recorded in the walked entries, by redacted sequence number
+    redactions: BTreeMap<i64, RedactionTrail>,
+    pub entries_checked: i64,
+    pub checkpoints_checked: i64,
+    pub redacted_entries: i64,
+}
+
+impl AuditChainVerifier {
+    pub fn new(checkpoints: HashMap<i64, String>) -> Self {
+        Self {
+            first_sequence: 1,
+            next_sequence: 1,
+            previous_hash: GENESIS_HASH.to_string(),
+            checkpoints,
+            mismatched: BTreeMap::new(),
+            redactions: BTreeMap::new(),
+            entries_checked: 0,
+            checkpoints_checked: 0,
+            redacted_entries: 0,
+        }
+    }
+
+    /// Resume the walk after `start` instead of at the genesis entry
+    pub fn starting_after(mut self, start: &AuditChainHead) -> Self {
+        self.first_sequence = start.sequence + 1;
+        self.next_sequence = start.sequence + 1;
+        self.previous_hash = start.hash.clone();
+        self
+    }
+
+    /// Check the next entry of the chain
+    pub fn check(&mut self, log: &AuditLog) -> Result<(), BrokenLink> {
+        let sequence = self.next_sequence;
+        let broken = |reason: &str| BrokenLink {
+            sequence,
+            id: Some(log.id),
+            reason: reason.to_string(),
+        };
+
+        if log.sequence != Some(sequence) {
+            return Err(BrokenLink {
+                sequence,
+                id: None,
+                reason: format!("Entry {} is missing", sequence),
+            });
+        }
+        let (Some(stored_metadata_hash), Some(previous_hash), Some(hash)) =
+            (&log.metadata_hash, &log.previous_hash, &log.hash)
+        else {
+            return Err(broken("Entry is missing its hashes"));
+        };
+
+        if previous_hash != &self.previous_hash {
+            return Err(broken("Previous hash does not match the preceding entry"));
+        }
+        if &link_hash(log, sequence, stored_metadata_hash, previous_hash) != hash {
+            return Err(broken("Entry content does not match its hash"));
+        }
+        let current_metadata_hash = metadata_hash(log.metadata.as_ref());
+        if &current_metadata_hash != stored_metadata_hash {
+            if log.redacted_at.is_none() {
+                return Err(broken("Metadata does not match its hash"));
+            }
+            // Checked against the redaction entries that follow it once the walk ends
+            self.mismatched
+                .insert(sequence, (log.id, stored_metadata_hash.clone(), current_metadata_hash));
+        } else if let Some(redaction) = AuditRedaction::from_entry(log) {
+            if redaction.sequence >= sequence {
+                return Err(broken("Redaction refers to a later entry"));
+            }
+            match self.redactions.get_mut(&redaction.sequence) {
+                Some(trail) if trail.current != redaction.replaced_metadata_hash => {
+                    return Err(broken("Redaction does not follow the previous redaction of its entry"));
+                }
+                Some(trail) => trail.current = redaction.metadata_hash,
+                None => {
+                    self.redactions.insert(
+                        redaction.sequence,
+                        RedactionTrail {
+                            original: redaction.replaced_metadata_hash,
+                            current: redaction.metadata_hash,
+                        },
+                    );
+                }
+            }
+        }
+        if let Some(expected) = self.checkpoints.get(&sequence) {
+            if expected != hash {
+                return Err(broken("Entry hash does not match a signed checkpoint"));
+            }
+            self.checkpoints_checked += 1;
+        }
+
+        self.entries_checked += 1;
+        self.next_sequence += 1;
+        self.previous_hash = hash.clone();
+        Ok(())
+    }
+
+    /// Entries before the start of the walk that walked entries redacted
+    pub fn redacted_before_start(&self) -> Vec<i64> {
+        self.redactions.range(..self.first_sequence).map(|(sequence, _)| *sequence).collect()
+    }
+
+    /// Check redacted metadata against the recorded redactions
+    ///
+    /// `earlier` holds the entries listed by [`Self::redacted_before_start`]
+    /// that are still in the database.
+    pub fn check_redactions(&mut self, earlier: &[AuditLog]) -> Result<(), BrokenLink> {
+        let earlier = earlier.iter().filter_map(|log| {
+            let sequence = log.sequence.filter(|sequence| *sequence < self.first_sequence)?;
+            let stored = log.metadata_hash.clone()?;
+            Some((sequence, (log.id, stored, metadata_hash(log.metadata.as_ref()))))
+        });
+        let redacted: Vec<_> = self.mismatched.clone().into_iter().chain(earlier).collect();
+
+        for (sequence, (id, stored, current)) in redacted {
+            let recorded = self.redactions.get(&sequence);
+            if stored == current && recorded.is_none() {
+                continue;
+            }
+            if !recorded.is_some_and(|trail| trail.original == stored && trail.current == current) {
+                return Err(BrokenLink {
+                    sequence,
+                    id: Some(id),
+                    reason: "Metadata does not match its hash or its recorded redactions".to_string(),
+                });
+            }
+            self.redacted_entries += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Check that the walk ended where the recorded chain head says it should
+    pub fn finish(&self, head: &AuditChainHead) -> Result<(), BrokenLink> {
+        if head.sequence != self.next_sequence - 1 || head.hash != self.previous_hash {
+            return Err(BrokenLink {
+                sequence: self.next_sequence,
+                id: None,
+                reason: format!("Chain ends before the recorded head at entry {}", head.sequence),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chain(length: i64) -> Vec<AuditLog> {
+        let mut previous_hash = GENESIS_HASH.to_string();
+        (1..=length)
+            .map(|sequence| {
+                let log = NewAuditLog::new("user.update", "user")
+                    .with_metadata(json!({ "after": { "name": format!("Jane {}", sequence) } }))
+                    .seal(sequence, &previous_hash);
+                previous_hash = log.hash.clone().unwrap();
+                log
+            })
+            .collect()
+    }
+
+    fn verify(logs: &[AuditLog]) -> Result<(), BrokenLink> {
+        let mut verifier = AuditChainVerifier::new(HashMap::new());
+        for log in logs {
+            verifier.check(log)?;
+        }
+        let last = logs.last().unwrap();
+        verifier.finish(&AuditChainHead {
+            sequence: last.sequence.unwrap(),
+            hash: last.hash.clone().unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        assert_eq!(
+            canonical_json(&json!({ "b": [2, { "d": 1, "c": null }], "a": "x" })),
+            r#"{"a":"x","b":[2,{"c":null,"d":1}]}"#
+        );
+    }
+
+    #[test]
+    fn test_intact_chain_verifies() {
+        assert_eq!(verify(&chain(3)), Ok(()));
+    }
+
+    #[test]
+    fn test_edits_break_the_chain() {
+        let mut logs = chain(3);
+        logs[1].action = "user.delete".to_string();
+        assert_eq!(verify(&logs).unwrap_err().sequence, 2);
+
+        let mut logs = chain(3);
+        logs.remove(1);
+        assert_eq!(verify(&logs).unwrap_err().reason, "Entry 2 is missing");
+
+        let mut logs = chain(3);
+        logs[2].metadata = Some(json!({ "after": { "name": "Mallory" } }));
+        assert_eq!(verify(&logs).unwrap_err().reason, "Metadata does not match its hash");
+    }
+
+    /// Redact the metadata of `logs[index]` and append the redaction entry
+    fn redact(logs: &mut Vec<AuditLog>, index: usize, name: &str) {
+        let redacted = json!({ "after": { "name": name } });
+        let redaction = AuditRedaction::new(logs[index].sequence.unwrap(), logs[index].metadata.as_ref().unwrap(), &redacted);
+        logs[index].metadata = Some(redacted);
+        logs[index].redacted_at = Some(Utc::now());
+
+        let last = logs.last().unwrap();
+        let entry = redaction
+            .to_entry(logs[index].id)
+            .seal(last.sequence.unwrap() + 1, last.hash.as_ref().unwrap());
+        logs.push(entry);
+    }
+
+    fn check_all(verifier: &mut AuditChainVerifier, logs: &[AuditLog]) -> Result<(), BrokenLink> {
+        for log in logs {
+            verifier.check(log)?;
+        }
+        verifier.check_redactions(&[])
+    }
+
+    #[test]
+    fn test_recorded_redactions_keep_the_chain() {
+        let mut logs = chain(3);
+        redact(&mut logs, 0, "[erased]");
+        redact(&mut logs, 0, "[erased again]");
+
+        let mut verifier = AuditChainVerifier::new(HashMap::new());
+        assert_eq!(check_all(&mut verifier, &logs), Ok(()));
+        assert_eq!(verifier.redacted_entries, 1);
+    }
+
+    #[test]
+    fn test_redaction_must_be_recorded() {
+        // Marked as redacted, but without a redaction entry
+        let mut logs = chain(3);
+        logs[1].metadata = Some(json!({ "after": { "name": "Mallory" } }));
+        logs[1].redacted_at = Some(Utc::now());
+        let mut verifier = AuditChainVerifier::new(HashMap::new());
+        assert_eq!(check_all(&mut verifier, &logs).unwrap_err().sequence, 2);
+
+        // Edited after the recorded redaction
+        let mut logs = chain(3);
+        redact(&mut logs, 1, "[erased]");
+        logs[1].metadata = Some(json!({ "after": { "name": "Mallory" } }));
+        let mut verifier = AuditChainVerifier::new(HashMap::new());
+        assert_eq!(
+            check_all(&mut verifier, &logs).unwrap_err().reason,
+            "Metadata does not match its hash or its recorded redactions"
+        );
+    }
+
+    #[test]
+    fn test_redactions_of_entries_before_the_walk_are_checked() {
+        let mut logs = chain(3);
+        redact(&mut logs, 0, "[erased]");
+        let start = AuditChainHead {
+            sequence: 3,
+            hash: logs[2].hash.clone().unwrap(),
+        };
+
+        let mut verifier = AuditChainVerifier::new(HashMap::new()).starting_after(&start);
+        verifier.check(&logs[3]).unwrap();
+        assert_eq!(verifier.redacted_before_start(), vec![1]);
+        assert_eq!(verifier.check_redactions(&logs[..1]), Ok(()));
+
+        logs[0].metadata = Some(json!({ "after": { "name": "Mallory" } }));
+        assert_eq!(verifier.check_redactions(&logs[..1]).unwrap_err().sequence, 1);
+    }
+
+    #[test]
+    fn test_walk_resumes_after_archived_entries() {
+        let logs = chain(4);
+        let start = AuditChainHead {
+            sequence: 2,
+            hash: logs[1].hash.clone().unwrap(),
+        };
+
+        let mut verifier = AuditChainVerifier::new(HashMap::new()).starting_after(&start);
+        for log in &logs[2..] {
+            verifier.check(log).unwrap();
+        }
+        assert_eq!(verifier.entries_checked, 2);
+
+        let mut verifier = AuditChainVerifier::new(HashMap::new()).starting_after(&start);
+        assert_eq!(verifier.check(&logs[3]).unwrap_err().reason, "Entry 3 is missing");
+    }
+
+    #[test]
+    fn test_checkpoint_signature() {
+        let head = AuditChainHead { sequence: 7, hash: GENESIS_HASH.to_string() };
+        let checkpoint = AuditCheckpoint::sign(&head, "secret");
+
+        assert!(checkpoint.verify("secret"));
+        assert!(!checkpoint.verify("other"));
+
+        let mut forged = checkpoint.clone();
+        forged.signature = "not hex".to_string();
+        assert!(!forged.verify("secret"));
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::audit_chain::AuditChainHead;
+
+const PARTITION_PREFIX: &str = "audit_logs_";
+
+/// One month of `audit_logs`, stored as its own partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AuditPartition {
+    /// First day of the month
+    month: NaiveDate,
+}
+
+impl AuditPartition {
+    pub fn new(year: i32, month: u32) -> Option<Self> {
+        NaiveDate::from_ymd_opt(year, month, 1).map(|month| Self { month })
+    }
+
+    /// Partition holding entries created at `at`
+    pub fn containing(at: DateTime<Utc>) -> Self {
+        Self {
+            month: at.date_naive().with_day(1).expect("every month has a first day"),
+        }
+    }
+
+    /// Partition `months` after this one, or before it when negative
+    pub fn offset(&self, months: i32) -> Self {
+        let month = if months >= 0 {
+            self.month.checked_add_months(Months::new(months.unsigned_abs()))
+        } else {
+            self.month.checked_sub_months(Months::new(months.unsigned_abs()))
+        };
+        Self {
+            month: month.expect("partition month out of range"),
+        }
+    }
+
+    /// Table name, such as `audit_logs_2026_10`
+    ///
+    /// Only digits are interpolated, so the name is safe to splice into DDL.
+    pub fn name(&self) -> String {
+        format!("{}{:04}_{:02}", PARTITION_PREFIX, self.month.year(), self.month.month())
+    }
+
+    /// Parse a name produced by [`AuditPartition::name`]
+    pub fn parse(name: &str) -> Option<Self> {
+        let (year, month) = name.strip_prefix(PARTITION_PREFIX)?.split_once('_')?;
+        if year.len() != 4 || month.len() != 2 {
+            return None;
+        }
+        Self::new(year.parse().ok()?, month.parse().ok()?)
+    }
+
+    /// Inclusive lower bound on `created_at`
+    pub fn range_start(&self) -> DateTime<Utc> {
+        self.month.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc()
+    }
+
+    /// Exclusive upper bound on `created_at`
+    pub fn range_end(&self) -> DateTime<Utc> {
+        self.offset(1).range_start()
+    }
+
+    /// Whether every entry is at least `retention_months` whole months older than `now`
+    pub fn is_expired(&self, retention_months: u32, now: DateTime<Utc>) -> bool {
+        self.range_end() <= Self::containing(now).offset(-(retention_months as i32)).range_start()
+    }
+}
+
+/// Partition detached from `audit_logs` by retention
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditLogArchive {
+    pub partition_name: String,
+    pub range_start: DateTime<Utc>,
+    pub range_end: DateTime<Utc>,
+    pub entries: i64,
+    /// Last chained entry of the partition, where verification resumes
+    pub last_sequence: Option<i64>,
+    pub last_hash: Option<String>,
+    /// Key prefix of the NDJSON files in the archive store
+    pub archive_prefix: Option<String>,
+    pub archive_parts: Option<i32>,
+    pub detached_at: DateTime<Utc>,
+    /// When the detached table was dropped; `None` while it is kept in the database
+    pub dropped_at: Option<DateTime<Utc>>,
+}
+
+impl AuditLogArchive {
+    pub fn partition(&self) -> Option<AuditPartition> {
+        AuditPartition::parse(&self.partition_name)
+    }
+
+    /// Chain position at the end of the partition, if it held chained entries
+    pub fn chain_end(&self) -> Option<AuditChainHead> {
+        Some(AuditChainHead {
+            sequence: self.last_sequence?,
+            hash: self.last_hash.clone()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_partition_names_round_trip() {
+        let partition = AuditPartition::new(2026, 3).unwrap();
+        assert_eq!(partition.name(), "audit_logs_2026_03");
+        assert_eq!(AuditPartition::parse("audit_logs_2026_03"), Some(partition));
+
+        assert_eq!(AuditPartition::parse("audit_logs_2026_13"), None);
+        assert_eq!(AuditPartition::parse("audit_logs_default"), None);
+        assert_eq!(AuditPartition::parse("audit_checkpoints"), None);
+    }
+
+    #[test]
+    fn test_partition_ranges_cross_years() {
+        let at = Utc.with_ymd_and_hms(2026, 12, 31, 23, 59, 59).unwrap();
+        let partition = AuditPartition::containing(at);
+
+        assert_eq!(partition.range_start(), Utc.with_ymd_and_hms(2026, 12, 1, 0, 0, 0).unwrap());
+        assert_eq!(partition.range_end(), Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(partition.offset(-12).name(), "audit_logs_2025_12");
+    }
+
+    #[test]
+    fn test_partitions_expire_after_whole_months() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+
+        assert!(AuditPartition::new(2025, 9).unwrap().is_expired(12, now));
+        assert!(!AuditPartition::new(2025, 10).unwrap().is_expired(12, now));
+        assert!(!AuditPartition::new(2026, 10).unwrap().is_expired(0, now));
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use super::group::{GroupId, GroupMembership, GroupRole};
+
 /// Authentication request
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct AuthRequest {
@@ -24,4 +26,24 @@ pub struct CurrentUser {
     pub id: crate::models::UserId,
     pub email: String,
     pub name: String,
+    /// Groups the user belongs to, loaded when the user is authenticated
+    pub groups: Vec<GroupMembership>,
+}
+
+impl CurrentUser {
+    /// The user's role in a group, if they are a member
+    pub fn group_role(&self, group_id: GroupId) -> Option<GroupRole> {
+        self.groups
+            .iter()
+            .find(|membership| membership.group_id == group_id)
+            .map(|membership| membership.role)
+    }
+
+    pub fn is_group_member(&self, group_id: GroupId) -> bool {
+        self.group_role(group_id).is_some()
+    }
+
+    pub fn is_group_owner(&self, group_id: GroupId) -> bool {
+        self.group_role(group_id) == Some(GroupRole::Owner)
+    }
 }
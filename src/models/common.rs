@@ -30,6 +30,22 @@ impl<T> ApiResponse<T> {
     }
 }
 
+/// One page of a larger listing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub pagination: super::user::PaginationMetadata,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: i64, limit: i64, offset: i64) -> Self {
+        Self {
+            items,
+            pagination: super::user::PaginationMetadata::new(total, limit, offset),
+        }
+    }
+}
+
 /// Common error response
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
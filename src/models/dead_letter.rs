@@ -0,0 +1,183 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::outbox::OutboxEvent;
+use super::webhook::{WebhookDelivery, WebhookSubscriptionId};
+
+/// Dead letter ID type
+pub type DeadLetterId = Uuid;
+
+/// Event delivery that was given up on
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DeadLetter {
+    pub id: DeadLetterId,
+    pub event_id: Uuid,
+    pub event_type: String,
+    /// Subscription that did not accept the event; `None` when the event
+    /// could not be dispatched at all
+    pub subscription_id: Option<WebhookSubscriptionId>,
+    /// Envelope as sent to subscribers
+    pub payload: serde_json::Value,
+    /// Attempts made, including failed replays
+    pub attempts: i32,
+    pub last_error: String,
+    pub response_status: Option<i32>,
+    /// Start of the last response body
+    pub response_body: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Time of the last attempt
+    pub updated_at: DateTime<Utc>,
+    /// When a bulk replay queued the letter; cleared once the replay is attempted
+    pub replay_requested_at: Option<DateTime<Utc>>,
+}
+
+/// Dead letter for database insertion
+#[derive(Debug, Clone)]
+pub struct NewDeadLetter {
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub subscription_id: Option<WebhookSubscriptionId>,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub last_error: String,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+}
+
+impl NewDeadLetter {
+    /// A subscription's last failed delivery of an event
+    pub fn for_delivery(event: &OutboxEvent, delivery: &WebhookDelivery) -> Self {
+        Self {
+            event_id: event.id,
+            event_type: event.event_type.clone(),
+            subscription_id: Some(delivery.subscription_id),
+            payload: event.envelope(),
+            attempts: event.attempts,
+            last_error: delivery.error.clone().unwrap_or_else(|| "Delivery failed".to_string()),
+            response_status: delivery.response_status,
+            response_body: delivery.response_body.clone(),
+        }
+    }
+
+    /// An event that could not be dispatched to its subscribers
+    pub fn for_event(event: &OutboxEvent, error: &str) -> Self {
+        Self {
+            event_id: event.id,
+            event_type: event.event_type.clone(),
+            subscription_id: None,
+            payload: event.envelope(),
+            attempts: event.attempts,
+            last_error: error.to_string(),
+            response_status: None,
+            response_body: None,
+        }
+    }
+}
+
+/// Criteria selecting dead letters; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct DeadLetterFilter {
+    pub ids: Option<Vec<DeadLetterId>>,
+    pub subscription_id: Option<WebhookSubscriptionId>,
+    pub event_type: Option<String>,
+}
+
+/// Request to queue the dead letters matching a filter for replay, oldest first
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReplayDeadLettersRequest {
+    #[validate(length(min = 1, max = 100, message = "Between 1 and 100 IDs may be given"))]
+    pub ids: Option<Vec<DeadLetterId>>,
+
+    pub subscription_id: Option<WebhookSubscriptionId>,
+
+    pub event_type: Option<String>,
+
+    #[serde(default = "default_replay_limit")]
+    #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+    pub limit: i64,
+}
+
+fn default_replay_limit() -> i64 {
+    100
+}
+
+impl ReplayDeadLettersRequest {
+    pub fn filter(&self) -> DeadLetterFilter {
+        DeadLetterFilter {
+            ids: self.ids.clone(),
+            subscription_id: self.subscription_id,
+            event_type: self.event_type.as_ref().map(|t| t.trim().to_lowercase()),
+        }
+    }
+}
+
+/// Outcome of replaying one dead letter
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetterReplay {
+    pub dead_letter_id: DeadLetterId,
+    /// Whether the letter was resolved and removed
+    pub success: bool,
+    pub error: Option<String>,
+    /// Redelivery attempt, for letters of a subscription
+    pub delivery: Option<WebhookDelivery>,
+}
+
+/// Dead letters queued by a bulk replay
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetterReplayQueue {
+    pub queued: usize,
+    pub ids: Vec<DeadLetterId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OutboxStatus;
+
+    fn event() -> OutboxEvent {
+        OutboxEvent {
+            id: Uuid::new_v4(),
+            event_type: "user.created".to_string(),
+            aggregate_type: "user".to_string(),
+            aggregate_id: Uuid::nil().to_string(),
+            payload: serde_json::json!({"email": "ada@example.com"}),
+            correlation_id: None,
+            created_at: Utc::now(),
+            status: OutboxStatus::Pending,
+            attempts: 4,
+            next_attempt_at: Utc::now(),
+            last_error: None,
+            delivered_at: None,
+        }
+    }
+
+    #[test]
+    fn test_dead_letter_from_failed_delivery() {
+        let event = event();
+        let delivery = WebhookDelivery {
+            id: Uuid::new_v4(),
+            subscription_id: Uuid::new_v4(),
+            event_id: event.id,
+            event_type: event.event_type.clone(),
+            attempt: 4,
+            success: false,
+            response_status: Some(503),
+            response_body: Some("unavailable".to_string()),
+            error: Some("Endpoint responded with status 503".to_string()),
+            duration_ms: 12,
+            created_at: Utc::now(),
+        };
+
+        let letter = NewDeadLetter::for_delivery(&event, &delivery);
+        assert_eq!(letter.subscription_id, Some(delivery.subscription_id));
+        assert_eq!(letter.attempts, 4);
+        assert_eq!(letter.response_status, Some(503));
+        assert_eq!(letter.payload, event.envelope());
+
+        let letter = NewDeadLetter::for_event(&event, "Database unavailable");
+        assert_eq!(letter.subscription_id, None);
+        assert_eq!(letter.last_error, "Database unavailable");
+    }
+}
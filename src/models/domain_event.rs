@@ -0,0 +1,198 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use super::audit::AuditContext;
+use super::common::UserId;
+use super::user::User;
+
+/// Fields left out of user diffs because every change bumps them
+const UNDIFFED_USER_FIELDS: &[&str] = &["updated_at", "version"];
+
+/// Old and new value of a changed field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+/// Something that happened to a resource, as published on the event bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+    #[serde(rename = "user.created")]
+    UserCreated { user: User },
+    /// `changes` holds the serialized fields that differ, keyed by name
+    #[serde(rename = "user.updated")]
+    UserUpdated { user: User, changes: BTreeMap<String, FieldChange> },
+    /// `user` is the user as it was before deletion, or as anonymized for
+    /// an erasure
+    #[serde(rename = "user.deleted")]
+    UserDeleted { user: User },
+    #[serde(rename = "user.status_changed")]
+    UserStatusChanged { user: User, reason: Option<String> },
+}
+
+/// Kind of a [`DomainEvent`], for handlers to choose what they receive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DomainEventKind {
+    UserCreated,
+    UserUpdated,
+    UserDeleted,
+    UserStatusChanged,
+}
+
+impl DomainEventKind {
+    pub const ALL: [DomainEventKind; 4] = [
+        DomainEventKind::UserCreated,
+        DomainEventKind::UserUpdated,
+        DomainEventKind::UserDeleted,
+        DomainEventKind::UserStatusChanged,
+    ];
+
+    /// Event name such as `user.created`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DomainEventKind::UserCreated => "user.created",
+            DomainEventKind::UserUpdated => "user.updated",
+            DomainEventKind::UserDeleted => "user.deleted",
+            DomainEventKind::UserStatusChanged => "user.status_changed",
+        }
+    }
+
+    /// Look up a kind by its event name
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+}
+
+impl DomainEvent {
+    /// A user changed from `before` to `after`
+    pub fn user_updated(before: &User, after: &User) -> Self {
+        DomainEvent::UserUpdated {
+            user: after.clone(),
+            changes: field_changes(before, after, UNDIFFED_USER_FIELDS),
+        }
+    }
+
+    pub fn kind(&self) -> DomainEventKind {
+        match self {
+            DomainEvent::UserCreated { .. } => DomainEventKind::UserCreated,
+            DomainEvent::UserUpdated { .. } => DomainEventKind::UserUpdated,
+            DomainEvent::UserDeleted { .. } => DomainEventKind::UserDeleted,
+            DomainEvent::UserStatusChanged { .. } => DomainEventKind::UserStatusChanged,
+        }
+    }
+
+    /// User the event is about
+    pub fn user_id(&self) -> UserId {
+        match self {
+            DomainEvent::UserCreated { user }
+            | DomainEvent::UserUpdated { user, .. }
+            | DomainEvent::UserDeleted { user }
+            | DomainEvent::UserStatusChanged { user, .. } => user.id,
+        }
+    }
+}
+
+/// A published event with when and on whose behalf it happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    /// User who caused the event; `None` for anonymous or system actions
+    pub actor: Option<UserId>,
+    pub correlation_id: Option<String>,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+}
+
+impl EventEnvelope {
+    pub fn new(event: DomainEvent, context: &AuditContext) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            occurred_at: Utc::now(),
+            actor: context.actor,
+            correlation_id: context.correlation_id.clone(),
+            event,
+        }
+    }
+}
+
+/// Serialized fields that differ between two values, other than `ignored` ones
+pub fn field_changes<T: Serialize>(before: &T, after: &T, ignored: &[&str]) -> BTreeMap<String, FieldChange> {
+    let to_object = |value: &T| match serde_json::to_value(value) {
+        Ok(serde_json::Value::Object(object)) => object,
+        _ => serde_json::Map::new(),
+    };
+    let (before, mut after) = (to_object(before), to_object(after));
+
+    let mut changes = BTreeMap::new();
+    for (key, old) in before {
+        let new = after.remove(&key).unwrap_or(serde_json::Value::Null);
+        if old != new && !ignored.contains(&key.as_str()) {
+            changes.insert(key, FieldChange { old, new });
+        }
+    }
+    for (key, new) in after {
+        if !ignored.contains(&key.as_str()) {
+            changes.insert(key, FieldChange { old: serde_json::Value::Null, new });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+            metadata: serde_json::json!({}),
+            avatar_key: None,
+            avatar_url: None,
+        }
+    }
+
+    #[test]
+    fn test_user_update_diff_lists_changed_fields() {
+        let before = user();
+        let after = User {
+            name: "Ada Lovelace".to_string(),
+            updated_at: Utc::now() + chrono::Duration::seconds(1),
+            version: 2,
+            ..before.clone()
+        };
+
+        let DomainEvent::UserUpdated { changes, .. } = DomainEvent::user_updated(&before, &after) else {
+            panic!("expected a user.updated event");
+        };
+        assert_eq!(changes.keys().collect::<Vec<_>>(), vec!["name"]);
+        assert_eq!(changes["name"].old, serde_json::json!("Ada"));
+        assert_eq!(changes["name"].new, serde_json::json!("Ada Lovelace"));
+    }
+
+    #[test]
+    fn test_envelope_round_trips_with_the_event_name() {
+        let context = AuditContext::new(None, Some("req-1".to_string()));
+        let envelope = EventEnvelope::new(DomainEvent::UserCreated { user: user() }, &context);
+
+        let json = serde_json::to_value(&envelope).unwrap();
+        assert_eq!(json["type"], "user.created");
+        assert_eq!(json["correlation_id"], "req-1");
+
+        let parsed: EventEnvelope = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.event.kind(), DomainEventKind::UserCreated);
+        assert_eq!(parsed.event.user_id(), envelope.event.user_id());
+        assert_eq!(DomainEventKind::parse("user.created"), Some(DomainEventKind::UserCreated));
+    }
+}
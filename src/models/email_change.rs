@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::common::UserId;
+
+/// Lifecycle of a requested email change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailChangeStatus {
+    /// Waiting for the new address to confirm
+    Pending,
+    /// Applied to the user
+    Confirmed,
+    /// Undone from the old address
+    Reverted,
+    /// Replaced by a newer request or withdrawn
+    Cancelled,
+    /// Not confirmed in time
+    Expired,
+}
+
+/// A requested change of a user's email address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailChange {
+    pub id: Uuid,
+    pub user_id: UserId,
+    pub old_email: String,
+    pub new_email: String,
+    pub status: EmailChangeStatus,
+    /// Deadline for confirming the change
+    pub expires_at: DateTime<Utc>,
+    /// Deadline for reverting the change from the old address
+    pub revert_expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub reverted_at: Option<DateTime<Utc>>,
+}
+
+/// Email change ready to be stored; only token hashes are kept
+#[derive(Debug, Clone)]
+pub struct NewEmailChange {
+    pub user_id: UserId,
+    pub old_email: String,
+    pub new_email: String,
+    pub confirm_token_hash: String,
+    pub revert_token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub revert_expires_at: DateTime<Utc>,
+}
+
+/// Request body carrying a token from an email link
+#[derive(Debug, Deserialize, Validate)]
+pub struct EmailChangeTokenRequest {
+    #[validate(length(min = 1, max = 128, message = "Token is required"))]
+    pub token: String,
+}
+
+/// A single-use secret mailed out as part of a link
+pub struct EmailToken {
+    /// Value sent to the recipient
+    pub token: String,
+    /// Value stored for lookup
+    pub hash: String,
+}
+
+impl EmailToken {
+    /// Generate a token with 256 bits of randomness
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let hash = Self::hash(&token);
+        Self { token, hash }
+    }
+
+    /// Hash a token as it is stored
+    pub fn hash(token: &str) -> String {
+        Sha256::digest(token.trim().as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_tokens_are_unique_and_hashed() {
+        let first = EmailToken::generate();
+        let second = EmailToken::generate();
+
+        assert_eq!(first.token.len(), 64);
+        assert_ne!(first.token, second.token);
+        assert_eq!(first.hash, EmailToken::hash(&first.token));
+        assert_ne!(first.hash, first.token);
+    }
+
+    #[test]
+    fn test_status_serializes_lowercase() {
+        assert_eq!(serde_json::to_value(EmailChangeStatus::Pending).unwrap(), "pending");
+        let status: EmailChangeStatus = serde_json::from_value(serde_json::json!("reverted")).unwrap();
+        assert_eq!(status, EmailChangeStatus::Reverted);
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::common::UserId;
+use super::domain_event::{DomainEventKind, EventEnvelope};
+
+/// Largest payload Postgres accepts in a NOTIFY
+pub const MAX_NOTIFICATION_BYTES: usize = 7999;
+
+/// Event sent to stream clients whose `Last-Event-ID` is no longer buffered
+pub const EVENT_STREAM_RESET_EVENT: &str = "stream.reset";
+
+/// Domain event as shared between instances and sent to stream clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamEvent {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    pub actor: Option<UserId>,
+    pub correlation_id: Option<String>,
+    pub data: serde_json::Value,
+    /// Whether `data` was cut down to the user ID to fit in a notification;
+    /// clients should fetch the user instead
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl StreamEvent {
+    pub fn from_envelope(envelope: &EventEnvelope) -> Self {
+        let data = match serde_json::to_value(&envelope.event) {
+            Ok(serde_json::Value::Object(mut event)) => event.remove("data").unwrap_or_default(),
+            _ => serde_json::Value::Null,
+        };
+
+        Self {
+            id: envelope.id,
+            event_type: envelope.event.kind().as_str().to_string(),
+            occurred_at: envelope.occurred_at,
+            actor: envelope.actor,
+            correlation_id: envelope.correlation_id.clone(),
+            data,
+            truncated: false,
+        }
+        .fit_notification(envelope.event.user_id())
+    }
+
+    /// JSON sent as the NOTIFY payload
+    pub fn to_notification(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Cut `data` down to the user ID if the event is too large to notify
+    fn fit_notification(mut self, user_id: UserId) -> Self {
+        if self.to_notification().len() > MAX_NOTIFICATION_BYTES {
+            self.data = serde_json::json!({ "user_id": user_id });
+            self.truncated = true;
+        }
+        self
+    }
+}
+
+/// Parse a comma-separated list of event names into the kinds to stream
+///
+/// An empty list selects every kind.
+pub fn parse_event_types(types: &str) -> Result<Vec<DomainEventKind>, String> {
+    types
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| DomainEventKind::parse(name).ok_or_else(|| format!("Unknown event type '{}'", name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AuditContext, DomainEvent, User};
+
+    fn user(metadata: serde_json::Value) -> User {
+        User {
+            id: Uuid::new_v4(),
+            name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+            metadata,
+            avatar_key: None,
+            avatar_url: None,
+        }
+    }
+
+    #[test]
+    fn test_oversized_events_are_truncated_to_the_user_id() {
+        let context = AuditContext::system();
+        let small = EventEnvelope::new(DomainEvent::UserCreated { user: user(serde_json::json!({})) }, &context);
+        let event = StreamEvent::from_envelope(&small);
+        assert!(!event.truncated);
+        assert_eq!(event.event_type, "user.created");
+        assert_eq!(event.data["user"]["email"], "ada@example.com");
+
+        let large = user(serde_json::json!({ "notes": "x".repeat(MAX_NOTIFICATION_BYTES) }));
+        let user_id = large.id;
+        let event = StreamEvent::from_envelope(&EventEnvelope::new(DomainEvent::UserDeleted { user: large }, &context));
+        assert!(event.truncated);
+        assert_eq!(event.data, serde_json::json!({ "user_id": user_id }));
+        assert!(event.to_notification().len() <= MAX_NOTIFICATION_BYTES);
+    }
+
+    #[test]
+    fn test_parse_event_types() {
+        assert_eq!(
+            parse_event_types("user.created, user.deleted"),
+            Ok(vec![DomainEventKind::UserCreated, DomainEventKind::UserDeleted])
+        );
+        assert_eq!(parse_event_types(""), Ok(vec![]));
+        assert!(parse_event_types("user.created,group.created").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+use super::user::User;
+
+/// Bulk data file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    Csv,
+    Ndjson,
+}
+
+impl DataFormat {
+    /// HTTP content type for the format
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            DataFormat::Csv => "text/csv; charset=utf-8",
+            DataFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DataFormat::Csv => "csv",
+            DataFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// User field that can be selected for export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserColumn {
+    Id,
+    Name,
+    Email,
+    IsActive,
+    CreatedAt,
+    UpdatedAt,
+    Version,
+    Metadata,
+}
+
+impl UserColumn {
+    /// All exportable columns in their default order
+    pub const ALL: [UserColumn; 8] = [
+        UserColumn::Id,
+        UserColumn::Name,
+        UserColumn::Email,
+        UserColumn::IsActive,
+        UserColumn::CreatedAt,
+        UserColumn::UpdatedAt,
+        UserColumn::Version,
+        UserColumn::Metadata,
+    ];
+
+    /// Column name as used in query parameters and file headers
+    pub fn name(&self) -> &'static str {
+        match self {
+            UserColumn::Id => "id",
+            UserColumn::Name => "name",
+            UserColumn::Email => "email",
+            UserColumn::IsActive => "is_active",
+            UserColumn::CreatedAt => "created_at",
+            UserColumn::UpdatedAt => "updated_at",
+            UserColumn::Version => "version",
+            UserColumn::Metadata => "metadata",
+        }
+    }
+
+    /// Parse a comma-separated column list, rejecting unknown and repeated names
+    pub fn parse_list(list: &str) -> Result<Vec<UserColumn>, String> {
+        let mut columns = Vec::new();
+
+        for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let column = Self::ALL
+                .into_iter()
+                .find(|column| column.name() == name)
+                .ok_or_else(|| format!("Unknown column: {}", name))?;
+            if columns.contains(&column) {
+                return Err(format!("Duplicate column: {}", name));
+            }
+            columns.push(column);
+        }
+
+        if columns.is_empty() {
+            return Err("At least one column must be selected".to_string());
+        }
+
+        Ok(columns)
+    }
+
+    /// Value of the column for a user
+    pub fn value(&self, user: &User) -> serde_json::Value {
+        match self {
+            UserColumn::Id => serde_json::json!(user.id),
+            UserColumn::Name => serde_json::json!(user.name),
+            UserColumn::Email => serde_json::json!(user.email),
+            UserColumn::IsActive => serde_json::json!(user.is_active),
+            UserColumn::CreatedAt => serde_json::json!(user.created_at),
+            UserColumn::UpdatedAt => serde_json::json!(user.updated_at),
+            UserColumn::Version => serde_json::json!(user.version),
+            UserColumn::Metadata => user.metadata.clone(),
+        }
+    }
+}
+
+/// Parameters of a user export
+#[derive(Debug, Clone)]
+pub struct UserExportRequest {
+    pub format: DataFormat,
+    pub columns: Vec<UserColumn>,
+    /// Same containment filter as user listing
+    pub metadata_filter: Option<serde_json::Value>,
+}
+
+impl UserExportRequest {
+    /// Encode the file preamble (the CSV header row)
+    pub fn encode_header(&self) -> Result<Vec<u8>, csv::Error> {
+        match self.format {
+            DataFormat::Csv => csv_record(self.columns.iter().map(|column| column.name().to_string())),
+            DataFormat::Ndjson => Ok(Vec::new()),
+        }
+    }
+
+    /// Encode a single user as one line of the export
+    pub fn encode_user(&self, user: &User) -> Result<Vec<u8>, csv::Error> {
+        match self.format {
+            DataFormat::Csv => csv_record(self.columns.iter().map(|column| match column.value(user) {
+                serde_json::Value::String(value) => value,
+                value => value.to_string(),
+            })),
+            DataFormat::Ndjson => {
+                let object: serde_json::Map<String, serde_json::Value> = self
+                    .columns
+                    .iter()
+                    .map(|column| (column.name().to_string(), column.value(user)))
+                    .collect();
+                let mut line = serde_json::to_vec(&object).map_err(std::io::Error::from)?;
+                line.push(b'\n');
+                Ok(line)
+            }
+        }
+    }
+}
+
+pub(crate) fn csv_record<I: IntoIterator<Item = String>>(fields: I) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::WriterBuilder::new().from_writer(Vec::new());
+    writer.write_record(fields)?;
+    writer.into_inner().map_err(|e| csv::Error::from(e.into_error()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn export_user() -> User {
+        User {
+            id: Uuid::nil(),
+            name: "Doe, Jane".to_string(),
+            email: "jane@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 2,
+            metadata: serde_json::json!({ "plan": "pro" }),
+            avatar_key: None,
+            avatar_url: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_column_list() {
+        assert_eq!(
+            UserColumn::parse_list("email, name").unwrap(),
+            vec![UserColumn::Email, UserColumn::Name]
+        );
+        assert!(UserColumn::parse_list("email,password_hash").is_err());
+        assert!(UserColumn::parse_list("email,email").is_err());
+        assert!(UserColumn::parse_list(" , ").is_err());
+    }
+
+    #[test]
+    fn test_csv_encoding_quotes_fields() {
+        let request = UserExportRequest {
+            format: DataFormat::Csv,
+            columns: vec![UserColumn::Name, UserColumn::Version, UserColumn::Metadata],
+            metadata_filter: None,
+        };
+
+        assert_eq!(request.encode_header().unwrap(), b"name,version,metadata\n");
+        assert_eq!(
+            String::from_utf8(request.encode_user(&export_user()).unwrap()).unwrap(),
+            "\"Doe, Jane\",2,\"{\"\"plan\"\":\"\"pro\"\"}\"\n"
+        );
+    }
+
+    #[test]
+    fn test_ndjson_encoding_selects_columns() {
+        let request = UserExportRequest {
+            format: DataFormat::Ndjson,
+            columns: vec![UserColumn::Email, UserColumn::IsActive],
+            metadata_filter: None,
+        };
+
+        assert!(request.encode_header().unwrap().is_empty());
+        let line = request.encode_user(&export_user()).unwrap();
+        assert_eq!(line.last(), Some(&b'\n'));
+
+        let parsed: serde_json::Value = serde_json::from_slice(&line).unwrap();
+        assert_eq!(parsed, serde_json::json!({ "email": "jane@example.com", "is_active": true }));
+    }
+}
@@ -0,0 +1,240 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::common::{ApiResponse, Page};
+
+/// A resource whose JSON representation clients can trim with `?fields=`
+/// and extend with `?include=`
+pub trait Resource: Serialize {
+    /// Top-level fields that may be selected
+    const FIELDS: &'static [&'static str];
+    /// Related resources that may be embedded
+    const RELATIONS: &'static [&'static str] = &[];
+
+    /// ID that related resources are keyed by
+    fn resource_id(&self) -> Uuid;
+}
+
+/// Response data made of resources that can be projected field by field
+pub trait Projectable: Serialize {
+    type Resource: Resource;
+
+    /// The resources in the data, in order
+    fn resources(&self) -> Vec<&Self::Resource>;
+
+    /// Serialize the data with each resource projected
+    fn project(&self, fieldset: &Fieldset, included: &Included) -> Result<Value, serde_json::Error>;
+}
+
+/// Sparse fieldset and embedded relations requested by a client
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fieldset {
+    /// Fields to keep; all fields when `None`
+    fields: Option<Vec<String>>,
+    /// Relations to embed
+    include: Vec<String>,
+}
+
+impl Fieldset {
+    /// Parse `fields` and `include` lists, rejecting names `R` does not have
+    pub fn parse<R: Resource>(fields: Option<&str>, include: Option<&str>) -> Result<Self, String> {
+        let fields = fields
+            .map(|list| parse_names(list, R::FIELDS, "field"))
+            .transpose()?;
+        let include = include
+            .map(|list| parse_names(list, R::RELATIONS, "relation"))
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Self { fields, include })
+    }
+
+    /// Whether responses carry the whole resource and nothing more
+    pub fn is_full(&self) -> bool {
+        self.fields.is_none() && self.include.is_empty()
+    }
+
+    /// Whether the client asked to embed `relation`
+    pub fn includes(&self, relation: &str) -> bool {
+        self.include.iter().any(|name| name == relation)
+    }
+
+    /// Project one serialized resource and embed its included relations
+    pub fn project_resource<R: Resource>(&self, resource: &R, included: &Included) -> Result<Value, serde_json::Error> {
+        let mut object = match serde_json::to_value(resource)? {
+            Value::Object(object) => object,
+            other => return Ok(other),
+        };
+
+        if let Some(fields) = &self.fields {
+            object.retain(|key, _| fields.contains(key));
+        }
+
+        for relation in &self.include {
+            let value = included.get(relation, resource.resource_id()).cloned().unwrap_or(Value::Null);
+            object.insert(relation.clone(), value);
+        }
+
+        Ok(Value::Object(object))
+    }
+}
+
+/// Parse a comma-separated list of names, all of which must be in `allowed`
+fn parse_names(list: &str, allowed: &[&str], kind: &str) -> Result<Vec<String>, String> {
+    let mut names = Vec::new();
+
+    for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        if !allowed.contains(&name) {
+            return Err(format!("Unknown {}: {}", kind, name));
+        }
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    if names.is_empty() {
+        return Err(format!("At least one {} must be listed", kind));
+    }
+
+    Ok(names)
+}
+
+/// Related resources loaded for `?include=`, keyed by relation and then by
+/// the ID of the resource they belong to
+#[derive(Debug, Clone, Default)]
+pub struct Included {
+    relations: HashMap<String, HashMap<Uuid, Value>>,
+}
+
+impl Included {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the value of `relation` for the resource with ID `owner`
+    pub fn insert(&mut self, relation: &str, owner: Uuid, value: Value) {
+        self.relations.entry(relation.to_string()).or_default().insert(owner, value);
+    }
+
+    pub fn get(&self, relation: &str, owner: Uuid) -> Option<&Value> {
+        self.relations.get(relation)?.get(&owner)
+    }
+}
+
+impl<R: Resource> Projectable for R {
+    type Resource = R;
+
+    fn resources(&self) -> Vec<&R> {
+        vec![self]
+    }
+
+    fn project(&self, fieldset: &Fieldset, included: &Included) -> Result<Value, serde_json::Error> {
+        fieldset.project_resource(self, included)
+    }
+}
+
+impl<R: Resource> Projectable for Vec<R> {
+    type Resource = R;
+
+    fn resources(&self) -> Vec<&R> {
+        self.iter().collect()
+    }
+
+    fn project(&self, fieldset: &Fieldset, included: &Included) -> Result<Value, serde_json::Error> {
+        self.iter()
+            .map(|resource| fieldset.project_resource(resource, included))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
+    }
+}
+
+impl<R: Resource> Projectable for Page<R> {
+    type Resource = R;
+
+    fn resources(&self) -> Vec<&R> {
+        self.items.iter().collect()
+    }
+
+    fn project(&self, fieldset: &Fieldset, included: &Included) -> Result<Value, serde_json::Error> {
+        let mut page = Map::new();
+        page.insert("items".to_string(), self.items.project(fieldset, included)?);
+        page.insert("pagination".to_string(), serde_json::to_value(&self.pagination)?);
+        Ok(Value::Object(page))
+    }
+}
+
+impl<T: Projectable> ApiResponse<T> {
+    /// Serialize the response with its data shaped to the client's fieldset
+    pub fn project(self, fieldset: &Fieldset, included: &Included) -> Result<ApiResponse<Value>, serde_json::Error> {
+        Ok(ApiResponse {
+            data: self.data.project(fieldset, included)?,
+            message: self.message,
+            timestamp: self.timestamp,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Widget {
+        id: Uuid,
+        name: String,
+        color: String,
+    }
+
+    impl Resource for Widget {
+        const FIELDS: &'static [&'static str] = &["id", "name", "color"];
+        const RELATIONS: &'static [&'static str] = &["parts"];
+
+        fn resource_id(&self) -> Uuid {
+            self.id
+        }
+    }
+
+    fn widget() -> Widget {
+        Widget {
+            id: Uuid::nil(),
+            name: "Sprocket".to_string(),
+            color: "red".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_fieldset() {
+        let fieldset = Fieldset::parse::<Widget>(Some("name, id,name"), Some("parts")).unwrap();
+        assert!(fieldset.includes("parts"));
+        assert!(!fieldset.is_full());
+        assert_eq!(fieldset.fields, Some(vec!["name".to_string(), "id".to_string()]));
+
+        assert_eq!(Fieldset::parse::<Widget>(None, None).unwrap(), Fieldset::default());
+        assert!(Fieldset::default().is_full());
+        assert!(Fieldset::parse::<Widget>(Some("name,weight"), None).is_err());
+        assert!(Fieldset::parse::<Widget>(None, Some("owner")).is_err());
+        assert!(Fieldset::parse::<Widget>(Some(" , "), None).is_err());
+    }
+
+    #[test]
+    fn test_project_keeps_selected_fields_and_embeds_relations() {
+        let fieldset = Fieldset::parse::<Widget>(Some("name"), Some("parts")).unwrap();
+        let mut included = Included::new();
+        included.insert("parts", Uuid::nil(), json!([{ "name": "cog" }]));
+
+        let response = ApiResponse::new(vec![widget()]).project(&fieldset, &included).unwrap();
+        assert_eq!(response.data, json!([{ "name": "Sprocket", "parts": [{ "name": "cog" }] }]));
+    }
+
+    #[test]
+    fn test_project_without_fieldset_keeps_everything() {
+        let page = Page::new(vec![widget()], 1, 20, 0);
+        let projected = page.project(&Fieldset::default(), &Included::new()).unwrap();
+
+        assert_eq!(projected["items"][0], serde_json::to_value(widget()).unwrap());
+        assert_eq!(projected["pagination"]["total"], json!(1));
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::common::UserId;
+use super::fieldset::Resource;
+
+/// Group ID type
+pub type GroupId = Uuid;
+
+/// A member's role within a group
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupRole {
+    /// Manages the group and its members
+    Owner,
+    #[default]
+    Member,
+}
+
+/// A named group of users
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Group {
+    pub id: GroupId,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Resource for Group {
+    const FIELDS: &'static [&'static str] = &["id", "name", "description", "created_by", "created_at", "updated_at"];
+
+    fn resource_id(&self) -> GroupId {
+        self.id
+    }
+}
+
+/// A user's membership of a group, as used for authorization checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupMembership {
+    pub group_id: GroupId,
+    pub role: GroupRole,
+}
+
+/// A member of a group, as listed for the group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupMember {
+    pub user_id: UserId,
+    pub name: String,
+    pub email: String,
+    pub role: GroupRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// A group a user belongs to, as listed for the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserGroup {
+    #[serde(flatten)]
+    pub group: Group,
+    pub role: GroupRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// Request to create a group; the creator becomes its owner
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateGroupRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: String,
+
+    #[validate(length(max = 1000, message = "Description must not exceed 1000 characters"))]
+    pub description: Option<String>,
+}
+
+/// Request to update a group
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateGroupRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: Option<String>,
+
+    #[validate(length(max = 1000, message = "Description must not exceed 1000 characters"))]
+    pub description: Option<String>,
+}
+
+/// Request to add a member to a group or change their role
+#[derive(Debug, Deserialize)]
+pub struct SetGroupMemberRequest {
+    #[serde(default)]
+    pub role: GroupRole,
+}
+
+impl CreateGroupRequest {
+    /// Trim the name and drop an empty description
+    pub fn normalize(mut self) -> Self {
+        self.name = self.name.trim().to_string();
+        self.description = self.description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+        self
+    }
+}
+
+impl UpdateGroupRequest {
+    /// Trim the fields; an empty description clears it
+    pub fn normalize(mut self) -> Self {
+        self.name = self.name.map(|name| name.trim().to_string());
+        self.description = self.description.map(|d| d.trim().to_string());
+        self
+    }
+
+    pub fn has_updates(&self) -> bool {
+        self.name.is_some() || self.description.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_member_is_default_role() {
+        let request: SetGroupMemberRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(request.role, GroupRole::Member);
+
+        let request: SetGroupMemberRequest = serde_json::from_str(r#"{"role":"owner"}"#).unwrap();
+        assert_eq!(request.role, GroupRole::Owner);
+    }
+
+    #[test]
+    fn test_create_request_normalization() {
+        let request = CreateGroupRequest {
+            name: "  Platform  ".to_string(),
+            description: Some("   ".to_string()),
+        }
+        .normalize();
+
+        assert_eq!(request.name, "Platform");
+        assert_eq!(request.description, None);
+        assert!(request.validate().is_ok());
+    }
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A response kept under an idempotency key, replayed byte for byte on retry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Outcome of claiming an idempotency key for a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyClaim {
+    /// The key is new (or its previous holder gave up); process the request
+    ///
+    /// `locked_until` identifies this claim when completing or releasing the key.
+    Acquired { locked_until: DateTime<Utc> },
+    /// The key already has a response for the same request
+    Replay(StoredResponse),
+    /// The first request under the key is still being processed
+    InFlight,
+    /// The key was first used for a different request
+    Mismatch,
+}
+
+/// Fingerprint a request so retries can be told apart from key reuse
+pub fn request_fingerprint(method: &str, path_and_query: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path_and_query.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_fingerprint() {
+        let fingerprint = request_fingerprint("POST", "/api/v1/users", br#"{"name":"A"}"#);
+
+        assert_eq!(fingerprint.len(), 64);
+        assert_eq!(fingerprint, request_fingerprint("POST", "/api/v1/users", br#"{"name":"A"}"#));
+        assert_ne!(fingerprint, request_fingerprint("POST", "/api/v1/users", br#"{"name":"B"}"#));
+        assert_ne!(fingerprint, request_fingerprint("POST", "/api/v1/groups", br#"{"name":"A"}"#));
+    }
+}
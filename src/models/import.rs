@@ -0,0 +1,310 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::common::UserId;
+use super::export::{csv_record, DataFormat};
+use super::user::CreateUserRequest;
+
+/// Column names accepted in a CSV import header
+const CSV_IMPORT_COLUMNS: [&str; 3] = ["name", "email", "metadata"];
+
+/// How rows whose email already belongs to a user are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Leave the existing user untouched
+    #[default]
+    Skip,
+    /// Overwrite the existing user's name and metadata
+    Update,
+}
+
+/// Parameters of a user import
+#[derive(Debug, Clone, Copy)]
+pub struct UserImportOptions {
+    pub format: DataFormat,
+    pub mode: ImportMode,
+    /// Validate and classify rows without writing anything
+    pub dry_run: bool,
+}
+
+/// A row read from an import file, before validation
+#[derive(Debug)]
+pub struct ImportRow {
+    /// Line of the file the row started on
+    pub line: u64,
+    pub request: Result<CreateUserRequest, String>,
+}
+
+/// Outcome of a single import row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    Created,
+    Updated,
+    Skipped,
+    /// The row failed validation and was not written
+    Invalid,
+    /// The row's batch could not be written
+    Failed,
+}
+
+/// Per-row entry of an import report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowResult {
+    pub line: u64,
+    pub email: Option<String>,
+    pub status: ImportRowStatus,
+    pub user_id: Option<UserId>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+impl ImportRowResult {
+    pub fn invalid(line: u64, email: Option<String>, errors: Vec<String>) -> Self {
+        Self {
+            line,
+            email,
+            status: ImportRowStatus::Invalid,
+            user_id: None,
+            errors,
+        }
+    }
+}
+
+/// Row counts of an import, by outcome
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub total: u64,
+    pub created: u64,
+    pub updated: u64,
+    pub skipped: u64,
+    pub invalid: u64,
+    pub failed: u64,
+}
+
+impl ImportSummary {
+    /// Count one row
+    pub fn record(&mut self, status: ImportRowStatus) {
+        self.total += 1;
+        match status {
+            ImportRowStatus::Created => self.created += 1,
+            ImportRowStatus::Updated => self.updated += 1,
+            ImportRowStatus::Skipped => self.skipped += 1,
+            ImportRowStatus::Invalid => self.invalid += 1,
+            ImportRowStatus::Failed => self.failed += 1,
+        }
+    }
+
+    /// Tally a report
+    pub fn from_results(results: &[ImportRowResult]) -> Self {
+        let mut summary = Self::default();
+        for result in results {
+            summary.record(result.status);
+        }
+        summary
+    }
+}
+
+/// Lifecycle of an import job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportJobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A user import job, as reported by the status endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub status: ImportJobStatus,
+    pub format: DataFormat,
+    pub mode: ImportMode,
+    pub dry_run: bool,
+    pub total_rows: i32,
+    pub processed_rows: i32,
+    pub summary: ImportSummary,
+    /// Reason the job failed as a whole
+    pub error: Option<String>,
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Result of an import that ran inline
+#[derive(Debug, Serialize)]
+pub struct ImportResult {
+    pub job: ImportJob,
+    pub report: Vec<ImportRowResult>,
+}
+
+/// Split an uploaded file into rows
+///
+/// Problems with the file as a whole (encoding, CSV header) are returned as
+/// an error; problems with a single row are kept on that row.
+pub fn parse_import(format: DataFormat, data: &[u8]) -> Result<Vec<ImportRow>, String> {
+    match format {
+        DataFormat::Csv => parse_csv_import(data),
+        DataFormat::Ndjson => parse_ndjson_import(data),
+    }
+}
+
+fn parse_csv_import(data: &[u8]) -> Result<Vec<ImportRow>, String> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data);
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Invalid CSV header: {}", e))?
+        .iter()
+        .map(str::to_lowercase)
+        .collect();
+    if let Some(unknown) = headers.iter().find(|name| !CSV_IMPORT_COLUMNS.contains(&name.as_str())) {
+        return Err(format!("Unknown CSV column: {}", unknown));
+    }
+    let position = |name: &str| headers.iter().position(|header| header == name);
+    let (Some(name_index), Some(email_index)) = (position("name"), position("email")) else {
+        return Err("CSV header must include name and email columns".to_string());
+    };
+    let metadata_index = position("metadata");
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let row = match record {
+            Ok(record) => {
+                let field = |index: usize| record.get(index).unwrap_or_default().to_string();
+                let metadata = match metadata_index.map(&field).filter(|value| !value.is_empty()) {
+                    Some(value) => serde_json::from_str(&value)
+                        .map(Some)
+                        .map_err(|e| format!("metadata: invalid JSON: {}", e)),
+                    None => Ok(None),
+                };
+                ImportRow {
+                    line: record.position().map_or(0, |position| position.line()),
+                    request: metadata.map(|metadata| CreateUserRequest {
+                        name: field(name_index),
+                        email: field(email_index),
+                        metadata,
+                    }),
+                }
+            }
+            Err(e) => ImportRow {
+                line: e.position().map_or(0, |position| position.line()),
+                request: Err(e.to_string()),
+            },
+        };
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+fn parse_ndjson_import(data: &[u8]) -> Result<Vec<ImportRow>, String> {
+    let text = std::str::from_utf8(data).map_err(|e| format!("Import file is not valid UTF-8: {}", e))?;
+
+    Ok(text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| ImportRow {
+            line: index as u64 + 1,
+            request: serde_json::from_str(line).map_err(|e| e.to_string()),
+        })
+        .collect())
+}
+
+/// Encode an import report for download
+pub fn encode_import_report(format: DataFormat, results: &[ImportRowResult]) -> Result<Vec<u8>, csv::Error> {
+    match format {
+        DataFormat::Csv => {
+            let mut output = csv_record(["line", "status", "email", "user_id", "errors"].map(String::from))?;
+            for result in results {
+                let status = serde_json::to_value(result.status).map_err(std::io::Error::from)?;
+                output.extend(csv_record([
+                    result.line.to_string(),
+                    status.as_str().unwrap_or_default().to_string(),
+                    result.email.clone().unwrap_or_default(),
+                    result.user_id.map(|id| id.to_string()).unwrap_or_default(),
+                    result.errors.join("; "),
+                ])?);
+            }
+            Ok(output)
+        }
+        DataFormat::Ndjson => {
+            let mut output = Vec::new();
+            for result in results {
+                serde_json::to_writer(&mut output, result).map_err(std::io::Error::from)?;
+                output.push(b'\n');
+            }
+            Ok(output)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_import() {
+        let data = b"Email,Name,metadata\njane@example.com, Jane ,\"{\"\"plan\"\":\"\"pro\"\"}\"\nbob@example.com,Bob,{not json\nshort\n";
+        let rows = parse_import(DataFormat::Csv, data).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        let first = rows[0].request.as_ref().unwrap();
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(first.name, "Jane");
+        assert_eq!(first.email, "jane@example.com");
+        assert_eq!(first.metadata, Some(serde_json::json!({ "plan": "pro" })));
+        assert!(rows[1].request.as_ref().unwrap_err().starts_with("metadata"));
+        assert_eq!(rows[2].line, 4);
+        assert!(rows[2].request.is_err());
+    }
+
+    #[test]
+    fn test_csv_import_header_is_checked() {
+        assert!(parse_import(DataFormat::Csv, b"name\nJane\n").is_err());
+        assert!(parse_import(DataFormat::Csv, b"name,email,password\nJane,j@example.com,x\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_ndjson_import_keeps_line_numbers() {
+        let data = b"{\"name\":\"Jane\",\"email\":\"jane@example.com\"}\n\n{\"name\":\"Bob\"}\n";
+        let rows = parse_import(DataFormat::Ndjson, data).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 1);
+        assert!(rows[0].request.is_ok());
+        assert_eq!(rows[1].line, 3);
+        assert!(rows[1].request.is_err());
+    }
+
+    #[test]
+    fn test_summary_and_csv_report() {
+        let results = vec![
+            ImportRowResult {
+                line: 2,
+                email: Some("jane@example.com".to_string()),
+                status: ImportRowStatus::Created,
+                user_id: Some(Uuid::nil()),
+                errors: Vec::new(),
+            },
+            ImportRowResult::invalid(3, None, vec!["email: Invalid email format".to_string(), "name: required".to_string()]),
+        ];
+
+        let summary = ImportSummary::from_results(&results);
+        assert_eq!((summary.total, summary.created, summary.invalid), (2, 1, 1));
+
+        let report = String::from_utf8(encode_import_report(DataFormat::Csv, &results).unwrap()).unwrap();
+        assert_eq!(
+            report,
+            "line,status,email,user_id,errors\n\
+             2,created,jane@example.com,00000000-0000-0000-0000-000000000000,\n\
+             3,invalid,,,email: Invalid email format; name: required\n"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::common::UserId;
+use super::group::{GroupId, GroupRole};
+
+/// Invitation ID type
+pub type InvitationId = Uuid;
+
+/// Lifecycle of an invitation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    /// Waiting for the invitee to accept
+    Pending,
+    /// The invitee created their account
+    Accepted,
+    /// Not accepted in time
+    Expired,
+    /// Withdrawn before it was accepted
+    Revoked,
+}
+
+/// A group the invitee joins when accepting, and their role in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvitationGroup {
+    pub group_id: GroupId,
+    #[serde(default)]
+    pub role: GroupRole,
+}
+
+/// An invitation for someone to create an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invitation {
+    pub id: InvitationId,
+    pub email: String,
+    /// Name suggested by the inviter; the invitee may replace it
+    pub name: Option<String>,
+    pub groups: Vec<InvitationGroup>,
+    pub status: InvitationStatus,
+    pub invited_by: Option<UserId>,
+    /// Account created on acceptance
+    pub user_id: Option<UserId>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Invitation ready to be stored; only the token hash is kept
+#[derive(Debug, Clone)]
+pub struct NewInvitation {
+    pub email: String,
+    pub name: Option<String>,
+    pub groups: Vec<InvitationGroup>,
+    pub token_hash: String,
+    pub invited_by: Option<UserId>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Request to invite someone by email
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateInvitationRequest {
+    #[validate(email(message = "Invalid email format"))]
+    #[validate(length(max = 320, message = "Email must not exceed 320 characters"))]
+    pub email: String,
+
+    #[validate(length(min = 1, max = 255, message = "Name must be between 1 and 255 characters"))]
+    pub name: Option<String>,
+
+    /// Groups to add the invitee to once they accept
+    #[serde(default)]
+    pub groups: Vec<InvitationGroup>,
+}
+
+/// Request to accept an invitation with the mailed token
+#[derive(Debug, Deserialize, Validate)]
+pub struct AcceptInvitationRequest {
+    #[validate(length(min = 1, max = 128, message = "Token is required"))]
+    pub token: String,
+
+    /// Required when the invitation does not carry a name
+    #[validate(length(min = 1, max = 255, message = "Name must be between 1 and 255 characters"))]
+    pub name: Option<String>,
+
+    #[validate(length(min = 8, max = 128, message = "Password must be between 8 and 128 characters"))]
+    pub password: String,
+}
+
+impl CreateInvitationRequest {
+    /// Trim the fields, lowercase the email and drop repeated groups
+    pub fn normalize(mut self) -> Self {
+        self.email = self.email.trim().to_lowercase();
+        self.name = self.name.map(|name| name.trim().to_string()).filter(|name| !name.is_empty());
+
+        let mut groups: Vec<InvitationGroup> = Vec::with_capacity(self.groups.len());
+        for group in self.groups {
+            match groups.iter_mut().find(|existing| existing.group_id == group.group_id) {
+                Some(existing) => existing.role = group.role,
+                None => groups.push(group),
+            }
+        }
+        self.groups = groups;
+        self
+    }
+}
+
+impl AcceptInvitationRequest {
+    /// Trim the name, dropping it when empty
+    pub fn normalize(mut self) -> Self {
+        self.name = self.name.map(|name| name.trim().to_string()).filter(|name| !name.is_empty());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_create_request_normalization() {
+        let group_id = Uuid::new_v4();
+        let request: CreateInvitationRequest = serde_json::from_value(json!({
+            "email": "  Jane@Example.COM ",
+            "name": "  ",
+            "groups": [
+                { "group_id": group_id },
+                { "group_id": group_id, "role": "owner" }
+            ]
+        }))
+        .unwrap();
+        let request = request.normalize();
+
+        assert_eq!(request.email, "jane@example.com");
+        assert_eq!(request.name, None);
+        assert_eq!(request.groups, vec![InvitationGroup { group_id, role: GroupRole::Owner }]);
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn test_accept_request_requires_long_password() {
+        let request: AcceptInvitationRequest =
+            serde_json::from_value(json!({ "token": "abc", "password": "short" })).unwrap();
+        assert!(request.validate().is_err());
+    }
+}
@@ -1,10 +1,46 @@
 pub mod common;
 pub mod user;
 pub mod auth;
+pub mod audit;
+pub mod audit_chain;
+pub mod audit_partition;
+pub mod export;
+pub mod import;
+pub mod privacy;
+pub mod email_change;
+pub mod group;
+pub mod idempotency;
+pub mod fieldset;
+pub mod invitation;
+pub mod outbox;
+pub mod webhook;
+pub mod dead_letter;
+pub mod domain_event;
+pub mod event_stream;
+pub mod websocket;
 
 pub use common::*;
 pub use user::{
     User, CreateUserRequest, UpdateUserRequest, NewUser, SafeUser,
-    UserListResponse, PaginationMetadata, UserStats, UserSearchFilters, UserStatusRequest
+    UserListResponse, PaginationMetadata, UserStats, UserSearchFilters, UserStatusRequest,
+    UserPatch, UserPatchError, UserSearchResult, UserSearchHighlight,
+    HEADLINE_START, HEADLINE_STOP
 };
 pub use auth::*;
+pub use audit::*;
+pub use audit_chain::*;
+pub use audit_partition::*;
+pub use export::*;
+pub use import::*;
+pub use privacy::*;
+pub use email_change::*;
+pub use group::*;
+pub use idempotency::*;
+pub use fieldset::*;
+pub use invitation::*;
+pub use outbox::*;
+pub use webhook::*;
+pub use dead_letter::*;
+pub use domain_event::*;
+pub use event_stream::*;
+pub use websocket::*;
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::audit::AuditContext;
+use super::common::UserId;
+use super::user::User;
+
+/// Delivery state of an outbox event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutboxStatus {
+    Pending,
+    Delivered,
+    /// Gave up after the last attempt
+    Failed,
+}
+
+/// Domain event waiting in, or delivered from, the outbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    pub id: Uuid,
+    /// Event name such as `user.created`
+    pub event_type: String,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub payload: serde_json::Value,
+    pub correlation_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub status: OutboxStatus,
+    /// Delivery attempts made so far
+    pub attempts: i32,
+    /// Earliest time of the next attempt while pending
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl OutboxEvent {
+    /// Body sent to receivers, with the event ID for deduplicating redeliveries
+    pub fn envelope(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "type": self.event_type,
+            "timestamp": self.created_at,
+            "data": self.payload,
+        })
+    }
+}
+
+/// Outbox event for database insertion
+#[derive(Debug, Clone)]
+pub struct NewOutboxEvent {
+    pub event_type: String,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub payload: serde_json::Value,
+    pub correlation_id: Option<String>,
+}
+
+impl NewOutboxEvent {
+    pub fn new<E: Into<String>, A: Into<String>, I: ToString>(
+        event_type: E,
+        aggregate_type: A,
+        aggregate_id: I,
+        payload: serde_json::Value,
+    ) -> Self {
+        Self {
+            event_type: event_type.into(),
+            aggregate_type: aggregate_type.into(),
+            aggregate_id: aggregate_id.to_string(),
+            payload,
+            correlation_id: None,
+        }
+    }
+
+    /// Event announcing a new user
+    pub fn user_created(user: &User) -> Self {
+        let payload = serde_json::json!({
+            "user_id": user.id,
+            "email": user.email,
+            "name": user.name,
+            "created_at": user.created_at,
+        });
+        Self::new("user.created", "user", user.id, payload)
+    }
+
+    /// Event announcing a change to a user's name, email or avatar
+    pub fn user_updated(old_user: &User, new_user: &User) -> Self {
+        let payload = serde_json::json!({
+            "user_id": new_user.id,
+            "changes": {
+                "name": {
+                    "old": old_user.name,
+                    "new": new_user.name
+                },
+                "email": {
+                    "old": old_user.email,
+                    "new": new_user.email
+                },
+                "avatar_url": {
+                    "old": old_user.avatar_url,
+                    "new": new_user.avatar_url
+                }
+            },
+            "updated_at": new_user.updated_at,
+        });
+        Self::new("user.updated", "user", new_user.id, payload)
+    }
+
+    /// Event announcing a deleted user
+    pub fn user_deleted(user: &User) -> Self {
+        let payload = serde_json::json!({
+            "user_id": user.id,
+            "email": user.email,
+            "name": user.name,
+            "deleted_at": Utc::now(),
+        });
+        Self::new("user.deleted", "user", user.id, payload)
+    }
+
+    /// Event announcing that a user was activated or deactivated
+    pub fn user_status_changed(user: &User, reason: Option<&str>) -> Self {
+        let payload = serde_json::json!({
+            "user_id": user.id,
+            "is_active": user.is_active,
+            "reason": reason,
+            "updated_at": user.updated_at,
+        });
+        Self::new("user.status_changed", "user", user.id, payload)
+    }
+
+    /// Event announcing that a user's personal data was erased
+    ///
+    /// Carries no personal data, so subscribers can erase their own copies.
+    pub fn user_erased(user_id: UserId, erasure_request_id: Uuid, erased_at: DateTime<Utc>) -> Self {
+        let payload = serde_json::json!({
+            "user_id": user_id,
+            "erasure_request_id": erasure_request_id,
+            "erased_at": erased_at,
+        });
+        Self::new("user.erased", "user", user_id, payload)
+    }
+
+    /// Carry the correlation ID of the request that caused the event
+    pub fn with_context(mut self, context: &AuditContext) -> Self {
+        self.correlation_id = context.correlation_id.clone();
+        self
+    }
+}
+
+/// Exponential delay before retrying after the given number of failed attempts
+pub fn outbox_retry_delay(attempts: i32, base_delay: Duration, max_delay: Duration) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 31) as u32;
+    base_delay.saturating_mul(2_u32.saturating_pow(exponent)).min(max_delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_the_cap() {
+        let base = Duration::from_secs(1);
+        let max = Duration::from_secs(60);
+
+        assert_eq!(outbox_retry_delay(1, base, max), Duration::from_secs(1));
+        assert_eq!(outbox_retry_delay(2, base, max), Duration::from_secs(2));
+        assert_eq!(outbox_retry_delay(4, base, max), Duration::from_secs(8));
+        assert_eq!(outbox_retry_delay(7, base, max), max);
+        assert_eq!(outbox_retry_delay(100, base, max), max);
+    }
+
+    #[test]
+    fn test_events_carry_the_correlation_id() {
+        let context = AuditContext::new(None, Some("req-1".to_string()));
+        let event = NewOutboxEvent::new("user.created", "user", Uuid::nil(), serde_json::json!({}))
+            .with_context(&context);
+
+        assert_eq!(event.aggregate_id, Uuid::nil().to_string());
+        assert_eq!(event.correlation_id.as_deref(), Some("req-1"));
+    }
+
+    #[test]
+    fn test_erasure_event_carries_no_personal_data() {
+        let event = NewOutboxEvent::user_erased(Uuid::nil(), Uuid::nil(), Utc::now());
+
+        assert_eq!(event.event_type, "user.erased");
+        let mut keys: Vec<&String> = event.payload.as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["erased_at", "erasure_request_id", "user_id"]);
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::common::UserId;
+use super::user::User;
+
+/// Replacement for erased personal data
+pub const ERASED_VALUE: &str = "[erased]";
+
+/// Name given to erased users
+pub const ERASED_USER_NAME: &str = "Erased User";
+
+/// Domain of the placeholder emails given to erased users
+pub const ERASED_EMAIL_DOMAIN: &str = "erased.invalid";
+
+/// The person a data export or erasure is about, with the PII to look for
+#[derive(Debug, Clone)]
+pub struct DataSubject {
+    pub user_id: UserId,
+    pub name: String,
+    pub email: String,
+}
+
+impl From<&User> for DataSubject {
+    fn from(user: &User) -> Self {
+        Self {
+            user_id: user.id,
+            name: user.name.clone(),
+            email: user.email.clone(),
+        }
+    }
+}
+
+impl DataSubject {
+    /// Placeholder email that keeps the unique constraint satisfied after erasure
+    pub fn erased_email(&self) -> String {
+        format!("erased-{}@{}", self.user_id.simple(), ERASED_EMAIL_DOMAIN)
+    }
+
+    /// Replace strings in a JSON document that identify the subject
+    ///
+    /// Strings containing the email or equal to the name are replaced with
+    /// [`ERASED_VALUE`]; keys and the document's structure are kept.
+    /// Returns whether anything was replaced.
+    pub fn redact(&self, value: &mut serde_json::Value) -> bool {
+        match value {
+            serde_json::Value::String(text) => {
+                let lowered = text.to_lowercase();
+                let identifies = lowered.contains(&self.email.to_lowercase())
+                    || (!self.name.is_empty() && lowered.trim() == self.name.to_lowercase());
+                if identifies {
+                    *text = ERASED_VALUE.to_string();
+                }
+                identifies
+            }
+            serde_json::Value::Array(items) => self.redact_all(items.iter_mut()),
+            serde_json::Value::Object(fields) => self.redact_all(fields.values_mut()),
+            _ => false,
+        }
+    }
+
+    /// Redact every value, without stopping at the first match
+    fn redact_all<'a>(&self, values: impl Iterator<Item = &'a mut serde_json::Value>) -> bool {
+        let mut changed = false;
+        for value in values {
+            changed |= self.redact(value);
+        }
+        changed
+    }
+}
+
+/// Everything held about a user, as handed out for a data subject access request
+#[derive(Debug, Serialize)]
+pub struct UserDataArchive {
+    pub user_id: UserId,
+    pub generated_at: DateTime<Utc>,
+    /// One section per data source, keyed by source name
+    pub sections: BTreeMap<String, serde_json::Value>,
+}
+
+/// Request body for erasing a user
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct EraseUserRequest {
+    #[validate(length(max = 1000, message = "Reason must not exceed 1000 characters"))]
+    pub reason: Option<String>,
+}
+
+/// Record of a completed erasure
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ErasureRequest {
+    pub id: Uuid,
+    pub user_id: UserId,
+    pub requested_by: Option<UserId>,
+    pub reason: Option<String>,
+    /// Rows anonymized per data source
+    pub affected_rows: serde_json::Value,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn subject() -> DataSubject {
+        DataSubject {
+            user_id: Uuid::nil(),
+            name: "Jane Doe".to_string(),
+            email: "jane@example.com".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_redact_replaces_identifying_strings() {
+        let mut metadata = json!({
+            "before": { "name": "Jane Doe", "email": "Jane@Example.com" },
+            "note": "sent to jane@example.com",
+            "recipients": ["bob@example.com", "jane@example.com"],
+            "plan": "Jane Doe's plan",
+            "count": 2
+        });
+
+        assert!(subject().redact(&mut metadata));
+        assert_eq!(
+            metadata,
+            json!({
+                "before": { "name": "[erased]", "email": "[erased]" },
+                "note": "[erased]",
+                "recipients": ["bob@example.com", "[erased]"],
+                "plan": "Jane Doe's plan",
+                "count": 2
+            })
+        );
+        assert!(!subject().redact(&mut metadata));
+    }
+
+    #[test]
+    fn test_erased_email_is_unique_per_user() {
+        let email = subject().erased_email();
+        assert_eq!(email, "erased-00000000000000000000000000000000@erased.invalid");
+    }
+}
@@ -4,6 +4,7 @@ use validator::{Validate, ValidationError};
 use std::collections::HashMap;
 
 use super::common::UserId;
+use super::fieldset::Resource;
 
 /// User domain model
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -14,6 +15,24 @@ pub struct User {
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub version: i64,
+    /// Free-form per-user attributes owned by product teams
+    pub metadata: serde_json::Value,
+    /// Blob store key of the avatar image
+    #[serde(skip)]
+    pub avatar_key: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+impl Resource for User {
+    const FIELDS: &'static [&'static str] = &[
+        "id", "name", "email", "is_active", "created_at", "updated_at", "version", "metadata", "avatar_url",
+    ];
+    const RELATIONS: &'static [&'static str] = &["groups"];
+
+    fn resource_id(&self) -> UserId {
+        self.id
+    }
 }
 
 /// Request to create a new user
@@ -28,6 +47,10 @@ pub struct CreateUserRequest {
     #[validate(length(max = 320, message = "Email must not exceed 320 characters"))]
     #[serde(deserialize_with = "deserialize_trimmed_lowercase_string")]
     pub email: String,
+
+    #[validate(custom(function = "validate_metadata"))]
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
 }
 
 /// Request to update an existing user
@@ -41,6 +64,11 @@ pub struct UpdateUserRequest {
     #[validate(length(max = 320, message = "Email must not exceed 320 characters"))]
     #[serde(deserialize_with = "deserialize_optional_trimmed_lowercase_string")]
     pub email: Option<String>,
+
+    /// Replaces the stored metadata object when present
+    #[validate(custom(function = "validate_metadata"))]
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
 }
 
 /// User for database insertion
@@ -48,6 +76,7 @@ pub struct UpdateUserRequest {
 pub struct NewUser {
     pub name: String,
     pub email: String,
+    pub metadata: serde_json::Value,
 }
 
 impl From<CreateUserRequest> for NewUser {
@@ -55,6 +84,7 @@ impl From<CreateUserRequest> for NewUser {
         Self {
             name: request.name,
             email: request.email,
+            metadata: request.metadata.unwrap_or_else(|| serde_json::json!({})),
         }
     }
 }
@@ -75,6 +105,17 @@ pub struct PaginationMetadata {
     pub has_more: bool,
 }
 
+impl PaginationMetadata {
+    pub fn new(total: i64, limit: i64, offset: i64) -> Self {
+        Self {
+            total,
+            limit,
+            offset,
+            has_more: offset + limit < total,
+        }
+    }
+}
+
 /// User statistics
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserStats {
@@ -116,6 +157,49 @@ impl Default for UserSearchFilters {
     }
 }
 
+/// A user matched by a search query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSearchResult {
+    pub user: User,
+    /// Relevance score combining full-text rank and trigram similarity
+    pub rank: f32,
+    pub highlight: UserSearchHighlight,
+}
+
+/// Search terms highlighted with `<mark>` tags in otherwise HTML-escaped text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSearchHighlight {
+    pub name: String,
+    pub email: String,
+}
+
+/// Start of a match in a headline; from the private use area, so it is
+/// never confused with the user's text
+pub const HEADLINE_START: char = '\u{E000}';
+/// End of a match in a headline
+pub const HEADLINE_STOP: char = '\u{E001}';
+
+impl UserSearchHighlight {
+    /// Render a headline delimited with [`HEADLINE_START`] and
+    /// [`HEADLINE_STOP`] as HTML, escaping everything but the `<mark>` tags
+    pub fn headline_html(headline: &str) -> String {
+        let mut html = String::with_capacity(headline.len());
+        for c in headline.chars() {
+            match c {
+                HEADLINE_START => html.push_str("<mark>"),
+                HEADLINE_STOP => html.push_str("</mark>"),
+                '&' => html.push_str("&amp;"),
+                '<' => html.push_str("&lt;"),
+                '>' => html.push_str("&gt;"),
+                '"' => html.push_str("&quot;"),
+                '\'' => html.push_str("&#39;"),
+                c => html.push(c),
+            }
+        }
+        html
+    }
+}
+
 /// User activation/deactivation request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserStatusRequest {
@@ -123,6 +207,75 @@ pub struct UserStatusRequest {
     pub reason: Option<String>,
 }
 
+/// Partial user update expressed as a patch document
+#[derive(Debug, Clone)]
+pub enum UserPatch {
+    /// RFC 7396 JSON Merge Patch (`application/merge-patch+json`)
+    Merge(serde_json::Value),
+    /// RFC 6902 JSON Patch (`application/json-patch+json`)
+    Json(json_patch::Patch),
+}
+
+/// Errors raised while applying a user patch
+#[derive(Debug, thiserror::Error)]
+pub enum UserPatchError {
+    #[error("Patch could not be applied: {0}")]
+    Conflict(String),
+
+    #[error("Patched user is invalid: {0}")]
+    Invalid(String),
+}
+
+impl UserPatch {
+    /// Media type for JSON Merge Patch documents
+    pub const MERGE_PATCH_CONTENT_TYPE: &'static str = "application/merge-patch+json";
+
+    /// Media type for JSON Patch documents
+    pub const JSON_PATCH_CONTENT_TYPE: &'static str = "application/json-patch+json";
+
+    /// Fields of the user document that a patch may touch
+    const PATCHABLE_FIELDS: &'static [&'static str] = &["name", "email", "metadata"];
+
+    /// Apply the patch to the user's editable fields and revalidate the result
+    ///
+    /// The patched document must still be a complete, valid user, so it is
+    /// run through the same rules as `CreateUserRequest`.
+    pub fn apply(&self, user: &User) -> Result<CreateUserRequest, UserPatchError> {
+        let mut document = serde_json::json!({
+            "name": user.name,
+            "email": user.email,
+            "metadata": user.metadata,
+        });
+
+        match self {
+            UserPatch::Merge(patch) => json_patch::merge(&mut document, patch),
+            UserPatch::Json(patch) => json_patch::patch(&mut document, patch)
+                .map_err(|e| UserPatchError::Conflict(e.to_string()))?,
+        }
+
+        match document.as_object() {
+            Some(fields) => {
+                if let Some(field) = fields.keys().find(|k| !Self::PATCHABLE_FIELDS.contains(&k.as_str())) {
+                    return Err(UserPatchError::Invalid(format!("Field '{}' cannot be patched", field)));
+                }
+            }
+            None => return Err(UserPatchError::Invalid("Patched user must be a JSON object".to_string())),
+        }
+
+        let request: CreateUserRequest = serde_json::from_value(document)
+            .map_err(|e| UserPatchError::Invalid(e.to_string()))?;
+
+        request.validate_and_normalize().map_err(|validation_errors| {
+            let error_message = validation_errors
+                .into_iter()
+                .map(|(field, errors)| format!("{}: {}", field, errors.join(", ")))
+                .collect::<Vec<_>>()
+                .join("; ");
+            UserPatchError::Invalid(error_message)
+        })
+    }
+}
+
 /// Validation functions
 fn validate_name(name: &str) -> Result<(), ValidationError> {
     let trimmed = name.trim();
@@ -148,7 +301,22 @@ fn validate_name(name: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Largest serialized metadata object accepted on a user
+pub const MAX_METADATA_BYTES: usize = 16 * 1024;
 
+fn validate_metadata(metadata: &serde_json::Value) -> Result<(), ValidationError> {
+    if !metadata.is_object() {
+        return Err(ValidationError::new("metadata_not_object")
+            .with_message("Metadata must be a JSON object".into()));
+    }
+
+    if metadata.to_string().len() > MAX_METADATA_BYTES {
+        return Err(ValidationError::new("metadata_too_large")
+            .with_message(format!("Metadata must not exceed {} bytes", MAX_METADATA_BYTES).into()));
+    }
+
+    Ok(())
+}
 
 /// Custom deserializers for data cleaning
 fn deserialize_trimmed_string<'de, D>(deserializer: D) -> Result<String, D::Error>
@@ -300,7 +468,7 @@ impl UpdateUserRequest {
 
     /// Check if the request has any updates
     pub fn has_updates(&self) -> bool {
-        self.name.is_some() || self.email.is_some()
+        self.name.is_some() || self.email.is_some() || self.metadata.is_some()
     }
 }
 
@@ -309,11 +477,21 @@ mod tests {
     use super::*;
     use uuid::Uuid;
 
+    #[test]
+    fn test_headline_html_escapes_all_but_the_marks() {
+        let headline = format!("{}Ada{} <img src=x onerror=\"alert('x')\"> & co", HEADLINE_START, HEADLINE_STOP);
+        assert_eq!(
+            UserSearchHighlight::headline_html(&headline),
+            "<mark>Ada</mark> &lt;img src=x onerror=&quot;alert(&#39;x&#39;)&quot;&gt; &amp; co"
+        );
+    }
+
     #[test]
     fn test_create_user_request_validation() {
         let valid_request = CreateUserRequest {
             name: "John Doe".to_string(),
             email: "john@example.com".to_string(),
+            metadata: None,
         };
 
         assert!(valid_request.validate().is_ok());
@@ -324,6 +502,7 @@ mod tests {
         let invalid_request = CreateUserRequest {
             name: "John Doe".to_string(),
             email: "invalid-email".to_string(),
+            metadata: None,
         };
 
         assert!(invalid_request.validate().is_err());
@@ -334,6 +513,7 @@ mod tests {
         let invalid_request = CreateUserRequest {
             name: "".to_string(),
             email: "john@example.com".to_string(),
+            metadata: None,
         };
 
         assert!(invalid_request.validate().is_err());
@@ -344,6 +524,7 @@ mod tests {
         let valid_request = UpdateUserRequest {
             name: Some("Jane Doe".to_string()),
             email: Some("jane@example.com".to_string()),
+            metadata: None,
         };
 
         assert!(valid_request.validate().is_ok());
@@ -354,6 +535,7 @@ mod tests {
         let request = UpdateUserRequest {
             name: None,
             email: None,
+            metadata: None,
         };
 
         assert!(!request.has_updates());
@@ -368,6 +550,10 @@ mod tests {
             is_active: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            version: 1,
+            metadata: serde_json::json!({}),
+            avatar_key: None,
+            avatar_url: None,
         };
 
         assert!(user.is_active());
@@ -386,6 +572,10 @@ mod tests {
             is_active: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            version: 1,
+            metadata: serde_json::json!({}),
+            avatar_key: None,
+            avatar_url: None,
         };
 
         let safe_user = user.to_safe_user();
@@ -416,6 +606,88 @@ mod tests {
         assert!(filters.is_active.is_none());
     }
 
+    fn patch_target() -> User {
+        User {
+            id: Uuid::new_v4(),
+            name: "Test User".to_string(),
+            email: "test@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+            metadata: serde_json::json!({}),
+            avatar_key: None,
+            avatar_url: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_patch_updates_only_given_fields() {
+        let patch = UserPatch::Merge(serde_json::json!({ "name": "Patched User" }));
+
+        let patched = patch.apply(&patch_target()).unwrap();
+        assert_eq!(patched.name, "Patched User");
+        assert_eq!(patched.email, "test@example.com");
+    }
+
+    #[test]
+    fn test_merge_patch_cannot_clear_required_field() {
+        let cleared = UserPatch::Merge(serde_json::json!({ "name": null }));
+        assert!(matches!(cleared.apply(&patch_target()), Err(UserPatchError::Invalid(_))));
+
+        let emptied = UserPatch::Merge(serde_json::json!({ "name": "" }));
+        assert!(matches!(emptied.apply(&patch_target()), Err(UserPatchError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_json_patch_replace_and_test() {
+        let patch: json_patch::Patch = serde_json::from_value(serde_json::json!([
+            { "op": "test", "path": "/email", "value": "test@example.com" },
+            { "op": "replace", "path": "/email", "value": "NEW@Example.com" }
+        ])).unwrap();
+
+        let patched = UserPatch::Json(patch).apply(&patch_target()).unwrap();
+        assert_eq!(patched.email, "new@example.com");
+    }
+
+    #[test]
+    fn test_json_patch_failed_test_is_conflict() {
+        let patch: json_patch::Patch = serde_json::from_value(serde_json::json!([
+            { "op": "test", "path": "/name", "value": "Someone Else" }
+        ])).unwrap();
+
+        assert!(matches!(UserPatch::Json(patch).apply(&patch_target()), Err(UserPatchError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_metadata_must_be_object() {
+        let mut request = CreateUserRequest {
+            name: "John Doe".to_string(),
+            email: "john@example.com".to_string(),
+            metadata: Some(serde_json::json!({ "plan": "pro" })),
+        };
+        assert!(request.validate().is_ok());
+
+        request.metadata = Some(serde_json::json!(["pro"]));
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn test_merge_patch_merges_metadata() {
+        let mut user = patch_target();
+        user.metadata = serde_json::json!({ "plan": "free", "seats": 3 });
+        let patch = UserPatch::Merge(serde_json::json!({ "metadata": { "plan": "pro" } }));
+
+        let patched = patch.apply(&user).unwrap();
+        assert_eq!(patched.metadata, Some(serde_json::json!({ "plan": "pro", "seats": 3 })));
+    }
+
+    #[test]
+    fn test_patch_rejects_unknown_fields() {
+        let patch = UserPatch::Merge(serde_json::json!({ "id": "00000000-0000-0000-0000-000000000000" }));
+        assert!(matches!(patch.apply(&patch_target()), Err(UserPatchError::Invalid(_))));
+    }
+
     #[test]
     fn test_pagination_metadata() {
         let metadata = PaginationMetadata {
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use super::event_stream::StreamEvent;
+
+/// Message sent by a WebSocket client
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Receive events of `types`, or of every type when empty, under the
+    /// client-chosen subscription `id`
+    Subscribe {
+        id: String,
+        #[serde(default)]
+        types: Vec<String>,
+    },
+    Unsubscribe { id: String },
+    /// Ask for a `pong` echoing `nonce`, to check the connection end to end
+    Ping {
+        #[serde(default)]
+        nonce: Option<serde_json::Value>,
+    },
+}
+
+/// Message sent to a WebSocket client
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Subscribed { id: String, types: Vec<String> },
+    Unsubscribed { id: String },
+    Pong {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        nonce: Option<serde_json::Value>,
+    },
+    /// An event matching the listed subscriptions
+    Event { subscriptions: Vec<String>, event: StreamEvent },
+    /// A message could not be handled; `id` is the subscription it was about
+    Error {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        code: WebSocketErrorCode,
+        message: String,
+    },
+}
+
+impl ServerMessage {
+    pub fn error(id: Option<String>, code: WebSocketErrorCode, message: impl Into<String>) -> Self {
+        ServerMessage::Error {
+            id,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// JSON text sent over the socket
+    pub fn to_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Reason a client message was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSocketErrorCode {
+    /// Not a JSON text message of a known type
+    InvalidMessage,
+    UnknownEventType,
+    SubscriptionExists,
+    SubscriptionNotFound,
+    TooManySubscriptions,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_use_the_type_tag() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type": "subscribe", "id": "users", "types": ["user.created"]}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Subscribe {
+                id: "users".to_string(),
+                types: vec!["user.created".to_string()],
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(r#"{"type": "ping"}"#).unwrap(),
+            ClientMessage::Ping { nonce: None }
+        );
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"type": "event"}"#).is_err());
+
+        let error = ServerMessage::error(None, WebSocketErrorCode::InvalidMessage, "Expected JSON");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&error.to_text()).unwrap(),
+            serde_json::json!({"type": "error", "code": "invalid_message", "message": "Expected JSON"})
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tracing::{debug, info, instrument};
+
+use crate::models::{AuditChainHead, AuditCursor, AuditLog, AuditLogArchive, AuditPartition};
+use crate::repository::audit_repository::AUDIT_LOG_COLUMNS;
+use crate::repository::RepositoryError;
+
+/// Management of the monthly `audit_logs` partitions
+///
+/// Partition names come from [`AuditPartition::name`] and bounds from its
+/// timestamps, which is what makes splicing them into DDL safe.
+#[async_trait]
+pub trait AuditPartitionRepository: Send + Sync {
+    /// Partitions attached to `audit_logs`, oldest first
+    async fn list_partitions(&self) -> Result<Vec<AuditPartition>, RepositoryError>;
+
+    /// Create a partition; creating one that exists is not an error
+    ///
+    /// Entries of its month that landed in the default partition are moved into it.
+    async fn create_partition(&self, partition: &AuditPartition) -> Result<(), RepositoryError>;
+
+    /// Number of entries in the default partition, which only holds entries
+    /// whose month had no partition when they were written
+    async fn count_unpartitioned(&self) -> Result<i64, RepositoryError>;
+
+    /// Detach a partition, recording where the hash chain stood at its end
+    async fn detach_partition(&self, partition: &AuditPartition) -> Result<AuditLogArchive, RepositoryError>;
+
+    /// Detached partitions whose table has not been dropped, oldest first
+    async fn list_detached(&self) -> Result<Vec<AuditLogArchive>, RepositoryError>;
+
+    /// Entries of a detached partition, oldest first, starting after `after`
+    async fn read_detached(
+        &self,
+        partition: &AuditPartition,
+        after: Option<&AuditCursor>,
+        limit: i64,
+    ) -> Result<Vec<AuditLog>, RepositoryError>;
+
+    /// Drop a detached partition, recording where its entries were archived
+    async fn drop_detached(
+        &self,
+        partition: &AuditPartition,
+        archive_prefix: &str,
+        archive_parts: i32,
+    ) -> Result<AuditLogArchive, RepositoryError>;
+}
+
+/// SQLx implementation of AuditPartitionRepository
+pub struct SqlxAuditPartitionRepository {
+    pool: PgPool,
+}
+
+impl SqlxAuditPartitionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Partition catching entries outside every monthly partition
+const DEFAULT_PARTITION: &str = "audit_logs_default";
+
+const ARCHIVE_COLUMNS: &str = "partition_name, range_start, range_end, entries, last_sequence, last_hash, \
+     archive_prefix, archive_parts, detached_at, dropped_at";
+
+#[async_trait]
+impl AuditPartitionRepository for SqlxAuditPartitionRepository {
+    async fn list_partitions(&self) -> Result<Vec<AuditPartition>, RepositoryError> {
+        let names: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT child.relname::TEXT
+            FROM pg_inherits
+            JOIN pg_class child ON child.oid = pg_inherits.inhrelid
+            WHERE pg_inherits.inhparent = 'audit_logs'::regclass
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Partitions created by hand under other names are left alone
+        let mut partitions: Vec<AuditPartition> = names.iter().filter_map(|name| AuditPartition::parse(name)).collect();
+        partitions.sort();
+        Ok(partitions)
+    }
+
+    #[instrument(skip(self), fields(partition = %partition.name()))]
+    async fn create_partition(&self, partition: &AuditPartition) -> Result<(), RepositoryError> {
+        let name = partition.name();
+        let mut tx = self.pool.begin().await?;
+
+        let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(&name)
+            .fetch_one(&mut *tx)
+            .await?;
+        if exists {
+            return Ok(());
+        }
+
+        // Attaching fails while the default partition holds entries of the
+        // month, so they are moved into the new table first
+        sqlx::query(&format!("CREATE TABLE {} (LIKE audit_logs INCLUDING DEFAULTS)", name))
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!("CREATE UNIQUE INDEX {0}_sequence_key ON {0} (sequence)", name))
+            .execute(&mut *tx)
+            .await?;
+        let moved = sqlx::query(&format!(
+            r#"
+            WITH moved AS (
+                DELETE FROM {} WHERE created_at >= $1 AND created_at < $2 RETURNING *
+            )
+            INSERT INTO {} SELECT * FROM moved
+            "#,
+            DEFAULT_PARTITION, name
+        ))
+        .bind(partition.range_start())
+        .bind(partition.range_end())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        sqlx::query(&format!(
+            "ALTER TABLE audit_logs ATTACH PARTITION {} FOR VALUES FROM ('{}') TO ('{}')",
+            name,
+            partition.range_start().to_rfc3339(),
+            partition.range_end().to_rfc3339()
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        if moved > 0 {
+            info!("Moved {} audit log entries from {} into {}", moved, DEFAULT_PARTITION, name);
+        }
+        debug!("Created audit log partition {}", name);
+        Ok(())
+    }
+
+    async fn count_unpartitioned(&self) -> Result<i64, RepositoryError> {
+        let count = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", DEFAULT_PARTITION))
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    #[instrument(skip(self), fields(partition = %partition.name()))]
+    async fn detach_partition(&self, partition: &AuditPartition) -> Result<AuditLogArchive, RepositoryError> {
+        let name = partition.name();
+        let mut tx = self.pool.begin().await?;
+
+        let entries: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", name))
+            .fetch_one(&mut *tx)
+            .await?;
+        let chain_end = sqlx::query_as::<_, AuditChainHead>(&format!(
+            "SELECT sequence, hash FROM {} WHERE sequence IS NOT NULL ORDER BY sequence DESC LIMIT 1",
+            name
+        ))
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!("ALTER TABLE audit_logs DETACH PARTITION {}", name))
+            .execute(&mut *tx)
+            .await?;
+
+        let archive = sqlx::query_as::<_, AuditLogArchive>(&format!(
+            r#"
+            INSERT INTO audit_log_archives (partition_name, range_start, range_end, entries, last_sequence, last_hash)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+            "#,
+            ARCHIVE_COLUMNS
+        ))
+        .bind(&name)
+        .bind(partition.range_start())
+        .bind(partition.range_end())
+        .bind(entries)
+        .bind(chain_end.as_ref().map(|end| end.sequence))
+        .bind(chain_end.as_ref().map(|end| end.hash.clone()))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!("Detached audit log partition {} with {} entries", name, entries);
+        Ok(archive)
+    }
+
+    async fn list_detached(&self) -> Result<Vec<AuditLogArchive>, RepositoryError> {
+        let archives = sqlx::query_as::<_, AuditLogArchive>(&format!(
+            "SELECT {} FROM audit_log_archives WHERE dropped_at IS NULL ORDER BY range_start",
+            ARCHIVE_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(archives)
+    }
+
+    async fn read_detached(
+        &self,
+        partition: &AuditPartition,
+        after: Option<&AuditCursor>,
+        limit: i64,
+    ) -> Result<Vec<AuditLog>, RepositoryError> {
+        let logs = sqlx::query_as::<_, AuditLog>(&format!(
+            r#"
+            SELECT {} FROM {}
+            WHERE $1::TIMESTAMPTZ IS NULL OR (created_at, id) > ($1, $2)
+            ORDER BY created_at, id
+            LIMIT $3
+            "#,
+            AUDIT_LOG_COLUMNS,
+            partition.name()
+        ))
+        .bind(after.map(|cursor| cursor.created_at))
+        .bind(after.map(|cursor| cursor.id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    #[instrument(skip(self), fields(partition = %partition.name()))]
+    async fn drop_detached(
+        &self,
+        partition: &AuditPartition,
+        archive_prefix: &str,
+        archive_parts: i32,
+    ) -> Result<AuditLogArchive, RepositoryError> {
+        let name = partition.name();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(&format!("DROP TABLE IF EXISTS {}", name))
+            .execute(&mut *tx)
+            .await?;
+
+        let archive = sqlx::query_as::<_, AuditLogArchive>(&format!(
+            r#"
+            UPDATE audit_log_archives
+            SET archive_prefix = $2, archive_parts = $3, dropped_at = NOW()
+            WHERE partition_name = $1
+            RETURNING {}
+            "#,
+            ARCHIVE_COLUMNS
+        ))
+        .bind(&name)
+        .bind(archive_prefix)
+        .bind(archive_parts)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(RepositoryError::NotFound)?;
+
+        tx.commit().await?;
+
+        info!("Dropped archived audit log partition {}", name);
+        Ok(archive)
+    }
+}
@@ -0,0 +1,239 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder, Transaction};
+use tracing::{debug, instrument, warn};
+
+use crate::models::{AuditChainHead, AuditCheckpoint, AuditCursor, AuditLog, AuditLogFilter, NewAuditLog};
+use crate::repository::RepositoryError;
+
+/// Audit log repository trait
+#[async_trait]
+pub trait AuditLogRepository: Send + Sync {
+    /// Record an audit log entry
+    async fn record(&self, entry: &NewAuditLog) -> Result<AuditLog, RepositoryError>;
+
+    /// Record an audit log entry within a transaction
+    async fn record_tx(&self, tx: &mut Transaction<'_, Postgres>, entry: &NewAuditLog) -> Result<AuditLog, RepositoryError>;
+
+    /// Find entries matching the filter, newest first, starting after `after`
+    async fn query(&self, filter: &AuditLogFilter, after: Option<&AuditCursor>, limit: i64) -> Result<Vec<AuditLog>, RepositoryError>;
+
+    /// Last entry of the hash chain
+    async fn chain_head(&self) -> Result<AuditChainHead, RepositoryError>;
+
+    /// Last chained entry moved out of `audit_logs` by retention, if any
+    async fn chain_start(&self) -> Result<Option<AuditChainHead>, RepositoryError>;
+
+    /// Chained entries after `after_sequence`, in sequence order
+    async fn list_chain(&self, after_sequence: i64, limit: i64) -> Result<Vec<AuditLog>, RepositoryError>;
+
+    /// Chained entries at the given sequence numbers that are still stored
+    async fn find_chain_entries(&self, sequences: &[i64]) -> Result<Vec<AuditLog>, RepositoryError>;
+
+    /// Store a signed checkpoint
+    async fn create_checkpoint(&self, checkpoint: &AuditCheckpoint) -> Result<(), RepositoryError>;
+
+    /// All checkpoints, oldest first
+    async fn list_checkpoints(&self) -> Result<Vec<AuditCheckpoint>, RepositoryError>;
+}
+
+/// SQLx implementation of AuditLogRepository
+pub struct SqlxAuditLogRepository {
+    pool: PgPool,
+}
+
+impl SqlxAuditLogRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+pub(crate) const AUDIT_LOG_COLUMNS: &str = "id, user_id, action, resource_type, resource_id, metadata, correlation_id, created_at, \
+     sequence, metadata_hash, previous_hash, hash, redacted_at";
+
+/// Append an audit log entry to the hash chain
+///
+/// The chain head stays locked until the surrounding transaction ends, so
+/// concurrent writers get consecutive sequence numbers in commit order.
+pub(crate) async fn insert_audit_log(conn: &mut PgConnection, entry: &NewAuditLog) -> Result<AuditLog, RepositoryError> {
+    let head = sqlx::query_as::<_, AuditChainHead>("SELECT sequence, hash FROM audit_chain_head FOR UPDATE")
+        .fetch_one(&mut *conn)
+        .await?;
+    let log = entry.seal(head.sequence + 1, &head.hash);
+
+    let stored = sqlx::query_as::<_, AuditLog>(&format!(
+        r#"
+        INSERT INTO audit_logs (id, user_id, action, resource_type, resource_id, metadata, correlation_id, created_at,
+                                sequence, metadata_hash, previous_hash, hash)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING {}
+        "#,
+        AUDIT_LOG_COLUMNS
+    ))
+    .bind(log.id)
+    .bind(log.user_id)
+    .bind(&log.action)
+    .bind(&log.resource_type)
+    .bind(&log.resource_id)
+    .bind(&log.metadata)
+    .bind(&log.correlation_id)
+    .bind(log.created_at)
+    .bind(log.sequence)
+    .bind(&log.metadata_hash)
+    .bind(&log.previous_hash)
+    .bind(&log.hash)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query("UPDATE audit_chain_head SET sequence = $1, hash = $2")
+        .bind(log.sequence)
+        .bind(&log.hash)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(stored)
+}
+
+#[async_trait]
+impl AuditLogRepository for SqlxAuditLogRepository {
+    #[instrument(skip(self, entry), fields(action = %entry.action))]
+    async fn record(&self, entry: &NewAuditLog) -> Result<AuditLog, RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+        let log = insert_audit_log(&mut tx, entry).await.map_err(|e| {
+            warn!("Failed to record audit log: {}", e);
+            e
+        })?;
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        debug!("Recorded audit log {} for action {}", log.id, log.action);
+        Ok(log)
+    }
+
+    #[instrument(skip(self, tx, entry), fields(action = %entry.action))]
+    async fn record_tx(&self, tx: &mut Transaction<'_, Postgres>, entry: &NewAuditLog) -> Result<AuditLog, RepositoryError> {
+        let log = insert_audit_log(tx, entry).await.map_err(|e| {
+            warn!("Failed to record audit log in transaction: {}", e);
+            e
+        })?;
+
+        debug!("Recorded audit log {} for action {} in transaction", log.id, log.action);
+        Ok(log)
+    }
+
+    #[instrument(skip(self, filter))]
+    async fn query(&self, filter: &AuditLogFilter, after: Option<&AuditCursor>, limit: i64) -> Result<Vec<AuditLog>, RepositoryError> {
+        // Only the criteria that are set become conditions, so the planner
+        // can use the user, resource and created_at indexes
+        let mut query = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM audit_logs WHERE TRUE", AUDIT_LOG_COLUMNS));
+        if let Some(actor) = filter.actor {
+            query.push(" AND user_id = ").push_bind(actor);
+        }
+        if let Some(resource_type) = &filter.resource_type {
+            query.push(" AND resource_type = ").push_bind(resource_type);
+        }
+        if let Some(resource_id) = &filter.resource_id {
+            query.push(" AND resource_id = ").push_bind(resource_id);
+        }
+        if let Some(action) = &filter.action {
+            query.push(" AND action = ").push_bind(action);
+        }
+        if let Some(from) = filter.from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+        if let Some(cursor) = after {
+            query
+                .push(" AND (created_at, id) < (")
+                .push_bind(cursor.created_at)
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        query.push(" ORDER BY created_at DESC, id DESC LIMIT ").push_bind(limit);
+
+        let logs = query.build_query_as::<AuditLog>().fetch_all(&self.pool).await?;
+
+        debug!("Audit log query returned {} entries", logs.len());
+        Ok(logs)
+    }
+
+    #[instrument(skip(self))]
+    async fn chain_head(&self) -> Result<AuditChainHead, RepositoryError> {
+        let head = sqlx::query_as::<_, AuditChainHead>("SELECT sequence, hash FROM audit_chain_head")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(head)
+    }
+
+    async fn chain_start(&self) -> Result<Option<AuditChainHead>, RepositoryError> {
+        let start = sqlx::query_as::<_, AuditChainHead>(
+            r#"
+            SELECT last_sequence AS sequence, last_hash AS hash
+            FROM audit_log_archives
+            WHERE last_sequence IS NOT NULL
+            ORDER BY last_sequence DESC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(start)
+    }
+
+    #[instrument(skip(self))]
+    async fn list_chain(&self, after_sequence: i64, limit: i64) -> Result<Vec<AuditLog>, RepositoryError> {
+        let logs = sqlx::query_as::<_, AuditLog>(&format!(
+            "SELECT {} FROM audit_logs WHERE sequence > $1 ORDER BY sequence LIMIT $2",
+            AUDIT_LOG_COLUMNS
+        ))
+        .bind(after_sequence)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    #[instrument(skip(self, sequences), fields(count = sequences.len()))]
+    async fn find_chain_entries(&self, sequences: &[i64]) -> Result<Vec<AuditLog>, RepositoryError> {
+        let logs = sqlx::query_as::<_, AuditLog>(&format!(
+            "SELECT {} FROM audit_logs WHERE sequence = ANY($1) ORDER BY sequence",
+            AUDIT_LOG_COLUMNS
+        ))
+        .bind(sequences)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    #[instrument(skip(self, checkpoint), fields(sequence = checkpoint.sequence))]
+    async fn create_checkpoint(&self, checkpoint: &AuditCheckpoint) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO audit_checkpoints (id, sequence, hash, signature, created_at) VALUES ($1, $2, $3, $4, $5)"
+        )
+        .bind(checkpoint.id)
+        .bind(checkpoint.sequence)
+        .bind(&checkpoint.hash)
+        .bind(&checkpoint.signature)
+        .bind(checkpoint.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        debug!("Stored audit checkpoint {} at sequence {}", checkpoint.id, checkpoint.sequence);
+        Ok(())
+    }
+
+    async fn list_checkpoints(&self) -> Result<Vec<AuditCheckpoint>, RepositoryError> {
+        let checkpoints = sqlx::query_as::<_, AuditCheckpoint>(
+            "SELECT id, sequence, hash, signature, created_at FROM audit_checkpoints ORDER BY sequence, created_at"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(checkpoints)
+    }
+}
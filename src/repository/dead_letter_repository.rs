@@ -0,0 +1,250 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{info, instrument};
+
+use crate::models::{DeadLetter, DeadLetterFilter, DeadLetterId, NewDeadLetter};
+use crate::repository::RepositoryError;
+
+const DEAD_LETTER_COLUMNS: &str = "id, event_id, event_type, subscription_id, payload, attempts, last_error, \
+     response_status, response_body, created_at, updated_at, replay_requested_at";
+
+/// Conditions matching a [`DeadLetterFilter`] bound as `$1` to `$3`
+const FILTER_CONDITIONS: &str = "($1::UUID[] IS NULL OR id = ANY($1)) \
+     AND ($2::UUID IS NULL OR subscription_id = $2) \
+     AND ($3::TEXT IS NULL OR event_type = $3)";
+
+/// Dead letter repository trait
+#[async_trait]
+pub trait DeadLetterRepository: Send + Sync {
+    /// Record a dead letter, replacing an earlier one for the same event and subscription
+    async fn insert(&self, letter: &NewDeadLetter) -> Result<DeadLetter, RepositoryError>;
+
+    async fn find_by_id(&self, id: DeadLetterId) -> Result<Option<DeadLetter>, RepositoryError>;
+
+    /// List matching dead letters, newest first, with the total number matching
+    async fn list(&self, filter: &DeadLetterFilter, limit: i64, offset: i64) -> Result<(Vec<DeadLetter>, i64), RepositoryError>;
+
+    /// Queue up to `limit` matching dead letters for replay, oldest first,
+    /// returning the IDs queued; letters already queued are left as they are
+    async fn queue_replay(&self, filter: &DeadLetterFilter, limit: i64) -> Result<Vec<DeadLetterId>, RepositoryError>;
+
+    /// Claim up to `limit` queued dead letters, hiding them from other
+    /// instances for `lease`
+    async fn claim_queued(&self, limit: i64, lease: Duration) -> Result<Vec<DeadLetter>, RepositoryError>;
+
+    /// Count a failed replay against a dead letter and take it off the replay queue
+    async fn record_failed_replay(
+        &self,
+        id: DeadLetterId,
+        error: &str,
+        response_status: Option<i32>,
+        response_body: Option<&str>,
+    ) -> Result<DeadLetter, RepositoryError>;
+
+    /// Remove a dead letter, returning whether it existed
+    async fn delete(&self, id: DeadLetterId) -> Result<bool, RepositoryError>;
+
+    /// Number of dead letters
+    async fn count(&self) -> Result<i64, RepositoryError>;
+
+    /// Delete dead letters recorded before `before`, returning how many were removed
+    async fn purge_before(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError>;
+}
+
+/// SQLx implementation of DeadLetterRepository
+pub struct SqlxDeadLetterRepository {
+    pool: PgPool,
+}
+
+impl SqlxDeadLetterRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DeadLetterRepository for SqlxDeadLetterRepository {
+    #[instrument(skip(self, letter), fields(event_id = %letter.event_id))]
+    async fn insert(&self, letter: &NewDeadLetter) -> Result<DeadLetter, RepositoryError> {
+        let dead_letter = sqlx::query_as::<_, DeadLetter>(&format!(
+            r#"
+            INSERT INTO dead_letters
+                (event_id, event_type, subscription_id, payload, attempts, last_error, response_status, response_body)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (event_id, COALESCE(subscription_id, '00000000-0000-0000-0000-000000000000'))
+            DO UPDATE SET payload = EXCLUDED.payload,
+                          attempts = EXCLUDED.attempts,
+                          last_error = EXCLUDED.last_error,
+                          response_status = EXCLUDED.response_status,
+                          response_body = EXCLUDED.response_body,
+                          updated_at = NOW()
+            RETURNING {}
+            "#,
+            DEAD_LETTER_COLUMNS
+        ))
+        .bind(letter.event_id)
+        .bind(&letter.event_type)
+        .bind(letter.subscription_id)
+        .bind(&letter.payload)
+        .bind(letter.attempts)
+        .bind(&letter.last_error)
+        .bind(letter.response_status)
+        .bind(&letter.response_body)
+        .fetch_one(&self.pool)
+        .await?;
+
+        info!("Dead-lettered event {} ({})", dead_letter.event_id, dead_letter.id);
+        Ok(dead_letter)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_by_id(&self, id: DeadLetterId) -> Result<Option<DeadLetter>, RepositoryError> {
+        let dead_letter =
+            sqlx::query_as::<_, DeadLetter>(&format!("SELECT {} FROM dead_letters WHERE id = $1", DEAD_LETTER_COLUMNS))
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(dead_letter)
+    }
+
+    #[instrument(skip(self))]
+    async fn list(&self, filter: &DeadLetterFilter, limit: i64, offset: i64) -> Result<(Vec<DeadLetter>, i64), RepositoryError> {
+        let dead_letters = sqlx::query_as::<_, DeadLetter>(&format!(
+            "SELECT {} FROM dead_letters WHERE {} ORDER BY created_at DESC, id LIMIT $4 OFFSET $5",
+            DEAD_LETTER_COLUMNS, FILTER_CONDITIONS
+        ))
+        .bind(&filter.ids)
+        .bind(filter.subscription_id)
+        .bind(&filter.event_type)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM dead_letters WHERE {}", FILTER_CONDITIONS))
+            .bind(&filter.ids)
+            .bind(filter.subscription_id)
+            .bind(&filter.event_type)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((dead_letters, total))
+    }
+
+    #[instrument(skip(self))]
+    async fn queue_replay(&self, filter: &DeadLetterFilter, limit: i64) -> Result<Vec<DeadLetterId>, RepositoryError> {
+        let ids: Vec<DeadLetterId> = sqlx::query_scalar(&format!(
+            r#"
+            UPDATE dead_letters SET replay_requested_at = NOW()
+            WHERE id IN (
+                SELECT id FROM dead_letters
+                WHERE {} AND replay_requested_at IS NULL
+                ORDER BY created_at, id
+                LIMIT $4
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id
+            "#,
+            FILTER_CONDITIONS
+        ))
+        .bind(&filter.ids)
+        .bind(filter.subscription_id)
+        .bind(&filter.event_type)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    #[instrument(skip(self))]
+    async fn claim_queued(&self, limit: i64, lease: Duration) -> Result<Vec<DeadLetter>, RepositoryError> {
+        let dead_letters = sqlx::query_as::<_, DeadLetter>(&format!(
+            r#"
+            UPDATE dead_letters SET replay_locked_until = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM dead_letters
+                WHERE replay_requested_at IS NOT NULL
+                  AND (replay_locked_until IS NULL OR replay_locked_until <= NOW())
+                ORDER BY replay_requested_at, created_at, id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            DEAD_LETTER_COLUMNS
+        ))
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(dead_letters)
+    }
+
+    #[instrument(skip(self, error, response_body))]
+    async fn record_failed_replay(
+        &self,
+        id: DeadLetterId,
+        error: &str,
+        response_status: Option<i32>,
+        response_body: Option<&str>,
+    ) -> Result<DeadLetter, RepositoryError> {
+        let dead_letter = sqlx::query_as::<_, DeadLetter>(&format!(
+            r#"
+            UPDATE dead_letters
+            SET attempts = attempts + 1,
+                last_error = $2,
+                response_status = $3,
+                response_body = $4,
+                updated_at = NOW(),
+                replay_requested_at = NULL,
+                replay_locked_until = NULL
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            DEAD_LETTER_COLUMNS
+        ))
+        .bind(id)
+        .bind(error)
+        .bind(response_status)
+        .bind(response_body)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RepositoryError::NotFound)?;
+
+        Ok(dead_letter)
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, id: DeadLetterId) -> Result<bool, RepositoryError> {
+        let result = sqlx::query("DELETE FROM dead_letters WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self))]
+    async fn count(&self) -> Result<i64, RepositoryError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM dead_letters")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    #[instrument(skip(self))]
+    async fn purge_before(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM dead_letters WHERE created_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,291 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::models::{EmailChange, EmailChangeStatus, NewEmailChange, NewOutboxEvent, User, UserId};
+use crate::repository::{from_text, outbox_repository::insert_outbox_event, RepositoryError};
+
+const EMAIL_CHANGE_COLUMNS: &str = "id, user_id, old_email, new_email, status, expires_at, revert_expires_at, \
+     created_at, confirmed_at, reverted_at";
+
+/// Email change repository trait
+#[async_trait]
+pub trait EmailChangeRepository: Send + Sync {
+    /// Store a pending change, cancelling any change already pending for the user
+    ///
+    /// The user is locked while the change is stored. `NotFound` is returned
+    /// if they are gone, and `VersionMismatch` if `expected_version` is given
+    /// and no longer matches.
+    async fn create(&self, change: &NewEmailChange, expected_version: Option<i64>) -> Result<EmailChange, RepositoryError>;
+
+    /// Find the user's pending change, if it has not expired
+    async fn find_pending(&self, user_id: UserId) -> Result<Option<EmailChange>, RepositoryError>;
+
+    /// Cancel the user's pending change, returning whether there was one
+    async fn cancel_pending(&self, user_id: UserId) -> Result<bool, RepositoryError>;
+
+    /// Apply the pending change identified by its confirmation token hash
+    ///
+    /// Returns `NotFound` if the token is unknown, already used or expired.
+    async fn confirm(&self, confirm_token_hash: &str) -> Result<(EmailChange, User), RepositoryError>;
+
+    /// Undo a pending or confirmed change identified by its revert token hash
+    ///
+    /// Returns `NotFound` if the token is unknown, already used or past the revert window.
+    async fn revert(&self, revert_token_hash: &str) -> Result<(EmailChange, User), RepositoryError>;
+}
+
+/// SQLx implementation of EmailChangeRepository
+pub struct SqlxEmailChangeRepository {
+    pool: PgPool,
+}
+
+impl SqlxEmailChangeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn begin(&self) -> Result<Transaction<'static, Postgres>, RepositoryError> {
+        self.pool.begin().await.map_err(|e| {
+            warn!("Failed to begin email change transaction: {}", e);
+            RepositoryError::Transaction(e.to_string())
+        })
+    }
+
+    /// Lock the change whose token hash matches in the given column
+    async fn lock_by_token(&self, tx: &mut Transaction<'_, Postgres>, column: &str, token_hash: &str) -> Result<Option<EmailChange>, RepositoryError> {
+        let row = sqlx::query_as::<_, EmailChangeRow>(&format!(
+            "SELECT {} FROM user_email_changes WHERE {} = $1 FOR UPDATE",
+            EMAIL_CHANGE_COLUMNS, column
+        ))
+        .bind(token_hash)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        row.map(EmailChange::try_from).transpose()
+    }
+}
+
+/// Email change as stored, with the status kept as text
+#[derive(sqlx::FromRow)]
+struct EmailChangeRow {
+    id: Uuid,
+    user_id: UserId,
+    old_email: String,
+    new_email: String,
+    status: String,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    revert_expires_at: chrono::DateTime<chrono::Utc>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    confirmed_at: Option<chrono::DateTime<chrono::Utc>>,
+    reverted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl TryFrom<EmailChangeRow> for EmailChange {
+    type Error = RepositoryError;
+
+    fn try_from(row: EmailChangeRow) -> Result<Self, Self::Error> {
+        Ok(EmailChange {
+            id: row.id,
+            user_id: row.user_id,
+            old_email: row.old_email,
+            new_email: row.new_email,
+            status: from_text(&row.status)?,
+            expires_at: row.expires_at,
+            revert_expires_at: row.revert_expires_at,
+            created_at: row.created_at,
+            confirmed_at: row.confirmed_at,
+            reverted_at: row.reverted_at,
+        })
+    }
+}
+
+/// Set a change's status, stamping the matching timestamp column
+async fn set_status(tx: &mut Transaction<'_, Postgres>, id: Uuid, status: &str) -> Result<EmailChange, RepositoryError> {
+    let row = sqlx::query_as::<_, EmailChangeRow>(&format!(
+        r#"
+        UPDATE user_email_changes
+        SET status = $2,
+            confirmed_at = CASE WHEN $2 = 'confirmed' THEN NOW() ELSE confirmed_at END,
+            reverted_at = CASE WHEN $2 = 'reverted' THEN NOW() ELSE reverted_at END
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        EMAIL_CHANGE_COLUMNS
+    ))
+    .bind(id)
+    .bind(status)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    row.try_into()
+}
+
+/// Move an active user from one email address to another, if they still have the first
+async fn swap_email(tx: &mut Transaction<'_, Postgres>, user_id: UserId, from: &str, to: &str) -> Result<Option<User>, RepositoryError> {
+    sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET email = $3, version = version + 1, updated_at = NOW()
+        WHERE id = $1 AND email = $2 AND is_active = true
+        RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
+        "#
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| {
+        if let sqlx::Error::Database(db_err) = &e {
+            if db_err.constraint() == Some("users_email_key") {
+                return RepositoryError::DuplicateEmail(to.to_string());
+            }
+        }
+        RepositoryError::Database(e)
+    })
+}
+
+#[async_trait]
+impl EmailChangeRepository for SqlxEmailChangeRepository {
+    #[instrument(skip(self, change), fields(user_id = %change.user_id))]
+    async fn create(&self, change: &NewEmailChange, expected_version: Option<i64>) -> Result<EmailChange, RepositoryError> {
+        let mut tx = self.begin().await?;
+
+        let version: Option<i64> = sqlx::query_scalar("SELECT version FROM users WHERE id = $1 FOR UPDATE")
+            .bind(change.user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        match (version, expected_version) {
+            (None, _) => return Err(RepositoryError::NotFound),
+            (Some(version), Some(expected)) if version != expected => return Err(RepositoryError::VersionMismatch),
+            _ => {}
+        }
+
+        sqlx::query("UPDATE user_email_changes SET status = 'cancelled' WHERE user_id = $1 AND status = 'pending'")
+            .bind(change.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let row = sqlx::query_as::<_, EmailChangeRow>(&format!(
+            r#"
+            INSERT INTO user_email_changes
+                (user_id, old_email, new_email, confirm_token_hash, revert_token_hash, expires_at, revert_expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {}
+            "#,
+            EMAIL_CHANGE_COLUMNS
+        ))
+        .bind(change.user_id)
+        .bind(&change.old_email)
+        .bind(&change.new_email)
+        .bind(&change.confirm_token_hash)
+        .bind(&change.revert_token_hash)
+        .bind(change.expires_at)
+        .bind(change.revert_expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        info!("Stored pending email change {} for user {}", row.id, change.user_id);
+        row.try_into()
+    }
+
+    #[instrument(skip(self))]
+    async fn find_pending(&self, user_id: UserId) -> Result<Option<EmailChange>, RepositoryError> {
+        let row = sqlx::query_as::<_, EmailChangeRow>(&format!(
+            "SELECT {} FROM user_email_changes WHERE user_id = $1 AND status = 'pending' AND expires_at > NOW()",
+            EMAIL_CHANGE_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(EmailChange::try_from).transpose()
+    }
+
+    #[instrument(skip(self))]
+    async fn cancel_pending(&self, user_id: UserId) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE user_email_changes SET status = 'cancelled' WHERE user_id = $1 AND status = 'pending'"
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self, confirm_token_hash))]
+    async fn confirm(&self, confirm_token_hash: &str) -> Result<(EmailChange, User), RepositoryError> {
+        let mut tx = self.begin().await?;
+
+        let change = match self.lock_by_token(&mut tx, "confirm_token_hash", confirm_token_hash).await? {
+            Some(change) if change.status == EmailChangeStatus::Pending => change,
+            _ => return Err(RepositoryError::NotFound),
+        };
+
+        if change.expires_at <= chrono::Utc::now() {
+            set_status(&mut tx, change.id, "expired").await?;
+            tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+            info!("Email change {} expired before confirmation", change.id);
+            return Err(RepositoryError::NotFound);
+        }
+
+        let Some(user) = swap_email(&mut tx, change.user_id, &change.old_email, &change.new_email).await? else {
+            // The user changed their address some other way in the meantime
+            set_status(&mut tx, change.id, "cancelled").await?;
+            tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+            warn!("Email change {} no longer matches user {}", change.id, change.user_id);
+            return Err(RepositoryError::NotFound);
+        };
+
+        let previous = User { email: change.old_email.clone(), ..user.clone() };
+        insert_outbox_event(&mut tx, &NewOutboxEvent::user_updated(&previous, &user)).await?;
+        let change = set_status(&mut tx, change.id, "confirmed").await?;
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        info!("Confirmed email change {} for user {}", change.id, change.user_id);
+        Ok((change, user))
+    }
+
+    #[instrument(skip(self, revert_token_hash))]
+    async fn revert(&self, revert_token_hash: &str) -> Result<(EmailChange, User), RepositoryError> {
+        let mut tx = self.begin().await?;
+
+        let change = match self.lock_by_token(&mut tx, "revert_token_hash", revert_token_hash).await? {
+            Some(change)
+                if matches!(change.status, EmailChangeStatus::Pending | EmailChangeStatus::Confirmed)
+                    && change.revert_expires_at > chrono::Utc::now() =>
+            {
+                change
+            }
+            _ => return Err(RepositoryError::NotFound),
+        };
+
+        let user = if change.status == EmailChangeStatus::Confirmed {
+            let user = swap_email(&mut tx, change.user_id, &change.new_email, &change.old_email).await?;
+            if let Some(user) = &user {
+                let previous = User { email: change.new_email.clone(), ..user.clone() };
+                insert_outbox_event(&mut tx, &NewOutboxEvent::user_updated(&previous, user)).await?;
+            }
+            user
+        } else {
+            sqlx::query_as::<_, User>(
+                "SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url FROM users WHERE id = $1"
+            )
+            .bind(change.user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+        };
+        let user = user.ok_or(RepositoryError::NotFound)?;
+
+        let change = set_status(&mut tx, change.id, "reverted").await?;
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        info!("Reverted email change {} for user {}", change.id, change.user_id);
+        Ok((change, user))
+    }
+}
@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
+use sqlx::{postgres::PgListener, PgPool};
+use tracing::instrument;
+
+use crate::repository::RepositoryError;
+
+/// Stream of notification payloads received on a channel
+pub type NotificationStream = BoxStream<'static, Result<String, RepositoryError>>;
+
+/// Postgres LISTEN/NOTIFY repository trait
+#[async_trait]
+pub trait EventNotificationRepository: Send + Sync {
+    /// Send a payload to every connection listening on a channel
+    async fn notify(&self, channel: &str, payload: &str) -> Result<(), RepositoryError>;
+
+    /// Listen on a channel on a dedicated connection
+    ///
+    /// The stream ends when the connection is lost, after an error if the
+    /// loss was not a clean disconnect, so callers know that notifications
+    /// may have been missed before they listen again.
+    async fn listen(&self, channel: &str) -> Result<NotificationStream, RepositoryError>;
+}
+
+/// SQLx implementation of EventNotificationRepository
+pub struct SqlxEventNotificationRepository {
+    pool: PgPool,
+}
+
+impl SqlxEventNotificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EventNotificationRepository for SqlxEventNotificationRepository {
+    #[instrument(skip(self, payload))]
+    async fn notify(&self, channel: &str, payload: &str) -> Result<(), RepositoryError> {
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(channel)
+            .bind(payload)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn listen(&self, channel: &str) -> Result<NotificationStream, RepositoryError> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(channel).await?;
+
+        // `recv` would reconnect silently, hiding the notifications missed meanwhile
+        Ok(futures::stream::unfold(Some(listener), |listener| async move {
+            let mut listener = listener?;
+            match listener.try_recv().await {
+                Ok(Some(notification)) => Some((Ok(notification.payload().to_string()), Some(listener))),
+                Ok(None) => None,
+                Err(e) => Some((Err(RepositoryError::from(e)), None)),
+            }
+        })
+        .boxed())
+    }
+}
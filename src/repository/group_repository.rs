@@ -0,0 +1,429 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use tracing::{info, instrument, warn};
+
+use crate::models::{Group, GroupId, GroupMember, GroupMembership, GroupRole, UserGroup, UserId};
+use crate::repository::{from_text, to_text, RepositoryError};
+
+const GROUP_COLUMNS: &str = "g.id, g.name, g.description, g.created_by, g.created_at, g.updated_at";
+
+/// Group and membership repository trait
+#[async_trait]
+pub trait GroupRepository: Send + Sync {
+    /// Create a group with `owner` as its first owner
+    async fn create(&self, name: &str, description: Option<&str>, owner: UserId) -> Result<Group, RepositoryError>;
+
+    /// Find group by ID
+    async fn find_by_id(&self, id: GroupId) -> Result<Option<Group>, RepositoryError>;
+
+    /// List groups by name, with the total number of groups
+    async fn list(&self, limit: i64, offset: i64) -> Result<(Vec<Group>, i64), RepositoryError>;
+
+    /// Update a group's name and description; an empty description clears it
+    async fn update(&self, id: GroupId, name: Option<&str>, description: Option<&str>) -> Result<Group, RepositoryError>;
+
+    /// Delete a group and its memberships
+    async fn delete(&self, id: GroupId) -> Result<(), RepositoryError>;
+
+    /// Add a member or change their role
+    ///
+    /// Taking the owner role from a group's only owner is refused with a
+    /// validation error.
+    async fn set_member(&self, group_id: GroupId, user_id: UserId, role: GroupRole) -> Result<GroupMember, RepositoryError>;
+
+    /// Remove a member, returning whether they were one
+    ///
+    /// Removing a group's only owner is refused with a validation error.
+    async fn remove_member(&self, group_id: GroupId, user_id: UserId) -> Result<bool, RepositoryError>;
+
+    /// List a group's members by name, with the total number of members
+    async fn list_members(&self, group_id: GroupId, limit: i64, offset: i64) -> Result<(Vec<GroupMember>, i64), RepositoryError>;
+
+    /// List the groups a user belongs to by name, with the total number of groups
+    ///
+    /// With `visible_to`, only groups that user is also a member of are listed and counted.
+    async fn list_user_groups(&self, user_id: UserId, visible_to: Option<UserId>, limit: i64, offset: i64) -> Result<(Vec<UserGroup>, i64), RepositoryError>;
+
+    /// The first `limit` groups of each user by name, in one query
+    ///
+    /// With `visible_to`, only groups that user is also a member of are included.
+    async fn list_groups_of_users(&self, user_ids: &[UserId], visible_to: Option<UserId>, limit: i64) -> Result<HashMap<UserId, Vec<UserGroup>>, RepositoryError>;
+
+    /// All of a user's memberships, for authorization checks
+    async fn memberships(&self, user_id: UserId) -> Result<Vec<GroupMembership>, RepositoryError>;
+}
+
+/// SQLx implementation of GroupRepository
+pub struct SqlxGroupRepository {
+    pool: PgPool,
+}
+
+impl SqlxGroupRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Lock a group's owners, refusing to go on if `user_id` is the only one
+///
+/// Concurrent changes to the owners wait for the lock and then see this
+/// change, so two owners cannot both step down at once.
+async fn ensure_other_owner(conn: &mut PgConnection, group_id: GroupId, user_id: UserId) -> Result<(), RepositoryError> {
+    let owners: Vec<UserId> =
+        sqlx::query_scalar("SELECT user_id FROM group_memberships WHERE group_id = $1 AND role = 'owner' FOR UPDATE")
+            .bind(group_id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+    if owners == [user_id] {
+        warn!("Refused to remove the last owner {} of group {}", user_id, group_id);
+        return Err(RepositoryError::Validation("A group must keep at least one owner".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Condition on the membership `m` that its group is shared with the user
+/// bound as parameter `$param`, if any
+fn visible_membership(param: usize) -> String {
+    format!(
+        "(${0}::uuid IS NULL OR EXISTS \
+         (SELECT 1 FROM group_memberships v WHERE v.group_id = m.group_id AND v.user_id = ${0}))",
+        param
+    )
+}
+
+/// Map a clash on the case-insensitive name index to a validation error
+fn name_conflict(name: &str) -> impl FnOnce(sqlx::Error) -> RepositoryError + '_ {
+    move |e| {
+        if let sqlx::Error::Database(db_err) = &e {
+            if db_err.constraint() == Some("idx_groups_name") {
+                return RepositoryError::Validation(format!("A group named {} already exists", name));
+            }
+        }
+        RepositoryError::Database(e)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MemberRow {
+    user_id: UserId,
+    name: String,
+    email: String,
+    role: String,
+    joined_at: chrono::DateTime<chrono::Utc>,
+    #[sqlx(default)]
+    total: i64,
+}
+
+impl TryFrom<MemberRow> for GroupMember {
+    type Error = RepositoryError;
+
+    fn try_from(row: MemberRow) -> Result<Self, Self::Error> {
+        Ok(GroupMember {
+            user_id: row.user_id,
+            name: row.name,
+            email: row.email,
+            role: from_text(&row.role)?,
+            joined_at: row.joined_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct UserGroupRow {
+    #[sqlx(flatten)]
+    group: Group,
+    role: String,
+    joined_at: chrono::DateTime<chrono::Utc>,
+    total: i64,
+}
+
+/// Group of one of several users
+#[derive(sqlx::FromRow)]
+struct MemberGroupRow {
+    #[sqlx(flatten)]
+    group: Group,
+    user_id: UserId,
+    role: String,
+    joined_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[async_trait]
+impl GroupRepository for SqlxGroupRepository {
+    #[instrument(skip(self, description))]
+    async fn create(&self, name: &str, description: Option<&str>, owner: UserId) -> Result<Group, RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            warn!("Failed to begin group transaction: {}", e);
+            RepositoryError::Transaction(e.to_string())
+        })?;
+
+        let group = sqlx::query_as::<_, Group>(
+            r#"
+            INSERT INTO groups (name, description, created_by)
+            VALUES ($1, $2, $3)
+            RETURNING id, name, description, created_by, created_at, updated_at
+            "#
+        )
+        .bind(name)
+        .bind(description)
+        .bind(owner)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(name_conflict(name))?;
+
+        sqlx::query("INSERT INTO group_memberships (group_id, user_id, role) VALUES ($1, $2, 'owner')")
+            .bind(group.id)
+            .bind(owner)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        info!("Created group {} owned by {}", group.id, owner);
+        Ok(group)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_by_id(&self, id: GroupId) -> Result<Option<Group>, RepositoryError> {
+        let group = sqlx::query_as::<_, Group>(&format!("SELECT {} FROM groups g WHERE g.id = $1", GROUP_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(group)
+    }
+
+    #[instrument(skip(self))]
+    async fn list(&self, limit: i64, offset: i64) -> Result<(Vec<Group>, i64), RepositoryError> {
+        let groups = sqlx::query_as::<_, Group>(&format!(
+            "SELECT {} FROM groups g ORDER BY LOWER(g.name) LIMIT $1 OFFSET $2",
+            GROUP_COLUMNS
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM groups")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((groups, total))
+    }
+
+    #[instrument(skip(self, description))]
+    async fn update(&self, id: GroupId, name: Option<&str>, description: Option<&str>) -> Result<Group, RepositoryError> {
+        let group = sqlx::query_as::<_, Group>(
+            r#"
+            UPDATE groups
+            SET name = COALESCE($2, name),
+                description = CASE WHEN $3::TEXT IS NULL THEN description ELSE NULLIF($3, '') END,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, name, description, created_by, created_at, updated_at
+            "#
+        )
+        .bind(id)
+        .bind(name)
+        .bind(description)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(name_conflict(name.unwrap_or_default()))?
+        .ok_or(RepositoryError::NotFound)?;
+
+        info!("Updated group {}", id);
+        Ok(group)
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, id: GroupId) -> Result<(), RepositoryError> {
+        let result = sqlx::query("DELETE FROM groups WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        info!("Deleted group {}", id);
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn set_member(&self, group_id: GroupId, user_id: UserId, role: GroupRole) -> Result<GroupMember, RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+        if role != GroupRole::Owner {
+            ensure_other_owner(&mut tx, group_id, user_id).await?;
+        }
+
+        let row = sqlx::query_as::<_, MemberRow>(
+            r#"
+            WITH membership AS (
+                INSERT INTO group_memberships (group_id, user_id, role)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (group_id, user_id) DO UPDATE SET role = EXCLUDED.role
+                RETURNING user_id, role, created_at
+            )
+            SELECT u.id AS user_id, u.name, u.email, m.role, m.created_at AS joined_at
+            FROM membership m
+            JOIN users u ON u.id = m.user_id
+            "#
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .bind(to_text(&role))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match &e {
+            // Either the group or the user does not exist
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => RepositoryError::NotFound,
+            _ => RepositoryError::Database(e),
+        })?;
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        info!("Set user {} as {:?} of group {}", user_id, role, group_id);
+        row.try_into()
+    }
+
+    #[instrument(skip(self))]
+    async fn remove_member(&self, group_id: GroupId, user_id: UserId) -> Result<bool, RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+        ensure_other_owner(&mut tx, group_id, user_id).await?;
+
+        let result = sqlx::query("DELETE FROM group_memberships WHERE group_id = $1 AND user_id = $2")
+            .bind(group_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self))]
+    async fn list_members(&self, group_id: GroupId, limit: i64, offset: i64) -> Result<(Vec<GroupMember>, i64), RepositoryError> {
+        let rows = sqlx::query_as::<_, MemberRow>(
+            r#"
+            SELECT u.id AS user_id, u.name, u.email, m.role, m.created_at AS joined_at, COUNT(*) OVER () AS total
+            FROM group_memberships m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.group_id = $1
+            ORDER BY LOWER(u.name), u.id
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(group_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = match rows.first() {
+            Some(row) => row.total,
+            None => sqlx::query_scalar("SELECT COUNT(*) FROM group_memberships WHERE group_id = $1")
+                .bind(group_id)
+                .fetch_one(&self.pool)
+                .await?,
+        };
+        let members = rows.into_iter().map(GroupMember::try_from).collect::<Result<_, _>>()?;
+
+        Ok((members, total))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_user_groups(&self, user_id: UserId, visible_to: Option<UserId>, limit: i64, offset: i64) -> Result<(Vec<UserGroup>, i64), RepositoryError> {
+        let rows = sqlx::query_as::<_, UserGroupRow>(&format!(
+            r#"
+            SELECT {}, m.role, m.created_at AS joined_at, COUNT(*) OVER () AS total
+            FROM group_memberships m
+            JOIN groups g ON g.id = m.group_id
+            WHERE m.user_id = $1 AND {}
+            ORDER BY LOWER(g.name), g.id
+            LIMIT $2 OFFSET $3
+            "#,
+            GROUP_COLUMNS,
+            visible_membership(4)
+        ))
+        .bind(user_id)
+        .bind(limit)
+        .bind(offset)
+        .bind(visible_to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = match rows.first() {
+            Some(row) => row.total,
+            None => sqlx::query_scalar(&format!(
+                "SELECT COUNT(*) FROM group_memberships m WHERE m.user_id = $1 AND {}",
+                visible_membership(2)
+            ))
+            .bind(user_id)
+            .bind(visible_to)
+            .fetch_one(&self.pool)
+            .await?,
+        };
+        let groups = rows
+            .into_iter()
+            .map(|row| {
+                Ok(UserGroup {
+                    group: row.group,
+                    role: from_text(&row.role)?,
+                    joined_at: row.joined_at,
+                })
+            })
+            .collect::<Result<_, RepositoryError>>()?;
+
+        Ok((groups, total))
+    }
+
+    #[instrument(skip(self, user_ids), fields(users = user_ids.len()))]
+    async fn list_groups_of_users(&self, user_ids: &[UserId], visible_to: Option<UserId>, limit: i64) -> Result<HashMap<UserId, Vec<UserGroup>>, RepositoryError> {
+        let rows = sqlx::query_as::<_, MemberGroupRow>(&format!(
+            r#"
+            SELECT {}, user_id, role, joined_at
+            FROM (
+                SELECT g.*, m.user_id, m.role, m.created_at AS joined_at,
+                       ROW_NUMBER() OVER (PARTITION BY m.user_id ORDER BY LOWER(g.name), g.id) AS position
+                FROM group_memberships m
+                JOIN groups g ON g.id = m.group_id
+                WHERE m.user_id = ANY($1) AND {}
+            ) g
+            WHERE position <= $2
+            ORDER BY user_id, position
+            "#,
+            GROUP_COLUMNS,
+            visible_membership(3)
+        ))
+        .bind(user_ids)
+        .bind(limit)
+        .bind(visible_to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut groups: HashMap<UserId, Vec<UserGroup>> = HashMap::new();
+        for row in rows {
+            groups.entry(row.user_id).or_default().push(UserGroup {
+                group: row.group,
+                role: from_text(&row.role)?,
+                joined_at: row.joined_at,
+            });
+        }
+
+        Ok(groups)
+    }
+
+    #[instrument(skip(self))]
+    async fn memberships(&self, user_id: UserId) -> Result<Vec<GroupMembership>, RepositoryError> {
+        let rows: Vec<(GroupId, String)> = sqlx::query_as("SELECT group_id, role FROM group_memberships WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|(group_id, role)| Ok(GroupMembership { group_id, role: from_text(&role)? }))
+            .collect()
+    }
+}
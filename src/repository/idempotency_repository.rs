@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{types::Json, PgPool};
+use tracing::{debug, instrument, warn};
+
+use crate::models::{IdempotencyClaim, StoredResponse};
+use crate::repository::RepositoryError;
+
+/// Idempotency key repository trait
+#[async_trait]
+pub trait IdempotencyRepository: Send + Sync {
+    /// Claim `key` within `scope` for a request with the given fingerprint
+    ///
+    /// A new key is locked for `lock_for` and kept for `ttl`. A key whose
+    /// holder never completed it is taken over once its lock has lapsed, and
+    /// an expired key is treated as new.
+    async fn claim(&self, scope: &str, key: &str, fingerprint: &str, ttl: Duration, lock_for: Duration) -> Result<IdempotencyClaim, RepositoryError>;
+
+    /// Store the response for a claimed key and release its lock
+    ///
+    /// `locked_until` is the one returned by the claim. Returns false, storing
+    /// nothing, if the lock lapsed and the key was taken over since.
+    async fn complete(&self, scope: &str, key: &str, locked_until: DateTime<Utc>, response: &StoredResponse) -> Result<bool, RepositoryError>;
+
+    /// Forget a claimed key without a response so the request can be retried,
+    /// unless it was taken over since the claim as for `complete`
+    async fn release(&self, scope: &str, key: &str, locked_until: DateTime<Utc>) -> Result<(), RepositoryError>;
+
+    /// Delete expired keys, returning how many were removed
+    async fn purge_expired(&self) -> Result<u64, RepositoryError>;
+}
+
+/// SQLx implementation of IdempotencyRepository
+pub struct SqlxIdempotencyRepository {
+    pool: PgPool,
+}
+
+impl SqlxIdempotencyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Existing key as stored
+#[derive(sqlx::FromRow)]
+struct IdempotencyKeyRow {
+    fingerprint: String,
+    response_status: Option<i16>,
+    response_headers: Option<Json<Vec<(String, String)>>>,
+    response_body: Option<Vec<u8>>,
+    locked: bool,
+}
+
+#[async_trait]
+impl IdempotencyRepository for SqlxIdempotencyRepository {
+    #[instrument(skip(self, fingerprint))]
+    async fn claim(&self, scope: &str, key: &str, fingerprint: &str, ttl: Duration, lock_for: Duration) -> Result<IdempotencyClaim, RepositoryError> {
+        // Expired keys are left to the periodic purge and replaced here
+        let inserted: Option<DateTime<Utc>> = sqlx::query_scalar(
+            "INSERT INTO idempotency_keys (scope, idempotency_key, fingerprint, locked_until, expires_at) \
+             VALUES ($1, $2, $3, NOW() + $4 * INTERVAL '1 millisecond', NOW() + $5 * INTERVAL '1 millisecond') \
+             ON CONFLICT (scope, idempotency_key) DO UPDATE \
+             SET fingerprint = EXCLUDED.fingerprint, response_status = NULL, response_headers = NULL, \
+                 response_body = NULL, locked_until = EXCLUDED.locked_until, created_at = NOW(), \
+                 expires_at = EXCLUDED.expires_at \
+             WHERE idempotency_keys.expires_at <= NOW() \
+             RETURNING locked_until",
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(fingerprint)
+        .bind(lock_for.num_milliseconds() as f64)
+        .bind(ttl.num_milliseconds() as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(locked_until) = inserted {
+            debug!("Claimed new idempotency key");
+            return Ok(IdempotencyClaim::Acquired { locked_until });
+        }
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            warn!("Failed to begin idempotency transaction: {}", e);
+            RepositoryError::Transaction(e.to_string())
+        })?;
+
+        let existing = sqlx::query_as::<_, IdempotencyKeyRow>(
+            "SELECT fingerprint, response_status, response_headers, response_body, locked_until > NOW() AS locked \
+             FROM idempotency_keys WHERE scope = $1 AND idempotency_key = $2 FOR UPDATE",
+        )
+        .bind(scope)
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        // The key was released or purged between the insert and the lookup
+        let Some(existing) = existing else {
+            return Ok(IdempotencyClaim::InFlight);
+        };
+
+        if existing.fingerprint != fingerprint {
+            return Ok(IdempotencyClaim::Mismatch);
+        }
+
+        if let Some(status) = existing.response_status {
+            return Ok(IdempotencyClaim::Replay(StoredResponse {
+                status: status as u16,
+                headers: existing.response_headers.map(|headers| headers.0).unwrap_or_default(),
+                body: existing.response_body.unwrap_or_default(),
+            }));
+        }
+
+        if existing.locked {
+            return Ok(IdempotencyClaim::InFlight);
+        }
+
+        let locked_until: DateTime<Utc> = sqlx::query_scalar(
+            "UPDATE idempotency_keys SET locked_until = NOW() + $3 * INTERVAL '1 millisecond' \
+             WHERE scope = $1 AND idempotency_key = $2 \
+             RETURNING locked_until",
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(lock_for.num_milliseconds() as f64)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        warn!("Took over idempotency key whose lock had lapsed");
+        Ok(IdempotencyClaim::Acquired { locked_until })
+    }
+
+    #[instrument(skip(self, response), fields(status = response.status))]
+    async fn complete(&self, scope: &str, key: &str, locked_until: DateTime<Utc>, response: &StoredResponse) -> Result<bool, RepositoryError> {
+        // A claim whose lock lapsed must not overwrite the response of the request that took over
+        let result = sqlx::query(
+            "UPDATE idempotency_keys \
+             SET response_status = $4, response_headers = $5, response_body = $6, locked_until = NOW() \
+             WHERE scope = $1 AND idempotency_key = $2 AND locked_until = $3 AND response_status IS NULL",
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(locked_until)
+        .bind(response.status as i16)
+        .bind(Json(&response.headers))
+        .bind(&response.body)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self))]
+    async fn release(&self, scope: &str, key: &str, locked_until: DateTime<Utc>) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "DELETE FROM idempotency_keys \
+             WHERE scope = $1 AND idempotency_key = $2 AND locked_until = $3 AND response_status IS NULL",
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(locked_until)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn purge_expired(&self) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,223 @@
+use async_trait::async_trait;
+use sqlx::{types::Json, PgPool};
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+use crate::models::{ImportJob, ImportRowResult, ImportSummary, UserId, UserImportOptions};
+use crate::repository::{from_text, to_text, RepositoryError};
+
+const IMPORT_JOB_COLUMNS: &str = "id, status, format, mode, dry_run, total_rows, processed_rows, summary, \
+     error, created_by, created_at, started_at, finished_at";
+
+/// User import job repository trait
+#[async_trait]
+pub trait ImportJobRepository: Send + Sync {
+    /// Create a queued job
+    async fn create(&self, options: &UserImportOptions, total_rows: i32, created_by: Option<UserId>) -> Result<ImportJob, RepositoryError>;
+
+    /// Find a job by ID
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<ImportJob>, RepositoryError>;
+
+    /// Get the per-row report of a finished job
+    async fn find_report(&self, id: Uuid) -> Result<Option<Vec<ImportRowResult>>, RepositoryError>;
+
+    /// Mark a job as running
+    async fn mark_running(&self, id: Uuid) -> Result<(), RepositoryError>;
+
+    /// Record how far a running job has got
+    async fn record_progress(&self, id: Uuid, processed_rows: i32, summary: &ImportSummary) -> Result<(), RepositoryError>;
+
+    /// Mark a job as completed and store its report
+    async fn complete(&self, id: Uuid, summary: &ImportSummary, report: &[ImportRowResult]) -> Result<ImportJob, RepositoryError>;
+
+    /// Mark a job as failed
+    async fn fail(&self, id: Uuid, error: &str) -> Result<(), RepositoryError>;
+
+    /// Mark unfinished jobs without progress for `stale_after` as failed, returning how many
+    async fn fail_stale(&self, stale_after: std::time::Duration, error: &str) -> Result<u64, RepositoryError>;
+}
+
+/// SQLx implementation of ImportJobRepository
+pub struct SqlxImportJobRepository {
+    pool: PgPool,
+}
+
+impl SqlxImportJobRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Import job as stored, with enums kept as text
+#[derive(sqlx::FromRow)]
+struct ImportJobRow {
+    id: Uuid,
+    status: String,
+    format: String,
+    mode: String,
+    dry_run: bool,
+    total_rows: i32,
+    processed_rows: i32,
+    summary: Json<ImportSummary>,
+    error: Option<String>,
+    created_by: Option<UserId>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl TryFrom<ImportJobRow> for ImportJob {
+    type Error = RepositoryError;
+
+    fn try_from(row: ImportJobRow) -> Result<Self, Self::Error> {
+        Ok(ImportJob {
+            id: row.id,
+            status: from_text(&row.status)?,
+            format: from_text(&row.format)?,
+            mode: from_text(&row.mode)?,
+            dry_run: row.dry_run,
+            total_rows: row.total_rows,
+            processed_rows: row.processed_rows,
+            summary: row.summary.0,
+            error: row.error,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            started_at: row.started_at,
+            finished_at: row.finished_at,
+        })
+    }
+}
+
+#[async_trait]
+impl ImportJobRepository for SqlxImportJobRepository {
+    #[instrument(skip(self, options))]
+    async fn create(&self, options: &UserImportOptions, total_rows: i32, created_by: Option<UserId>) -> Result<ImportJob, RepositoryError> {
+        let row = sqlx::query_as::<_, ImportJobRow>(&format!(
+            r#"
+            INSERT INTO user_import_jobs (format, mode, dry_run, total_rows, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {}
+            "#,
+            IMPORT_JOB_COLUMNS
+        ))
+        .bind(to_text(&options.format))
+        .bind(to_text(&options.mode))
+        .bind(options.dry_run)
+        .bind(total_rows)
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            warn!("Failed to create import job: {}", e);
+            RepositoryError::Database(e)
+        })?;
+
+        debug!("Created import job {}", row.id);
+        row.try_into()
+    }
+
+    #[instrument(skip(self))]
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<ImportJob>, RepositoryError> {
+        sqlx::query_as::<_, ImportJobRow>(&format!(
+            "SELECT {} FROM user_import_jobs WHERE id = $1",
+            IMPORT_JOB_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(ImportJob::try_from)
+        .transpose()
+    }
+
+    #[instrument(skip(self))]
+    async fn find_report(&self, id: Uuid) -> Result<Option<Vec<ImportRowResult>>, RepositoryError> {
+        let report: Option<Option<Json<Vec<ImportRowResult>>>> = sqlx::query_scalar(
+            "SELECT report FROM user_import_jobs WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(report.flatten().map(|report| report.0))
+    }
+
+    #[instrument(skip(self))]
+    async fn mark_running(&self, id: Uuid) -> Result<(), RepositoryError> {
+        sqlx::query("UPDATE user_import_jobs SET status = 'running', started_at = NOW(), heartbeat_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, summary))]
+    async fn record_progress(&self, id: Uuid, processed_rows: i32, summary: &ImportSummary) -> Result<(), RepositoryError> {
+        sqlx::query("UPDATE user_import_jobs SET processed_rows = $2, summary = $3, heartbeat_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(processed_rows)
+            .bind(Json(summary))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, summary, report))]
+    async fn complete(&self, id: Uuid, summary: &ImportSummary, report: &[ImportRowResult]) -> Result<ImportJob, RepositoryError> {
+        let row = sqlx::query_as::<_, ImportJobRow>(&format!(
+            r#"
+            UPDATE user_import_jobs
+            SET status = 'completed',
+                processed_rows = total_rows,
+                summary = $2,
+                report = $3,
+                started_at = COALESCE(started_at, NOW()),
+                finished_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            IMPORT_JOB_COLUMNS
+        ))
+        .bind(id)
+        .bind(Json(summary))
+        .bind(Json(report))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RepositoryError::NotFound)?;
+
+        debug!("Completed import job {}", id);
+        row.try_into()
+    }
+
+    #[instrument(skip(self))]
+    async fn fail(&self, id: Uuid, error: &str) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE user_import_jobs SET status = 'failed', error = $2, finished_at = NOW() WHERE id = $1"
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn fail_stale(&self, stale_after: std::time::Duration, error: &str) -> Result<u64, RepositoryError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_import_jobs
+            SET status = 'failed', error = $2, finished_at = NOW()
+            WHERE status IN ('queued', 'running')
+              AND COALESCE(heartbeat_at, created_at) < NOW() - make_interval(secs => $1)
+            "#
+        )
+        .bind(stale_after.as_secs_f64())
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
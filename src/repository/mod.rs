@@ -1,3 +1,43 @@
 pub mod user_repository;
+pub mod audit_repository;
+pub mod audit_partition_repository;
+pub mod import_job_repository;
+pub mod personal_data_repository;
+pub mod email_change_repository;
+pub mod group_repository;
+pub mod idempotency_repository;
+pub mod invitation_repository;
+pub mod outbox_repository;
+pub mod webhook_repository;
+pub mod dead_letter_repository;
+pub mod event_notification_repository;
 
-pub use user_repository::{UserRepository, UserRepositoryTransaction, SqlxUserRepository, RepositoryError};
+pub use user_repository::{UserRepository, UserRepositoryTransaction, SqlxUserRepository, RepositoryError, UserStream, UpsertOutcome};
+#[cfg(test)]
+pub(crate) use user_repository::TakenEmails;
+pub use audit_repository::{AuditLogRepository, SqlxAuditLogRepository};
+pub use audit_partition_repository::{AuditPartitionRepository, SqlxAuditPartitionRepository};
+pub use import_job_repository::{ImportJobRepository, SqlxImportJobRepository};
+pub use personal_data_repository::{PersonalDataRepository, PersonalDataSource, SqlxPersonalDataRepository};
+pub use email_change_repository::{EmailChangeRepository, SqlxEmailChangeRepository};
+pub use group_repository::{GroupRepository, SqlxGroupRepository};
+pub use idempotency_repository::{IdempotencyRepository, SqlxIdempotencyRepository};
+pub use invitation_repository::{InvitationRepository, SqlxInvitationRepository};
+pub use outbox_repository::{OutboxRepository, SqlxOutboxRepository};
+pub use webhook_repository::{WebhookRepository, SqlxWebhookRepository};
+pub use dead_letter_repository::{DeadLetterRepository, SqlxDeadLetterRepository};
+pub use event_notification_repository::{EventNotificationRepository, NotificationStream, SqlxEventNotificationRepository};
+
+/// Store a unit enum under its serde name
+pub(crate) fn to_text<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Read back a unit enum stored with [`to_text`]
+pub(crate) fn from_text<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, RepositoryError> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| RepositoryError::Validation(format!("Unexpected stored value: {}", value)))
+}
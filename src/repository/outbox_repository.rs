@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+use crate::models::{NewOutboxEvent, OutboxEvent, OutboxStatus};
+use crate::repository::{from_text, to_text, RepositoryError};
+
+const OUTBOX_COLUMNS: &str = "id, event_type, aggregate_type, aggregate_id, payload, correlation_id, created_at, \
+     status, attempts, next_attempt_at, last_error, delivered_at";
+
+/// Outbox repository trait, used by the dispatcher
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    /// Claim up to `limit` due events, oldest first, counting this as an attempt
+    ///
+    /// Claimed events are hidden from other dispatchers for `lease`, which
+    /// ends at their returned `next_attempt_at`; if the claimer dies they
+    /// become due again afterwards.
+    async fn claim_due(&self, limit: i64, lease: Duration) -> Result<Vec<OutboxEvent>, RepositoryError>;
+
+    /// Mark a claimed event as delivered, returning whether the claim was still held
+    ///
+    /// `leased_until` is the claimed event's `next_attempt_at`. Once the lease
+    /// has run out and another dispatcher has claimed the event, nothing is
+    /// changed and the outcome is left to that dispatcher.
+    async fn mark_delivered(&self, id: Uuid, leased_until: DateTime<Utc>) -> Result<bool, RepositoryError>;
+
+    /// Record a failed attempt, retrying at `retry_at` or giving up when it is
+    /// `None`, returning whether the claim was still held as for `mark_delivered`
+    async fn record_failure(
+        &self,
+        id: Uuid,
+        leased_until: DateTime<Utc>,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<bool, RepositoryError>;
+
+    /// Make a failed event due again with a fresh set of attempts, returning
+    /// whether it was found in the failed state
+    async fn requeue(&self, id: Uuid) -> Result<bool, RepositoryError>;
+
+    /// Delete events delivered before `before`, returning how many were deleted
+    async fn purge_delivered(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError>;
+}
+
+/// SQLx implementation of OutboxRepository
+pub struct SqlxOutboxRepository {
+    pool: PgPool,
+}
+
+impl SqlxOutboxRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Outbox event as stored, with the status kept as text
+#[derive(sqlx::FromRow)]
+struct OutboxEventRow {
+    id: Uuid,
+    event_type: String,
+    aggregate_type: String,
+    aggregate_id: String,
+    payload: serde_json::Value,
+    correlation_id: Option<String>,
+    created_at: DateTime<Utc>,
+    status: String,
+    attempts: i32,
+    next_attempt_at: DateTime<Utc>,
+    last_error: Option<String>,
+    delivered_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<OutboxEventRow> for OutboxEvent {
+    type Error = RepositoryError;
+
+    fn try_from(row: OutboxEventRow) -> Result<Self, Self::Error> {
+        Ok(OutboxEvent {
+            id: row.id,
+            event_type: row.event_type,
+            aggregate_type: row.aggregate_type,
+            aggregate_id: row.aggregate_id,
+            payload: row.payload,
+            correlation_id: row.correlation_id,
+            created_at: row.created_at,
+            status: from_text(&row.status)?,
+            attempts: row.attempts,
+            next_attempt_at: row.next_attempt_at,
+            last_error: row.last_error,
+            delivered_at: row.delivered_at,
+        })
+    }
+}
+
+/// Add an event to the outbox, normally in the transaction of the change it describes
+pub(crate) async fn insert_outbox_event(conn: &mut PgConnection, event: &NewOutboxEvent) -> Result<Uuid, RepositoryError> {
+    let id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO outbox (event_type, aggregate_type, aggregate_id, payload, correlation_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#
+    )
+    .bind(&event.event_type)
+    .bind(&event.aggregate_type)
+    .bind(&event.aggregate_id)
+    .bind(&event.payload)
+    .bind(&event.correlation_id)
+    .fetch_one(conn)
+    .await?;
+
+    debug!("Queued outbox event {} ({})", id, event.event_type);
+    Ok(id)
+}
+
+#[async_trait]
+impl OutboxRepository for SqlxOutboxRepository {
+    #[instrument(skip(self))]
+    async fn claim_due(&self, limit: i64, lease: Duration) -> Result<Vec<OutboxEvent>, RepositoryError> {
+        let rows = sqlx::query_as::<_, OutboxEventRow>(&format!(
+            r#"
+            UPDATE outbox
+            SET attempts = attempts + 1, next_attempt_at = NOW() + make_interval(secs => $3)
+            WHERE id IN (
+                SELECT id FROM outbox
+                WHERE status = $1 AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at, created_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            OUTBOX_COLUMNS
+        ))
+        .bind(to_text(&OutboxStatus::Pending))
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut events = rows.into_iter().map(OutboxEvent::try_from).collect::<Result<Vec<_>, _>>()?;
+        events.sort_by_key(|event| event.created_at);
+        Ok(events)
+    }
+
+    #[instrument(skip(self))]
+    async fn mark_delivered(&self, id: Uuid, leased_until: DateTime<Utc>) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE outbox
+            SET status = $3, delivered_at = NOW(), last_error = NULL
+            WHERE id = $1 AND next_attempt_at = $2 AND status = $4
+            "#
+        )
+        .bind(id)
+        .bind(leased_until)
+        .bind(to_text(&OutboxStatus::Delivered))
+        .bind(to_text(&OutboxStatus::Pending))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self, error))]
+    async fn record_failure(
+        &self,
+        id: Uuid,
+        leased_until: DateTime<Utc>,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<bool, RepositoryError> {
+        let status = if retry_at.is_some() { OutboxStatus::Pending } else { OutboxStatus::Failed };
+
+        let result = sqlx::query(
+            r#"
+            UPDATE outbox
+            SET status = $3, last_error = $4, next_attempt_at = COALESCE($5, next_attempt_at)
+            WHERE id = $1 AND next_attempt_at = $2 AND status = $6
+            "#
+        )
+        .bind(id)
+        .bind(leased_until)
+        .bind(to_text(&status))
+        .bind(error)
+        .bind(retry_at)
+        .bind(to_text(&OutboxStatus::Pending))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self))]
+    async fn requeue(&self, id: Uuid) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE outbox
+            SET status = $2, attempts = 0, next_attempt_at = NOW(), last_error = NULL
+            WHERE id = $1 AND status = $3
+            "#
+        )
+        .bind(id)
+        .bind(to_text(&OutboxStatus::Pending))
+        .bind(to_text(&OutboxStatus::Failed))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self))]
+    async fn purge_delivered(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM outbox WHERE status = $1 AND delivered_at < $2")
+            .bind(to_text(&OutboxStatus::Delivered))
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
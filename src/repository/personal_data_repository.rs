@@ -0,0 +1,476 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use sqlx::{Acquire, PgPool, Postgres, Transaction};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::config::AuditFailureMode;
+use crate::models::{
+    AuditLog, AuditPartition, AuditRedaction, DataSubject, ErasureRequest, NewAuditLog, NewOutboxEvent, User, UserId,
+    AUDIT_REDACTION_ACTION, ERASED_USER_NAME, ERASED_VALUE,
+};
+use crate::repository::{
+    audit_repository::insert_audit_log, outbox_repository::insert_outbox_event, RepositoryError,
+};
+
+/// A table (or group of tables) holding personal data about users
+///
+/// Every source contributes a section to data exports and anonymizes its rows
+/// during erasure. New tables with personal data should add a source and
+/// register it with [`SqlxPersonalDataRepository::with_source`].
+#[async_trait]
+pub trait PersonalDataSource: Send + Sync {
+    /// Section name in the export archive
+    fn name(&self) -> &'static str;
+
+    /// Everything the source holds about the subject
+    async fn export(&self, pool: &PgPool, subject: &DataSubject) -> Result<Value, RepositoryError>;
+
+    /// Anonymize the subject's data, returning the number of rows changed
+    async fn erase(&self, tx: &mut Transaction<'_, Postgres>, subject: &DataSubject) -> Result<u64, RepositoryError>;
+}
+
+/// Personal data export and erasure across all registered sources
+#[async_trait]
+pub trait PersonalDataRepository: Send + Sync {
+    /// Collect every source's section for the subject
+    async fn export(&self, subject: &DataSubject) -> Result<BTreeMap<String, Value>, RepositoryError>;
+
+    /// Anonymize the subject in every source and record the erasure, in one transaction
+    ///
+    /// `audit` is written in the same transaction, with the erasure request
+    /// and rows changed added as metadata. If it cannot be written the
+    /// erasure is rolled back, unless `audit_failure_mode` is open.
+    async fn erase(
+        &self,
+        subject: &DataSubject,
+        requested_by: Option<UserId>,
+        reason: Option<&str>,
+        audit: &NewAuditLog,
+        audit_failure_mode: AuditFailureMode,
+    ) -> Result<ErasureRequest, RepositoryError>;
+}
+
+/// SQLx implementation of PersonalDataRepository
+pub struct SqlxPersonalDataRepository {
+    pool: PgPool,
+    sources: Vec<Arc<dyn PersonalDataSource>>,
+}
+
+impl SqlxPersonalDataRepository {
+    /// Create a repository covering every table that currently holds personal data
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            sources: vec![
+                Arc::new(UserRowSource),
+                Arc::new(AuditLogSource),
+                Arc::new(ImportJobSource),
+                Arc::new(EmailChangeSource),
+                Arc::new(InvitationSource),
+                Arc::new(ErasureRequestSource),
+            ],
+        }
+    }
+
+    /// Register an additional source
+    pub fn with_source(mut self, source: Arc<dyn PersonalDataSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+}
+
+#[async_trait]
+impl PersonalDataRepository for SqlxPersonalDataRepository {
+    #[instrument(skip(self, subject), fields(user_id = %subject.user_id))]
+    async fn export(&self, subject: &DataSubject) -> Result<BTreeMap<String, Value>, RepositoryError> {
+        let mut sections = BTreeMap::new();
+        for source in &self.sources {
+            sections.insert(source.name().to_string(), source.export(&self.pool, subject).await?);
+        }
+
+        info!("Exported {} personal data sections for user {}", sections.len(), subject.user_id);
+        Ok(sections)
+    }
+
+    #[instrument(skip(self, subject, reason, audit), fields(user_id = %subject.user_id))]
+    async fn erase(
+        &self,
+        subject: &DataSubject,
+        requested_by: Option<UserId>,
+        reason: Option<&str>,
+        audit: &NewAuditLog,
+        audit_failure_mode: AuditFailureMode,
+    ) -> Result<ErasureRequest, RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            warn!("Failed to begin erasure transaction: {}", e);
+            RepositoryError::Transaction(e.to_string())
+        })?;
+
+        let mut affected_rows = serde_json::Map::new();
+        for source in &self.sources {
+            let rows = source.erase(&mut tx, subject).await?;
+            affected_rows.insert(source.name().to_string(), json!(rows));
+        }
+
+        let request = sqlx::query_as::<_, ErasureRequest>(
+            r#"
+            INSERT INTO user_erasure_requests (user_id, requested_by, reason, affected_rows)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, user_id, requested_by, reason, affected_rows, completed_at
+            "#
+        )
+        .bind(subject.user_id)
+        .bind(requested_by)
+        .bind(reason)
+        .bind(Value::Object(affected_rows))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut event = NewOutboxEvent::user_erased(subject.user_id, request.id, request.completed_at);
+        event.correlation_id = audit.correlation_id.clone();
+        insert_outbox_event(&mut tx, &event).await?;
+
+        let entry = audit.clone().with_metadata(json!({
+            "erasure_request_id": request.id,
+            "affected_rows": request.affected_rows,
+        }));
+        // A savepoint keeps a failed entry from aborting the erasure when failing open
+        let mut savepoint = tx.begin().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+        match insert_audit_log(&mut savepoint, &entry).await {
+            Ok(_) => savepoint.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?,
+            Err(e) => {
+                if let Err(rollback_err) = savepoint.rollback().await {
+                    warn!("Failed to roll back audit log savepoint: {}", rollback_err);
+                }
+                match audit_failure_mode {
+                    AuditFailureMode::Closed => {
+                        warn!("Failed to record erasure of user {} in audit log, rolling back: {}", subject.user_id, e);
+                        return Err(e);
+                    }
+                    AuditFailureMode::Open => {
+                        warn!("Failed to record erasure of user {} in audit log, keeping it: {}", subject.user_id, e);
+                    }
+                }
+            }
+        }
+
+        tx.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        info!("Erased personal data of user {} (request {})", subject.user_id, request.id);
+        Ok(request)
+    }
+}
+
+/// The `users` row itself
+pub struct UserRowSource;
+
+#[async_trait]
+impl PersonalDataSource for UserRowSource {
+    fn name(&self) -> &'static str {
+        "user"
+    }
+
+    async fn export(&self, pool: &PgPool, subject: &DataSubject) -> Result<Value, RepositoryError> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url FROM users WHERE id = $1"
+        )
+        .bind(subject.user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(RepositoryError::NotFound)?;
+
+        Ok(serde_json::to_value(user).unwrap_or_default())
+    }
+
+    async fn erase(&self, tx: &mut Transaction<'_, Postgres>, subject: &DataSubject) -> Result<u64, RepositoryError> {
+        // The row stays so that foreign keys and audit history still resolve
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET name = $2, email = $3, metadata = '{}'::jsonb, avatar_key = NULL, avatar_url = NULL,
+                is_active = false, version = version + 1, updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(subject.user_id)
+        .bind(ERASED_USER_NAME)
+        .bind(subject.erased_email())
+        .execute(&mut **tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(result.rows_affected())
+    }
+}
+
+/// Audit entries performed by or about the user
+///
+/// Entries are kept for accountability; only the PII inside their metadata is
+/// redacted, in `audit_logs` and in partitions detached by retention that are
+/// not dropped yet. Partitions already archived to files are exempt.
+pub struct AuditLogSource;
+
+const AUDIT_SUBJECT_FILTER: &str = "user_id = $1 OR (resource_type = 'user' AND resource_id = $1::text)";
+
+#[async_trait]
+impl PersonalDataSource for AuditLogSource {
+    fn name(&self) -> &'static str {
+        "audit_logs"
+    }
+
+    async fn export(&self, pool: &PgPool, subject: &DataSubject) -> Result<Value, RepositoryError> {
+        let logs = sqlx::query_as::<_, AuditLog>(&format!(
+            "SELECT id, user_id, action, resource_type, resource_id, metadata, correlation_id, created_at \
+             FROM audit_logs WHERE {} ORDER BY created_at",
+            AUDIT_SUBJECT_FILTER
+        ))
+        .bind(subject.user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(serde_json::to_value(logs).unwrap_or_default())
+    }
+
+    async fn erase(&self, tx: &mut Transaction<'_, Postgres>, subject: &DataSubject) -> Result<u64, RepositoryError> {
+        let detached: Vec<String> =
+            sqlx::query_scalar("SELECT partition_name FROM audit_log_archives WHERE dropped_at IS NULL")
+                .fetch_all(&mut **tx)
+                .await?;
+        // Names are only spliced into the queries once they parse as partition names
+        let tables = std::iter::once("audit_logs".to_string())
+            .chain(detached.into_iter().filter(|name| AuditPartition::parse(name).is_some()));
+
+        let mut redacted = 0;
+        for table in tables {
+            let candidates: Vec<(Uuid, Option<i64>, Value)> = sqlx::query_as(&format!(
+                "SELECT id, sequence, metadata FROM {} \
+                 WHERE metadata IS NOT NULL AND action <> $3 AND ({} OR metadata::text ILIKE '%' || $2 || '%') \
+                 ORDER BY sequence",
+                table, AUDIT_SUBJECT_FILTER
+            ))
+            .bind(subject.user_id)
+            .bind(&subject.email)
+            .bind(AUDIT_REDACTION_ACTION)
+            .fetch_all(&mut **tx)
+            .await?;
+
+            for (id, sequence, metadata) in candidates {
+                let mut redacted_metadata = metadata.clone();
+                if subject.redact(&mut redacted_metadata) {
+                    sqlx::query(&format!("UPDATE {} SET metadata = $2, redacted_at = NOW() WHERE id = $1", table))
+                        .bind(id)
+                        .bind(&redacted_metadata)
+                        .execute(&mut **tx)
+                        .await?;
+                    // Chained entries keep verifying against the redaction recorded in the chain
+                    if let Some(sequence) = sequence {
+                        let redaction = AuditRedaction::new(sequence, &metadata, &redacted_metadata);
+                        insert_audit_log(tx, &redaction.to_entry(id)).await?;
+                    }
+                    redacted += 1;
+                }
+            }
+        }
+
+        Ok(redacted)
+    }
+}
+
+/// Bulk import jobs started by the user and import report rows naming them
+pub struct ImportJobSource;
+
+#[async_trait]
+impl PersonalDataSource for ImportJobSource {
+    fn name(&self) -> &'static str {
+        "user_imports"
+    }
+
+    async fn export(&self, pool: &PgPool, subject: &DataSubject) -> Result<Value, RepositoryError> {
+        let started: Vec<(Uuid, String, Value, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+            "SELECT id, status, summary, created_at FROM user_import_jobs WHERE created_by = $1 ORDER BY created_at"
+        )
+        .bind(subject.user_id)
+        .fetch_all(pool)
+        .await?;
+
+        let report_rows: Vec<(Uuid, Value)> = sqlx::query_as(
+            r#"
+            SELECT jobs.id, row
+            FROM user_import_jobs jobs, jsonb_array_elements(jobs.report) AS row
+            WHERE jobs.report @> jsonb_build_array(jsonb_build_object('email', $1::text))
+              AND row->>'email' = $1
+            "#
+        )
+        .bind(&subject.email)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(json!({
+            "jobs_started": started
+                .into_iter()
+                .map(|(id, status, summary, created_at)| json!({
+                    "id": id,
+                    "status": status,
+                    "summary": summary,
+                    "created_at": created_at,
+                }))
+                .collect::<Vec<_>>(),
+            "report_rows": report_rows
+                .into_iter()
+                .map(|(job_id, row)| json!({ "job_id": job_id, "row": row }))
+                .collect::<Vec<_>>(),
+        }))
+    }
+
+    async fn erase(&self, tx: &mut Transaction<'_, Postgres>, subject: &DataSubject) -> Result<u64, RepositoryError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_import_jobs
+            SET report = (
+                SELECT jsonb_agg(
+                    CASE WHEN row->>'email' = $1 THEN jsonb_set(row, '{email}', to_jsonb($2::text)) ELSE row END
+                    ORDER BY position
+                )
+                FROM jsonb_array_elements(report) WITH ORDINALITY AS rows(row, position)
+            )
+            WHERE report @> jsonb_build_array(jsonb_build_object('email', $1::text))
+            "#
+        )
+        .bind(&subject.email)
+        .bind(ERASED_VALUE)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Requested changes of the user's email address
+pub struct EmailChangeSource;
+
+#[async_trait]
+impl PersonalDataSource for EmailChangeSource {
+    fn name(&self) -> &'static str {
+        "email_changes"
+    }
+
+    async fn export(&self, pool: &PgPool, subject: &DataSubject) -> Result<Value, RepositoryError> {
+        let changes: Vec<(Uuid, String, String, String, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+            "SELECT id, old_email, new_email, status, created_at FROM user_email_changes WHERE user_id = $1 ORDER BY created_at"
+        )
+        .bind(subject.user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(changes
+            .into_iter()
+            .map(|(id, old_email, new_email, status, created_at)| json!({
+                "id": id,
+                "old_email": old_email,
+                "new_email": new_email,
+                "status": status,
+                "created_at": created_at,
+            }))
+            .collect())
+    }
+
+    async fn erase(&self, tx: &mut Transaction<'_, Postgres>, subject: &DataSubject) -> Result<u64, RepositoryError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_email_changes
+            SET old_email = $2, new_email = $2,
+                status = CASE WHEN status = 'pending' THEN 'cancelled' ELSE status END
+            WHERE user_id = $1
+            "#
+        )
+        .bind(subject.user_id)
+        .bind(ERASED_VALUE)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Invitations sent to the user's address or accepted by the user
+pub struct InvitationSource;
+
+#[async_trait]
+impl PersonalDataSource for InvitationSource {
+    fn name(&self) -> &'static str {
+        "invitations"
+    }
+
+    async fn export(&self, pool: &PgPool, subject: &DataSubject) -> Result<Value, RepositoryError> {
+        let invitations: Vec<(Uuid, String, Option<String>, String, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+            "SELECT id, email, name, status, created_at FROM invitations \
+             WHERE user_id = $1 OR LOWER(email) = LOWER($2) ORDER BY created_at"
+        )
+        .bind(subject.user_id)
+        .bind(&subject.email)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(invitations
+            .into_iter()
+            .map(|(id, email, name, status, created_at)| json!({
+                "id": id,
+                "email": email,
+                "name": name,
+                "status": status,
+                "created_at": created_at,
+            }))
+            .collect())
+    }
+
+    async fn erase(&self, tx: &mut Transaction<'_, Postgres>, subject: &DataSubject) -> Result<u64, RepositoryError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE invitations
+            SET email = $3, name = NULL,
+                status = CASE WHEN status = 'pending' THEN 'revoked' ELSE status END,
+                revoked_at = CASE WHEN status = 'pending' THEN NOW() ELSE revoked_at END
+            WHERE user_id = $1 OR LOWER(email) = LOWER($2)
+            "#
+        )
+        .bind(subject.user_id)
+        .bind(&subject.email)
+        .bind(ERASED_VALUE)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Earlier erasure requests for the user
+pub struct ErasureRequestSource;
+
+#[async_trait]
+impl PersonalDataSource for ErasureRequestSource {
+    fn name(&self) -> &'static str {
+        "erasure_requests"
+    }
+
+    async fn export(&self, pool: &PgPool, subject: &DataSubject) -> Result<Value, RepositoryError> {
+        let requests = sqlx::query_as::<_, ErasureRequest>(
+            "SELECT id, user_id, requested_by, reason, affected_rows, completed_at \
+             FROM user_erasure_requests WHERE user_id = $1 ORDER BY completed_at"
+        )
+        .bind(subject.user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(serde_json::to_value(requests).unwrap_or_default())
+    }
+
+    async fn erase(&self, _tx: &mut Transaction<'_, Postgres>, _subject: &DataSubject) -> Result<u64, RepositoryError> {
+        // The erasure record is the evidence the request was honored
+        Ok(0)
+    }
+}
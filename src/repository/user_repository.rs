@@ -1,8 +1,11 @@
 use async_trait::async_trait;
-use sqlx::{PgPool, Transaction, Postgres};
+use futures::{stream::BoxStream, SinkExt, StreamExt};
+use sqlx::{Acquire, PgPool, Transaction, Postgres};
 use tracing::{info, warn, instrument};
 
-use crate::models::{User, NewUser, UserId};
+use crate::models::{AuditLog, NewAuditLog, NewOutboxEvent, User, NewUser, UserId, UserSearchResult, UserSearchHighlight, HEADLINE_START, HEADLINE_STOP};
+use crate::repository::audit_repository::insert_audit_log;
+use crate::repository::outbox_repository::insert_outbox_event;
 
 /// Repository error types
 #[derive(Debug, thiserror::Error)]
@@ -13,6 +16,9 @@ pub enum RepositoryError {
     #[error("User not found")]
     NotFound,
 
+    #[error("Version mismatch")]
+    VersionMismatch,
+
     #[error("Duplicate email: {0}")]
     DuplicateEmail(String),
 
@@ -26,6 +32,21 @@ pub enum RepositoryError {
     Connection(String),
 }
 
+/// Stream of users read from a database cursor
+pub type UserStream = BoxStream<'static, Result<User, RepositoryError>>;
+
+/// Result of inserting a user that may already exist
+#[derive(Debug)]
+pub enum UpsertOutcome {
+    Created(User),
+    Updated { before: User, after: User },
+    /// A user with the email exists and was left untouched
+    Skipped,
+}
+
+/// Rows buffered between the database cursor and the stream consumer
+const USER_STREAM_BUFFER: usize = 256;
+
 /// User repository trait with comprehensive data access methods
 #[async_trait]
 pub trait UserRepository: Send + Sync {
@@ -42,13 +63,19 @@ pub trait UserRepository: Send + Sync {
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, RepositoryError>;
 
     /// Update user information
-    async fn update(&self, id: UserId, name: Option<String>, email: Option<String>) -> Result<User, RepositoryError>;
+    ///
+    /// When `expected_version` is set the update only applies if the stored
+    /// version still matches, otherwise `VersionMismatch` is returned.
+    async fn update(&self, id: UserId, name: Option<String>, email: Option<String>, metadata: Option<serde_json::Value>, expected_version: Option<i64>) -> Result<User, RepositoryError>;
 
     /// Update user within a transaction
-    async fn update_tx(&self, tx: &mut Transaction<'_, Postgres>, id: UserId, name: Option<String>, email: Option<String>) -> Result<User, RepositoryError>;
+    async fn update_tx(&self, tx: &mut Transaction<'_, Postgres>, id: UserId, name: Option<String>, email: Option<String>, metadata: Option<serde_json::Value>, expected_version: Option<i64>) -> Result<User, RepositoryError>;
+
+    /// Soft delete user (set is_active to false), honoring `expected_version` like `update`
+    async fn soft_delete(&self, id: UserId, expected_version: Option<i64>) -> Result<(), RepositoryError>;
 
-    /// Soft delete user (set is_active to false)
-    async fn soft_delete(&self, id: UserId) -> Result<(), RepositoryError>;
+    /// Set or clear a user's avatar
+    async fn set_avatar(&self, id: UserId, avatar_key: Option<&str>, avatar_url: Option<&str>, expected_version: Option<i64>) -> Result<User, RepositoryError>;
 
     /// Hard delete user (remove from database)
     async fn delete(&self, id: UserId) -> Result<(), RepositoryError>;
@@ -57,7 +84,17 @@ pub trait UserRepository: Send + Sync {
     async fn list(&self, limit: i64, offset: i64) -> Result<Vec<User>, RepositoryError>;
 
     /// List active users only
-    async fn list_active(&self, limit: i64, offset: i64) -> Result<Vec<User>, RepositoryError>;
+    ///
+    /// `metadata` restricts results to users whose metadata contains the given object.
+    async fn list_active(&self, limit: i64, offset: i64, metadata: Option<&serde_json::Value>) -> Result<Vec<User>, RepositoryError>;
+
+    /// Stream active users from a cursor without loading them all into memory
+    ///
+    /// The cursor stops as soon as the returned stream is dropped.
+    fn stream_active(&self, metadata: Option<serde_json::Value>) -> UserStream;
+
+    /// Search users by full-text and trigram similarity, best matches first
+    async fn search(&self, query: &str, is_active: Option<bool>, limit: i64) -> Result<Vec<UserSearchResult>, RepositoryError>;
 
     /// Count total users
     async fn count(&self) -> Result<i64, RepositoryError>;
@@ -71,6 +108,9 @@ pub trait UserRepository: Send + Sync {
     /// Check if email exists for different user
     async fn email_exists_for_other_user(&self, email: &str, user_id: UserId) -> Result<bool, RepositoryError>;
 
+    /// Return which of the given emails already belong to a user
+    async fn existing_emails(&self, emails: &[String]) -> Result<Vec<String>, RepositoryError>;
+
     /// Activate user
     async fn activate(&self, id: UserId) -> Result<(), RepositoryError>;
 
@@ -87,8 +127,33 @@ pub trait UserRepositoryTransaction: Send + Sync {
     /// Create a new user within the transaction
     async fn create(&mut self, user: &NewUser) -> Result<User, RepositoryError>;
 
+    /// Find user by ID, locking the row until the transaction ends
+    async fn find_for_update(&mut self, id: UserId) -> Result<Option<User>, RepositoryError>;
+
     /// Update user within the transaction
-    async fn update(&mut self, id: UserId, name: Option<String>, email: Option<String>) -> Result<User, RepositoryError>;
+    async fn update(&mut self, id: UserId, name: Option<String>, email: Option<String>, metadata: Option<serde_json::Value>, expected_version: Option<i64>) -> Result<User, RepositoryError>;
+
+    /// Create a user, or update the name and metadata of the user holding the
+    /// same email when `update_existing` is set
+    async fn upsert(&mut self, user: &NewUser, update_existing: bool) -> Result<UpsertOutcome, RepositoryError>;
+
+    /// Soft delete user within the transaction, returning the deleted row
+    async fn soft_delete(&mut self, id: UserId, expected_version: Option<i64>) -> Result<User, RepositoryError>;
+
+    /// Replace the avatar reference within the transaction
+    async fn set_avatar(&mut self, id: UserId, avatar_key: Option<&str>, avatar_url: Option<&str>, expected_version: Option<i64>) -> Result<User, RepositoryError>;
+
+    /// Activate or deactivate user within the transaction
+    async fn set_active(&mut self, id: UserId, is_active: bool, expected_version: Option<i64>) -> Result<User, RepositoryError>;
+
+    /// Record an audit log entry alongside the changes in the transaction
+    ///
+    /// The entry is written under a savepoint, so a failed write leaves the
+    /// rest of the transaction usable.
+    async fn record_audit(&mut self, entry: &NewAuditLog) -> Result<AuditLog, RepositoryError>;
+
+    /// Queue a domain event for delivery once the transaction commits
+    async fn enqueue_event(&mut self, event: &NewOutboxEvent) -> Result<(), RepositoryError>;
 
     /// Commit the transaction
     async fn commit(self: Box<Self>) -> Result<(), RepositoryError>;
@@ -97,6 +162,55 @@ pub trait UserRepositoryTransaction: Send + Sync {
     async fn rollback(self: Box<Self>) -> Result<(), RepositoryError>;
 }
 
+/// Work out why a conditional write on a user matched no rows
+///
+/// The row is either gone (`NotFound`) or its version moved on (`VersionMismatch`).
+async fn missing_row_error<'e, E>(executor: E, id: UserId) -> RepositoryError
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    match sqlx::query_scalar::<_, i64>("SELECT version FROM users WHERE id = $1")
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+    {
+        Ok(Some(version)) => {
+            warn!("Version mismatch for user {} (current version: {})", id, version);
+            RepositoryError::VersionMismatch
+        }
+        Ok(None) => RepositoryError::NotFound,
+        Err(e) => RepositoryError::Database(e),
+    }
+}
+
+/// Row returned by the user search query
+#[derive(sqlx::FromRow)]
+struct UserSearchRow {
+    #[sqlx(flatten)]
+    user: User,
+    rank: f32,
+    name_highlight: String,
+    email_highlight: String,
+}
+
+impl From<UserSearchRow> for UserSearchResult {
+    fn from(row: UserSearchRow) -> Self {
+        Self {
+            user: row.user,
+            rank: row.rank,
+            highlight: UserSearchHighlight {
+                name: UserSearchHighlight::headline_html(&row.name_highlight),
+                email: UserSearchHighlight::headline_html(&row.email_highlight),
+            },
+        }
+    }
+}
+
+/// Escape LIKE wildcards so user input is matched literally
+fn escape_like(input: &str) -> String {
+    input.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
 /// SQLx implementation of UserRepository
 pub struct SqlxUserRepository {
     pool: PgPool,
@@ -116,13 +230,14 @@ impl UserRepository for SqlxUserRepository {
 
         let user = sqlx::query_as::<_, User>(
             r#"
-            INSERT INTO users (name, email, is_active, created_at, updated_at)
-            VALUES ($1, $2, true, NOW(), NOW())
-            RETURNING id, name, email, is_active, created_at, updated_at
+            INSERT INTO users (name, email, metadata, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, true, NOW(), NOW())
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
             "#
         )
         .bind(&user.name)
         .bind(&user.email)
+        .bind(&user.metadata)
         .fetch_one(&self.pool)
         .await
         .map_err(|e| {
@@ -145,13 +260,14 @@ impl UserRepository for SqlxUserRepository {
 
         let user = sqlx::query_as::<_, User>(
             r#"
-            INSERT INTO users (name, email, is_active, created_at, updated_at)
-            VALUES ($1, $2, true, NOW(), NOW())
-            RETURNING id, name, email, is_active, created_at, updated_at
+            INSERT INTO users (name, email, metadata, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, true, NOW(), NOW())
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
             "#
         )
         .bind(&user.name)
         .bind(&user.email)
+        .bind(&user.metadata)
         .fetch_one(&mut **tx)
         .await
         .map_err(|e| {
@@ -171,7 +287,7 @@ impl UserRepository for SqlxUserRepository {
     #[instrument(skip(self), fields(user_id = %id))]
     async fn find_by_id(&self, id: UserId) -> Result<Option<User>, RepositoryError> {
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, name, email, is_active, created_at, updated_at FROM users WHERE id = $1"
+            "SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url FROM users WHERE id = $1"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -188,7 +304,7 @@ impl UserRepository for SqlxUserRepository {
     #[instrument(skip(self), fields(email = %email))]
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, RepositoryError> {
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, name, email, is_active, created_at, updated_at FROM users WHERE email = $1"
+            "SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url FROM users WHERE email = $1"
         )
         .bind(email)
         .fetch_optional(&self.pool)
@@ -203,7 +319,7 @@ impl UserRepository for SqlxUserRepository {
     }
 
     #[instrument(skip(self), fields(user_id = %id))]
-    async fn update(&self, id: UserId, name: Option<String>, email: Option<String>) -> Result<User, RepositoryError> {
+    async fn update(&self, id: UserId, name: Option<String>, email: Option<String>, metadata: Option<serde_json::Value>, expected_version: Option<i64>) -> Result<User, RepositoryError> {
         info!("Updating user with ID: {}", id);
 
         // Check for email conflicts if email is being updated
@@ -218,24 +334,32 @@ impl UserRepository for SqlxUserRepository {
             UPDATE users
             SET name = COALESCE($2, name),
                 email = COALESCE($3, email),
+                metadata = COALESCE($4, metadata),
+                version = version + 1,
                 updated_at = NOW()
-            WHERE id = $1
-            RETURNING id, name, email, is_active, created_at, updated_at
+            WHERE id = $1 AND ($5::BIGINT IS NULL OR version = $5)
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
             "#
         )
         .bind(id)
         .bind(name)
         .bind(email)
+        .bind(metadata)
+        .bind(expected_version)
         .fetch_optional(&self.pool)
-        .await?
-        .ok_or(RepositoryError::NotFound)?;
+        .await?;
+
+        let user = match user {
+            Some(user) => user,
+            None => return Err(missing_row_error(&self.pool, id).await),
+        };
 
         info!("Successfully updated user with ID: {}", id);
         Ok(user)
     }
 
     #[instrument(skip(self, tx), fields(user_id = %id))]
-    async fn update_tx(&self, tx: &mut Transaction<'_, Postgres>, id: UserId, name: Option<String>, email: Option<String>) -> Result<User, RepositoryError> {
+    async fn update_tx(&self, tx: &mut Transaction<'_, Postgres>, id: UserId, name: Option<String>, email: Option<String>, metadata: Option<serde_json::Value>, expected_version: Option<i64>) -> Result<User, RepositoryError> {
         info!("Updating user in transaction with ID: {}", id);
 
         let user = sqlx::query_as::<_, User>(
@@ -243,41 +367,79 @@ impl UserRepository for SqlxUserRepository {
             UPDATE users
             SET name = COALESCE($2, name),
                 email = COALESCE($3, email),
+                metadata = COALESCE($4, metadata),
+                version = version + 1,
                 updated_at = NOW()
-            WHERE id = $1
-            RETURNING id, name, email, is_active, created_at, updated_at
+            WHERE id = $1 AND ($5::BIGINT IS NULL OR version = $5)
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
             "#
         )
         .bind(id)
         .bind(name)
         .bind(email)
+        .bind(metadata)
+        .bind(expected_version)
         .fetch_optional(&mut **tx)
-        .await?
-        .ok_or(RepositoryError::NotFound)?;
+        .await?;
+
+        let user = match user {
+            Some(user) => user,
+            None => return Err(missing_row_error(&mut **tx, id).await),
+        };
 
         info!("Successfully updated user in transaction with ID: {}", id);
         Ok(user)
     }
 
     #[instrument(skip(self), fields(user_id = %id))]
-    async fn soft_delete(&self, id: UserId) -> Result<(), RepositoryError> {
+    async fn soft_delete(&self, id: UserId, expected_version: Option<i64>) -> Result<(), RepositoryError> {
         info!("Soft deleting user with ID: {}", id);
 
         let result = sqlx::query(
-            "UPDATE users SET is_active = false, updated_at = NOW() WHERE id = $1"
+            r#"
+            UPDATE users
+            SET is_active = false, avatar_key = NULL, avatar_url = NULL, version = version + 1, updated_at = NOW()
+            WHERE id = $1 AND ($2::BIGINT IS NULL OR version = $2)
+            "#
         )
         .bind(id)
+        .bind(expected_version)
         .execute(&self.pool)
         .await?;
 
         if result.rows_affected() == 0 {
-            return Err(RepositoryError::NotFound);
+            return Err(missing_row_error(&self.pool, id).await);
         }
 
         info!("Successfully soft deleted user with ID: {}", id);
         Ok(())
     }
 
+    #[instrument(skip(self), fields(user_id = %id))]
+    async fn set_avatar(&self, id: UserId, avatar_key: Option<&str>, avatar_url: Option<&str>, expected_version: Option<i64>) -> Result<User, RepositoryError> {
+        info!("Setting avatar for user with ID: {}", id);
+
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET avatar_key = $2, avatar_url = $3, version = version + 1, updated_at = NOW()
+            WHERE id = $1 AND ($4::BIGINT IS NULL OR version = $4)
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
+            "#
+        )
+        .bind(id)
+        .bind(avatar_key)
+        .bind(avatar_url)
+        .bind(expected_version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match user {
+            Some(user) => Ok(user),
+            None => Err(missing_row_error(&self.pool, id).await),
+        }
+    }
+
     #[instrument(skip(self), fields(user_id = %id))]
     async fn delete(&self, id: UserId) -> Result<(), RepositoryError> {
         info!("Hard deleting user with ID: {}", id);
@@ -299,7 +461,7 @@ impl UserRepository for SqlxUserRepository {
     async fn list(&self, limit: i64, offset: i64) -> Result<Vec<User>, RepositoryError> {
         let users = sqlx::query_as::<_, User>(
             r#"
-            SELECT id, name, email, is_active, created_at, updated_at
+            SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
             FROM users
             ORDER BY created_at DESC
             LIMIT $1 OFFSET $2
@@ -315,18 +477,20 @@ impl UserRepository for SqlxUserRepository {
     }
 
     #[instrument(skip(self))]
-    async fn list_active(&self, limit: i64, offset: i64) -> Result<Vec<User>, RepositoryError> {
+    async fn list_active(&self, limit: i64, offset: i64, metadata: Option<&serde_json::Value>) -> Result<Vec<User>, RepositoryError> {
         let users = sqlx::query_as::<_, User>(
             r#"
-            SELECT id, name, email, is_active, created_at, updated_at
+            SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
             FROM users
             WHERE is_active = true
+              AND ($3::JSONB IS NULL OR metadata @> $3)
             ORDER BY created_at DESC
             LIMIT $1 OFFSET $2
             "#
         )
         .bind(limit)
         .bind(offset)
+        .bind(metadata)
         .fetch_all(&self.pool)
         .await?;
 
@@ -334,6 +498,78 @@ impl UserRepository for SqlxUserRepository {
         Ok(users)
     }
 
+    fn stream_active(&self, metadata: Option<serde_json::Value>) -> UserStream {
+        let pool = self.pool.clone();
+        let (mut sender, receiver) = futures::channel::mpsc::channel(USER_STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            let mut rows = sqlx::query_as::<_, User>(
+                r#"
+                SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
+                FROM users
+                WHERE is_active = true
+                  AND ($1::JSONB IS NULL OR metadata @> $1)
+                ORDER BY created_at
+                "#
+            )
+            .bind(metadata)
+            .fetch(&pool);
+
+            let mut streamed = 0u64;
+            while let Some(row) = rows.next().await {
+                let failed = row.is_err();
+                if sender.send(row.map_err(RepositoryError::from)).await.is_err() {
+                    warn!("User stream consumer went away after {} rows", streamed);
+                    return;
+                }
+                if failed {
+                    return;
+                }
+                streamed += 1;
+            }
+
+            info!("Streamed {} users", streamed);
+        });
+
+        receiver.boxed()
+    }
+
+    #[instrument(skip(self))]
+    async fn search(&self, query: &str, is_active: Option<bool>, limit: i64) -> Result<Vec<UserSearchResult>, RepositoryError> {
+        let rows = sqlx::query_as::<_, UserSearchRow>(
+            r#"
+            WITH q AS (SELECT websearch_to_tsquery('simple', $1) AS tsq)
+            SELECT u.id, u.name, u.email, u.is_active, u.created_at, u.updated_at, u.version, u.metadata, u.avatar_key, u.avatar_url,
+                   (ts_rank(u.search_vector, q.tsq)
+                       + GREATEST(similarity(u.name, $1), similarity(u.email, $1)))::REAL AS rank,
+                   ts_headline('simple', translate(u.name, $5, ''), q.tsq, $6) AS name_highlight,
+                   ts_headline('simple', translate(u.email, $5, ''), q.tsq, $6) AS email_highlight
+            FROM users u, q
+            WHERE (u.search_vector @@ q.tsq
+                   OR u.name % $1
+                   OR u.email % $1
+                   OR u.name ILIKE '%' || $2 || '%'
+                   OR u.email ILIKE '%' || $2 || '%')
+              AND ($3::BOOLEAN IS NULL OR u.is_active = $3)
+            ORDER BY rank DESC, u.created_at DESC
+            LIMIT $4
+            "#
+        )
+        .bind(query)
+        .bind(escape_like(query))
+        .bind(is_active)
+        .bind(limit)
+        // Matches are delimited with marks removed from the text itself, and
+        // turned into tags once the text is escaped
+        .bind(format!("{}{}", HEADLINE_START, HEADLINE_STOP))
+        .bind(format!("StartSel={}, StopSel={}, HighlightAll=true", HEADLINE_START, HEADLINE_STOP))
+        .fetch_all(&self.pool)
+        .await?;
+
+        info!("Search matched {} users (limit: {})", rows.len(), limit);
+        Ok(rows.into_iter().map(UserSearchResult::from).collect())
+    }
+
     #[instrument(skip(self))]
     async fn count(&self) -> Result<i64, RepositoryError> {
         let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
@@ -381,12 +617,25 @@ impl UserRepository for SqlxUserRepository {
         Ok(exists.0)
     }
 
+    #[instrument(skip(self, emails), fields(count = emails.len()))]
+    async fn existing_emails(&self, emails: &[String]) -> Result<Vec<String>, RepositoryError> {
+        let existing: Vec<String> = sqlx::query_scalar(
+            "SELECT email FROM users WHERE email = ANY($1)"
+        )
+        .bind(emails)
+        .fetch_all(&self.pool)
+        .await?;
+
+        info!("{} of {} emails already exist", existing.len(), emails.len());
+        Ok(existing)
+    }
+
     #[instrument(skip(self), fields(user_id = %id))]
     async fn activate(&self, id: UserId) -> Result<(), RepositoryError> {
         info!("Activating user with ID: {}", id);
 
         let result = sqlx::query(
-            "UPDATE users SET is_active = true, updated_at = NOW() WHERE id = $1"
+            "UPDATE users SET is_active = true, version = version + 1, updated_at = NOW() WHERE id = $1"
         )
         .bind(id)
         .execute(&self.pool)
@@ -405,7 +654,7 @@ impl UserRepository for SqlxUserRepository {
         info!("Deactivating user with ID: {}", id);
 
         let result = sqlx::query(
-            "UPDATE users SET is_active = false, updated_at = NOW() WHERE id = $1"
+            "UPDATE users SET is_active = false, version = version + 1, updated_at = NOW() WHERE id = $1"
         )
         .bind(id)
         .execute(&self.pool)
@@ -441,13 +690,14 @@ impl UserRepositoryTransaction for SqlxUserRepositoryTransaction {
 
         let user = sqlx::query_as::<_, User>(
             r#"
-            INSERT INTO users (name, email, is_active, created_at, updated_at)
-            VALUES ($1, $2, true, NOW(), NOW())
-            RETURNING id, name, email, is_active, created_at, updated_at
+            INSERT INTO users (name, email, metadata, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, true, NOW(), NOW())
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
             "#
         )
         .bind(&user.name)
         .bind(&user.email)
+        .bind(&user.metadata)
         .fetch_one(&mut *self.tx)
         .await
         .map_err(|e| {
@@ -464,7 +714,18 @@ impl UserRepositoryTransaction for SqlxUserRepositoryTransaction {
         Ok(user)
     }
 
-    async fn update(&mut self, id: UserId, name: Option<String>, email: Option<String>) -> Result<User, RepositoryError> {
+    async fn find_for_update(&mut self, id: UserId) -> Result<Option<User>, RepositoryError> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url FROM users WHERE id = $1 FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.tx)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn update(&mut self, id: UserId, name: Option<String>, email: Option<String>, metadata: Option<serde_json::Value>, expected_version: Option<i64>) -> Result<User, RepositoryError> {
         info!("Updating user in transaction with ID: {}", id);
 
         let user = sqlx::query_as::<_, User>(
@@ -472,22 +733,184 @@ impl UserRepositoryTransaction for SqlxUserRepositoryTransaction {
             UPDATE users
             SET name = COALESCE($2, name),
                 email = COALESCE($3, email),
+                metadata = COALESCE($4, metadata),
+                version = version + 1,
                 updated_at = NOW()
-            WHERE id = $1
-            RETURNING id, name, email, is_active, created_at, updated_at
+            WHERE id = $1 AND ($5::BIGINT IS NULL OR version = $5)
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
             "#
         )
         .bind(id)
         .bind(name)
         .bind(email)
+        .bind(metadata)
+        .bind(expected_version)
         .fetch_optional(&mut *self.tx)
-        .await?
-        .ok_or(RepositoryError::NotFound)?;
+        .await?;
+
+        let user = match user {
+            Some(user) => user,
+            None => return Err(missing_row_error(&mut *self.tx, id).await),
+        };
 
         info!("Successfully updated user in transaction with ID: {}", id);
         Ok(user)
     }
 
+    async fn upsert(&mut self, user: &NewUser, update_existing: bool) -> Result<UpsertOutcome, RepositoryError> {
+        // A user inserted concurrently makes the insert do nothing; the second
+        // pass then finds and locks it
+        for _ in 0..2 {
+            if update_existing {
+                let existing = sqlx::query_as::<_, User>(
+                    "SELECT id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url \
+                     FROM users WHERE email = $1 FOR UPDATE"
+                )
+                .bind(&user.email)
+                .fetch_optional(&mut *self.tx)
+                .await?;
+
+                if let Some(before) = existing {
+                    let after = sqlx::query_as::<_, User>(
+                        r#"
+                        UPDATE users
+                        SET name = $2, metadata = $3, version = version + 1, updated_at = NOW()
+                        WHERE id = $1
+                        RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
+                        "#
+                    )
+                    .bind(before.id)
+                    .bind(&user.name)
+                    .bind(&user.metadata)
+                    .fetch_one(&mut *self.tx)
+                    .await
+                    .map_err(|e| {
+                        warn!("Failed to update imported user in transaction: {}", e);
+                        RepositoryError::Database(e)
+                    })?;
+                    return Ok(UpsertOutcome::Updated { before, after });
+                }
+            }
+
+            let created = sqlx::query_as::<_, User>(
+                r#"
+                INSERT INTO users (name, email, metadata, is_active, created_at, updated_at)
+                VALUES ($1, $2, $3, true, NOW(), NOW())
+                ON CONFLICT (email) DO NOTHING
+                RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
+                "#
+            )
+            .bind(&user.name)
+            .bind(&user.email)
+            .bind(&user.metadata)
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(|e| {
+                warn!("Failed to insert imported user in transaction: {}", e);
+                RepositoryError::Database(e)
+            })?;
+
+            match created {
+                Some(created) => return Ok(UpsertOutcome::Created(created)),
+                None if !update_existing => return Ok(UpsertOutcome::Skipped),
+                None => continue,
+            }
+        }
+
+        Err(RepositoryError::Transaction(format!("User {} kept changing during the import", user.email)))
+    }
+
+    async fn soft_delete(&mut self, id: UserId, expected_version: Option<i64>) -> Result<User, RepositoryError> {
+        info!("Soft deleting user in transaction with ID: {}", id);
+
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET is_active = false, avatar_key = NULL, avatar_url = NULL, version = version + 1, updated_at = NOW()
+            WHERE id = $1 AND ($2::BIGINT IS NULL OR version = $2)
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
+            "#
+        )
+        .bind(id)
+        .bind(expected_version)
+        .fetch_optional(&mut *self.tx)
+        .await?;
+
+        match user {
+            Some(user) => Ok(user),
+            None => Err(missing_row_error(&mut *self.tx, id).await),
+        }
+    }
+
+    async fn set_avatar(&mut self, id: UserId, avatar_key: Option<&str>, avatar_url: Option<&str>, expected_version: Option<i64>) -> Result<User, RepositoryError> {
+        info!("Setting avatar in transaction for user with ID: {}", id);
+
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET avatar_key = $2, avatar_url = $3, version = version + 1, updated_at = NOW()
+            WHERE id = $1 AND ($4::BIGINT IS NULL OR version = $4)
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
+            "#
+        )
+        .bind(id)
+        .bind(avatar_key)
+        .bind(avatar_url)
+        .bind(expected_version)
+        .fetch_optional(&mut *self.tx)
+        .await?;
+
+        match user {
+            Some(user) => Ok(user),
+            None => Err(missing_row_error(&mut *self.tx, id).await),
+        }
+    }
+
+    async fn set_active(&mut self, id: UserId, is_active: bool, expected_version: Option<i64>) -> Result<User, RepositoryError> {
+        info!("Setting active = {} in transaction for user with ID: {}", is_active, id);
+
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET is_active = $2, version = version + 1, updated_at = NOW()
+            WHERE id = $1 AND ($3::BIGINT IS NULL OR version = $3)
+            RETURNING id, name, email, is_active, created_at, updated_at, version, metadata, avatar_key, avatar_url
+            "#
+        )
+        .bind(id)
+        .bind(is_active)
+        .bind(expected_version)
+        .fetch_optional(&mut *self.tx)
+        .await?;
+
+        match user {
+            Some(user) => Ok(user),
+            None => Err(missing_row_error(&mut *self.tx, id).await),
+        }
+    }
+
+    async fn record_audit(&mut self, entry: &NewAuditLog) -> Result<AuditLog, RepositoryError> {
+        let mut savepoint = self.tx.begin().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+
+        match insert_audit_log(&mut savepoint, entry).await {
+            Ok(log) => {
+                savepoint.commit().await.map_err(|e| RepositoryError::Transaction(e.to_string()))?;
+                Ok(log)
+            }
+            Err(e) => {
+                warn!("Failed to record audit log in transaction: {}", e);
+                if let Err(rollback_err) = savepoint.rollback().await {
+                    warn!("Failed to roll back audit log savepoint: {}", rollback_err);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    async fn enqueue_event(&mut self, event: &NewOutboxEvent) -> Result<(), RepositoryError> {
+        insert_outbox_event(&mut self.tx, event).await.map(|_| ())
+    }
+
     async fn commit(self: Box<Self>) -> Result<(), RepositoryError> {
         self.tx.commit().await.map_err(|e| {
             warn!("Failed to commit transaction: {}", e);
@@ -519,6 +942,134 @@ impl SqlxUserRepository {
     }
 }
 
+/// User repository for service tests that only ask which emails are taken
+///
+/// Every other method fails, so a test reaching one gets an error rather
+/// than a panic.
+#[cfg(test)]
+pub(crate) struct TakenEmails(pub Vec<String>);
+
+#[cfg(test)]
+impl TakenEmails {
+    fn unsupported<T>() -> Result<T, RepositoryError> {
+        Err(RepositoryError::Connection("not supported by TakenEmails".to_string()))
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl UserRepository for TakenEmails {
+    async fn create(&self, _user: &NewUser) -> Result<User, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn create_tx(&self, _tx: &mut Transaction<'_, Postgres>, _user: &NewUser) -> Result<User, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn find_by_id(&self, _id: UserId) -> Result<Option<User>, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn find_by_email(&self, _email: &str) -> Result<Option<User>, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn update(
+        &self,
+        _id: UserId,
+        _name: Option<String>,
+        _email: Option<String>,
+        _metadata: Option<serde_json::Value>,
+        _expected_version: Option<i64>,
+    ) -> Result<User, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn update_tx(
+        &self,
+        _tx: &mut Transaction<'_, Postgres>,
+        _id: UserId,
+        _name: Option<String>,
+        _email: Option<String>,
+        _metadata: Option<serde_json::Value>,
+        _expected_version: Option<i64>,
+    ) -> Result<User, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn soft_delete(&self, _id: UserId, _expected_version: Option<i64>) -> Result<(), RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn set_avatar(
+        &self,
+        _id: UserId,
+        _avatar_key: Option<&str>,
+        _avatar_url: Option<&str>,
+        _expected_version: Option<i64>,
+    ) -> Result<User, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn delete(&self, _id: UserId) -> Result<(), RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn list(&self, _limit: i64, _offset: i64) -> Result<Vec<User>, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn list_active(
+        &self,
+        _limit: i64,
+        _offset: i64,
+        _metadata: Option<&serde_json::Value>,
+    ) -> Result<Vec<User>, RepositoryError> {
+        Self::unsupported()
+    }
+
+    fn stream_active(&self, _metadata: Option<serde_json::Value>) -> UserStream {
+        futures::stream::once(async { Self::unsupported() }).boxed()
+    }
+
+    async fn search(&self, _query: &str, _is_active: Option<bool>, _limit: i64) -> Result<Vec<UserSearchResult>, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn count(&self) -> Result<i64, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn count_active(&self) -> Result<i64, RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn email_exists(&self, email: &str) -> Result<bool, RepositoryError> {
+        Ok(self.0.iter().any(|taken| taken == email))
+    }
+
+    async fn email_exists_for_other_user(&self, email: &str, _user_id: UserId) -> Result<bool, RepositoryError> {
+        self.email_exists(email).await
+    }
+
+    async fn existing_emails(&self, emails: &[String]) -> Result<Vec<String>, RepositoryError> {
+        Ok(emails.iter().filter(|email| self.0.contains(email)).cloned().collect())
+    }
+
+    async fn activate(&self, _id: UserId) -> Result<(), RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn deactivate(&self, _id: UserId) -> Result<(), RepositoryError> {
+        Self::unsupported()
+    }
+
+    async fn begin_transaction(&self) -> Result<Box<dyn UserRepositoryTransaction>, RepositoryError> {
+        Self::unsupported()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -541,6 +1092,7 @@ mod tests {
         let new_user = NewUser {
             name: "Test User".to_string(),
             email: "test@example.com".to_string(),
+            metadata: serde_json::json!({}),
         };
 
         // This test would require a real database connection
@@ -581,7 +1133,7 @@ mod tests {
         let user_id = Uuid::new_v4();
 
         // This test would require a real database connection
-        // let result = repo.update(user_id, Some("Updated Name".to_string()), None).await;
+        // let result = repo.update(user_id, Some("Updated Name".to_string()), None, None).await;
         // assert!(result.is_ok());
     }
 
@@ -627,7 +1179,7 @@ mod tests {
         let user_id = Uuid::new_v4();
 
         // This test would require a real database connection
-        // let result = repo.soft_delete(user_id).await;
+        // let result = repo.soft_delete(user_id, None).await;
         // assert!(result.is_ok());
     }
 
@@ -650,4 +1202,10 @@ mod tests {
         //
         // tx.commit().await.unwrap();
     }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("john"), "john");
+        assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+    }
 }
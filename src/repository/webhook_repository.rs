@@ -0,0 +1,330 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::models::{NewWebhookDelivery, UserId, WebhookDelivery, WebhookSubscription, WebhookSubscriptionId};
+use crate::repository::RepositoryError;
+
+const SUBSCRIPTION_COLUMNS: &str = "s.id, s.url, s.event_types, s.secret, s.previous_secret, s.previous_secret_expires_at, \
+     s.description, s.is_active, s.created_by, s.created_at, s.updated_at";
+
+const DELIVERY_COLUMNS: &str = "id, subscription_id, event_id, event_type, attempt, success, response_status, \
+     response_body, error, duration_ms, created_at";
+
+/// Webhook subscription repository trait
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    /// Register a subscription
+    async fn create(
+        &self,
+        url: &str,
+        event_types: &[String],
+        secret: &str,
+        description: Option<&str>,
+        is_active: bool,
+        created_by: UserId,
+    ) -> Result<WebhookSubscription, RepositoryError>;
+
+    /// Find a subscription by ID
+    async fn find_by_id(&self, id: WebhookSubscriptionId) -> Result<Option<WebhookSubscription>, RepositoryError>;
+
+    /// List subscriptions, oldest first, with the total number of subscriptions
+    async fn list(&self, limit: i64, offset: i64) -> Result<(Vec<WebhookSubscription>, i64), RepositoryError>;
+
+    /// Update a subscription; an empty description clears it
+    async fn update(
+        &self,
+        id: WebhookSubscriptionId,
+        url: Option<&str>,
+        event_types: Option<&[String]>,
+        description: Option<&str>,
+        is_active: Option<bool>,
+    ) -> Result<WebhookSubscription, RepositoryError>;
+
+    /// Replace a subscription's secret, keeping the old one until
+    /// `previous_expires_at`, or dropping it when that is `None`
+    async fn rotate_secret(
+        &self,
+        id: WebhookSubscriptionId,
+        secret: &str,
+        previous_expires_at: Option<DateTime<Utc>>,
+    ) -> Result<WebhookSubscription, RepositoryError>;
+
+    /// Delete a subscription and its delivery log
+    async fn delete(&self, id: WebhookSubscriptionId) -> Result<(), RepositoryError>;
+
+    /// Active subscriptions to an event type, created by `created_before`, that
+    /// have not yet had the event delivered successfully
+    async fn find_undelivered(
+        &self,
+        event_id: Uuid,
+        event_type: &str,
+        created_before: DateTime<Utc>,
+    ) -> Result<Vec<WebhookSubscription>, RepositoryError>;
+
+    /// Record a delivery attempt
+    async fn record_delivery(&self, delivery: &NewWebhookDelivery) -> Result<WebhookDelivery, RepositoryError>;
+
+    /// List a subscription's delivery attempts, newest first, with the total number of attempts
+    async fn list_deliveries(
+        &self,
+        subscription_id: WebhookSubscriptionId,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<WebhookDelivery>, i64), RepositoryError>;
+
+    /// Delete delivery attempts made before `before`, returning how many were deleted
+    async fn purge_deliveries(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError>;
+}
+
+/// SQLx implementation of WebhookRepository
+pub struct SqlxWebhookRepository {
+    pool: PgPool,
+}
+
+impl SqlxWebhookRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for SqlxWebhookRepository {
+    #[instrument(skip(self, secret, description))]
+    async fn create(
+        &self,
+        url: &str,
+        event_types: &[String],
+        secret: &str,
+        description: Option<&str>,
+        is_active: bool,
+        created_by: UserId,
+    ) -> Result<WebhookSubscription, RepositoryError> {
+        let subscription = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            INSERT INTO webhook_subscriptions AS s (url, event_types, secret, description, is_active, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(url)
+        .bind(event_types)
+        .bind(secret)
+        .bind(description)
+        .bind(is_active)
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        info!("Created webhook subscription {} for {}", subscription.id, subscription.url);
+        Ok(subscription)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_by_id(&self, id: WebhookSubscriptionId) -> Result<Option<WebhookSubscription>, RepositoryError> {
+        let subscription = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            "SELECT {} FROM webhook_subscriptions s WHERE s.id = $1",
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    #[instrument(skip(self))]
+    async fn list(&self, limit: i64, offset: i64) -> Result<(Vec<WebhookSubscription>, i64), RepositoryError> {
+        let subscriptions = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            "SELECT {} FROM webhook_subscriptions s ORDER BY s.created_at, s.id LIMIT $1 OFFSET $2",
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_subscriptions")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((subscriptions, total))
+    }
+
+    #[instrument(skip(self, description))]
+    async fn update(
+        &self,
+        id: WebhookSubscriptionId,
+        url: Option<&str>,
+        event_types: Option<&[String]>,
+        description: Option<&str>,
+        is_active: Option<bool>,
+    ) -> Result<WebhookSubscription, RepositoryError> {
+        let subscription = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            UPDATE webhook_subscriptions s
+            SET url = COALESCE($2, s.url),
+                event_types = COALESCE($3, s.event_types),
+                description = CASE WHEN $4::TEXT IS NULL THEN s.description ELSE NULLIF($4, '') END,
+                is_active = COALESCE($5, s.is_active),
+                updated_at = NOW()
+            WHERE s.id = $1
+            RETURNING {}
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(id)
+        .bind(url)
+        .bind(event_types)
+        .bind(description)
+        .bind(is_active)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RepositoryError::NotFound)?;
+
+        info!("Updated webhook subscription {}", id);
+        Ok(subscription)
+    }
+
+    #[instrument(skip(self, secret))]
+    async fn rotate_secret(
+        &self,
+        id: WebhookSubscriptionId,
+        secret: &str,
+        previous_expires_at: Option<DateTime<Utc>>,
+    ) -> Result<WebhookSubscription, RepositoryError> {
+        // A secret still overlapping from an earlier rotation is dropped, so
+        // at most two secrets are ever in use
+        let subscription = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            UPDATE webhook_subscriptions s
+            SET previous_secret = CASE WHEN $3::TIMESTAMPTZ IS NULL THEN NULL ELSE s.secret END,
+                previous_secret_expires_at = $3,
+                secret = $2,
+                updated_at = NOW()
+            WHERE s.id = $1
+            RETURNING {}
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(id)
+        .bind(secret)
+        .bind(previous_expires_at)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RepositoryError::NotFound)?;
+
+        info!("Rotated the secret of webhook subscription {}", id);
+        Ok(subscription)
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, id: WebhookSubscriptionId) -> Result<(), RepositoryError> {
+        let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        info!("Deleted webhook subscription {}", id);
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn find_undelivered(
+        &self,
+        event_id: Uuid,
+        event_type: &str,
+        created_before: DateTime<Utc>,
+    ) -> Result<Vec<WebhookSubscription>, RepositoryError> {
+        let subscriptions = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            SELECT {}
+            FROM webhook_subscriptions s
+            WHERE s.is_active
+              AND s.event_types @> ARRAY[$2]::TEXT[]
+              AND s.created_at <= $3
+              AND NOT EXISTS (
+                  SELECT 1 FROM webhook_deliveries d
+                  WHERE d.subscription_id = s.id AND d.event_id = $1 AND d.success
+              )
+            ORDER BY s.created_at, s.id
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(event_id)
+        .bind(event_type)
+        .bind(created_before)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(subscriptions)
+    }
+
+    #[instrument(skip(self, delivery), fields(subscription_id = %delivery.subscription_id, event_id = %delivery.event_id))]
+    async fn record_delivery(&self, delivery: &NewWebhookDelivery) -> Result<WebhookDelivery, RepositoryError> {
+        let delivery = sqlx::query_as::<_, WebhookDelivery>(&format!(
+            r#"
+            INSERT INTO webhook_deliveries
+                (subscription_id, event_id, event_type, attempt, success, response_status, response_body, error, duration_ms)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING {}
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(delivery.subscription_id)
+        .bind(delivery.event_id)
+        .bind(&delivery.event_type)
+        .bind(delivery.attempt)
+        .bind(delivery.success)
+        .bind(delivery.response_status)
+        .bind(&delivery.response_body)
+        .bind(&delivery.error)
+        .bind(delivery.duration_ms)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(delivery)
+    }
+
+    #[instrument(skip(self))]
+    async fn list_deliveries(
+        &self,
+        subscription_id: WebhookSubscriptionId,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<WebhookDelivery>, i64), RepositoryError> {
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE subscription_id = $1 ORDER BY created_at DESC, id LIMIT $2 OFFSET $3",
+            DELIVERY_COLUMNS
+        ))
+        .bind(subscription_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_deliveries WHERE subscription_id = $1")
+            .bind(subscription_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((deliveries, total))
+    }
+
+    #[instrument(skip(self))]
+    async fn purge_deliveries(&self, before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM webhook_deliveries WHERE created_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
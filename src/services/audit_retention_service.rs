@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::config::AuditConfig;
+use crate::models::{AuditCursor, AuditLogArchive, AuditPartition, DataFormat, NewAuditLog};
+use crate::repository::{AuditLogRepository, AuditPartitionRepository};
+use crate::services::ServiceError;
+use crate::storage::BlobStore;
+
+/// Entries written per archive file
+const ARCHIVE_PART_SIZE: i64 = 10_000;
+
+/// Partitioning and retention of the audit log
+#[async_trait]
+pub trait AuditRetentionService: Send + Sync {
+    /// Create the partitions for the current month and the months ahead that are missing,
+    /// reporting entries left in the default partition
+    async fn create_partitions(&self) -> Result<Vec<AuditPartition>, ServiceError>;
+
+    /// Detach partitions past retention, then archive and drop them when an archive store is set
+    async fn prune_partitions(&self) -> Result<Vec<AuditLogArchive>, ServiceError>;
+}
+
+/// Audit log retention service implementation
+pub struct AuditRetentionServiceImpl {
+    partition_repository: Arc<dyn AuditPartitionRepository>,
+    audit_repository: Arc<dyn AuditLogRepository>,
+    archive_store: Option<Arc<dyn BlobStore>>,
+    premake_months: u32,
+    retention_months: Option<u32>,
+}
+
+impl AuditRetentionServiceImpl {
+    pub fn new(
+        partition_repository: Arc<dyn AuditPartitionRepository>,
+        audit_repository: Arc<dyn AuditLogRepository>,
+        archive_store: Option<Arc<dyn BlobStore>>,
+        config: &AuditConfig,
+    ) -> Self {
+        Self {
+            partition_repository,
+            audit_repository,
+            archive_store,
+            premake_months: config.partition_premake_months,
+            retention_months: config.retention_months,
+        }
+    }
+
+    /// Write a detached partition to the archive store as NDJSON files of
+    /// [`ARCHIVE_PART_SIZE`] entries, returning the key prefix and file count
+    ///
+    /// Files are overwritten, so an archive interrupted part way is redone
+    /// from the start on the next run.
+    async fn archive(&self, store: &dyn BlobStore, partition: &AuditPartition) -> Result<(String, i32), ServiceError> {
+        let prefix = format!("audit-logs/{}", partition.name());
+        let mut after: Option<AuditCursor> = None;
+        let mut parts = 0;
+
+        loop {
+            let batch = self
+                .partition_repository
+                .read_detached(partition, after.as_ref(), ARCHIVE_PART_SIZE)
+                .await?;
+            let Some(last) = batch.last() else {
+                break;
+            };
+            after = Some(AuditCursor::after(last));
+
+            let mut data = Vec::new();
+            for log in &batch {
+                serde_json::to_writer(&mut data, log)
+                    .map_err(|e| ServiceError::Storage(format!("Failed to encode audit log entry {}: {}", log.id, e)))?;
+                data.push(b'\n');
+            }
+
+            parts += 1;
+            let key = format!("{}/part-{:05}.{}", prefix, parts, DataFormat::Ndjson.extension());
+            store
+                .put(&key, data, DataFormat::Ndjson.content_type())
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to store audit log archive {}: {}", key, e);
+                    ServiceError::Storage(e.to_string())
+                })?;
+
+            if (batch.len() as i64) < ARCHIVE_PART_SIZE {
+                break;
+            }
+        }
+
+        Ok((prefix, parts))
+    }
+
+    /// Record retention of a partition in the audit log; failures are only logged
+    /// since the partition is already gone from `audit_logs`
+    async fn record(&self, action: &str, archive: &AuditLogArchive) {
+        let entry = NewAuditLog::new(action, "audit_log_partition")
+            .with_resource_id(&archive.partition_name)
+            .with_metadata(serde_json::json!({
+                "entries": archive.entries,
+                "last_sequence": archive.last_sequence,
+                "archive_prefix": archive.archive_prefix,
+                "archive_parts": archive.archive_parts,
+            }));
+
+        if let Err(e) = self.audit_repository.record(&entry).await {
+            tracing::error!("Failed to record {} of {} in audit log: {}", action, archive.partition_name, e);
+        }
+    }
+}
+
+#[async_trait]
+impl AuditRetentionService for AuditRetentionServiceImpl {
+    #[tracing::instrument(skip(self))]
+    async fn create_partitions(&self) -> Result<Vec<AuditPartition>, ServiceError> {
+        let existing = self.partition_repository.list_partitions().await?;
+        let current = AuditPartition::containing(Utc::now());
+
+        let mut created = Vec::new();
+        for offset in 0..=self.premake_months as i32 {
+            let partition = current.offset(offset);
+            if !existing.contains(&partition) {
+                self.partition_repository.create_partition(&partition).await?;
+                tracing::info!("Created audit log partition {}", partition.name());
+                created.push(partition);
+            }
+        }
+
+        // Entries only land in the default partition when their month has no
+        // partition, such as timestamps far off the current month
+        let unpartitioned = self.partition_repository.count_unpartitioned().await?;
+        if unpartitioned > 0 {
+            tracing::error!(
+                "{} audit log entries are in the default partition, outside every monthly partition; \
+                 they are not covered by retention until their month's partition is created",
+                unpartitioned
+            );
+        }
+
+        Ok(created)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn prune_partitions(&self) -> Result<Vec<AuditLogArchive>, ServiceError> {
+        let mut pruned = Vec::new();
+
+        if let Some(retention_months) = self.retention_months {
+            let now = Utc::now();
+            for partition in self.partition_repository.list_partitions().await? {
+                if !partition.is_expired(retention_months, now) {
+                    continue;
+                }
+
+                let archive = self.partition_repository.detach_partition(&partition).await?;
+                self.record("audit_log.detach", &archive).await;
+                pruned.push(archive);
+            }
+        }
+
+        // Also picks up partitions detached by an earlier run that stopped before archiving them
+        if let Some(store) = &self.archive_store {
+            for detached in self.partition_repository.list_detached().await? {
+                let Some(partition) = detached.partition() else {
+                    tracing::warn!("Skipping detached audit log partition {} with an unexpected name", detached.partition_name);
+                    continue;
+                };
+
+                let (prefix, parts) = self.archive(store.as_ref(), &partition).await?;
+                let archive = self.partition_repository.drop_detached(&partition, &prefix, parts).await?;
+                tracing::info!(
+                    "Archived {} audit log entries of {} to {} files under {}",
+                    archive.entries,
+                    archive.partition_name,
+                    parts,
+                    prefix
+                );
+                self.record("audit_log.archive", &archive).await;
+
+                match pruned.iter_mut().find(|pruned| pruned.partition_name == archive.partition_name) {
+                    Some(pruned) => *pruned = archive,
+                    None => pruned.push(archive),
+                }
+            }
+        }
+
+        Ok(pruned)
+    }
+}
@@ -0,0 +1,253 @@
+use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::{
+    AuditChainHead, AuditChainReport, AuditChainVerifier, AuditCheckpoint, AuditCursor, AuditLog, AuditLogFilter,
+    AuditLogPage, BrokenLink, NewAuditLog, UserId,
+};
+use crate::repository::AuditLogRepository;
+use crate::services::ServiceError;
+
+/// Entries read per query while exporting
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+/// Entries read per query while verifying the hash chain
+const VERIFY_BATCH_SIZE: i64 = 1000;
+
+/// Stream of audit log entries produced by an export
+pub type AuditLogStream = BoxStream<'static, Result<AuditLog, ServiceError>>;
+
+/// Audit log search service trait
+///
+/// Authorization is left to callers; only auditors should reach it.
+#[async_trait]
+pub trait AuditService: Send + Sync {
+    /// Find entries matching the filter, newest first, continuing from `cursor`
+    async fn query_logs(&self, filter: &AuditLogFilter, cursor: Option<&str>, limit: i64) -> Result<AuditLogPage, ServiceError>;
+
+    /// Stream every entry matching the filter, recording the export in the audit log first
+    async fn export_logs(&self, filter: AuditLogFilter, actor: Option<UserId>) -> Result<AuditLogStream, ServiceError>;
+
+    /// Walk the hash chain and report the first broken link
+    ///
+    /// Unless `full` is set, the walk starts after the latest validly signed
+    /// checkpoint instead of at the first stored entry.
+    async fn verify_chain(&self, full: bool) -> Result<AuditChainReport, ServiceError>;
+
+    /// Signed checkpoints of the chain head, oldest first
+    async fn list_checkpoints(&self) -> Result<Vec<AuditCheckpoint>, ServiceError>;
+
+    /// Sign the current chain head, unless checkpoints are disabled or it is already signed
+    async fn create_checkpoint(&self) -> Result<Option<AuditCheckpoint>, ServiceError>;
+}
+
+/// Audit log search service implementation
+pub struct AuditServiceImpl {
+    repository: Arc<dyn AuditLogRepository>,
+    checkpoint_secret: Option<String>,
+}
+
+impl AuditServiceImpl {
+    pub fn new(repository: Arc<dyn AuditLogRepository>) -> Self {
+        Self {
+            repository,
+            checkpoint_secret: None,
+        }
+    }
+
+    /// Sign checkpoints with `secret`, and check existing signatures against it
+    pub fn with_checkpoint_secret(mut self, secret: Option<String>) -> Self {
+        self.checkpoint_secret = secret;
+        self
+    }
+}
+
+fn validate_filter(filter: &AuditLogFilter) -> Result<(), ServiceError> {
+    if let (Some(from), Some(to)) = (filter.from, filter.to) {
+        if from >= to {
+            return Err(ServiceError::Validation("from must be earlier than to".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl AuditService for AuditServiceImpl {
+    #[tracing::instrument(skip(self, filter, cursor))]
+    async fn query_logs(&self, filter: &AuditLogFilter, cursor: Option<&str>, limit: i64) -> Result<AuditLogPage, ServiceError> {
+        if !(1..=100).contains(&limit) {
+            return Err(ServiceError::Validation("Limit must be between 1 and 100".to_string()));
+        }
+        validate_filter(filter)?;
+
+        let after = cursor
+            .map(|token| AuditCursor::decode(token).ok_or_else(|| ServiceError::Validation("Invalid cursor".to_string())))
+            .transpose()?;
+
+        // Fetch one extra entry to tell whether another page follows
+        let mut items = self.repository.query(filter, after.as_ref(), limit + 1).await?;
+        let next_cursor = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(|log| AuditCursor::after(log).encode())
+        } else {
+            None
+        };
+
+        Ok(AuditLogPage { items, next_cursor })
+    }
+
+    #[tracing::instrument(skip(self, filter))]
+    async fn export_logs(&self, filter: AuditLogFilter, actor: Option<UserId>) -> Result<AuditLogStream, ServiceError> {
+        validate_filter(&filter)?;
+
+        let entry = NewAuditLog::new("audit.export", "audit_log")
+            .with_actor(actor)
+            .with_metadata(serde_json::json!({ "filter": filter }));
+        if let Err(e) = self.repository.record(&entry).await {
+            tracing::error!("Failed to record audit log export in audit log: {}", e);
+            return Err(ServiceError::Repository(e));
+        }
+
+        // Page through with the keyset cursor so no connection is held between batches
+        let repository = self.repository.clone();
+        let batches = futures::stream::try_unfold(Some(None), move |state: Option<Option<AuditCursor>>| {
+            let repository = repository.clone();
+            let filter = filter.clone();
+            async move {
+                let Some(after) = state else {
+                    return Ok(None);
+                };
+
+                let batch = repository.query(&filter, after.as_ref(), EXPORT_BATCH_SIZE).await?;
+                let next = match batch.last() {
+                    Some(last) if batch.len() as i64 == EXPORT_BATCH_SIZE => Some(Some(AuditCursor::after(last))),
+                    _ => None,
+                };
+                Ok::<_, ServiceError>(Some((batch, next)))
+            }
+        });
+
+        Ok(batches
+            .map(|batch| match batch {
+                Ok(batch) => futures::stream::iter(batch.into_iter().map(Ok)).boxed(),
+                Err(e) => futures::stream::once(async move { Err(e) }).boxed(),
+            })
+            .flatten()
+            .boxed())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn verify_chain(&self, full: bool) -> Result<AuditChainReport, ServiceError> {
+        // Entries written after the head is read are left for the next run
+        let head = self.repository.chain_head().await?;
+        // Entries archived by retention are no longer here to check
+        let start = self.repository.chain_start().await?;
+        let archived_through = start.as_ref().map_or(0, |start| start.sequence);
+
+        let mut expected_hashes = HashMap::new();
+        let mut forged_checkpoint = None;
+        let mut resume_from = None;
+        for checkpoint in self.repository.list_checkpoints().await? {
+            if let Some(secret) = &self.checkpoint_secret {
+                if !checkpoint.verify(secret) {
+                    if forged_checkpoint.is_none() {
+                        forged_checkpoint = Some(BrokenLink {
+                            sequence: checkpoint.sequence,
+                            id: None,
+                            reason: format!("Checkpoint {} has an invalid signature", checkpoint.id),
+                        });
+                    }
+                    continue;
+                }
+                if !full && checkpoint.sequence > archived_through && checkpoint.sequence <= head.sequence {
+                    resume_from = Some(AuditChainHead {
+                        sequence: checkpoint.sequence,
+                        hash: checkpoint.hash.clone(),
+                    });
+                }
+            }
+            expected_hashes.insert(checkpoint.sequence, checkpoint.hash);
+        }
+
+        let mut verifier = AuditChainVerifier::new(expected_hashes);
+        let mut after_sequence = 0;
+        if let Some(start) = resume_from.as_ref().or(start.as_ref()) {
+            verifier = verifier.starting_after(start);
+            after_sequence = start.sequence;
+        }
+        let mut first_broken = forged_checkpoint;
+        'walk: while first_broken.is_none() && after_sequence < head.sequence {
+            let batch = self
+                .repository
+                .list_chain(after_sequence, VERIFY_BATCH_SIZE.min(head.sequence - after_sequence))
+                .await?;
+            if batch.is_empty() {
+                break;
+            }
+
+            for log in &batch {
+                if let Err(broken) = verifier.check(log) {
+                    first_broken = Some(broken);
+                    break 'walk;
+                }
+                after_sequence = log.sequence.unwrap_or(after_sequence);
+            }
+        }
+        if first_broken.is_none() {
+            // Redactions in the walk may refer to entries before it
+            let earlier = self.repository.find_chain_entries(&verifier.redacted_before_start()).await?;
+            first_broken = verifier
+                .check_redactions(&earlier)
+                .and_then(|_| verifier.finish(&head))
+                .err();
+        }
+
+        match &first_broken {
+            Some(broken) => tracing::error!(
+                "Audit chain is broken at entry {}: {}",
+                broken.sequence,
+                broken.reason
+            ),
+            None => tracing::info!("Audit chain verified up to entry {}", head.sequence),
+        }
+
+        Ok(AuditChainReport {
+            entries_checked: verifier.entries_checked,
+            checkpoints_checked: verifier.checkpoints_checked,
+            head,
+            archived_through: start.map(|start| start.sequence),
+            verified_from_checkpoint: resume_from.map(|checkpoint| checkpoint.sequence),
+            redacted_entries: verifier.redacted_entries,
+            first_broken,
+        })
+    }
+
+    async fn list_checkpoints(&self) -> Result<Vec<AuditCheckpoint>, ServiceError> {
+        Ok(self.repository.list_checkpoints().await?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn create_checkpoint(&self) -> Result<Option<AuditCheckpoint>, ServiceError> {
+        let Some(secret) = &self.checkpoint_secret else {
+            return Ok(None);
+        };
+
+        let head = self.repository.chain_head().await?;
+        if head.sequence == 0 {
+            return Ok(None);
+        }
+        let latest = self.repository.list_checkpoints().await?.pop();
+        if latest.is_some_and(|checkpoint| checkpoint.sequence == head.sequence) {
+            return Ok(None);
+        }
+
+        let checkpoint = AuditCheckpoint::sign(&head, secret);
+        self.repository.create_checkpoint(&checkpoint).await?;
+
+        tracing::info!("Signed audit chain checkpoint at entry {}", checkpoint.sequence);
+        Ok(Some(checkpoint))
+    }
+}
@@ -1,12 +1,39 @@
 use std::sync::Arc;
 use sqlx::PgPool;
 
-use crate::repository::{UserRepository, SqlxUserRepository};
+use crate::config::AppConfig;
+use crate::repository::{
+    UserRepository, SqlxUserRepository, AuditLogRepository, SqlxAuditLogRepository, SqlxImportJobRepository,
+    SqlxPersonalDataRepository, SqlxEmailChangeRepository, GroupRepository, SqlxGroupRepository,
+    IdempotencyRepository, SqlxIdempotencyRepository, SqlxInvitationRepository, SqlxAuditPartitionRepository,
+    SqlxOutboxRepository, SqlxWebhookRepository, SqlxDeadLetterRepository,
+    SqlxEventNotificationRepository,
+};
 use crate::services::{
     UserService, UserServiceImpl,
+    UserImportService, UserImportServiceImpl,
+    PrivacyService, PrivacyServiceImpl,
+    EmailChangeService, EmailChangeServiceImpl, mailer_from_config,
+    GroupService, GroupServiceImpl,
+    InvitationService, InvitationServiceImpl,
+    AuditService, AuditServiceImpl,
+    AuditRetentionService, AuditRetentionServiceImpl,
+    OutboxService, OutboxServiceImpl,
+    WebhookSubscriptionService, WebhookSubscriptionServiceImpl,
+    DeadLetterService, DeadLetterServiceImpl,
+    EventBus, EventHandler, EventStream, WebSocketSessions,
     AuthService, AuthServiceImpl,
-    ExternalService, HttpExternalService,
+    ExternalService, HttpExternalService, HttpClientConfig, WebhookService,
+    MetadataSchemaError, UserMetadataValidator, AvatarPolicy,
 };
+use crate::storage;
+
+/// Errors raised while wiring the service container from configuration
+#[derive(Debug, thiserror::Error)]
+pub enum ContainerError {
+    #[error("User metadata schema error: {0}")]
+    MetadataSchema(#[from] MetadataSchemaError),
+}
 
 /// Service container for dependency injection
 ///
@@ -17,11 +44,29 @@ use crate::services::{
 pub struct ServiceContainer {
     // Repository layer
     user_repository: Arc<dyn UserRepository>,
+    audit_repository: Arc<dyn AuditLogRepository>,
+    group_repository: Arc<dyn GroupRepository>,
+    idempotency_repository: Arc<dyn IdempotencyRepository>,
 
     // Service layer
     user_service: Arc<dyn UserService>,
+    user_import_service: Arc<dyn UserImportService>,
+    privacy_service: Arc<dyn PrivacyService>,
+    email_change_service: Arc<dyn EmailChangeService>,
+    group_service: Arc<dyn GroupService>,
+    invitation_service: Arc<dyn InvitationService>,
+    audit_service: Arc<dyn AuditService>,
+    audit_retention_service: Arc<dyn AuditRetentionService>,
+    outbox_service: Arc<dyn OutboxService>,
+    webhook_subscription_service: Arc<dyn WebhookSubscriptionService>,
+    dead_letter_service: Arc<dyn DeadLetterService>,
     auth_service: Arc<dyn AuthService>,
     external_service: Arc<dyn ExternalService>,
+
+    // Domain events
+    event_bus: Arc<EventBus>,
+    event_stream: Arc<EventStream>,
+    websocket_sessions: Arc<WebSocketSessions>,
 }
 
 impl ServiceContainer {
@@ -34,16 +79,141 @@ impl ServiceContainer {
     /// # Returns
     /// A fully configured service container with all dependencies wired
     pub fn new(db_pool: PgPool, external_timeout_seconds: u64) -> Self {
+        let mut config = AppConfig::default();
+        config.external_service.timeout_seconds = Some(external_timeout_seconds);
+
+        Self::build(db_pool, &config, UserMetadataValidator::new())
+    }
+
+    /// Create a service container configured from the application config
+    ///
+    /// Unlike [`ServiceContainer::new`], this loads optional resources such as
+    /// the user metadata JSON Schema and fails if they are invalid.
+    pub fn from_config(db_pool: PgPool, config: &AppConfig) -> Result<Self, ContainerError> {
+        let metadata_validator = UserMetadataValidator::from_config(&config.user_metadata)?;
+
+        Ok(Self::build(db_pool, config, metadata_validator))
+    }
+
+    fn build(db_pool: PgPool, config: &AppConfig, metadata_validator: UserMetadataValidator) -> Self {
+        let external_timeout_seconds = config.external_service.timeout_seconds.unwrap_or(30);
+        let external_config = &config.external_service;
+        let audit_config = &config.audit;
+        let webhook_config = config.webhooks.clone();
+        let outbox_config = config.outbox.clone();
+
         // Initialize repository layer
-        let user_repository = Arc::new(SqlxUserRepository::new(db_pool));
+        let user_repository = Arc::new(SqlxUserRepository::new(db_pool.clone()));
+        let audit_repository = Arc::new(SqlxAuditLogRepository::new(db_pool.clone()));
+        let import_job_repository = Arc::new(SqlxImportJobRepository::new(db_pool.clone()));
+        let personal_data_repository = Arc::new(SqlxPersonalDataRepository::new(db_pool.clone()));
+        let email_change_repository = Arc::new(SqlxEmailChangeRepository::new(db_pool.clone()));
+        let group_repository = Arc::new(SqlxGroupRepository::new(db_pool.clone()));
+        let invitation_repository = Arc::new(SqlxInvitationRepository::new(db_pool.clone()));
+        let audit_partition_repository = Arc::new(SqlxAuditPartitionRepository::new(db_pool.clone()));
+        let outbox_repository = Arc::new(SqlxOutboxRepository::new(db_pool.clone()));
+        let webhook_repository = Arc::new(SqlxWebhookRepository::new(db_pool.clone()));
+        let dead_letter_repository = Arc::new(SqlxDeadLetterRepository::new(db_pool.clone()));
+        let event_notification_repository = Arc::new(SqlxEventNotificationRepository::new(db_pool.clone()));
+        let idempotency_repository = Arc::new(SqlxIdempotencyRepository::new(db_pool));
+        let avatar_store = storage::from_config(&config.avatar.storage);
 
         // Initialize external service
         let external_service = Arc::new(HttpExternalService::new(external_timeout_seconds));
 
+        let mailer = mailer_from_config(&config.mailer, external_service.clone());
+
+        let event_bus = Arc::new(EventBus::new(config.event_bus.clone()));
+        let event_stream = Arc::new(EventStream::new(event_notification_repository, config.event_stream.clone()));
+        let websocket_sessions = Arc::new(WebSocketSessions::new(config.websocket.clone()));
+
         // Initialize service layer with dependencies
-        let user_service = Arc::new(UserServiceImpl::new(
-            user_repository.clone(),
-            external_service.clone(),
+        let email_change_service = Arc::new(
+            EmailChangeServiceImpl::new(
+                user_repository.clone(),
+                email_change_repository,
+                mailer.clone(),
+                config.email_change.clone(),
+            )
+            .with_event_bus(event_bus.clone()),
+        );
+
+        let invitation_service = Arc::new(
+            InvitationServiceImpl::new(
+                user_repository.clone(),
+                invitation_repository,
+                mailer,
+                config.invitation.clone(),
+            )
+            .with_event_bus(event_bus.clone()),
+        );
+
+        let metadata_validator = Arc::new(metadata_validator);
+        let user_service = Arc::new(
+            UserServiceImpl::new(user_repository.clone(), audit_repository.clone())
+                .with_metadata_validator(metadata_validator.clone())
+                .with_avatar_store(avatar_store.clone(), AvatarPolicy::from_config(&config.avatar))
+                .with_email_change_service(email_change_service.clone())
+                .with_audit_failure_mode(audit_config.failure_mode)
+                .with_event_bus(event_bus.clone()),
+        );
+
+        let user_import_service = Arc::new(
+            UserImportServiceImpl::new(
+                user_repository.clone(),
+                import_job_repository,
+                metadata_validator,
+                config.user_import.clone(),
+            )
+            .with_event_bus(event_bus.clone()),
+        );
+
+        let privacy_service = Arc::new(
+            PrivacyServiceImpl::new(user_repository.clone(), personal_data_repository, audit_repository.clone())
+                .with_avatar_store(avatar_store)
+                .with_audit_failure_mode(audit_config.failure_mode)
+                .with_event_bus(event_bus.clone()),
+        );
+
+        let group_service = Arc::new(GroupServiceImpl::new(group_repository.clone(), user_repository.clone()));
+
+        let audit_service = Arc::new(
+            AuditServiceImpl::new(audit_repository.clone()).with_checkpoint_secret(audit_config.checkpoint_secret.clone()),
+        );
+
+        let audit_retention_service = Arc::new(AuditRetentionServiceImpl::new(
+            audit_partition_repository,
+            audit_repository.clone(),
+            audit_config.archive_storage.as_ref().map(storage::from_config),
+            audit_config,
+        ));
+
+        // The dispatcher schedules its own retries, so each delivery is a single request
+        let webhooks = WebhookService::for_endpoints(HttpClientConfig {
+            timeout_seconds: external_config.timeout_seconds.unwrap_or(external_timeout_seconds),
+            max_retries: 0,
+            retry_delay_ms: external_config.retry_delay_ms,
+            circuit_breaker_enabled: false,
+            circuit_breaker_threshold: external_config.circuit_breaker_threshold,
+            circuit_breaker_timeout_seconds: external_config.circuit_breaker_timeout_seconds,
+        }, webhook_config.allow_private_networks);
+        let webhook_subscription_service = Arc::new(WebhookSubscriptionServiceImpl::new(
+            webhook_repository,
+            webhooks,
+            webhook_config,
+        ));
+        let outbox_service = Arc::new(OutboxServiceImpl::new(
+            outbox_repository.clone(),
+            dead_letter_repository.clone(),
+            webhook_subscription_service.clone(),
+            outbox_config.clone(),
+            external_config,
+        ));
+        let dead_letter_service = Arc::new(DeadLetterServiceImpl::new(
+            dead_letter_repository,
+            outbox_repository,
+            webhook_subscription_service.clone(),
+            outbox_config,
         ));
 
         let auth_service = Arc::new(AuthServiceImpl::new(
@@ -52,17 +222,98 @@ impl ServiceContainer {
 
         Self {
             user_repository,
+            audit_repository,
+            group_repository,
+            idempotency_repository,
             user_service,
+            user_import_service,
+            privacy_service,
+            email_change_service,
+            group_service,
+            invitation_service,
+            audit_service,
+            audit_retention_service,
+            outbox_service,
+            webhook_subscription_service,
+            dead_letter_service,
             auth_service,
             external_service,
+            event_bus,
+            event_stream,
+            websocket_sessions,
         }
     }
 
+    /// Use a different authentication service
+    ///
+    /// The built-in one does not validate tokens yet, so deployments plug in
+    /// the one matching their identity provider here.
+    pub fn with_auth_service(mut self, auth_service: Arc<dyn AuthService>) -> Self {
+        self.auth_service = auth_service;
+        self
+    }
+
+    /// Use a different source of group memberships, such as a directory service
+    pub fn with_group_service(mut self, group_service: Arc<dyn GroupService>) -> Self {
+        self.group_service = group_service;
+        self
+    }
+
     /// Get user service instance
     pub fn user_service(&self) -> Arc<dyn UserService> {
         self.user_service.clone()
     }
 
+    /// Get user import service instance
+    pub fn user_import_service(&self) -> Arc<dyn UserImportService> {
+        self.user_import_service.clone()
+    }
+
+    /// Get privacy service instance
+    pub fn privacy_service(&self) -> Arc<dyn PrivacyService> {
+        self.privacy_service.clone()
+    }
+
+    /// Get email change service instance
+    pub fn email_change_service(&self) -> Arc<dyn EmailChangeService> {
+        self.email_change_service.clone()
+    }
+
+    /// Get group service instance
+    pub fn group_service(&self) -> Arc<dyn GroupService> {
+        self.group_service.clone()
+    }
+
+    /// Get invitation service instance
+    pub fn invitation_service(&self) -> Arc<dyn InvitationService> {
+        self.invitation_service.clone()
+    }
+
+    /// Get audit log search service instance
+    pub fn audit_service(&self) -> Arc<dyn AuditService> {
+        self.audit_service.clone()
+    }
+
+    /// Get audit log partitioning and retention service instance
+    pub fn audit_retention_service(&self) -> Arc<dyn AuditRetentionService> {
+        self.audit_retention_service.clone()
+    }
+
+    /// Get outbox dispatcher instance
+    pub fn outbox_service(&self) -> Arc<dyn OutboxService> {
+        self.outbox_service.clone()
+    }
+
+    /// Get webhook subscription service instance
+    pub fn webhook_subscription_service(&self) -> Arc<dyn WebhookSubscriptionService> {
+        self.webhook_subscription_service.clone()
+    }
+
+    /// Get dead letter service instance
+    pub fn dead_letter_service(&self) -> Arc<dyn DeadLetterService> {
+        self.dead_letter_service.clone()
+    }
+
     /// Get authentication service instance
     pub fn auth_service(&self) -> Arc<dyn AuthService> {
         self.auth_service.clone()
@@ -73,10 +324,49 @@ impl ServiceContainer {
         self.external_service.clone()
     }
 
+    /// Get the domain event bus
+    pub fn event_bus(&self) -> Arc<EventBus> {
+        self.event_bus.clone()
+    }
+
+    /// Get the stream of domain events shared between instances
+    ///
+    /// Not fed or listening until registered as an event handler and started.
+    pub fn event_stream(&self) -> Arc<EventStream> {
+        self.event_stream.clone()
+    }
+
+    /// Get the registry of open WebSocket sessions
+    pub fn websocket_sessions(&self) -> Arc<WebSocketSessions> {
+        self.websocket_sessions.clone()
+    }
+
+    /// Hand domain events to a handler from now on
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn register_event_handler(&self, handler: Arc<dyn EventHandler>) {
+        self.event_bus.register(handler);
+    }
+
     /// Get user repository instance (for advanced use cases)
     pub fn user_repository(&self) -> Arc<dyn UserRepository> {
         self.user_repository.clone()
     }
+
+    /// Get audit log repository instance
+    pub fn audit_repository(&self) -> Arc<dyn AuditLogRepository> {
+        self.audit_repository.clone()
+    }
+
+    /// Get group repository instance
+    pub fn group_repository(&self) -> Arc<dyn GroupRepository> {
+        self.group_repository.clone()
+    }
+
+    /// Get idempotency key repository instance
+    pub fn idempotency_repository(&self) -> Arc<dyn IdempotencyRepository> {
+        self.idempotency_repository.clone()
+    }
 }
 
 /// Application state that holds the service container
@@ -231,15 +521,15 @@ mod tests {
             todo!("Mock implementation")
         }
 
-        async fn update(&self, _id: crate::models::UserId, _name: Option<String>, _email: Option<String>) -> Result<crate::models::User, crate::repository::RepositoryError> {
+        async fn update(&self, _id: crate::models::UserId, _name: Option<String>, _email: Option<String>, _metadata: Option<serde_json::Value>, _expected_version: Option<i64>) -> Result<crate::models::User, crate::repository::RepositoryError> {
             todo!("Mock implementation")
         }
 
-        async fn update_tx(&self, _tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, _id: crate::models::UserId, _name: Option<String>, _email: Option<String>) -> Result<crate::models::User, crate::repository::RepositoryError> {
+        async fn update_tx(&self, _tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, _id: crate::models::UserId, _name: Option<String>, _email: Option<String>, _metadata: Option<serde_json::Value>, _expected_version: Option<i64>) -> Result<crate::models::User, crate::repository::RepositoryError> {
             todo!("Mock implementation")
         }
 
-        async fn soft_delete(&self, _id: crate::models::UserId) -> Result<(), crate::repository::RepositoryError> {
+        async fn soft_delete(&self, _id: crate::models::UserId, _expected_version: Option<i64>) -> Result<(), crate::repository::RepositoryError> {
             todo!("Mock implementation")
         }
 
@@ -251,7 +541,15 @@ mod tests {
             todo!("Mock implementation")
         }
 
-        async fn list_active(&self, _limit: i64, _offset: i64) -> Result<Vec<crate::models::User>, crate::repository::RepositoryError> {
+        async fn list_active(&self, _limit: i64, _offset: i64, _metadata: Option<&serde_json::Value>) -> Result<Vec<crate::models::User>, crate::repository::RepositoryError> {
+            todo!("Mock implementation")
+        }
+
+        fn stream_active(&self, _metadata: Option<serde_json::Value>) -> crate::repository::UserStream {
+            todo!("Mock implementation")
+        }
+
+        async fn search(&self, _query: &str, _is_active: Option<bool>, _limit: i64) -> Result<Vec<crate::models::UserSearchResult>, crate::repository::RepositoryError> {
             todo!("Mock implementation")
         }
 
@@ -271,6 +569,14 @@ mod tests {
             todo!("Mock implementation")
         }
 
+        async fn existing_emails(&self, _emails: &[String]) -> Result<Vec<String>, crate::repository::RepositoryError> {
+            todo!("Mock implementation")
+        }
+
+        async fn set_avatar(&self, _id: crate::models::UserId, _avatar_key: Option<&str>, _avatar_url: Option<&str>, _expected_version: Option<i64>) -> Result<crate::models::User, crate::repository::RepositoryError> {
+            todo!("Mock implementation")
+        }
+
         async fn activate(&self, _id: crate::models::UserId) -> Result<(), crate::repository::RepositoryError> {
             todo!("Mock implementation")
         }
@@ -278,6 +584,10 @@ mod tests {
         async fn deactivate(&self, _id: crate::models::UserId) -> Result<(), crate::repository::RepositoryError> {
             todo!("Mock implementation")
         }
+
+        async fn begin_transaction(&self) -> Result<Box<dyn crate::repository::UserRepositoryTransaction>, crate::repository::RepositoryError> {
+            todo!("Mock implementation")
+        }
     }
 
     #[test]
@@ -0,0 +1,201 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use validator::Validate;
+
+use crate::config::OutboxConfig;
+use crate::models::{
+    DeadLetter, DeadLetterFilter, DeadLetterId, DeadLetterReplay, DeadLetterReplayQueue, Page, ReplayDeadLettersRequest,
+};
+use crate::repository::{DeadLetterRepository, OutboxRepository};
+use crate::services::{ServiceError, WebhookSubscriptionService};
+
+/// Inspection and replay of deliveries the outbox gave up on
+///
+/// Authorization is left to callers.
+#[async_trait]
+pub trait DeadLetterService: Send + Sync {
+    async fn list_dead_letters(&self, filter: DeadLetterFilter, limit: i64, offset: i64) -> Result<Page<DeadLetter>, ServiceError>;
+    async fn get_dead_letter(&self, id: DeadLetterId) -> Result<DeadLetter, ServiceError>;
+
+    /// Try a dead letter again, removing it if that succeeds
+    ///
+    /// A subscription's letter is redelivered to it straight away. A letter
+    /// of an event that could not be dispatched puts the event back in the
+    /// outbox.
+    async fn replay(&self, id: DeadLetterId) -> Result<DeadLetterReplay, ServiceError>;
+
+    /// Queue the oldest matching dead letters for [`Self::replay_queued`]
+    async fn queue_replay(&self, request: ReplayDeadLettersRequest) -> Result<DeadLetterReplayQueue, ServiceError>;
+
+    /// Claim one batch of queued dead letters and replay them, returning how many were claimed
+    ///
+    /// A letter that fails again stays, with its error and attempt count
+    /// updated, and leaves the queue.
+    async fn replay_queued(&self) -> Result<usize, ServiceError>;
+
+    /// Remove a dead letter without replaying it
+    async fn discard(&self, id: DeadLetterId) -> Result<(), ServiceError>;
+
+    /// Number of dead letters, for metrics
+    async fn count(&self) -> Result<i64, ServiceError>;
+
+    /// Delete dead letters older than the configured retention
+    async fn purge_expired(&self) -> Result<u64, ServiceError>;
+}
+
+/// Dead letter service implementation
+pub struct DeadLetterServiceImpl {
+    repository: Arc<dyn DeadLetterRepository>,
+    outbox_repository: Arc<dyn OutboxRepository>,
+    webhooks: Arc<dyn WebhookSubscriptionService>,
+    config: OutboxConfig,
+}
+
+impl DeadLetterServiceImpl {
+    /// Queued replays are claimed and run like outbox events, with the
+    /// batch size, concurrency and lease of `config`
+    pub fn new(
+        repository: Arc<dyn DeadLetterRepository>,
+        outbox_repository: Arc<dyn OutboxRepository>,
+        webhooks: Arc<dyn WebhookSubscriptionService>,
+        config: OutboxConfig,
+    ) -> Self {
+        Self {
+            repository,
+            outbox_repository,
+            webhooks,
+            config,
+        }
+    }
+
+    async fn replay_letter(&self, letter: DeadLetter) -> Result<DeadLetterReplay, ServiceError> {
+        let Some(subscription_id) = letter.subscription_id else {
+            if !self.outbox_repository.requeue(letter.event_id).await? {
+                return Err(ServiceError::Conflict(format!(
+                    "Outbox event {} is no longer failed and cannot be requeued",
+                    letter.event_id
+                )));
+            }
+
+            self.repository.delete(letter.id).await?;
+            tracing::info!("Requeued outbox event {} from dead letter {}", letter.event_id, letter.id);
+            return Ok(DeadLetterReplay {
+                dead_letter_id: letter.id,
+                success: true,
+                error: None,
+                delivery: None,
+            });
+        };
+
+        let delivery = self
+            .webhooks
+            .redeliver(subscription_id, letter.event_id, &letter.event_type, &letter.payload, letter.attempts + 1)
+            .await?;
+
+        if delivery.success {
+            self.repository.delete(letter.id).await?;
+            tracing::info!("Replayed dead letter {} to subscription {}", letter.id, subscription_id);
+        } else {
+            self.repository
+                .record_failed_replay(
+                    letter.id,
+                    delivery.error.as_deref().unwrap_or("Delivery failed"),
+                    delivery.response_status,
+                    delivery.response_body.as_deref(),
+                )
+                .await?;
+        }
+
+        Ok(DeadLetterReplay {
+            dead_letter_id: letter.id,
+            success: delivery.success,
+            error: delivery.error.clone(),
+            delivery: Some(delivery),
+        })
+    }
+}
+
+#[async_trait]
+impl DeadLetterService for DeadLetterServiceImpl {
+    async fn list_dead_letters(&self, filter: DeadLetterFilter, limit: i64, offset: i64) -> Result<Page<DeadLetter>, ServiceError> {
+        let (dead_letters, total) = self.repository.list(&filter, limit, offset).await?;
+        Ok(Page::new(dead_letters, total, limit, offset))
+    }
+
+    async fn get_dead_letter(&self, id: DeadLetterId) -> Result<DeadLetter, ServiceError> {
+        self.repository.find_by_id(id).await?.ok_or(ServiceError::NotFound)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn replay(&self, id: DeadLetterId) -> Result<DeadLetterReplay, ServiceError> {
+        let letter = self.get_dead_letter(id).await?;
+        self.replay_letter(letter).await
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn queue_replay(&self, request: ReplayDeadLettersRequest) -> Result<DeadLetterReplayQueue, ServiceError> {
+        request.validate().map_err(|e| ServiceError::Validation(e.to_string()))?;
+
+        let ids = self.repository.queue_replay(&request.filter(), request.limit).await?;
+        tracing::info!("Queued {} dead letters for replay", ids.len());
+        Ok(DeadLetterReplayQueue { queued: ids.len(), ids })
+    }
+
+    async fn replay_queued(&self) -> Result<usize, ServiceError> {
+        let lease = Duration::from_secs(self.config.lease_seconds);
+        let letters = self.repository.claim_queued(self.config.batch_size, lease).await?;
+        let claimed = letters.len();
+        if claimed == 0 {
+            return Ok(0);
+        }
+
+        let succeeded = futures::stream::iter(letters)
+            .map(|letter| async move {
+                let id = letter.id;
+                match self.replay_letter(letter).await {
+                    Ok(result) => result.success,
+                    Err(e) => {
+                        // Take the letter off the queue so it is not retried until replayed again
+                        if let Err(e) = self.repository.record_failed_replay(id, &e.to_string(), None, None).await {
+                            tracing::error!("Failed to record replay of dead letter {}: {}", id, e);
+                        }
+                        false
+                    }
+                }
+            })
+            .buffer_unordered(self.config.concurrency)
+            .filter(|success| futures::future::ready(*success))
+            .count()
+            .await;
+
+        tracing::info!("Replayed {} queued dead letters, {} succeeded", claimed, succeeded);
+        Ok(claimed)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn discard(&self, id: DeadLetterId) -> Result<(), ServiceError> {
+        if !self.repository.delete(id).await? {
+            return Err(ServiceError::NotFound);
+        }
+
+        tracing::info!("Discarded dead letter {}", id);
+        Ok(())
+    }
+
+    async fn count(&self) -> Result<i64, ServiceError> {
+        Ok(self.repository.count().await?)
+    }
+
+    async fn purge_expired(&self) -> Result<u64, ServiceError> {
+        let retention = chrono::Duration::days(self.config.dead_letter_retention_days as i64);
+        let purged = self.repository.purge_before(Utc::now() - retention).await?;
+        if purged > 0 {
+            tracing::info!("Purged {} expired dead letters", purged);
+        }
+
+        Ok(purged)
+    }
+}
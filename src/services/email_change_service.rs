@@ -0,0 +1,435 @@
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use crate::config::EmailChangeConfig;
+use crate::models::{AuditContext, DomainEvent, EmailChange, EmailToken, NewEmailChange, User, UserId};
+use crate::repository::{EmailChangeRepository, RepositoryError, UserRepository};
+use crate::services::{user_service::write_error, EmailMessage, EventBus, Mailer, ServiceError};
+
+const INVALID_LINK: &str = "Email change link is invalid or has expired";
+
+/// Confirmed email change service trait
+///
+/// A new address only replaces the old one once it has been confirmed with
+/// the token mailed to it. The old address is told about the change and can
+/// revert it until the revert window closes.
+#[async_trait]
+pub trait EmailChangeService: Send + Sync {
+    /// Hold a change of `user`'s email as pending and mail both addresses
+    ///
+    /// Fails with `PreconditionFailed` if `expected_version` is given and the
+    /// user has changed since.
+    async fn request_change(&self, user: &User, new_email: &str, expected_version: Option<i64>) -> Result<EmailChange, ServiceError>;
+
+    /// Get the user's pending change, if any
+    async fn pending_change(&self, user_id: UserId) -> Result<Option<EmailChange>, ServiceError>;
+
+    /// Withdraw the user's pending change
+    async fn cancel_change(&self, user_id: UserId) -> Result<(), ServiceError>;
+
+    /// Apply a change using the token mailed to the new address
+    async fn confirm_change(&self, token: &str, context: &AuditContext) -> Result<User, ServiceError>;
+
+    /// Cancel or undo a change using the token mailed to the old address
+    async fn revert_change(&self, token: &str, context: &AuditContext) -> Result<User, ServiceError>;
+}
+
+/// Email change service implementation
+pub struct EmailChangeServiceImpl {
+    user_repository: Arc<dyn UserRepository>,
+    repository: Arc<dyn EmailChangeRepository>,
+    mailer: Arc<dyn Mailer>,
+    config: EmailChangeConfig,
+    events: Option<Arc<EventBus>>,
+}
+
+impl EmailChangeServiceImpl {
+    pub fn new(
+        user_repository: Arc<dyn UserRepository>,
+        repository: Arc<dyn EmailChangeRepository>,
+        mailer: Arc<dyn Mailer>,
+        config: EmailChangeConfig,
+    ) -> Self {
+        Self {
+            user_repository,
+            repository,
+            mailer,
+            config,
+            events: None,
+        }
+    }
+
+    /// Publish confirmed and reverted changes to the given event bus
+    pub fn with_event_bus(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Hand a committed change to the event bus, if there is one
+    fn publish(&self, event: DomainEvent, context: &AuditContext) {
+        if let Some(events) = &self.events {
+            events.publish(event, context);
+        }
+    }
+
+    fn link(template: &str, token: &str) -> String {
+        template.replace("{token}", token)
+    }
+}
+
+fn token_error(error: RepositoryError) -> ServiceError {
+    match error {
+        RepositoryError::NotFound => ServiceError::Validation(INVALID_LINK.to_string()),
+        RepositoryError::DuplicateEmail(_) => ServiceError::AlreadyExists,
+        e => ServiceError::Repository(e),
+    }
+}
+
+#[async_trait]
+impl EmailChangeService for EmailChangeServiceImpl {
+    #[tracing::instrument(skip(self, user), fields(user_id = %user.id))]
+    async fn request_change(&self, user: &User, new_email: &str, expected_version: Option<i64>) -> Result<EmailChange, ServiceError> {
+        if new_email == user.email {
+            return Err(ServiceError::Validation("New email is the same as the current email".to_string()));
+        }
+        if self.user_repository.email_exists_for_other_user(new_email, user.id).await? {
+            tracing::warn!("Attempted to change user {} to existing email: {}", user.id, new_email);
+            return Err(ServiceError::AlreadyExists);
+        }
+
+        let confirm_token = EmailToken::generate();
+        let revert_token = EmailToken::generate();
+        let expires_at = Utc::now() + Duration::minutes(self.config.expiry_minutes as i64);
+
+        let change = self
+            .repository
+            .create(&NewEmailChange {
+                user_id: user.id,
+                old_email: user.email.clone(),
+                new_email: new_email.to_string(),
+                confirm_token_hash: confirm_token.hash,
+                revert_token_hash: revert_token.hash,
+                expires_at,
+                revert_expires_at: expires_at + Duration::hours(self.config.revert_window_hours as i64),
+            }, expected_version)
+            .await
+            .map_err(|e| write_error(user.id, e))?;
+
+        let confirmation = EmailMessage {
+            to: change.new_email.clone(),
+            subject: "Confirm your new email address".to_string(),
+            body: format!(
+                "Hi {},\n\nConfirm that this is your new email address by opening the link below \
+                 before {}:\n\n{}\n\nIf you did not ask for this change, ignore this email.\n",
+                user.name,
+                change.expires_at.format("%Y-%m-%d %H:%M UTC"),
+                Self::link(&self.config.confirm_url, &confirm_token.token),
+            ),
+        };
+        if let Err(e) = self.mailer.send(&confirmation).await {
+            // Without the confirmation email the change could never complete
+            tracing::error!("Failed to send email change confirmation for user {}: {}", user.id, e);
+            if let Err(cancel_error) = self.repository.cancel_pending(user.id).await {
+                tracing::error!("Failed to cancel undeliverable email change for user {}: {}", user.id, cancel_error);
+            }
+            return Err(ServiceError::ExternalService(e.to_string()));
+        }
+
+        let notice = EmailMessage {
+            to: change.old_email.clone(),
+            subject: "Your email address is being changed".to_string(),
+            body: format!(
+                "Hi {},\n\nA change of your account's email address to {} was requested. \
+                 If this was not you, keep your current address by opening the link below \
+                 before {}:\n\n{}\n",
+                user.name,
+                change.new_email,
+                change.revert_expires_at.format("%Y-%m-%d %H:%M UTC"),
+                Self::link(&self.config.revert_url, &revert_token.token),
+            ),
+        };
+        if let Err(e) = self.mailer.send(&notice).await {
+            tracing::warn!("Failed to notify old address of email change for user {}: {}", user.id, e);
+        }
+
+        tracing::info!("Email change {} for user {} pending confirmation", change.id, user.id);
+        Ok(change)
+    }
+
+    async fn pending_change(&self, user_id: UserId) -> Result<Option<EmailChange>, ServiceError> {
+        Ok(self.repository.find_pending(user_id).await?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn cancel_change(&self, user_id: UserId) -> Result<(), ServiceError> {
+        if !self.repository.cancel_pending(user_id).await? {
+            return Err(ServiceError::NotFound);
+        }
+
+        tracing::info!("Cancelled pending email change for user {}", user_id);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, token, context))]
+    async fn confirm_change(&self, token: &str, context: &AuditContext) -> Result<User, ServiceError> {
+        let (change, user) = self.repository.confirm(&EmailToken::hash(token)).await.map_err(token_error)?;
+
+        tracing::info!("User {} confirmed email change {}", user.id, change.id);
+        let previous = User { email: change.old_email.clone(), ..user.clone() };
+        self.publish(DomainEvent::user_updated(&previous, &user), context);
+        Ok(user)
+    }
+
+    #[tracing::instrument(skip(self, token, context))]
+    async fn revert_change(&self, token: &str, context: &AuditContext) -> Result<User, ServiceError> {
+        let (change, user) = self.repository.revert(&EmailToken::hash(token)).await.map_err(token_error)?;
+
+        tracing::info!("User {} reverted email change {}", user.id, change.id);
+        // Reverting a change that was never confirmed leaves the user as it was
+        if change.confirmed_at.is_some() {
+            let previous = User { email: change.new_email.clone(), ..user.clone() };
+            self.publish(DomainEvent::user_updated(&previous, &user), context);
+        }
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EventBusConfig;
+    use crate::models::{EmailChangeStatus, EventEnvelope};
+    use crate::repository::TakenEmails;
+    use crate::services::{EventHandler, MailerError};
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    /// A stored change with the token hashes it is looked up by
+    struct StoredChange {
+        change: EmailChange,
+        confirm_token_hash: String,
+        revert_token_hash: String,
+    }
+
+    /// Email changes of a single user, applied to that user in memory
+    struct MemoryEmailChanges {
+        user: Mutex<User>,
+        changes: Mutex<Vec<StoredChange>>,
+    }
+
+    #[async_trait]
+    impl EmailChangeRepository for MemoryEmailChanges {
+        async fn create(&self, change: &NewEmailChange, expected_version: Option<i64>) -> Result<EmailChange, RepositoryError> {
+            if expected_version.is_some_and(|expected| expected != self.user.lock().unwrap().version) {
+                return Err(RepositoryError::VersionMismatch);
+            }
+            self.cancel_pending(change.user_id).await?;
+
+            let stored = EmailChange {
+                id: Uuid::new_v4(),
+                user_id: change.user_id,
+                old_email: change.old_email.clone(),
+                new_email: change.new_email.clone(),
+                status: EmailChangeStatus::Pending,
+                expires_at: change.expires_at,
+                revert_expires_at: change.revert_expires_at,
+                created_at: Utc::now(),
+                confirmed_at: None,
+                reverted_at: None,
+            };
+            self.changes.lock().unwrap().push(StoredChange {
+                change: stored.clone(),
+                confirm_token_hash: change.confirm_token_hash.clone(),
+                revert_token_hash: change.revert_token_hash.clone(),
+            });
+            Ok(stored)
+        }
+
+        async fn find_pending(&self, _user_id: UserId) -> Result<Option<EmailChange>, RepositoryError> {
+            let changes = self.changes.lock().unwrap();
+            Ok(changes.iter().map(|stored| &stored.change).find(|change| change.status == EmailChangeStatus::Pending).cloned())
+        }
+
+        async fn cancel_pending(&self, _user_id: UserId) -> Result<bool, RepositoryError> {
+            let mut cancelled = false;
+            for stored in self.changes.lock().unwrap().iter_mut() {
+                if stored.change.status == EmailChangeStatus::Pending {
+                    stored.change.status = EmailChangeStatus::Cancelled;
+                    cancelled = true;
+                }
+            }
+            Ok(cancelled)
+        }
+
+        async fn confirm(&self, confirm_token_hash: &str) -> Result<(EmailChange, User), RepositoryError> {
+            let mut changes = self.changes.lock().unwrap();
+            let stored = changes
+                .iter_mut()
+                .find(|stored| stored.confirm_token_hash == confirm_token_hash && stored.change.status == EmailChangeStatus::Pending)
+                .ok_or(RepositoryError::NotFound)?;
+
+            let mut user = self.user.lock().unwrap();
+            user.email = stored.change.new_email.clone();
+            user.version += 1;
+            stored.change.status = EmailChangeStatus::Confirmed;
+            stored.change.confirmed_at = Some(Utc::now());
+            Ok((stored.change.clone(), user.clone()))
+        }
+
+        async fn revert(&self, revert_token_hash: &str) -> Result<(EmailChange, User), RepositoryError> {
+            let mut changes = self.changes.lock().unwrap();
+            let stored = changes
+                .iter_mut()
+                .find(|stored| {
+                    stored.revert_token_hash == revert_token_hash
+                        && matches!(stored.change.status, EmailChangeStatus::Pending | EmailChangeStatus::Confirmed)
+                })
+                .ok_or(RepositoryError::NotFound)?;
+
+            let mut user = self.user.lock().unwrap();
+            if stored.change.status == EmailChangeStatus::Confirmed {
+                user.email = stored.change.old_email.clone();
+                user.version += 1;
+            }
+            stored.change.status = EmailChangeStatus::Reverted;
+            stored.change.reverted_at = Some(Utc::now());
+            Ok((stored.change.clone(), user.clone()))
+        }
+    }
+
+    /// Keeps every message instead of sending it
+    #[derive(Default)]
+    struct SentMail {
+        sent: Mutex<Vec<EmailMessage>>,
+    }
+
+    #[async_trait]
+    impl Mailer for SentMail {
+        async fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+            self.sent.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+    }
+
+    impl SentMail {
+        /// The token of the link with `prefix` in the last message to `to`
+        fn token(&self, to: &str, prefix: &str) -> String {
+            let sent = self.sent.lock().unwrap();
+            let message = sent.iter().rev().find(|message| message.to == to).expect("no message sent");
+            message
+                .body
+                .split_whitespace()
+                .find_map(|word| word.strip_prefix(prefix))
+                .expect("no link in message")
+                .to_string()
+        }
+    }
+
+    /// Keeps every event it is handed
+    #[derive(Default)]
+    struct RecordedEvents {
+        events: Mutex<Vec<EventEnvelope>>,
+    }
+
+    #[async_trait]
+    impl EventHandler for RecordedEvents {
+        fn name(&self) -> &str {
+            "recorded"
+        }
+
+        async fn handle(&self, event: &EventEnvelope) -> Result<(), ServiceError> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    fn user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 3,
+            metadata: serde_json::json!({}),
+            avatar_key: None,
+            avatar_url: None,
+        }
+    }
+
+    fn service(user: &User) -> (EmailChangeServiceImpl, Arc<MemoryEmailChanges>, Arc<SentMail>, Arc<EventBus>) {
+        let repository = Arc::new(MemoryEmailChanges {
+            user: Mutex::new(user.clone()),
+            changes: Mutex::new(Vec::new()),
+        });
+        let mailer = Arc::new(SentMail::default());
+        let service = EmailChangeServiceImpl::new(
+            Arc::new(TakenEmails(Vec::new())),
+            repository.clone(),
+            mailer.clone(),
+            EmailChangeConfig {
+                confirm_url: "confirm:{token}".to_string(),
+                revert_url: "revert:{token}".to_string(),
+                ..EmailChangeConfig::default()
+            },
+        );
+        let events = Arc::new(EventBus::new(EventBusConfig::default()));
+        (service.with_event_bus(events.clone()), repository, mailer, events)
+    }
+
+    #[tokio::test]
+    async fn test_request_confirm_and_revert_an_email_change() {
+        let user = user();
+        let (service, repository, mailer, events) = service(&user);
+        let recorded = Arc::new(RecordedEvents::default());
+        events.register(recorded.clone());
+        let context = AuditContext::system();
+
+        let change = service.request_change(&user, "ada@new.example.com", Some(user.version)).await.unwrap();
+        assert_eq!(change.status, EmailChangeStatus::Pending);
+        assert_eq!(service.pending_change(user.id).await.unwrap().map(|change| change.id), Some(change.id));
+        assert_eq!(repository.user.lock().unwrap().email, "ada@example.com");
+
+        let confirmed = service.confirm_change(&mailer.token("ada@new.example.com", "confirm:"), &context).await.unwrap();
+        assert_eq!(confirmed.email, "ada@new.example.com");
+        assert!(service.pending_change(user.id).await.unwrap().is_none());
+
+        // The revert link went to the old address and undoes the confirmed change
+        let reverted = service.revert_change(&mailer.token("ada@example.com", "revert:"), &context).await.unwrap();
+        assert_eq!(reverted.email, "ada@example.com");
+        assert!(service.revert_change(&mailer.token("ada@example.com", "revert:"), &context).await.is_err());
+
+        // Both the confirmation and the revert were published with the email diff
+        events.shutdown(std::time::Duration::from_secs(1)).await;
+        let emails: Vec<_> = recorded
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|envelope| match &envelope.event {
+                DomainEvent::UserUpdated { changes, .. } => (changes["email"].old.clone(), changes["email"].new.clone()),
+                event => panic!("unexpected event {:?}", event),
+            })
+            .collect();
+        assert_eq!(
+            emails,
+            vec![
+                (serde_json::json!("ada@example.com"), serde_json::json!("ada@new.example.com")),
+                (serde_json::json!("ada@new.example.com"), serde_json::json!("ada@example.com")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_change_checks_the_version() {
+        let user = user();
+        let (service, _, mailer, _) = service(&user);
+
+        let result = service.request_change(&user, "ada@new.example.com", Some(user.version - 1)).await;
+        assert!(matches!(result, Err(ServiceError::PreconditionFailed(_))));
+        assert!(service.pending_change(user.id).await.unwrap().is_none());
+        assert!(mailer.sent.lock().unwrap().is_empty());
+    }
+}
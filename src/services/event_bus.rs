@@ -0,0 +1,348 @@
+use async_trait::async_trait;
+use futures::FutureExt;
+use serde::Serialize;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+
+use crate::config::EventBusConfig;
+use crate::models::{AuditContext, DomainEvent, DomainEventKind, EventEnvelope};
+use crate::services::ServiceError;
+
+/// Reacts to domain events published on the [`EventBus`]
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    /// Name used in logs and metrics
+    fn name(&self) -> &str;
+
+    /// Whether the handler wants events of a kind; all of them by default
+    fn handles(&self, _kind: DomainEventKind) -> bool {
+        true
+    }
+
+    async fn handle(&self, event: &EventEnvelope) -> Result<(), ServiceError>;
+}
+
+/// Dispatch counters of one handler
+#[derive(Debug, Clone, Serialize)]
+pub struct EventHandlerStats {
+    pub name: String,
+    pub handled: u64,
+    /// Events the handler returned an error for, timed out on or panicked over
+    pub failed: u64,
+    /// Events not queued because the handler's queue was full
+    pub dropped: u64,
+    /// Events waiting in the handler's queue
+    pub queued: usize,
+}
+
+/// Dispatch counters of the event bus
+#[derive(Debug, Clone, Serialize)]
+pub struct EventBusStats {
+    pub published: u64,
+    pub handlers: Vec<EventHandlerStats>,
+}
+
+#[derive(Default)]
+struct HandlerCounters {
+    handled: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+}
+
+struct Subscription {
+    handler: Arc<dyn EventHandler>,
+    queue: mpsc::Sender<Arc<EventEnvelope>>,
+    counters: Arc<HandlerCounters>,
+    worker: JoinHandle<()>,
+}
+
+/// In-process bus handing domain events to registered handlers
+///
+/// Publishing never waits for handlers. Each handler has its own bounded
+/// queue and task, so one that is slow, fails or panics only affects its own
+/// events; when its queue is full, new events are dropped for it. Events are
+/// not persisted, so handlers that must not miss any should use the outbox.
+pub struct EventBus {
+    subscriptions: RwLock<Vec<Subscription>>,
+    published: AtomicU64,
+    config: EventBusConfig,
+}
+
+impl EventBus {
+    pub fn new(config: EventBusConfig) -> Self {
+        Self {
+            subscriptions: RwLock::new(Vec::new()),
+            published: AtomicU64::new(0),
+            config,
+        }
+    }
+
+    /// Start handing events to a handler
+    ///
+    /// Must be called within a Tokio runtime, which runs the handler's task.
+    pub fn register(&self, handler: Arc<dyn EventHandler>) {
+        let (queue, events) = mpsc::channel(self.config.queue_capacity);
+        let counters = Arc::new(HandlerCounters::default());
+        let timeout = Duration::from_secs(self.config.handler_timeout_seconds);
+        let worker = tokio::spawn(run_handler(handler.clone(), events, counters.clone(), timeout));
+
+        tracing::info!("Registered event handler '{}'", handler.name());
+        self.subscriptions.write().unwrap().push(Subscription {
+            handler,
+            queue,
+            counters,
+            worker,
+        });
+    }
+
+    /// Queue an event for every handler that wants it
+    pub fn publish(&self, event: DomainEvent, context: &AuditContext) {
+        let kind = event.kind();
+        let envelope = Arc::new(EventEnvelope::new(event, context));
+        self.published.fetch_add(1, Ordering::Relaxed);
+
+        let subscriptions = self.subscriptions.read().unwrap();
+        for subscription in subscriptions.iter().filter(|s| s.handler.handles(kind)) {
+            if let Err(e) = subscription.queue.try_send(envelope.clone()) {
+                subscription.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                let reason = match e {
+                    TrySendError::Full(_) => "its queue is full",
+                    TrySendError::Closed(_) => "it has stopped",
+                };
+                tracing::warn!(
+                    "Dropped {} event {} for handler '{}' because {}",
+                    kind.as_str(),
+                    envelope.id,
+                    subscription.handler.name(),
+                    reason
+                );
+            }
+        }
+    }
+
+    pub fn stats(&self) -> EventBusStats {
+        let subscriptions = self.subscriptions.read().unwrap();
+        EventBusStats {
+            published: self.published.load(Ordering::Relaxed),
+            handlers: subscriptions
+                .iter()
+                .map(|subscription| EventHandlerStats {
+                    name: subscription.handler.name().to_string(),
+                    handled: subscription.counters.handled.load(Ordering::Relaxed),
+                    failed: subscription.counters.failed.load(Ordering::Relaxed),
+                    dropped: subscription.counters.dropped.load(Ordering::Relaxed),
+                    queued: subscription.queue.max_capacity() - subscription.queue.capacity(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Stop accepting events and wait up to `timeout` for handlers to finish the queued ones
+    ///
+    /// Returns the names of handlers that were still busy and got aborted.
+    pub async fn shutdown(&self, timeout: Duration) -> Vec<String> {
+        let subscriptions = std::mem::take(&mut *self.subscriptions.write().unwrap());
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        let mut aborted = Vec::new();
+        for subscription in subscriptions {
+            // Closing the queue lets the task exit once it is empty
+            drop(subscription.queue);
+            let mut worker = subscription.worker;
+            if tokio::time::timeout_at(deadline, &mut worker).await.is_err() {
+                worker.abort();
+                aborted.push(subscription.handler.name().to_string());
+            }
+        }
+
+        aborted
+    }
+}
+
+/// Hand queued events to a handler one at a time until the queue is closed
+async fn run_handler(
+    handler: Arc<dyn EventHandler>,
+    mut events: mpsc::Receiver<Arc<EventEnvelope>>,
+    counters: Arc<HandlerCounters>,
+    timeout: Duration,
+) {
+    while let Some(event) = events.recv().await {
+        let started = Instant::now();
+        let outcome = AssertUnwindSafe(tokio::time::timeout(timeout, handler.handle(&event)))
+            .catch_unwind()
+            .await;
+
+        let error = match outcome {
+            Ok(Ok(Ok(()))) => None,
+            Ok(Ok(Err(e))) => Some(e.to_string()),
+            Ok(Err(_)) => Some(format!("timed out after {:?}", timeout)),
+            Err(_) => Some("panicked".to_string()),
+        };
+        match error {
+            None => {
+                counters.handled.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(
+                    "Handler '{}' handled {} event {} in {:?}",
+                    handler.name(),
+                    event.event.kind().as_str(),
+                    event.id,
+                    started.elapsed()
+                );
+            }
+            Some(error) => {
+                counters.failed.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(
+                    "Handler '{}' failed on {} event {}: {}",
+                    handler.name(),
+                    event.event.kind().as_str(),
+                    event.id,
+                    error
+                );
+            }
+        }
+    }
+
+    tracing::info!("Event handler '{}' stopped", handler.name());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::User;
+    use chrono::Utc;
+    use tokio::sync::Mutex;
+
+    /// Records the events it handles, failing or panicking on request
+    struct TestHandler {
+        name: &'static str,
+        kinds: Option<Vec<DomainEventKind>>,
+        behaviour: Behaviour,
+        seen: Mutex<Vec<DomainEventKind>>,
+    }
+
+    enum Behaviour {
+        Succeed,
+        Fail,
+        Panic,
+    }
+
+    impl TestHandler {
+        fn new(name: &'static str, behaviour: Behaviour) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                kinds: None,
+                behaviour,
+                seen: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl EventHandler for TestHandler {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn handles(&self, kind: DomainEventKind) -> bool {
+            self.kinds.as_ref().map_or(true, |kinds| kinds.contains(&kind))
+        }
+
+        async fn handle(&self, event: &EventEnvelope) -> Result<(), ServiceError> {
+            self.seen.lock().await.push(event.event.kind());
+            match self.behaviour {
+                Behaviour::Succeed => Ok(()),
+                Behaviour::Fail => Err(ServiceError::ExternalService("index unavailable".to_string())),
+                Behaviour::Panic => panic!("handler bug"),
+            }
+        }
+    }
+
+    fn user() -> User {
+        User {
+            id: uuid::Uuid::new_v4(),
+            name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+            metadata: serde_json::json!({}),
+            avatar_key: None,
+            avatar_url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_handlers_do_not_affect_others() {
+        let bus = EventBus::new(EventBusConfig::default());
+        let good = TestHandler::new("good", Behaviour::Succeed);
+        bus.register(good.clone());
+        bus.register(TestHandler::new("failing", Behaviour::Fail));
+        bus.register(TestHandler::new("panicking", Behaviour::Panic));
+
+        bus.publish(DomainEvent::UserCreated { user: user() }, &AuditContext::system());
+        bus.publish(DomainEvent::UserDeleted { user: user() }, &AuditContext::system());
+        assert!(bus.shutdown(Duration::from_secs(5)).await.is_empty());
+
+        assert_eq!(
+            *good.seen.lock().await,
+            vec![DomainEventKind::UserCreated, DomainEventKind::UserDeleted]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_outcomes_per_handler() {
+        let bus = EventBus::new(EventBusConfig::default());
+        let created_only = Arc::new(TestHandler {
+            name: "created",
+            kinds: Some(vec![DomainEventKind::UserCreated]),
+            behaviour: Behaviour::Succeed,
+            seen: Mutex::new(Vec::new()),
+        });
+        bus.register(created_only.clone());
+        bus.register(TestHandler::new("panicking", Behaviour::Panic));
+
+        bus.publish(DomainEvent::UserCreated { user: user() }, &AuditContext::system());
+        bus.publish(DomainEvent::UserDeleted { user: user() }, &AuditContext::system());
+
+        // One event for the first handler and two for the second
+        for _ in 0..100 {
+            if bus.stats().handlers.iter().map(|h| h.handled + h.failed).sum::<u64>() == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let stats = bus.stats();
+        assert_eq!(stats.published, 2);
+        let counts: Vec<_> = stats.handlers.iter().map(|h| (h.name.as_str(), h.handled, h.failed)).collect();
+        assert_eq!(counts, vec![("created", 1, 0), ("panicking", 0, 2)]);
+        assert_eq!(*created_only.seen.lock().await, vec![DomainEventKind::UserCreated]);
+    }
+
+    #[tokio::test]
+    async fn test_full_queues_drop_events() {
+        let bus = EventBus::new(EventBusConfig {
+            queue_capacity: 1,
+            ..EventBusConfig::default()
+        });
+        let handler = TestHandler::new("slow", Behaviour::Succeed);
+        // Hold the handler up so events pile up in its queue
+        let guard = handler.seen.lock().await;
+        bus.register(handler.clone());
+
+        for _ in 0..3 {
+            bus.publish(DomainEvent::UserCreated { user: user() }, &AuditContext::system());
+        }
+
+        // At most one event reaches the handler and one waits in its queue
+        let stats = bus.stats();
+        assert!(stats.handlers[0].dropped >= 1);
+        drop(guard);
+        assert!(bus.shutdown(Duration::from_secs(5)).await.is_empty());
+    }
+}
@@ -0,0 +1,361 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::config::EventStreamConfig;
+use crate::models::{EventEnvelope, StreamEvent};
+use crate::repository::EventNotificationRepository;
+use crate::services::{EventHandler, ServiceError};
+
+/// Delay before the first attempt to listen again after losing the connection
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// Longest delay between attempts to listen again
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Events for one client of the [`EventStream`]
+pub struct EventSubscription {
+    /// Buffered events after the client's last event, oldest first
+    pub replay: Vec<Arc<StreamEvent>>,
+    /// False when the client's last event is no longer buffered, so it may
+    /// have missed events and should reload what it shows
+    pub resumed: bool,
+    /// Events received from now on
+    pub events: broadcast::Receiver<Arc<StreamEvent>>,
+    /// Becomes true when the stream is closed for shutdown
+    pub closed: watch::Receiver<bool>,
+}
+
+/// Shares domain events between instances and fans them out to clients
+///
+/// As an [`EventHandler`] it sends each local event to Postgres with NOTIFY.
+/// A listener task receives the events of every instance, this one included,
+/// keeps the most recent ones for clients resuming after a disconnect and
+/// broadcasts them to subscribers. Events notified while the listener is
+/// reconnecting are lost, so the buffer is cleared when it reconnects and
+/// clients resuming from before the gap are told to reload.
+pub struct EventStream {
+    notifications: Arc<dyn EventNotificationRepository>,
+    config: EventStreamConfig,
+    buffer: Mutex<VecDeque<Arc<StreamEvent>>>,
+    sender: broadcast::Sender<Arc<StreamEvent>>,
+    closed: watch::Sender<bool>,
+    listener: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl EventStream {
+    pub fn new(notifications: Arc<dyn EventNotificationRepository>, config: EventStreamConfig) -> Self {
+        let (sender, _) = broadcast::channel(config.replay_buffer_size);
+        let (closed, _) = watch::channel(false);
+
+        Self {
+            notifications,
+            buffer: Mutex::new(VecDeque::with_capacity(config.replay_buffer_size)),
+            config,
+            sender,
+            closed,
+            listener: Mutex::new(None),
+        }
+    }
+
+    /// Start listening for events from every instance
+    ///
+    /// Must be called within a Tokio runtime, which runs the listener task.
+    pub fn start(self: &Arc<Self>) {
+        let handle = tokio::spawn(self.clone().listen());
+        if let Some(previous) = self.listener.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+    }
+
+    /// Subscribe to events, replaying the buffered ones after `last_event_id`
+    pub fn subscribe(&self, last_event_id: Option<Uuid>) -> EventSubscription {
+        // Subscribing under the buffer lock means no event is both replayed and received
+        let buffer = self.buffer.lock().unwrap();
+        let events = self.sender.subscribe();
+
+        let (replay, resumed) = match last_event_id {
+            None => (Vec::new(), true),
+            Some(id) => match buffer.iter().position(|event| event.id == id) {
+                Some(position) => (buffer.iter().skip(position + 1).cloned().collect(), true),
+                None => (Vec::new(), false),
+            },
+        };
+
+        EventSubscription {
+            replay,
+            resumed,
+            events,
+            closed: self.closed.subscribe(),
+        }
+    }
+
+    /// Number of clients currently subscribed
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    pub fn heartbeat_interval(&self) -> Duration {
+        Duration::from_secs(self.config.heartbeat_seconds)
+    }
+
+    /// End every subscription and stop listening
+    pub async fn close(&self) {
+        self.closed.send_replace(true);
+
+        let listener = self.listener.lock().unwrap().take();
+        if let Some(listener) = listener {
+            if let Err(e) = listener.await {
+                if e.is_panic() {
+                    tracing::error!("Event stream listener panicked: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Listen on the channel until closed, reconnecting with backoff
+    async fn listen(self: Arc<Self>) {
+        let mut closed = self.closed.subscribe();
+        let mut delay = MIN_RECONNECT_DELAY;
+
+        while !*closed.borrow() {
+            match self.notifications.listen(&self.config.channel).await {
+                Ok(mut payloads) => {
+                    tracing::info!("Listening for domain events on channel '{}'", self.config.channel);
+                    delay = MIN_RECONNECT_DELAY;
+                    self.clear_buffer();
+                    loop {
+                        tokio::select! {
+                            _ = closed.wait_for(|closed| *closed) => return,
+                            payload = payloads.next() => match payload {
+                                Some(Ok(payload)) => self.receive(&payload),
+                                Some(Err(e)) => {
+                                    tracing::warn!("Lost the domain event listener connection: {}", e);
+                                    break;
+                                }
+                                None => {
+                                    tracing::warn!("Lost the domain event listener connection");
+                                    break;
+                                }
+                            },
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to listen for domain events: {}", e);
+                }
+            }
+
+            tokio::select! {
+                _ = closed.wait_for(|closed| *closed) => return,
+                _ = tokio::time::sleep(delay) => {}
+            }
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    /// Forget the buffered events, which no longer lead up to the live ones
+    /// after a gap
+    fn clear_buffer(&self) {
+        let mut buffer = self.buffer.lock().unwrap();
+        if !buffer.is_empty() {
+            tracing::warn!("Dropping {} buffered events after reconnecting; resuming clients will be reset", buffer.len());
+            buffer.clear();
+        }
+    }
+
+    /// Buffer and broadcast an event received from the channel
+    fn receive(&self, payload: &str) {
+        let event: StreamEvent = match serde_json::from_str(payload) {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!("Ignoring malformed domain event notification: {}", e);
+                return;
+            }
+        };
+        let event = Arc::new(event);
+
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() == self.config.replay_buffer_size {
+            buffer.pop_front();
+        }
+        buffer.push_back(event.clone());
+        // Nobody may be subscribed, which is fine
+        let _ = self.sender.send(event);
+    }
+}
+
+#[async_trait]
+impl EventHandler for EventStream {
+    fn name(&self) -> &str {
+        "Event Stream"
+    }
+
+    async fn handle(&self, event: &EventEnvelope) -> Result<(), ServiceError> {
+        let payload = StreamEvent::from_envelope(event).to_notification();
+        self.notifications.notify(&self.config.channel, &payload).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AuditContext, DomainEvent, User};
+    use crate::repository::{NotificationStream, RepositoryError};
+    use chrono::Utc;
+
+    /// Delivers notifications to listeners in the same process
+    struct LoopbackNotifications {
+        sender: broadcast::Sender<String>,
+    }
+
+    #[async_trait]
+    impl EventNotificationRepository for LoopbackNotifications {
+        async fn notify(&self, _channel: &str, payload: &str) -> Result<(), RepositoryError> {
+            let _ = self.sender.send(payload.to_string());
+            Ok(())
+        }
+
+        async fn listen(&self, _channel: &str) -> Result<NotificationStream, RepositoryError> {
+            let receiver = self.sender.subscribe();
+            Ok(futures::stream::unfold(receiver, |mut receiver| async move {
+                receiver.recv().await.ok().map(|payload| (Ok(payload), receiver))
+            })
+            .boxed())
+        }
+    }
+
+    fn event_stream(replay_buffer_size: usize) -> Arc<EventStream> {
+        let (sender, _) = broadcast::channel(16);
+        Arc::new(EventStream::new(
+            Arc::new(LoopbackNotifications { sender }),
+            EventStreamConfig {
+                replay_buffer_size,
+                ..EventStreamConfig::default()
+            },
+        ))
+    }
+
+    fn envelope() -> EventEnvelope {
+        let user = User {
+            id: Uuid::new_v4(),
+            name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+            metadata: serde_json::json!({}),
+            avatar_key: None,
+            avatar_url: None,
+        };
+        EventEnvelope::new(DomainEvent::UserCreated { user }, &AuditContext::system())
+    }
+
+    #[test]
+    fn test_subscribe_replays_buffered_events_after_the_last_one_seen() {
+        let stream = event_stream(2);
+        let events: Vec<_> = (0..3).map(|_| StreamEvent::from_envelope(&envelope())).collect();
+        for event in &events {
+            stream.receive(&event.to_notification());
+        }
+
+        let subscription = stream.subscribe(Some(events[1].id));
+        assert!(subscription.resumed);
+        assert_eq!(subscription.replay.iter().map(|e| e.id).collect::<Vec<_>>(), vec![events[2].id]);
+
+        // The first event was evicted from the buffer
+        let subscription = stream.subscribe(Some(events[0].id));
+        assert!(!subscription.resumed);
+        assert!(subscription.replay.is_empty());
+
+        let subscription = stream.subscribe(None);
+        assert!(subscription.resumed);
+        assert!(subscription.replay.is_empty());
+    }
+
+    /// Ends its first stream after one notification, as when the connection is lost
+    struct DroppedConnection {
+        payload: String,
+        listens: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl EventNotificationRepository for DroppedConnection {
+        async fn notify(&self, _channel: &str, _payload: &str) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn listen(&self, _channel: &str) -> Result<NotificationStream, RepositoryError> {
+            let mut listens = self.listens.lock().unwrap();
+            *listens += 1;
+            if *listens == 1 {
+                Ok(futures::stream::iter(vec![Ok(self.payload.clone())]).boxed())
+            } else {
+                Ok(futures::stream::pending().boxed())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resuming_across_a_reconnect_resets_the_client() {
+        let event = StreamEvent::from_envelope(&envelope());
+        let stream = Arc::new(EventStream::new(
+            Arc::new(DroppedConnection {
+                payload: event.to_notification(),
+                listens: Mutex::new(0),
+            }),
+            EventStreamConfig::default(),
+        ));
+        let mut subscription = stream.subscribe(None);
+        stream.start();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), subscription.events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.id, event.id);
+
+        // The listener waits before reconnecting, then drops what it buffered
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while stream.subscribe(Some(event.id)).resumed {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        stream.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_handled_events_reach_subscribers_through_the_channel() {
+        let stream = event_stream(10);
+        stream.start();
+        let mut subscription = stream.subscribe(None);
+
+        // The listener subscribes to the channel asynchronously
+        let envelope = envelope();
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                stream.handle(&envelope).await.unwrap();
+                if let Ok(Ok(event)) = tokio::time::timeout(Duration::from_millis(50), subscription.events.recv()).await {
+                    return event;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(received.id, envelope.id);
+        assert_eq!(received.event_type, "user.created");
+
+        stream.close().await;
+        assert!(*subscription.closed.borrow());
+    }
+}
@@ -4,7 +4,9 @@ use serde_json::Value;
 use std::time::{Duration, Instant};
 use std::sync::{Arc, Mutex};
 use tokio::time::sleep;
-use tracing::{info, warn, error, instrument};
+use tracing::{debug, info, warn, error, instrument};
+
+use crate::models::{is_public_address, sign_webhook};
 
 /// External service error types
 #[derive(Debug, thiserror::Error)]
@@ -32,6 +34,9 @@ pub enum ExternalServiceError {
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("Address not allowed: {0}")]
+    ForbiddenAddress(String),
 }
 
 /// External service trait for making HTTP calls
@@ -571,38 +576,174 @@ pub struct ExternalServiceHealthStatus {
 }
 
 /// Specialized external service implementations
+///
+/// Webhooks are signed following the Standard Webhooks convention: the
+/// `webhook-id`, `webhook-timestamp` and `webhook-signature` headers carry
+/// an HMAC-SHA256 per secret over `<id>.<timestamp>.<body>`.
+///
+/// [`WebhookService::deliver`] posts to user-supplied URLs, so it follows no
+/// redirects and only connects to public addresses unless private networks
+/// are allowed.
 pub struct WebhookService {
-    http_service: HttpExternalService,
-    base_url: String,
+    endpoint_client: Client,
+    endpoint_timeout: Duration,
+    allow_private_networks: bool,
 }
 
 impl WebhookService {
-    pub fn new(base_url: String, config: HttpClientConfig) -> Self {
+    /// Webhook service for posting to absolute URLs with [`WebhookService::deliver`]
+    pub fn for_endpoints(config: HttpClientConfig, allow_private_networks: bool) -> Self {
         Self {
-            http_service: HttpExternalService::with_config(config),
-            base_url,
+            endpoint_client: Self::endpoint_client_builder(&config).build().expect("Failed to create HTTP client"),
+            endpoint_timeout: Duration::from_secs(config.timeout_seconds),
+            allow_private_networks,
         }
     }
 
-    /// Send webhook notification
-    pub async fn send_notification(&self, endpoint: &str, payload: Value) -> Result<(), ExternalServiceError> {
-        let url = format!("{}/{}", self.base_url.trim_end_matches('/'), endpoint.trim_start_matches('/'));
+    fn endpoint_client_builder(config: &HttpClientConfig) -> reqwest::ClientBuilder {
+        Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .redirect(reqwest::redirect::Policy::none())
+    }
 
-        info!("Sending webhook notification to: {}", url);
+    /// Client for one delivery to `url`, connecting only to public addresses
+    ///
+    /// The host is resolved and checked here, and the client is pinned to
+    /// the checked addresses so a changed DNS answer cannot lead elsewhere.
+    async fn delivery_client(&self, url: &str) -> Result<Client, ExternalServiceError> {
+        if self.allow_private_networks {
+            return Ok(self.endpoint_client.clone());
+        }
 
-        let headers = {
-            let mut headers = reqwest::header::HeaderMap::new();
-            headers.insert("Content-Type", "application/json".parse().unwrap());
-            headers.insert("User-Agent", "rust-api-microservice-webhook/1.0".parse().unwrap());
-            Some(headers)
+        let parsed = reqwest::Url::parse(url).map_err(|e| ExternalServiceError::ForbiddenAddress(e.to_string()))?;
+        match parsed.host() {
+            Some(url::Host::Domain(domain)) => {
+                let port = parsed.port_or_known_default().unwrap_or(443);
+                let addresses = resolve_public(domain, port).await?;
+                Ok(Client::builder()
+                    .timeout(self.endpoint_timeout)
+                    .redirect(reqwest::redirect::Policy::none())
+                    .resolve_to_addrs(domain, &addresses)
+                    .build()?)
+            }
+            Some(host) => {
+                Self::check_address_host(&host)?;
+                Ok(self.endpoint_client.clone())
+            }
+            None => Err(ExternalServiceError::ForbiddenAddress("URL has no host".to_string())),
+        }
+    }
+
+    /// Check that an endpoint URL only leads to public addresses, resolving its host
+    ///
+    /// Deliveries check again when connecting, as DNS answers can change.
+    pub async fn check_endpoint(&self, url: &str) -> Result<(), ExternalServiceError> {
+        self.delivery_client(url).await.map(|_| ())
+    }
+
+    /// Reject IP address hosts that are not public
+    fn check_address_host(host: &url::Host<&str>) -> Result<(), ExternalServiceError> {
+        let address = match host {
+            url::Host::Ipv4(address) => std::net::IpAddr::V4(*address),
+            url::Host::Ipv6(address) => std::net::IpAddr::V6(*address),
+            url::Host::Domain(_) => return Ok(()),
         };
 
-        self.http_service
-            .custom_request(reqwest::Method::POST, &url, headers, Some(payload))
-            .await?;
+        match is_public_address(address) {
+            true => Ok(()),
+            false => Err(ExternalServiceError::ForbiddenAddress(address.to_string())),
+        }
+    }
 
-        info!("Webhook notification sent successfully");
-        Ok(())
+    /// POST a payload to an absolute URL once, signed with each of `secrets`
+    /// under the message ID `id`, keeping at most `max_body_bytes` of the response
+    ///
+    /// Every HTTP response is returned, whatever its status. Retries and the
+    /// circuit breaker are skipped, since each endpoint fails independently
+    /// and callers schedule their own retries.
+    pub async fn deliver(
+        &self,
+        url: &str,
+        id: &str,
+        payload: &Value,
+        secrets: &[&str],
+        max_body_bytes: usize,
+    ) -> Result<WebhookResponse, ExternalServiceError> {
+        debug!("Delivering webhook {} to: {}", id, url);
+
+        let client = self.delivery_client(url).await?;
+        let body = serde_json::to_vec(payload).map_err(|e| ExternalServiceError::Serialization(e.to_string()))?;
+        let headers = Self::signature_headers(id, &body, secrets)?;
+
+        let mut response = client
+            .post(url)
+            .headers(headers)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "rust-api-microservice-webhook/1.0")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| if e.is_timeout() { ExternalServiceError::Timeout } else { ExternalServiceError::Http(e) })?;
+
+        let status = response.status().as_u16();
+        let mut body = Vec::new();
+        while body.len() < max_body_bytes {
+            match response.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                Ok(None) => break,
+                // The status is what counts, so a body cut short is kept as is
+                Err(_) => break,
+            }
+        }
+        body.truncate(max_body_bytes);
+
+        Ok(WebhookResponse {
+            status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+
+    /// Standard Webhooks headers for a message signed now with each of `secrets`
+    fn signature_headers(id: &str, body: &[u8], secrets: &[&str]) -> Result<reqwest::header::HeaderMap, ExternalServiceError> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = sign_webhook(secrets, id, timestamp, body)
+            .ok_or_else(|| ExternalServiceError::Serialization("Invalid webhook signing secret".to_string()))?;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        let invalid = |e: reqwest::header::InvalidHeaderValue| ExternalServiceError::Serialization(e.to_string());
+        headers.insert("webhook-id", id.parse().map_err(invalid)?);
+        headers.insert("webhook-timestamp", timestamp.into());
+        headers.insert("webhook-signature", signature.parse().map_err(invalid)?);
+        Ok(headers)
+    }
+}
+
+/// Resolve a host, failing if any of its addresses is not public
+async fn resolve_public(host: &str, port: u16) -> Result<Vec<std::net::SocketAddr>, ExternalServiceError> {
+    let addresses: Vec<std::net::SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| ExternalServiceError::ForbiddenAddress(format!("Cannot resolve {}: {}", host, e)))?
+        .collect();
+
+    match addresses.iter().find(|address| !is_public_address(address.ip())) {
+        Some(address) => Err(ExternalServiceError::ForbiddenAddress(format!("{} resolves to {}", host, address.ip()))),
+        None if addresses.is_empty() => Err(ExternalServiceError::ForbiddenAddress(format!("{} has no addresses", host))),
+        None => Ok(addresses),
+    }
+}
+
+/// Response of a webhook endpoint
+#[derive(Debug, Clone)]
+pub struct WebhookResponse {
+    pub status: u16,
+    /// Start of the response body
+    pub body: String,
+}
+
+impl WebhookResponse {
+    /// Whether the endpoint accepted the webhook
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
     }
 }
 
@@ -748,8 +889,21 @@ mod tests {
     #[test]
     fn test_webhook_service_creation() {
         let config = HttpClientConfig::default();
-        let webhook = WebhookService::new("https://api.example.com".to_string(), config);
-        assert_eq!(webhook.base_url, "https://api.example.com");
+        let webhook = WebhookService::for_endpoints(config, false);
+        assert!(!webhook.allow_private_networks);
+    }
+
+    #[test]
+    fn test_webhook_signature_headers() {
+        let secret = "whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw";
+        let headers = WebhookService::signature_headers("msg_1", b"{}", &[secret, secret]).unwrap();
+
+        assert_eq!(headers["webhook-id"], "msg_1");
+        let timestamp: i64 = headers["webhook-timestamp"].to_str().unwrap().parse().unwrap();
+        let expected = sign_webhook(&[secret, secret], "msg_1", timestamp, b"{}").unwrap();
+        assert_eq!(headers["webhook-signature"], expected.as_str());
+
+        assert!(WebhookService::signature_headers("msg_1", b"{}", &["plain-secret"]).is_err());
     }
 
     #[test]
@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::models::{
+    CreateGroupRequest, Group, GroupId, GroupMember, GroupMembership, GroupRole, Page, UpdateGroupRequest, UserGroup,
+    UserId,
+};
+use crate::repository::{GroupRepository, RepositoryError, UserRepository};
+use crate::services::ServiceError;
+
+/// Group and membership service trait
+///
+/// Authorization is left to callers, which can check the caller's
+/// memberships on `CurrentUser` or through [`GroupService::memberships`].
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait GroupService: Send + Sync {
+    /// Create a group owned by `owner`
+    async fn create_group(&self, request: CreateGroupRequest, owner: UserId) -> Result<Group, ServiceError>;
+    async fn get_group(&self, id: GroupId) -> Result<Group, ServiceError>;
+    async fn list_groups(&self, limit: i64, offset: i64) -> Result<Page<Group>, ServiceError>;
+    async fn update_group(&self, id: GroupId, request: UpdateGroupRequest) -> Result<Group, ServiceError>;
+    async fn delete_group(&self, id: GroupId) -> Result<(), ServiceError>;
+    /// Add a member or change their role; a group always keeps at least one owner
+    async fn set_member(&self, group_id: GroupId, user_id: UserId, role: GroupRole) -> Result<GroupMember, ServiceError>;
+    /// Remove a member; the last owner cannot be removed
+    async fn remove_member(&self, group_id: GroupId, user_id: UserId) -> Result<(), ServiceError>;
+    async fn list_members(&self, group_id: GroupId, limit: i64, offset: i64) -> Result<Page<GroupMember>, ServiceError>;
+    /// List a user's groups; with `visible_to`, only those that user is also a member of
+    async fn list_user_groups(&self, user_id: UserId, visible_to: Option<UserId>, limit: i64, offset: i64) -> Result<Page<UserGroup>, ServiceError>;
+    /// The first `limit` groups of each of the users, by name; users without groups are left out
+    ///
+    /// With `visible_to`, only groups that user is also a member of are included.
+    async fn groups_of_users(&self, user_ids: &[UserId], visible_to: Option<UserId>, limit: i64) -> Result<HashMap<UserId, Vec<UserGroup>>, ServiceError>;
+    /// All of a user's memberships, for authorization checks
+    async fn memberships(&self, user_id: UserId) -> Result<Vec<GroupMembership>, ServiceError>;
+}
+
+/// Group service implementation
+pub struct GroupServiceImpl {
+    repository: Arc<dyn GroupRepository>,
+    user_repository: Arc<dyn UserRepository>,
+}
+
+impl GroupServiceImpl {
+    pub fn new(repository: Arc<dyn GroupRepository>, user_repository: Arc<dyn UserRepository>) -> Self {
+        Self {
+            repository,
+            user_repository,
+        }
+    }
+
+    async fn require_group(&self, id: GroupId) -> Result<Group, ServiceError> {
+        self.repository.find_by_id(id).await?.ok_or(ServiceError::NotFound)
+    }
+}
+
+/// Group names are unique regardless of case, and groups keep an owner
+fn group_error(error: RepositoryError) -> ServiceError {
+    match error {
+        RepositoryError::Validation(msg) => ServiceError::Conflict(msg),
+        RepositoryError::NotFound => ServiceError::NotFound,
+        e => ServiceError::Repository(e),
+    }
+}
+
+#[async_trait]
+impl GroupService for GroupServiceImpl {
+    #[tracing::instrument(skip(self, request))]
+    async fn create_group(&self, request: CreateGroupRequest, owner: UserId) -> Result<Group, ServiceError> {
+        let request = request.normalize();
+        request.validate().map_err(|e| ServiceError::Validation(e.to_string()))?;
+
+        let group = self
+            .repository
+            .create(&request.name, request.description.as_deref(), owner)
+            .await
+            .map_err(group_error)?;
+
+        tracing::info!("Created group {} ({})", group.id, group.name);
+        Ok(group)
+    }
+
+    async fn get_group(&self, id: GroupId) -> Result<Group, ServiceError> {
+        self.require_group(id).await
+    }
+
+    async fn list_groups(&self, limit: i64, offset: i64) -> Result<Page<Group>, ServiceError> {
+        let (groups, total) = self.repository.list(limit, offset).await?;
+        Ok(Page::new(groups, total, limit, offset))
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn update_group(&self, id: GroupId, request: UpdateGroupRequest) -> Result<Group, ServiceError> {
+        let request = request.normalize();
+        request.validate().map_err(|e| ServiceError::Validation(e.to_string()))?;
+        if !request.has_updates() {
+            return Err(ServiceError::Validation("No updates provided".to_string()));
+        }
+
+        self.repository
+            .update(id, request.name.as_deref(), request.description.as_deref())
+            .await
+            .map_err(group_error)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_group(&self, id: GroupId) -> Result<(), ServiceError> {
+        self.repository.delete(id).await.map_err(group_error)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn set_member(&self, group_id: GroupId, user_id: UserId, role: GroupRole) -> Result<GroupMember, ServiceError> {
+        self.repository.set_member(group_id, user_id, role).await.map_err(group_error)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn remove_member(&self, group_id: GroupId, user_id: UserId) -> Result<(), ServiceError> {
+        if !self.repository.remove_member(group_id, user_id).await.map_err(group_error)? {
+            return Err(ServiceError::NotFound);
+        }
+
+        tracing::info!("Removed user {} from group {}", user_id, group_id);
+        Ok(())
+    }
+
+    async fn list_members(&self, group_id: GroupId, limit: i64, offset: i64) -> Result<Page<GroupMember>, ServiceError> {
+        self.require_group(group_id).await?;
+
+        let (members, total) = self.repository.list_members(group_id, limit, offset).await?;
+        Ok(Page::new(members, total, limit, offset))
+    }
+
+    async fn list_user_groups(&self, user_id: UserId, visible_to: Option<UserId>, limit: i64, offset: i64) -> Result<Page<UserGroup>, ServiceError> {
+        if self.user_repository.find_by_id(user_id).await?.is_none() {
+            return Err(ServiceError::NotFound);
+        }
+
+        let (groups, total) = self.repository.list_user_groups(user_id, visible_to, limit, offset).await?;
+        Ok(Page::new(groups, total, limit, offset))
+    }
+
+    async fn groups_of_users(&self, user_ids: &[UserId], visible_to: Option<UserId>, limit: i64) -> Result<HashMap<UserId, Vec<UserGroup>>, ServiceError> {
+        if user_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(self.repository.list_groups_of_users(user_ids, visible_to, limit).await?)
+    }
+
+    async fn memberships(&self, user_id: UserId) -> Result<Vec<GroupMembership>, ServiceError> {
+        Ok(self.repository.memberships(user_id).await?)
+    }
+}
@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::config::InvitationConfig;
+use crate::models::{
+    AcceptInvitationRequest, AuditContext, CreateInvitationRequest, DomainEvent, EmailToken, Invitation, InvitationId, InvitationStatus,
+    NewInvitation, Page, User, UserId,
+};
+use crate::repository::{InvitationRepository, RepositoryError, UserRepository};
+use crate::services::{EmailMessage, EventBus, Mailer, ServiceError};
+use crate::utils::hash_password;
+
+const INVALID_LINK: &str = "Invitation link is invalid or has expired";
+
+/// User invitation service trait
+///
+/// Authorization is left to callers; pre-assigned groups should only be
+/// ones the inviter owns.
+#[async_trait]
+pub trait InvitationService: Send + Sync {
+    /// Store a pending invitation and mail its link to the invitee
+    async fn invite(&self, request: CreateInvitationRequest, invited_by: UserId) -> Result<Invitation, ServiceError>;
+
+    async fn get_invitation(&self, id: InvitationId) -> Result<Invitation, ServiceError>;
+
+    /// List invitations newest first, optionally only those in one state
+    async fn list_invitations(&self, status: Option<InvitationStatus>, limit: i64, offset: i64) -> Result<Page<Invitation>, ServiceError>;
+
+    /// Withdraw a pending invitation
+    async fn revoke_invitation(&self, id: InvitationId) -> Result<(), ServiceError>;
+
+    /// Create the invitee's account using the mailed token and a new password
+    async fn accept_invitation(&self, request: AcceptInvitationRequest, context: &AuditContext) -> Result<User, ServiceError>;
+}
+
+/// Invitation service implementation
+pub struct InvitationServiceImpl {
+    user_repository: Arc<dyn UserRepository>,
+    repository: Arc<dyn InvitationRepository>,
+    mailer: Arc<dyn Mailer>,
+    config: InvitationConfig,
+    events: Option<Arc<EventBus>>,
+}
+
+impl InvitationServiceImpl {
+    pub fn new(
+        user_repository: Arc<dyn UserRepository>,
+        repository: Arc<dyn InvitationRepository>,
+        mailer: Arc<dyn Mailer>,
+        config: InvitationConfig,
+    ) -> Self {
+        Self {
+            user_repository,
+            repository,
+            mailer,
+            config,
+            events: None,
+        }
+    }
+
+    /// Publish users created from invitations to the given event bus
+    pub fn with_event_bus(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Hand a committed change to the event bus, if there is one
+    fn publish(&self, event: DomainEvent, context: &AuditContext) {
+        if let Some(events) = &self.events {
+            events.publish(event, context);
+        }
+    }
+}
+
+fn token_error(error: RepositoryError) -> ServiceError {
+    match error {
+        RepositoryError::NotFound => ServiceError::Validation(INVALID_LINK.to_string()),
+        RepositoryError::DuplicateEmail(_) => ServiceError::AlreadyExists,
+        e => ServiceError::Repository(e),
+    }
+}
+
+#[async_trait]
+impl InvitationService for InvitationServiceImpl {
+    #[tracing::instrument(skip(self, request))]
+    async fn invite(&self, request: CreateInvitationRequest, invited_by: UserId) -> Result<Invitation, ServiceError> {
+        let request = request.normalize();
+        request.validate().map_err(|e| ServiceError::Validation(e.to_string()))?;
+
+        if self.user_repository.email_exists(&request.email).await? {
+            tracing::warn!("Attempted to invite existing user: {}", request.email);
+            return Err(ServiceError::AlreadyExists);
+        }
+
+        let token = EmailToken::generate();
+        let invitation = self
+            .repository
+            .create(&NewInvitation {
+                email: request.email,
+                name: request.name,
+                groups: request.groups,
+                token_hash: token.hash,
+                invited_by: Some(invited_by),
+                expires_at: Utc::now() + Duration::hours(self.config.expiry_hours as i64),
+            })
+            .await
+            .map_err(|e| match e {
+                RepositoryError::Validation(msg) => ServiceError::Conflict(msg),
+                RepositoryError::NotFound => ServiceError::Validation("Unknown group in invitation".to_string()),
+                e => ServiceError::Repository(e),
+            })?;
+
+        let message = EmailMessage {
+            to: invitation.email.clone(),
+            subject: "You have been invited".to_string(),
+            body: format!(
+                "Hi{},\n\nYou have been invited to create an account. Choose a password by opening \
+                 the link below before {}:\n\n{}\n\nIf you were not expecting this, ignore this email.\n",
+                invitation.name.as_deref().map(|name| format!(" {}", name)).unwrap_or_default(),
+                invitation.expires_at.format("%Y-%m-%d %H:%M UTC"),
+                self.config.accept_url.replace("{token}", &token.token),
+            ),
+        };
+        if let Err(e) = self.mailer.send(&message).await {
+            // Without the email the invitation could never be accepted
+            tracing::error!("Failed to send invitation {}: {}", invitation.id, e);
+            if let Err(revoke_error) = self.repository.revoke(invitation.id).await {
+                tracing::error!("Failed to revoke undeliverable invitation {}: {}", invitation.id, revoke_error);
+            }
+            return Err(ServiceError::ExternalService(e.to_string()));
+        }
+
+        tracing::info!("User {} invited {} with invitation {}", invited_by, invitation.email, invitation.id);
+        Ok(invitation)
+    }
+
+    async fn get_invitation(&self, id: InvitationId) -> Result<Invitation, ServiceError> {
+        self.repository.find_by_id(id).await?.ok_or(ServiceError::NotFound)
+    }
+
+    async fn list_invitations(&self, status: Option<InvitationStatus>, limit: i64, offset: i64) -> Result<Page<Invitation>, ServiceError> {
+        let (invitations, total) = self.repository.list(status, limit, offset).await?;
+        Ok(Page::new(invitations, total, limit, offset))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn revoke_invitation(&self, id: InvitationId) -> Result<(), ServiceError> {
+        if !self.repository.revoke(id).await? {
+            // Tell a missing invitation apart from one that is no longer pending
+            self.get_invitation(id).await?;
+            return Err(ServiceError::Conflict("Only pending invitations can be revoked".to_string()));
+        }
+
+        tracing::info!("Revoked invitation {}", id);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, request, context))]
+    async fn accept_invitation(&self, request: AcceptInvitationRequest, context: &AuditContext) -> Result<User, ServiceError> {
+        let request = request.normalize();
+        request.validate().map_err(|e| ServiceError::Validation(e.to_string()))?;
+
+        let token_hash = EmailToken::hash(&request.token);
+        let invitation = self
+            .repository
+            .find_by_token(&token_hash)
+            .await?
+            .filter(|invitation| invitation.status == InvitationStatus::Pending)
+            .ok_or_else(|| ServiceError::Validation(INVALID_LINK.to_string()))?;
+
+        let name = request
+            .name
+            .or(invitation.name)
+            .ok_or_else(|| ServiceError::Validation("name: Name is required".to_string()))?;
+        let password_hash = hash_password(&request.password)
+            .map_err(|e| ServiceError::Validation(format!("password: {}", e)))?;
+
+        let (invitation, user) = self
+            .repository
+            .accept(&token_hash, &name, &password_hash)
+            .await
+            .map_err(token_error)?;
+
+        tracing::info!("Invitation {} accepted by user {}", invitation.id, user.id);
+        self.publish(DomainEvent::UserCreated { user: user.clone() }, context);
+        Ok(user)
+    }
+}
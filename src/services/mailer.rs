@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::config::MailerConfig;
+use crate::services::{ExternalService, ExternalServiceError};
+
+/// Mailer error types
+#[derive(Debug, thiserror::Error)]
+pub enum MailerError {
+    #[error("Mail delivery failed: {0}")]
+    Delivery(#[from] ExternalServiceError),
+}
+
+/// A plain-text email
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing email delivery
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailerError>;
+}
+
+/// Mailer that writes messages to the log instead of sending them
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        tracing::info!(to = %message.to, subject = %message.subject, "Email not sent (log transport):\n{}", message.body);
+        Ok(())
+    }
+}
+
+/// Mailer that posts messages as JSON to a mail relay or provider API
+pub struct HttpMailer {
+    external_service: Arc<dyn ExternalService>,
+    endpoint: String,
+    from: String,
+}
+
+impl HttpMailer {
+    pub fn new(external_service: Arc<dyn ExternalService>, endpoint: String, from: String) -> Self {
+        Self {
+            external_service,
+            endpoint,
+            from,
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for HttpMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        let payload = serde_json::json!({
+            "from": self.from,
+            "to": message.to,
+            "subject": message.subject,
+            "text": message.body,
+        });
+
+        self.external_service.post(&self.endpoint, payload).await?;
+        tracing::debug!("Sent email \"{}\" to {}", message.subject, message.to);
+        Ok(())
+    }
+}
+
+/// Create the mailer selected in the configuration
+pub fn mailer_from_config(config: &MailerConfig, external_service: Arc<dyn ExternalService>) -> Arc<dyn Mailer> {
+    match config {
+        MailerConfig::Log => Arc::new(LogMailer),
+        MailerConfig::Http { endpoint, from } => Arc::new(HttpMailer::new(external_service, endpoint.clone(), from.clone())),
+    }
+}
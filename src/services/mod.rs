@@ -2,8 +2,40 @@ pub mod user_service;
 pub mod auth_service;
 pub mod external_service;
 pub mod container;
+pub mod user_metadata;
+pub mod user_import_service;
+pub mod user_avatar;
+pub mod privacy_service;
+pub mod mailer;
+pub mod email_change_service;
+pub mod group_service;
+pub mod invitation_service;
+pub mod audit_service;
+pub mod audit_retention_service;
+pub mod outbox_service;
+pub mod webhook_subscription_service;
+pub mod dead_letter_service;
+pub mod event_bus;
+pub mod event_stream;
+pub mod websocket_sessions;
 
 pub use user_service::*;
 pub use auth_service::*;
 pub use external_service::*;
 pub use container::*;
+pub use user_metadata::*;
+pub use user_import_service::*;
+pub use user_avatar::*;
+pub use privacy_service::*;
+pub use mailer::*;
+pub use email_change_service::*;
+pub use group_service::*;
+pub use invitation_service::*;
+pub use audit_service::*;
+pub use audit_retention_service::*;
+pub use outbox_service::*;
+pub use webhook_subscription_service::*;
+pub use dead_letter_service::*;
+pub use event_bus::*;
+pub use event_stream::*;
+pub use websocket_sessions::*;
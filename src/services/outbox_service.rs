@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{ExternalServiceConfig, OutboxConfig};
+use crate::models::{outbox_retry_delay, NewDeadLetter, OutboxEvent};
+use crate::repository::{DeadLetterRepository, OutboxRepository};
+use crate::services::{ServiceError, WebhookSubscriptionService};
+
+/// Delivery of events from the transactional outbox
+#[async_trait]
+pub trait OutboxService: Send + Sync {
+    /// Claim one batch of due events and deliver them, returning how many were claimed
+    async fn dispatch_due(&self) -> Result<usize, ServiceError>;
+
+    /// Delete delivered events older than the configured retention
+    async fn purge_delivered(&self) -> Result<u64, ServiceError>;
+}
+
+/// Outbox dispatcher implementation
+///
+/// Events go to the webhook subscriptions of their type. Delivery is at
+/// least once: an event is retried until every subscriber accepts it, so
+/// subscribers should deduplicate on the envelope's `id`. Deliveries still
+/// failing after the last attempt are moved to the dead letters.
+pub struct OutboxServiceImpl {
+    repository: Arc<dyn OutboxRepository>,
+    dead_letters: Arc<dyn DeadLetterRepository>,
+    webhooks: Arc<dyn WebhookSubscriptionService>,
+    config: OutboxConfig,
+    max_attempts: i32,
+    retry_delay: Duration,
+}
+
+impl OutboxServiceImpl {
+    /// Attempts are limited to `max_retries + 1` of `external_service`, with
+    /// exponential backoff starting at its `retry_delay_ms`
+    pub fn new(
+        repository: Arc<dyn OutboxRepository>,
+        dead_letters: Arc<dyn DeadLetterRepository>,
+        webhooks: Arc<dyn WebhookSubscriptionService>,
+        config: OutboxConfig,
+        external_config: &ExternalServiceConfig,
+    ) -> Self {
+        Self {
+            repository,
+            dead_letters,
+            webhooks,
+            config,
+            max_attempts: external_config.max_retries.saturating_add(1).min(i32::MAX as u32) as i32,
+            retry_delay: Duration::from_millis(external_config.retry_delay_ms),
+        }
+    }
+
+    /// Deliver an event to its subscribers, failing if any of them did not accept it
+    async fn deliver(&self, event: &OutboxEvent) -> Result<(), DeliveryFailure> {
+        let deliveries = self.webhooks.dispatch(event).await.map_err(|e| DeliveryFailure {
+            error: e.to_string(),
+            dead_letters: vec![NewDeadLetter::for_event(event, &e.to_string())],
+        })?;
+
+        let failed: Vec<_> = deliveries.iter().filter(|delivery| !delivery.success).collect();
+        if failed.is_empty() {
+            return Ok(());
+        }
+
+        Err(DeliveryFailure {
+            error: format!("Delivery failed for {} of {} webhook subscriptions", failed.len(), deliveries.len()),
+            dead_letters: failed
+                .into_iter()
+                .map(|delivery| NewDeadLetter::for_delivery(event, delivery))
+                .collect(),
+        })
+    }
+
+    /// Deliver one event and record the outcome
+    async fn process(&self, event: OutboxEvent) -> Result<(), ServiceError> {
+        match self.deliver(&event).await {
+            Ok(()) => {
+                if self.repository.mark_delivered(event.id, event.next_attempt_at).await? {
+                    tracing::debug!("Delivered outbox event {} ({})", event.id, event.event_type);
+                } else {
+                    lease_lost(&event);
+                }
+            }
+            Err(failure) if event.attempts >= self.max_attempts => {
+                // Dead letters go first, so a failure here leaves the event to be given up on again
+                for dead_letter in &failure.dead_letters {
+                    self.dead_letters.insert(dead_letter).await?;
+                }
+                tracing::warn!(
+                    "Giving up on outbox event {} ({}) after {} attempts, moved {} deliveries to dead letters: {}",
+                    event.id,
+                    event.event_type,
+                    event.attempts,
+                    failure.dead_letters.len(),
+                    failure.error
+                );
+                if !self.repository.record_failure(event.id, event.next_attempt_at, &failure.error, None).await? {
+                    lease_lost(&event);
+                }
+            }
+            Err(DeliveryFailure { error: e, .. }) => {
+                let max_delay = Duration::from_secs(self.config.max_backoff_seconds);
+                let delay = outbox_retry_delay(event.attempts, self.retry_delay, max_delay);
+                let retry_at = Utc::now() + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX);
+                tracing::info!(
+                    "Outbox event {} failed on attempt {}, retrying in {:?}: {}",
+                    event.id,
+                    event.attempts,
+                    delay,
+                    e
+                );
+                if !self.repository.record_failure(event.id, event.next_attempt_at, &e, Some(retry_at)).await? {
+                    lease_lost(&event);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Note an outcome dropped because the event's lease ran out and it was claimed again
+fn lease_lost(event: &OutboxEvent) {
+    tracing::warn!(
+        "Lease on outbox event {} ({}) ran out before attempt {} finished, leaving it to the next claim",
+        event.id,
+        event.event_type,
+        event.attempts
+    );
+}
+
+/// Why an event was not delivered, with the dead letters to record if it is given up on
+struct DeliveryFailure {
+    error: String,
+    dead_letters: Vec<NewDeadLetter>,
+}
+
+#[async_trait]
+impl OutboxService for OutboxServiceImpl {
+    async fn dispatch_due(&self) -> Result<usize, ServiceError> {
+        let lease = Duration::from_secs(self.config.lease_seconds);
+        let events = self.repository.claim_due(self.config.batch_size, lease).await?;
+        let claimed = events.len();
+        if claimed == 0 {
+            return Ok(0);
+        }
+
+        tracing::debug!("Dispatching {} outbox events", claimed);
+        futures::stream::iter(events)
+            .map(|event| async move {
+                let id = event.id;
+                // A failure to record the outcome leaves the event claimed until its lease runs out
+                if let Err(e) = self.process(event).await {
+                    tracing::error!("Failed to record delivery of outbox event {}: {}", id, e);
+                }
+            })
+            .buffer_unordered(self.config.concurrency)
+            .collect::<()>()
+            .await;
+
+        Ok(claimed)
+    }
+
+    async fn purge_delivered(&self) -> Result<u64, ServiceError> {
+        let retention = chrono::Duration::hours(self.config.delivered_retention_hours.min(i64::MAX as u64) as i64);
+        let purged = self.repository.purge_delivered(Utc::now() - retention).await?;
+        if purged > 0 {
+            tracing::info!("Purged {} delivered outbox events", purged);
+        }
+
+        Ok(purged)
+    }
+}
@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::config::AuditFailureMode;
+use crate::models::{
+    AuditContext, DataSubject, DomainEvent, ErasureRequest, NewAuditLog, User, UserDataArchive, UserId,
+    ERASED_EMAIL_DOMAIN, ERASED_USER_NAME,
+};
+use crate::repository::{AuditLogRepository, PersonalDataRepository, UserRepository};
+use crate::services::{EventBus, ServiceError};
+use crate::storage::BlobStore;
+
+/// Data subject access and erasure service trait
+#[async_trait]
+pub trait PrivacyService: Send + Sync {
+    /// Assemble everything held about a user into an archive
+    async fn export_user_data(&self, id: UserId, actor: Option<UserId>) -> Result<UserDataArchive, ServiceError>;
+
+    /// Anonymize a user's personal data in place, keeping the row and its audit history
+    async fn erase_user(&self, id: UserId, actor: Option<UserId>, reason: Option<String>) -> Result<ErasureRequest, ServiceError>;
+}
+
+/// Privacy service implementation
+pub struct PrivacyServiceImpl {
+    user_repository: Arc<dyn UserRepository>,
+    personal_data_repository: Arc<dyn PersonalDataRepository>,
+    audit_repository: Arc<dyn AuditLogRepository>,
+    avatar_store: Option<Arc<dyn BlobStore>>,
+    audit_failure_mode: AuditFailureMode,
+    events: Option<Arc<EventBus>>,
+}
+
+impl PrivacyServiceImpl {
+    pub fn new(
+        user_repository: Arc<dyn UserRepository>,
+        personal_data_repository: Arc<dyn PersonalDataRepository>,
+        audit_repository: Arc<dyn AuditLogRepository>,
+    ) -> Self {
+        Self {
+            user_repository,
+            personal_data_repository,
+            audit_repository,
+            avatar_store: None,
+            audit_failure_mode: AuditFailureMode::default(),
+            events: None,
+        }
+    }
+
+    /// Delete stored avatar images of erased users
+    pub fn with_avatar_store(mut self, store: Arc<dyn BlobStore>) -> Self {
+        self.avatar_store = Some(store);
+        self
+    }
+
+    /// Choose whether an erasure is kept when its audit log entry cannot be written
+    pub fn with_audit_failure_mode(mut self, mode: AuditFailureMode) -> Self {
+        self.audit_failure_mode = mode;
+        self
+    }
+
+    /// Publish erasures to the given event bus
+    pub fn with_event_bus(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Hand a committed change to the event bus, if there is one
+    fn publish(&self, event: DomainEvent, context: &AuditContext) {
+        if let Some(events) = &self.events {
+            events.publish(event, context);
+        }
+    }
+}
+
+/// The user row as the erasure left it
+///
+/// Events about an erased user carry this rather than the original, so that
+/// subscribers never receive the personal data that was just erased.
+fn erased_user(user: &User, request: &ErasureRequest) -> User {
+    User {
+        name: ERASED_USER_NAME.to_string(),
+        email: DataSubject::from(user).erased_email(),
+        is_active: false,
+        updated_at: request.completed_at,
+        version: user.version + 1,
+        metadata: serde_json::json!({}),
+        avatar_key: None,
+        avatar_url: None,
+        ..user.clone()
+    }
+}
+
+#[async_trait]
+impl PrivacyService for PrivacyServiceImpl {
+    #[tracing::instrument(skip(self))]
+    async fn export_user_data(&self, id: UserId, actor: Option<UserId>) -> Result<UserDataArchive, ServiceError> {
+        let user = self.user_repository.find_by_id(id).await?.ok_or(ServiceError::NotFound)?;
+
+        // Handing out personal data must always leave a trace
+        let entry = NewAuditLog::new("user.data_export", "user")
+            .with_actor(actor)
+            .with_resource_id(id.to_string());
+        if let Err(e) = self.audit_repository.record(&entry).await {
+            tracing::error!("Failed to record data export in audit log: {}", e);
+            return Err(ServiceError::Repository(e));
+        }
+
+        let sections = self.personal_data_repository.export(&DataSubject::from(&user)).await?;
+
+        tracing::info!("Exported personal data of user {}", id);
+        Ok(UserDataArchive {
+            user_id: id,
+            generated_at: chrono::Utc::now(),
+            sections,
+        })
+    }
+
+    #[tracing::instrument(skip(self, reason))]
+    async fn erase_user(&self, id: UserId, actor: Option<UserId>, reason: Option<String>) -> Result<ErasureRequest, ServiceError> {
+        let user = self.user_repository.find_by_id(id).await?.ok_or(ServiceError::NotFound)?;
+        if user.email.ends_with(&format!("@{}", ERASED_EMAIL_DOMAIN)) {
+            return Err(ServiceError::Conflict(format!("User {} has already been erased", id)));
+        }
+
+        let entry = NewAuditLog::new("user.erase", "user")
+            .with_actor(actor)
+            .with_resource_id(id.to_string());
+        let request = self
+            .personal_data_repository
+            .erase(&DataSubject::from(&user), actor, reason.as_deref(), &entry, self.audit_failure_mode)
+            .await?;
+
+        if let (Some(store), Some(key)) = (&self.avatar_store, &user.avatar_key) {
+            if let Err(e) = store.delete(key).await {
+                tracing::warn!("Failed to delete avatar blob {} of erased user {}: {}", key, id, e);
+            }
+        }
+
+        tracing::info!("Erased personal data of user {} (request {})", id, request.id);
+        self.publish(DomainEvent::UserDeleted { user: erased_user(&user, &request) }, &AuditContext::new(actor, None));
+        Ok(request)
+    }
+}
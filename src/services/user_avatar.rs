@@ -0,0 +1,123 @@
+use imagesize::ImageType;
+
+use crate::config::AvatarConfig;
+
+/// An uploaded avatar that passed the policy checks
+#[derive(Debug)]
+pub struct AvatarImage {
+    pub content_type: &'static str,
+    pub extension: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Size, format and dimension rules for avatar uploads
+#[derive(Debug, Clone)]
+pub struct AvatarPolicy {
+    max_bytes: usize,
+    min_dimension: u32,
+    max_dimension: u32,
+}
+
+impl Default for AvatarPolicy {
+    fn default() -> Self {
+        Self::from_config(&AvatarConfig::default())
+    }
+}
+
+impl AvatarPolicy {
+    pub fn from_config(config: &AvatarConfig) -> Self {
+        Self {
+            max_bytes: config.max_bytes,
+            min_dimension: config.min_dimension,
+            max_dimension: config.max_dimension,
+        }
+    }
+
+    /// Largest accepted upload, in bytes
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Check an upload, identifying its format from its content rather than
+    /// from the client-supplied content type
+    pub fn inspect(&self, data: &[u8]) -> Result<AvatarImage, String> {
+        if data.is_empty() {
+            return Err("Avatar image is empty".to_string());
+        }
+        if data.len() > self.max_bytes {
+            return Err(format!("Avatar image must not exceed {} bytes", self.max_bytes));
+        }
+
+        let (content_type, extension) = match imagesize::image_type(data) {
+            Ok(ImageType::Png) => ("image/png", "png"),
+            Ok(ImageType::Jpeg) => ("image/jpeg", "jpg"),
+            Ok(ImageType::Gif) => ("image/gif", "gif"),
+            Ok(ImageType::Webp) => ("image/webp", "webp"),
+            _ => return Err("Avatar must be a PNG, JPEG, GIF or WebP image".to_string()),
+        };
+
+        let size = imagesize::blob_size(data).map_err(|e| format!("Avatar image could not be read: {}", e))?;
+        let (width, height) = (size.width as u32, size.height as u32);
+        let allowed = self.min_dimension..=self.max_dimension;
+        if !allowed.contains(&width) || !allowed.contains(&height) {
+            return Err(format!(
+                "Avatar must be between {min}x{min} and {max}x{max} pixels, got {}x{}",
+                width,
+                height,
+                min = self.min_dimension,
+                max = self.max_dimension,
+            ));
+        }
+
+        Ok(AvatarImage {
+            content_type,
+            extension,
+            width,
+            height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PNG signature and IHDR chunk header for an image of the given size
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut data = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+        data.extend_from_slice(b"IHDR");
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&[8, 6, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_accepts_png_within_limits() {
+        let image = AvatarPolicy::default().inspect(&png_header(128, 64)).unwrap();
+        assert_eq!(image.content_type, "image/png");
+        assert_eq!((image.width, image.height), (128, 64));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_content() {
+        let policy = AvatarPolicy::default();
+        assert!(policy.inspect(b"").is_err());
+        assert!(policy.inspect(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>").is_err());
+    }
+
+    #[test]
+    fn test_enforces_size_and_dimensions() {
+        let policy = AvatarPolicy::from_config(&AvatarConfig {
+            max_bytes: 64,
+            min_dimension: 32,
+            max_dimension: 512,
+            ..AvatarConfig::default()
+        });
+
+        assert!(policy.inspect(&png_header(16, 64)).is_err());
+        assert!(policy.inspect(&png_header(64, 1024)).is_err());
+        assert!(policy.inspect(&[png_header(64, 64), vec![0; 64]].concat()).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use crate::config::UserImportConfig;
+use crate::models::{
+    parse_import, AuditContext, CreateUserRequest, DomainEvent, ImportJob, ImportMode, ImportResult, ImportRow, ImportRowResult,
+    ImportRowStatus, ImportSummary, NewOutboxEvent, NewUser, UserId, UserImportOptions,
+};
+use crate::repository::{
+    ImportJobRepository, RepositoryError, UpsertOutcome, UserRepository, UserRepositoryTransaction,
+};
+use crate::services::{EventBus, ServiceError, UserMetadataValidator};
+
+/// What happened to an accepted import
+#[derive(Debug)]
+pub enum UserImportOutcome {
+    /// The import finished while the request waited
+    Completed(ImportResult),
+    /// The import continues as a background job
+    Queued(ImportJob),
+}
+
+/// Bulk user import service trait
+#[async_trait]
+pub trait UserImportService: Send + Sync {
+    /// Import users from an uploaded CSV or NDJSON file
+    ///
+    /// Every import runs as a job. Files up to the configured row threshold
+    /// are waited for, up to the inline timeout; the others, and those that
+    /// take longer, continue in the background.
+    async fn import_users(&self, data: &[u8], options: UserImportOptions, actor: Option<UserId>) -> Result<UserImportOutcome, ServiceError>;
+
+    /// Get the status of an import job
+    async fn get_import_job(&self, id: Uuid) -> Result<Option<ImportJob>, ServiceError>;
+
+    /// Get the per-row report of a finished import job
+    async fn get_import_report(&self, id: Uuid) -> Result<Option<Vec<ImportRowResult>>, ServiceError>;
+
+    /// Mark jobs left unfinished by a stopped instance as failed, returning how many
+    async fn fail_stale_jobs(&self) -> Result<u64, ServiceError>;
+
+    /// Stop accepting imports and interrupt the running ones after their current batch
+    ///
+    /// Jobs still running after `timeout` are aborted. Interrupted jobs are
+    /// marked failed; their IDs are returned.
+    async fn shutdown(&self, timeout: Duration) -> Vec<Uuid>;
+}
+
+/// Import jobs running on this instance
+#[derive(Default)]
+struct RunningJobs {
+    tasks: Mutex<JoinSet<()>>,
+    /// Jobs whose task has not finished yet
+    unfinished: Mutex<HashSet<Uuid>>,
+    stopping: AtomicBool,
+}
+
+/// Bulk user import service implementation
+#[derive(Clone)]
+pub struct UserImportServiceImpl {
+    repository: Arc<dyn UserRepository>,
+    job_repository: Arc<dyn ImportJobRepository>,
+    metadata_validator: Arc<UserMetadataValidator>,
+    config: UserImportConfig,
+    jobs: Arc<RunningJobs>,
+    events: Option<Arc<EventBus>>,
+}
+
+/// Write one row and queue the outbox event announcing it
+async fn write_row(
+    tx: &mut dyn UserRepositoryTransaction,
+    user: &NewUser,
+    mode: ImportMode,
+) -> Result<UpsertOutcome, RepositoryError> {
+    let outcome = tx.upsert(user, mode == ImportMode::Update).await?;
+    let event = match &outcome {
+        UpsertOutcome::Created(user) => Some(NewOutboxEvent::user_created(user)),
+        UpsertOutcome::Updated { before, after } => Some(NewOutboxEvent::user_updated(before, after)),
+        UpsertOutcome::Skipped => None,
+    };
+    if let Some(event) = event {
+        tx.enqueue_event(&event).await?;
+    }
+
+    Ok(outcome)
+}
+
+impl UserImportServiceImpl {
+    pub fn new(
+        repository: Arc<dyn UserRepository>,
+        job_repository: Arc<dyn ImportJobRepository>,
+        metadata_validator: Arc<UserMetadataValidator>,
+        config: UserImportConfig,
+    ) -> Self {
+        Self {
+            repository,
+            job_repository,
+            metadata_validator,
+            config,
+            jobs: Arc::default(),
+            events: None,
+        }
+    }
+
+    /// Publish imported users to the given event bus
+    pub fn with_event_bus(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Hand a committed change to the event bus, if there is one
+    fn publish(&self, event: DomainEvent, context: &AuditContext) {
+        if let Some(events) = &self.events {
+            events.publish(event, context);
+        }
+    }
+
+    /// Run a job on its own task, tracked for shutdown
+    fn spawn(&self, job_id: Uuid, rows: Vec<ImportRow>, options: UserImportOptions, context: AuditContext) -> oneshot::Receiver<Result<ImportResult, ServiceError>> {
+        let (done, result) = oneshot::channel();
+        let service = self.clone();
+        self.jobs.unfinished.lock().unwrap().insert(job_id);
+
+        let mut tasks = self.jobs.tasks.lock().unwrap();
+        // Drop the results of finished tasks
+        while tasks.try_join_next().is_some() {}
+        tasks.spawn(async move {
+            // Failures are recorded on the job by `run`; nobody may be waiting
+            let _ = done.send(service.run(job_id, rows, options, &context).await);
+            service.jobs.unfinished.lock().unwrap().remove(&job_id);
+        });
+
+        result
+    }
+
+    /// Run a job to completion, recording the outcome on the job
+    async fn run(&self, job_id: Uuid, rows: Vec<ImportRow>, options: UserImportOptions, context: &AuditContext) -> Result<ImportResult, ServiceError> {
+        self.job_repository.mark_running(job_id).await?;
+
+        let report = match self.process(job_id, rows, options, context).await {
+            Ok(report) => report,
+            Err(e) => {
+                tracing::error!("User import job {} failed: {}", job_id, e);
+                if let Err(fail_error) = self.job_repository.fail(job_id, &e.to_string()).await {
+                    tracing::error!("Failed to mark import job {} as failed: {}", job_id, fail_error);
+                }
+                return Err(e);
+            }
+        };
+
+        let summary = ImportSummary::from_results(&report);
+        let job = self.job_repository.complete(job_id, &summary, &report).await?;
+
+        tracing::info!(
+            "User import job {} completed: {} created, {} updated, {} skipped, {} invalid, {} failed",
+            job_id, summary.created, summary.updated, summary.skipped, summary.invalid, summary.failed
+        );
+        Ok(ImportResult { job, report })
+    }
+
+    /// Validate every row, then classify (dry run) or write the valid ones in batches
+    async fn process(&self, job_id: Uuid, rows: Vec<ImportRow>, options: UserImportOptions, context: &AuditContext) -> Result<Vec<ImportRowResult>, ServiceError> {
+        let mut results = Vec::with_capacity(rows.len());
+        let mut pending: Vec<(usize, NewUser)> = Vec::new();
+        let mut first_seen: HashMap<String, u64> = HashMap::new();
+        let mut progress = ImportSummary::default();
+
+        for row in rows {
+            let result = match self.validate_row(row.request) {
+                Ok(user) => match first_seen.get(&user.email) {
+                    Some(first_line) => ImportRowResult::invalid(
+                        row.line,
+                        Some(user.email.clone()),
+                        vec![format!("email: Duplicate of line {}", first_line)],
+                    ),
+                    None => {
+                        first_seen.insert(user.email.clone(), row.line);
+                        let result = ImportRowResult {
+                            line: row.line,
+                            email: Some(user.email.clone()),
+                            status: ImportRowStatus::Created,
+                            user_id: None,
+                            errors: Vec::new(),
+                        };
+                        pending.push((results.len(), user));
+                        results.push(result);
+                        continue;
+                    }
+                },
+                Err((email, errors)) => ImportRowResult::invalid(row.line, email, errors),
+            };
+            progress.record(result.status);
+            results.push(result);
+        }
+
+        if options.dry_run {
+            let emails: Vec<String> = pending.iter().map(|(_, user)| user.email.clone()).collect();
+            let mut existing = HashSet::new();
+            for chunk in emails.chunks(self.config.batch_size) {
+                existing.extend(self.repository.existing_emails(chunk).await?);
+            }
+
+            for (index, user) in &pending {
+                results[*index].status = match (existing.contains(&user.email), options.mode) {
+                    (false, _) => ImportRowStatus::Created,
+                    (true, ImportMode::Skip) => ImportRowStatus::Skipped,
+                    (true, ImportMode::Update) => ImportRowStatus::Updated,
+                };
+            }
+            return Ok(results);
+        }
+
+        for batch in pending.chunks(self.config.batch_size) {
+            if self.jobs.stopping.load(Ordering::Relaxed) {
+                return Err(ServiceError::Conflict(format!(
+                    "Import interrupted by shutdown after {} of {} rows",
+                    progress.total,
+                    results.len()
+                )));
+            }
+
+            match self.write_batch(batch, options.mode).await {
+                Ok(outcomes) => {
+                    for ((index, _), outcome) in batch.iter().zip(outcomes) {
+                        let result = &mut results[*index];
+                        match outcome {
+                            UpsertOutcome::Created(user) => {
+                                result.status = ImportRowStatus::Created;
+                                result.user_id = Some(user.id);
+                                self.publish(DomainEvent::UserCreated { user }, context);
+                            }
+                            UpsertOutcome::Updated { before, after } => {
+                                result.status = ImportRowStatus::Updated;
+                                result.user_id = Some(after.id);
+                                self.publish(DomainEvent::user_updated(&before, &after), context);
+                            }
+                            UpsertOutcome::Skipped => result.status = ImportRowStatus::Skipped,
+                        }
+                        progress.record(result.status);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Import batch of {} rows rolled back: {}", batch.len(), e);
+                    for (index, _) in batch {
+                        let result = &mut results[*index];
+                        result.status = ImportRowStatus::Failed;
+                        result.errors = vec![format!("Batch rolled back: {}", e)];
+                        progress.record(result.status);
+                    }
+                }
+            }
+
+            if let Err(e) = self.job_repository.record_progress(job_id, progress.total as i32, &progress).await {
+                tracing::warn!("Failed to record progress of import job {}: {}", job_id, e);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Write one batch of rows and their outbox events in a single transaction
+    async fn write_batch(&self, batch: &[(usize, NewUser)], mode: ImportMode) -> Result<Vec<UpsertOutcome>, RepositoryError> {
+        let mut tx = self.repository.begin_transaction().await?;
+        let mut outcomes = Vec::with_capacity(batch.len());
+
+        for (_, user) in batch {
+            match write_row(tx.as_mut(), user, mode).await {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(e) => {
+                    if let Err(rollback_error) = tx.rollback().await {
+                        tracing::error!("Failed to rollback import batch: {}", rollback_error);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(outcomes)
+    }
+
+    /// Apply the `CreateUserRequest` rules and the metadata schema to a row
+    fn validate_row(&self, request: Result<CreateUserRequest, String>) -> Result<NewUser, (Option<String>, Vec<String>)> {
+        let request = request.map_err(|e| (None, vec![e]))?;
+        let email = Some(request.email.trim().to_lowercase()).filter(|email| !email.is_empty());
+
+        let request = request.validate_and_normalize().map_err(|field_errors| {
+            let mut errors: Vec<String> = field_errors
+                .into_iter()
+                .map(|(field, messages)| format!("{}: {}", field, messages.join(", ")))
+                .collect();
+            errors.sort();
+            (email.clone(), errors)
+        })?;
+
+        if let Some(metadata) = &request.metadata {
+            self.metadata_validator
+                .validate(metadata)
+                .map_err(|e| (email.clone(), vec![format!("metadata: {}", e)]))?;
+        }
+
+        Ok(request.into())
+    }
+}
+
+#[async_trait]
+impl UserImportService for UserImportServiceImpl {
+    async fn import_users(&self, data: &[u8], options: UserImportOptions, actor: Option<UserId>) -> Result<UserImportOutcome, ServiceError> {
+        if self.jobs.stopping.load(Ordering::Relaxed) {
+            return Err(ServiceError::Conflict("Imports are not accepted while shutting down".to_string()));
+        }
+
+        let rows = parse_import(options.format, data).map_err(ServiceError::Validation)?;
+        if rows.is_empty() {
+            return Err(ServiceError::Validation("Import file contains no rows".to_string()));
+        }
+        if rows.len() > self.config.max_rows {
+            return Err(ServiceError::Validation(format!(
+                "Import file has {} rows; at most {} are allowed",
+                rows.len(),
+                self.config.max_rows
+            )));
+        }
+
+        let job = self.job_repository.create(&options, rows.len() as i32, actor).await?;
+        tracing::info!(
+            "Created user import job {} with {} rows (mode: {:?}, dry run: {})",
+            job.id, rows.len(), options.mode, options.dry_run
+        );
+
+        let wait = rows.len() <= self.config.background_threshold_rows;
+        let result = self.spawn(job.id, rows, options, AuditContext::new(actor, None));
+        if !wait {
+            return Ok(UserImportOutcome::Queued(job));
+        }
+
+        let inline_timeout = Duration::from_secs(self.config.inline_timeout_seconds);
+        match tokio::time::timeout(inline_timeout, result).await {
+            Ok(Ok(result)) => result.map(UserImportOutcome::Completed),
+            Ok(Err(_)) => Err(ServiceError::Conflict("Import interrupted by shutdown".to_string())),
+            Err(_) => {
+                tracing::info!("User import job {} continues in the background after {:?}", job.id, inline_timeout);
+                let job = self.job_repository.find_by_id(job.id).await?.unwrap_or(job);
+                Ok(UserImportOutcome::Queued(job))
+            }
+        }
+    }
+
+    async fn get_import_job(&self, id: Uuid) -> Result<Option<ImportJob>, ServiceError> {
+        Ok(self.job_repository.find_by_id(id).await?)
+    }
+
+    async fn get_import_report(&self, id: Uuid) -> Result<Option<Vec<ImportRowResult>>, ServiceError> {
+        Ok(self.job_repository.find_report(id).await?)
+    }
+
+    async fn fail_stale_jobs(&self) -> Result<u64, ServiceError> {
+        let stale_after = Duration::from_secs(self.config.stale_job_minutes * 60);
+        let failed = self
+            .job_repository
+            .fail_stale(stale_after, "Import stopped making progress and was abandoned")
+            .await?;
+
+        if failed > 0 {
+            tracing::warn!("Marked {} stale user import jobs as failed", failed);
+        }
+        Ok(failed)
+    }
+
+    async fn shutdown(&self, timeout: Duration) -> Vec<Uuid> {
+        self.jobs.stopping.store(true, Ordering::Relaxed);
+
+        let mut tasks = std::mem::take(&mut *self.jobs.tasks.lock().unwrap());
+        let drained = tokio::time::timeout(timeout, async { while tasks.join_next().await.is_some() {} }).await;
+        if drained.is_err() {
+            tasks.abort_all();
+            while tasks.join_next().await.is_some() {}
+        }
+
+        // Jobs that stopped between batches recorded their failure themselves
+        let interrupted: Vec<Uuid> = self.jobs.unfinished.lock().unwrap().drain().collect();
+        for job_id in &interrupted {
+            if let Err(e) = self.job_repository.fail(*job_id, "Import interrupted by shutdown").await {
+                tracing::error!("Failed to mark import job {} as failed: {}", job_id, e);
+            }
+        }
+        interrupted
+    }
+}
@@ -0,0 +1,112 @@
+use jsonschema::JSONSchema;
+
+use crate::config::UserMetadataConfig;
+
+/// Errors raised while loading the user metadata schema
+#[derive(Debug, thiserror::Error)]
+pub enum MetadataSchemaError {
+    #[error("Failed to read metadata schema {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Metadata schema is not valid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Metadata schema is not a valid JSON Schema: {0}")]
+    Schema(String),
+}
+
+/// Validates user metadata against the optional JSON Schema from config
+#[derive(Default)]
+pub struct UserMetadataValidator {
+    schema: Option<JSONSchema>,
+}
+
+impl UserMetadataValidator {
+    /// Create a validator that accepts any metadata object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compile a validator from a JSON Schema document
+    pub fn from_schema(schema: &serde_json::Value) -> Result<Self, MetadataSchemaError> {
+        let compiled = JSONSchema::compile(schema)
+            .map_err(|e| MetadataSchemaError::Schema(e.to_string()))?;
+
+        Ok(Self { schema: Some(compiled) })
+    }
+
+    /// Load the validator described by the metadata configuration
+    pub fn from_config(config: &UserMetadataConfig) -> Result<Self, MetadataSchemaError> {
+        let Some(path) = &config.schema_path else {
+            return Ok(Self::new());
+        };
+
+        let contents = std::fs::read_to_string(path).map_err(|source| MetadataSchemaError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let schema: serde_json::Value = serde_json::from_str(&contents)?;
+
+        tracing::info!("Loaded user metadata schema from {}", path);
+        Self::from_schema(&schema)
+    }
+
+    /// Check metadata against the schema, returning a readable list of violations
+    pub fn validate(&self, metadata: &serde_json::Value) -> Result<(), String> {
+        let Some(schema) = &self.schema else {
+            return Ok(());
+        };
+
+        schema.validate(metadata).map_err(|errors| {
+            errors
+                .map(|error| {
+                    let path = error.instance_path.to_string();
+                    if path.is_empty() {
+                        error.to_string()
+                    } else {
+                        format!("{}: {}", path, error)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_without_schema_accepts_anything() {
+        let validator = UserMetadataValidator::new();
+        assert!(validator.validate(&json!({ "anything": [1, 2, 3] })).is_ok());
+    }
+
+    #[test]
+    fn test_schema_violations_are_reported() {
+        let validator = UserMetadataValidator::from_schema(&json!({
+            "type": "object",
+            "properties": { "plan": { "enum": ["free", "pro"] } },
+            "additionalProperties": false
+        }))
+        .unwrap();
+
+        assert!(validator.validate(&json!({ "plan": "pro" })).is_ok());
+
+        let error = validator.validate(&json!({ "plan": "gold" })).unwrap_err();
+        assert!(error.contains("/plan"));
+        assert!(validator.validate(&json!({ "other": true })).is_err());
+    }
+
+    #[test]
+    fn test_invalid_schema_is_rejected() {
+        let result = UserMetadataValidator::from_schema(&json!({ "type": "not-a-type" }));
+        assert!(matches!(result, Err(MetadataSchemaError::Schema(_))));
+    }
+}
@@ -1,8 +1,15 @@
 use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
 use std::sync::Arc;
 
-use crate::models::{User, CreateUserRequest, UpdateUserRequest, NewUser, UserId};
-use crate::repository::{UserRepository, RepositoryError};
+use crate::config::AuditFailureMode;
+use crate::models::{
+    User, CreateUserRequest, UpdateUserRequest, NewUser, UserId, UserPatch, UserPatchError, UserSearchResult,
+    UserExportRequest, UserStatusRequest, NewAuditLog, NewOutboxEvent, AuditContext, DomainEvent,
+};
+use crate::repository::{UserRepository, UserRepositoryTransaction, RepositoryError, AuditLogRepository};
+use crate::services::{AvatarPolicy, EmailChangeService, EventBus, UserMetadataValidator};
+use crate::storage::BlobStore;
 
 /// Service error types
 #[derive(Debug, thiserror::Error)]
@@ -19,117 +26,241 @@ pub enum ServiceError {
     #[error("User already exists")]
     AlreadyExists,
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
     #[error("External service error: {0}")]
     ExternalService(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
 }
 
+/// Stream of users produced by an export
+pub type UserExportStream = BoxStream<'static, Result<User, ServiceError>>;
+
 /// User service trait
+///
+/// Every change is recorded in the audit log under the given `AuditContext`.
 #[async_trait]
 pub trait UserService: Send + Sync {
-    async fn create_user(&self, request: CreateUserRequest) -> Result<User, ServiceError>;
+    async fn create_user(&self, request: CreateUserRequest, context: &AuditContext) -> Result<User, ServiceError>;
     async fn get_user(&self, id: UserId) -> Result<User, ServiceError>;
     async fn get_user_by_email(&self, email: &str) -> Result<User, ServiceError>;
-    async fn update_user(&self, id: UserId, request: UpdateUserRequest) -> Result<User, ServiceError>;
-    async fn delete_user(&self, id: UserId) -> Result<(), ServiceError>;
-    async fn list_users(&self, limit: i64, offset: i64) -> Result<Vec<User>, ServiceError>;
+    /// Update a user; `expected_version` guards against lost updates when set
+    async fn update_user(&self, id: UserId, request: UpdateUserRequest, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError>;
+    async fn patch_user(&self, id: UserId, patch: UserPatch, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError>;
+    async fn delete_user(&self, id: UserId, expected_version: Option<i64>, context: &AuditContext) -> Result<(), ServiceError>;
+    /// Activate or deactivate a user
+    async fn set_user_status(&self, id: UserId, request: UserStatusRequest, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError>;
+    /// List active users, optionally only those whose metadata contains `metadata_filter`
+    async fn list_users(&self, limit: i64, offset: i64, metadata_filter: Option<serde_json::Value>) -> Result<Vec<User>, ServiceError>;
+    async fn search_users(&self, query: &str, is_active: Option<bool>, limit: i64) -> Result<Vec<UserSearchResult>, ServiceError>;
+    /// Start a streaming export of active users, recording it in the audit log first
+    async fn export_users(&self, request: &UserExportRequest, context: &AuditContext) -> Result<UserExportStream, ServiceError>;
+    /// Replace a user's avatar with an uploaded image
+    async fn set_avatar(&self, id: UserId, image: Vec<u8>, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError>;
+}
+
+/// Reject the operation early if the caller's version is already stale
+fn check_version(user: &User, expected_version: Option<i64>) -> Result<(), ServiceError> {
+    match expected_version {
+        Some(expected) if expected != user.version => {
+            tracing::warn!("Version mismatch for user {}: expected {}, current {}", user.id, expected, user.version);
+            Err(version_mismatch(user.id))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn version_mismatch(id: UserId) -> ServiceError {
+    ServiceError::PreconditionFailed(format!("User {} has been modified by another request", id))
+}
+
+/// Map a failed write to a user onto the error reported to callers
+pub(crate) fn write_error(id: UserId, error: RepositoryError) -> ServiceError {
+    match error {
+        RepositoryError::NotFound => ServiceError::NotFound,
+        RepositoryError::VersionMismatch => version_mismatch(id),
+        RepositoryError::DuplicateEmail(_) => ServiceError::AlreadyExists,
+        e => ServiceError::Repository(e),
+    }
+}
+
+/// Audit log entry for a change to a user, with the fields it changed
+fn user_audit(action: &str, user_id: UserId, before: Option<&User>, after: Option<&User>, context: &AuditContext) -> NewAuditLog {
+    NewAuditLog::new(action, "user")
+        .with_resource_id(user_id.to_string())
+        .with_context(context)
+        .with_changes(before, after)
+}
+
+/// Roll back a failed change, logging rather than masking the original error
+async fn rollback(tx: Box<dyn UserRepositoryTransaction>) {
+    if let Err(e) = tx.rollback().await {
+        tracing::error!("Failed to rollback transaction: {}", e);
+    }
 }
 
 /// User service implementation
 pub struct UserServiceImpl {
     repository: Arc<dyn UserRepository>,
-    external_service: Arc<dyn crate::services::ExternalService>,
+    audit_repository: Arc<dyn AuditLogRepository>,
+    metadata_validator: Arc<UserMetadataValidator>,
+    avatar_store: Option<Arc<dyn BlobStore>>,
+    avatar_policy: AvatarPolicy,
+    email_changes: Option<Arc<dyn EmailChangeService>>,
+    audit_failure_mode: AuditFailureMode,
+    events: Option<Arc<EventBus>>,
 }
 
 impl UserServiceImpl {
     pub fn new(
         repository: Arc<dyn UserRepository>,
-        external_service: Arc<dyn crate::services::ExternalService>
+        audit_repository: Arc<dyn AuditLogRepository>,
     ) -> Self {
         Self {
             repository,
-            external_service,
+            audit_repository,
+            metadata_validator: Arc::new(UserMetadataValidator::new()),
+            avatar_store: None,
+            avatar_policy: AvatarPolicy::default(),
+            email_changes: None,
+            audit_failure_mode: AuditFailureMode::default(),
+            events: None,
         }
     }
 
-    /// Notify external services about user creation
-    async fn notify_user_created(&self, user: &User) -> Result<(), ServiceError> {
-        let notification_payload = serde_json::json!({
-            "event": "user_created",
-            "user_id": user.id,
-            "email": user.email,
-            "name": user.name,
-            "created_at": user.created_at,
-            "timestamp": chrono::Utc::now()
-        });
-
-        // Example: Send to webhook endpoint
-        if let Err(e) = self.external_service
-            .post("https://api.example.com/webhooks/user-created", notification_payload)
-            .await
-        {
-            tracing::warn!("Failed to send user creation notification: {}", e);
-            return Err(ServiceError::ExternalService(format!("Notification failed: {}", e)));
+    /// Validate user metadata against the given schema validator
+    pub fn with_metadata_validator(mut self, validator: Arc<UserMetadataValidator>) -> Self {
+        self.metadata_validator = validator;
+        self
+    }
+
+    /// Store avatars in the given blob store, subject to the given policy
+    pub fn with_avatar_store(mut self, store: Arc<dyn BlobStore>, policy: AvatarPolicy) -> Self {
+        self.avatar_store = Some(store);
+        self.avatar_policy = policy;
+        self
+    }
+
+    /// Hold email changes until the new address confirms them
+    ///
+    /// Without this, `update_user` changes the email immediately.
+    pub fn with_email_change_service(mut self, email_changes: Arc<dyn EmailChangeService>) -> Self {
+        self.email_changes = Some(email_changes);
+        self
+    }
+
+    /// Choose whether a change is kept when its audit log entry cannot be written
+    pub fn with_audit_failure_mode(mut self, mode: AuditFailureMode) -> Self {
+        self.audit_failure_mode = mode;
+        self
+    }
+
+    /// Publish committed changes to the given event bus
+    pub fn with_event_bus(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Hand a committed change to the event bus, if there is one
+    fn publish(&self, event: DomainEvent, context: &AuditContext) {
+        if let Some(events) = &self.events {
+            events.publish(event, context);
         }
+    }
 
-        Ok(())
+    /// Write changed fields, their audit log entry and outbox event in one transaction
+    async fn update_fields(&self, existing_user: &User, request: UpdateUserRequest, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError> {
+        let id = existing_user.id;
+        let mut tx = self.repository.begin_transaction().await?;
+        let user = match tx.update(id, request.name, request.email, request.metadata, expected_version).await {
+            Ok(user) => user,
+            Err(e) => {
+                tracing::warn!("Failed to update user {}: {}", id, e);
+                rollback(tx).await;
+                return Err(write_error(id, e));
+            }
+        };
+        self.commit_audited(
+            tx,
+            &[user_audit("user.update", id, Some(existing_user), Some(&user), context)],
+            &[NewOutboxEvent::user_updated(existing_user, &user).with_context(context)],
+        ).await?;
+
+        tracing::info!("Successfully updated user with ID: {}", id);
+        self.publish(DomainEvent::user_updated(existing_user, &user), context);
+
+        Ok(user)
     }
 
-    /// Notify external services about user update
-    async fn notify_user_updated(&self, old_user: &User, new_user: &User) -> Result<(), ServiceError> {
-        let notification_payload = serde_json::json!({
-            "event": "user_updated",
-            "user_id": new_user.id,
-            "changes": {
-                "name": {
-                    "old": old_user.name,
-                    "new": new_user.name
-                },
-                "email": {
-                    "old": old_user.email,
-                    "new": new_user.email
+    /// Write the audit log entries and outbox events for the changes in a
+    /// transaction, then commit it
+    ///
+    /// If an entry cannot be written the transaction is rolled back, unless
+    /// audit failures are configured to fail open. Events are always written
+    /// with the change or not at all.
+    async fn commit_audited(
+        &self,
+        mut tx: Box<dyn UserRepositoryTransaction>,
+        entries: &[NewAuditLog],
+        events: &[NewOutboxEvent],
+    ) -> Result<(), ServiceError> {
+        for entry in entries {
+            if let Err(e) = tx.record_audit(entry).await {
+                match self.audit_failure_mode {
+                    AuditFailureMode::Closed => {
+                        tracing::error!("Failed to record {} in audit log, rolling back: {}", entry.action, e);
+                        rollback(tx).await;
+                        return Err(ServiceError::Repository(e));
+                    }
+                    AuditFailureMode::Open => {
+                        tracing::warn!("Failed to record {} in audit log, keeping the change: {}", entry.action, e);
+                    }
                 }
-            },
-            "updated_at": new_user.updated_at,
-            "timestamp": chrono::Utc::now()
-        });
-
-        // Example: Send to webhook endpoint
-        if let Err(e) = self.external_service
-            .post("https://api.example.com/webhooks/user-updated", notification_payload)
-            .await
-        {
-            tracing::warn!("Failed to send user update notification: {}", e);
-            return Err(ServiceError::ExternalService(format!("Notification failed: {}", e)));
+            }
         }
 
-        Ok(())
+        for event in events {
+            if let Err(e) = tx.enqueue_event(event).await {
+                tracing::error!("Failed to queue {} event, rolling back: {}", event.event_type, e);
+                rollback(tx).await;
+                return Err(ServiceError::Repository(e));
+            }
+        }
+
+        tx.commit().await.map_err(|e| {
+            tracing::error!("Failed to commit transaction: {}", e);
+            ServiceError::Repository(e)
+        })
     }
 
-    /// Notify external services about user deletion
-    async fn notify_user_deleted(&self, user: &User) -> Result<(), ServiceError> {
-        let notification_payload = serde_json::json!({
-            "event": "user_deleted",
-            "user_id": user.id,
-            "email": user.email,
-            "name": user.name,
-            "deleted_at": chrono::Utc::now(),
-            "timestamp": chrono::Utc::now()
-        });
-
-        // Example: Send to webhook endpoint
-        if let Err(e) = self.external_service
-            .post("https://api.example.com/webhooks/user-deleted", notification_payload)
-            .await
-        {
-            tracing::warn!("Failed to send user deletion notification: {}", e);
-            return Err(ServiceError::ExternalService(format!("Notification failed: {}", e)));
+    /// Remove a blob that is no longer referenced, logging rather than failing
+    async fn delete_avatar_blob(&self, key: &str) {
+        if let Some(store) = &self.avatar_store {
+            if let Err(e) = store.delete(key).await {
+                tracing::warn!("Failed to delete avatar blob {}: {}", key, e);
+            }
         }
+    }
 
-        Ok(())
+    /// Check metadata against the configured schema
+    fn validate_metadata(&self, metadata: Option<&serde_json::Value>) -> Result<(), ServiceError> {
+        match metadata {
+            Some(metadata) => self.metadata_validator.validate(metadata).map_err(|e| {
+                tracing::warn!("User metadata failed schema validation: {}", e);
+                ServiceError::Validation(format!("metadata: {}", e))
+            }),
+            None => Ok(()),
+        }
     }
 
     /// Create user with transaction handling for complex operations
-    pub async fn create_user_with_transaction(&self, request: CreateUserRequest) -> Result<User, ServiceError> {
+    pub async fn create_user_with_transaction(&self, request: CreateUserRequest, context: &AuditContext) -> Result<User, ServiceError> {
         tracing::info!("Creating user with transaction: {}", request.email);
 
         // Validate and normalize the request
@@ -139,6 +270,7 @@ impl UserServiceImpl {
                 return Err(ServiceError::Validation(format!("{:?}", validation_errors)));
             }
         };
+        self.validate_metadata(normalized_request.metadata.as_ref())?;
 
         // Begin transaction
         let mut tx = match self.repository.begin_transaction().await {
@@ -165,28 +297,21 @@ impl UserServiceImpl {
             }
         };
 
-        // Additional operations within the same transaction could go here
-        // For example: creating audit logs, updating statistics, etc.
-
-        // Commit transaction
-        if let Err(e) = tx.commit().await {
-            tracing::error!("Failed to commit transaction: {}", e);
-            return Err(ServiceError::Repository(e));
-        }
+        // Audit, announce and commit the new user together
+        self.commit_audited(
+            tx,
+            &[user_audit("user.create", user.id, None, Some(&user), context)],
+            &[NewOutboxEvent::user_created(&user).with_context(context)],
+        ).await?;
 
         tracing::info!("Successfully created user with transaction: {}", user.id);
-
-        // External notifications happen after transaction commit
-        if let Err(e) = self.notify_user_created(&user).await {
-            tracing::warn!("Failed to notify external services: {}", e);
-            // Don't fail the operation if external notification fails
-        }
+        self.publish(DomainEvent::UserCreated { user: user.clone() }, context);
 
         Ok(user)
     }
 
     /// Batch update users with transaction handling
-    pub async fn batch_update_users(&self, updates: Vec<(UserId, UpdateUserRequest)>) -> Result<Vec<User>, ServiceError> {
+    pub async fn batch_update_users(&self, updates: Vec<(UserId, UpdateUserRequest)>, context: &AuditContext) -> Result<Vec<User>, ServiceError> {
         if updates.is_empty() {
             return Ok(Vec::new());
         }
@@ -203,6 +328,9 @@ impl UserServiceImpl {
         };
 
         let mut updated_users = Vec::new();
+        let mut audit_entries = Vec::new();
+        let mut events = Vec::new();
+        let mut changes = Vec::new();
 
         // Process each update within the transaction
         for (user_id, update_request) in updates {
@@ -218,38 +346,63 @@ impl UserServiceImpl {
                 }
             };
 
+            if let Err(e) = self.validate_metadata(normalized_request.metadata.as_ref()) {
+                if let Err(rollback_err) = tx.rollback().await {
+                    tracing::error!("Failed to rollback batch update transaction: {}", rollback_err);
+                }
+                return Err(e);
+            }
+
+            // Email changes need the new address confirmed, which a batch cannot wait for
+            if normalized_request.email.is_some() {
+                tracing::warn!("Rejected email change for user {} in batch update", user_id);
+                rollback(tx).await;
+                return Err(ServiceError::Validation(format!(
+                    "User {}: email cannot be changed in a batch update; update the user on its own",
+                    user_id
+                )));
+            }
+
             if !normalized_request.has_updates() {
                 continue; // Skip users with no updates
             }
 
+            // Keep the current row for the audit diff, locked until the batch commits
+            let existing_user = match tx.find_for_update(user_id).await {
+                Ok(Some(user)) => user,
+                Ok(None) => {
+                    tracing::warn!("Attempted to batch update non-existent user: {}", user_id);
+                    rollback(tx).await;
+                    return Err(ServiceError::NotFound);
+                }
+                Err(e) => {
+                    rollback(tx).await;
+                    return Err(write_error(user_id, e));
+                }
+            };
+
             // Update user within transaction
-            match tx.update(user_id, normalized_request.name, normalized_request.email).await {
+            match tx.update(user_id, normalized_request.name, None, normalized_request.metadata, None).await {
                 Ok(user) => {
+                    audit_entries.push(user_audit("user.update", user_id, Some(&existing_user), Some(&user), context));
+                    events.push(NewOutboxEvent::user_updated(&existing_user, &user).with_context(context));
+                    changes.push(DomainEvent::user_updated(&existing_user, &user));
                     updated_users.push(user);
                 },
                 Err(e) => {
                     tracing::error!("Failed to update user {} in batch: {}", user_id, e);
-                    if let Err(rollback_err) = tx.rollback().await {
-                        tracing::error!("Failed to rollback batch update transaction: {}", rollback_err);
-                    }
-                    return Err(ServiceError::Repository(e));
+                    rollback(tx).await;
+                    return Err(write_error(user_id, e));
                 }
             }
         }
 
-        // Commit transaction
-        if let Err(e) = tx.commit().await {
-            tracing::error!("Failed to commit batch update transaction: {}", e);
-            return Err(ServiceError::Repository(e));
-        }
+        // Audit, announce and commit all updates together
+        self.commit_audited(tx, &audit_entries, &events).await?;
 
         tracing::info!("Successfully completed batch update for {} users", updated_users.len());
-
-        // Send notifications for all updated users (fire and forget)
-        for user in &updated_users {
-            if let Err(e) = self.notify_user_updated(user, user).await {
-                tracing::warn!("Failed to notify external services about user {} update: {}", user.id, e);
-            }
+        for change in changes {
+            self.publish(change, context);
         }
 
         Ok(updated_users)
@@ -259,7 +412,7 @@ impl UserServiceImpl {
 #[async_trait]
 impl UserService for UserServiceImpl {
     #[tracing::instrument(skip(self, request), fields(email = %request.email))]
-    async fn create_user(&self, request: CreateUserRequest) -> Result<User, ServiceError> {
+    async fn create_user(&self, request: CreateUserRequest, context: &AuditContext) -> Result<User, ServiceError> {
         tracing::info!("Creating user with email: {}", request.email);
 
         // Validate and normalize the request
@@ -270,6 +423,7 @@ impl UserService for UserServiceImpl {
                 return Err(ServiceError::Validation(format!("{:?}", validation_errors)));
             }
         };
+        self.validate_metadata(normalized_request.metadata.as_ref())?;
 
         // Check if email already exists
         if self.repository.email_exists(&normalized_request.email).await? {
@@ -279,28 +433,29 @@ impl UserService for UserServiceImpl {
 
         let new_user = NewUser::from(normalized_request);
 
-        // Create user with transaction for complex operations
-        let user = match self.repository.create(&new_user).await {
-            Ok(user) => {
-                tracing::info!("Successfully created user with ID: {}", user.id);
-
-                // Notify external services about user creation (fire and forget)
-                if let Err(e) = self.notify_user_created(&user).await {
-                    tracing::warn!("Failed to notify external services about user creation: {}", e);
-                    // Don't fail the operation if external notification fails
-                }
-
-                user
-            },
+        // Create user and its audit log entry in one transaction
+        let mut tx = self.repository.begin_transaction().await?;
+        let user = match tx.create(&new_user).await {
+            Ok(user) => user,
             Err(RepositoryError::DuplicateEmail(email)) => {
                 tracing::warn!("Duplicate email detected during creation: {}", email);
+                rollback(tx).await;
                 return Err(ServiceError::AlreadyExists);
             },
             Err(e) => {
                 tracing::error!("Failed to create user: {}", e);
+                rollback(tx).await;
                 return Err(ServiceError::Repository(e));
             }
         };
+        self.commit_audited(
+            tx,
+            &[user_audit("user.create", user.id, None, Some(&user), context)],
+            &[NewOutboxEvent::user_created(&user).with_context(context)],
+        ).await?;
+
+        tracing::info!("Successfully created user with ID: {}", user.id);
+        self.publish(DomainEvent::UserCreated { user: user.clone() }, context);
 
         Ok(user)
     }
@@ -341,17 +496,18 @@ impl UserService for UserServiceImpl {
     }
 
     #[tracing::instrument(skip(self, request), fields(user_id = %id))]
-    async fn update_user(&self, id: UserId, request: UpdateUserRequest) -> Result<User, ServiceError> {
+    async fn update_user(&self, id: UserId, request: UpdateUserRequest, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError> {
         tracing::info!("Updating user with ID: {}", id);
 
         // Validate and normalize the request
-        let normalized_request = match request.validate_and_normalize() {
+        let mut normalized_request = match request.validate_and_normalize() {
             Ok(req) => req,
             Err(validation_errors) => {
                 tracing::warn!("User update validation failed: {:?}", validation_errors);
                 return Err(ServiceError::Validation(format!("{:?}", validation_errors)));
             }
         };
+        self.validate_metadata(normalized_request.metadata.as_ref())?;
 
         // Check if the request has any updates
         if !normalized_request.has_updates() {
@@ -368,6 +524,8 @@ impl UserService for UserServiceImpl {
             }
         };
 
+        check_version(&existing_user, expected_version)?;
+
         // Check for email conflicts if email is being updated
         if let Some(ref new_email) = normalized_request.email {
             if new_email != &existing_user.email && self.repository.email_exists_for_other_user(new_email, id).await? {
@@ -376,38 +534,84 @@ impl UserService for UserServiceImpl {
             }
         }
 
-        // Perform the update with transaction handling
-        let updated_user = match self.repository.update(id, normalized_request.name, normalized_request.email).await {
-            Ok(user) => {
-                tracing::info!("Successfully updated user with ID: {}", id);
+        // A new email is held as pending until the new address confirms it
+        let pending_email = if self.email_changes.is_some() {
+            normalized_request.email.take().filter(|email| email != &existing_user.email)
+        } else {
+            None
+        };
 
-                // Notify external services about user update (fire and forget)
-                if let Err(e) = self.notify_user_updated(&existing_user, &user).await {
-                    tracing::warn!("Failed to notify external services about user update: {}", e);
-                    // Don't fail the operation if external notification fails
+        // The change is requested first, under the same version check as the
+        // other fields, so that it cannot fail once they have been committed
+        let email_changes = self.email_changes.as_ref().filter(|_| pending_email.is_some());
+        if let (Some(email_changes), Some(email)) = (email_changes, &pending_email) {
+            email_changes.request_change(&existing_user, email, expected_version).await?;
+        }
+
+        if !normalized_request.has_updates() {
+            return Ok(existing_user);
+        }
+
+        // Perform the update and its audit log entry in one transaction
+        match self.update_fields(&existing_user, normalized_request, expected_version, context).await {
+            Ok(user) => Ok(user),
+            Err(e) => {
+                if let Some(email_changes) = email_changes {
+                    // The confirmation already mailed then leads nowhere
+                    if let Err(cancel_error) = email_changes.cancel_change(id).await {
+                        tracing::error!("Failed to withdraw email change of user {} after a failed update: {}", id, cancel_error);
+                    }
                 }
+                Err(e)
+            }
+        }
+    }
 
-                user
-            },
-            Err(RepositoryError::NotFound) => {
-                tracing::warn!("User not found during update: {}", id);
+    #[tracing::instrument(skip(self, patch), fields(user_id = %id))]
+    async fn patch_user(&self, id: UserId, patch: UserPatch, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError> {
+        tracing::info!("Patching user with ID: {}", id);
+
+        let existing_user = match self.repository.find_by_id(id).await? {
+            Some(user) => user,
+            None => {
+                tracing::warn!("Attempted to patch non-existent user: {}", id);
                 return Err(ServiceError::NotFound);
-            },
-            Err(RepositoryError::DuplicateEmail(email)) => {
-                tracing::warn!("Duplicate email detected during update: {}", email);
-                return Err(ServiceError::AlreadyExists);
-            },
-            Err(e) => {
-                tracing::error!("Failed to update user {}: {}", id, e);
-                return Err(ServiceError::Repository(e));
             }
         };
 
-        Ok(updated_user)
+        check_version(&existing_user, expected_version)?;
+
+        // Apply the patch to the current representation and revalidate it
+        let patched = match patch.apply(&existing_user) {
+            Ok(patched) => patched,
+            Err(UserPatchError::Conflict(msg)) => {
+                tracing::warn!("Patch could not be applied to user {}: {}", id, msg);
+                return Err(ServiceError::Conflict(msg));
+            }
+            Err(UserPatchError::Invalid(msg)) => {
+                tracing::warn!("Patched user {} failed validation: {}", id, msg);
+                return Err(ServiceError::Validation(msg));
+            }
+        };
+
+        // Only send the fields that actually changed through the regular update path
+        let request = UpdateUserRequest {
+            name: Some(patched.name).filter(|name| name != &existing_user.name),
+            email: Some(patched.email).filter(|email| email != &existing_user.email),
+            metadata: Some(patched.metadata.unwrap_or_else(|| serde_json::json!({})))
+                .filter(|metadata| metadata != &existing_user.metadata),
+        };
+
+        if !request.has_updates() {
+            tracing::debug!("Patch produced no changes for user: {}", id);
+            return Ok(existing_user);
+        }
+
+        self.update_user(id, request, expected_version, context).await
     }
 
     #[tracing::instrument(skip(self), fields(user_id = %id))]
-    async fn delete_user(&self, id: UserId) -> Result<(), ServiceError> {
+    async fn delete_user(&self, id: UserId, expected_version: Option<i64>, context: &AuditContext) -> Result<(), ServiceError> {
         tracing::info!("Deleting user with ID: {}", id);
 
         // Get user details before deletion for external notifications
@@ -419,32 +623,72 @@ impl UserService for UserServiceImpl {
             }
         };
 
+        check_version(&user, expected_version)?;
+
         // Perform soft delete instead of hard delete for data integrity
-        match self.repository.soft_delete(id).await {
-            Ok(()) => {
-                tracing::info!("Successfully soft deleted user with ID: {}", id);
-
-                // Notify external services about user deletion (fire and forget)
-                if let Err(e) = self.notify_user_deleted(&user).await {
-                    tracing::warn!("Failed to notify external services about user deletion: {}", e);
-                    // Don't fail the operation if external notification fails
-                }
+        let mut tx = self.repository.begin_transaction().await?;
+        let deleted = match tx.soft_delete(id, expected_version).await {
+            Ok(deleted) => deleted,
+            Err(e) => {
+                tracing::warn!("Failed to delete user {}: {}", id, e);
+                rollback(tx).await;
+                return Err(write_error(id, e));
+            }
+        };
+        self.commit_audited(
+            tx,
+            &[user_audit("user.delete", id, Some(&user), Some(&deleted), context)],
+            &[NewOutboxEvent::user_deleted(&user).with_context(context)],
+        ).await?;
 
-                Ok(())
-            },
-            Err(RepositoryError::NotFound) => {
-                tracing::warn!("User not found during deletion: {}", id);
-                Err(ServiceError::NotFound)
-            },
+        tracing::info!("Successfully soft deleted user with ID: {}", id);
+
+        if let Some(key) = &user.avatar_key {
+            self.delete_avatar_blob(key).await;
+        }
+        self.publish(DomainEvent::UserDeleted { user }, context);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, request), fields(user_id = %id, is_active = request.is_active))]
+    async fn set_user_status(&self, id: UserId, request: UserStatusRequest, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError> {
+        let user = self.repository.find_by_id(id).await?.ok_or(ServiceError::NotFound)?;
+        check_version(&user, expected_version)?;
+
+        if user.is_active == request.is_active {
+            tracing::debug!("User {} already has is_active = {}", id, request.is_active);
+            return Ok(user);
+        }
+
+        let action = if request.is_active { "user.activate" } else { "user.deactivate" };
+        let mut tx = self.repository.begin_transaction().await?;
+        let updated = match tx.set_active(id, request.is_active, expected_version).await {
+            Ok(updated) => updated,
             Err(e) => {
-                tracing::error!("Failed to delete user {}: {}", id, e);
-                Err(ServiceError::Repository(e))
+                tracing::warn!("Failed to set status of user {}: {}", id, e);
+                rollback(tx).await;
+                return Err(write_error(id, e));
             }
+        };
+
+        let mut entry = user_audit(action, id, Some(&user), Some(&updated), context);
+        if let Some(reason) = &request.reason {
+            entry = entry.with_detail("reason", serde_json::Value::String(reason.clone()));
         }
+        let event = NewOutboxEvent::user_status_changed(&updated, request.reason.as_deref()).with_context(context);
+        self.commit_audited(tx, &[entry], &[event]).await?;
+
+        tracing::info!("Successfully set is_active = {} for user {}", request.is_active, id);
+        self.publish(
+            DomainEvent::UserStatusChanged { user: updated.clone(), reason: request.reason },
+            context,
+        );
+        Ok(updated)
     }
 
     #[tracing::instrument(skip(self))]
-    async fn list_users(&self, limit: i64, offset: i64) -> Result<Vec<User>, ServiceError> {
+    async fn list_users(&self, limit: i64, offset: i64, metadata_filter: Option<serde_json::Value>) -> Result<Vec<User>, ServiceError> {
         tracing::debug!("Listing users with limit: {}, offset: {}", limit, offset);
 
         // Validate pagination parameters
@@ -456,9 +700,113 @@ impl UserService for UserServiceImpl {
             return Err(ServiceError::Validation("Offset must be non-negative".to_string()));
         }
 
-        let users = self.repository.list_active(limit, offset).await?;
+        let users = self.repository.list_active(limit, offset, metadata_filter.as_ref()).await?;
         tracing::debug!("Retrieved {} users", users.len());
 
         Ok(users)
     }
+
+    #[tracing::instrument(skip(self))]
+    async fn search_users(&self, query: &str, is_active: Option<bool>, limit: i64) -> Result<Vec<UserSearchResult>, ServiceError> {
+        let query = query.trim();
+        tracing::debug!("Searching users for '{}' (limit: {})", query, limit);
+
+        if query.is_empty() {
+            return Err(ServiceError::Validation("Search query cannot be empty".to_string()));
+        }
+
+        if query.chars().count() > 255 {
+            return Err(ServiceError::Validation("Search query cannot exceed 255 characters".to_string()));
+        }
+
+        if limit <= 0 || limit > 100 {
+            return Err(ServiceError::Validation("Limit must be between 1 and 100".to_string()));
+        }
+
+        let results = self.repository.search(query, is_active, limit).await?;
+        tracing::debug!("Search for '{}' returned {} users", query, results.len());
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn export_users(&self, request: &UserExportRequest, context: &AuditContext) -> Result<UserExportStream, ServiceError> {
+        tracing::info!("Starting {:?} export of users", request.format);
+
+        // An export that cannot be audited must not run
+        let entry = NewAuditLog::new("user.export", "user")
+            .with_context(context)
+            .with_metadata(serde_json::json!({
+                "format": request.format,
+                "columns": request.columns,
+                "metadata_filter": request.metadata_filter,
+            }));
+        if let Err(e) = self.audit_repository.record(&entry).await {
+            tracing::error!("Failed to record user export in audit log: {}", e);
+            return Err(ServiceError::Repository(e));
+        }
+
+        let users = self
+            .repository
+            .stream_active(request.metadata_filter.clone())
+            .map(|row| row.map_err(ServiceError::from));
+
+        Ok(users.boxed())
+    }
+
+    #[tracing::instrument(skip(self, image), fields(size = image.len()))]
+    async fn set_avatar(&self, id: UserId, image: Vec<u8>, expected_version: Option<i64>, context: &AuditContext) -> Result<User, ServiceError> {
+        let store = self
+            .avatar_store
+            .clone()
+            .ok_or_else(|| ServiceError::Storage("Avatar storage is not configured".to_string()))?;
+
+        let user = self.repository.find_by_id(id).await?.ok_or(ServiceError::NotFound)?;
+        check_version(&user, expected_version)?;
+
+        let avatar = self.avatar_policy.inspect(&image).map_err(|e| {
+            tracing::warn!("Rejected avatar upload for user {}: {}", id, e);
+            ServiceError::Validation(e)
+        })?;
+
+        let key = format!("{}/{}.{}", id, uuid::Uuid::new_v4(), avatar.extension);
+        store.put(&key, image, avatar.content_type).await.map_err(|e| {
+            tracing::error!("Failed to store avatar for user {}: {}", id, e);
+            ServiceError::Storage(e.to_string())
+        })?;
+
+        let mut tx = match self.repository.begin_transaction().await {
+            Ok(tx) => tx,
+            Err(e) => {
+                self.delete_avatar_blob(&key).await;
+                return Err(ServiceError::Repository(e));
+            }
+        };
+        let updated = match tx.set_avatar(id, Some(&key), Some(&store.url(&key)), expected_version).await {
+            Ok(updated) => updated,
+            Err(e) => {
+                rollback(tx).await;
+                // The new blob is unreferenced if the row could not be updated
+                self.delete_avatar_blob(&key).await;
+                return Err(write_error(id, e));
+            }
+        };
+        let entry = user_audit("user.avatar", id, Some(&user), Some(&updated), context);
+        let event = NewOutboxEvent::user_updated(&user, &updated).with_context(context);
+        if let Err(e) = self.commit_audited(tx, &[entry], &[event]).await {
+            self.delete_avatar_blob(&key).await;
+            return Err(e);
+        }
+
+        if let Some(previous) = &user.avatar_key {
+            self.delete_avatar_blob(previous).await;
+        }
+        self.publish(DomainEvent::user_updated(&user, &updated), context);
+
+        tracing::info!(
+            "Stored {}x{} {} avatar for user {}",
+            avatar.width, avatar.height, avatar.content_type, id
+        );
+        Ok(updated)
+    }
 }
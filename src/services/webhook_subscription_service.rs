@@ -0,0 +1,347 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Instant;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::config::WebhookConfig;
+use crate::models::{
+    generate_webhook_secret, validate_webhook_event_types, validate_webhook_url, CreateWebhookSubscriptionRequest,
+    CreatedWebhookSubscription, NewWebhookDelivery, OutboxEvent, Page, RotateWebhookSecretRequest,
+    UpdateWebhookSubscriptionRequest, UserId, WebhookDelivery, WebhookSubscription, WebhookSubscriptionId,
+    MAX_DELIVERY_RESPONSE_BYTES, WEBHOOK_PING_EVENT,
+};
+use crate::repository::{RepositoryError, WebhookRepository};
+use crate::services::{ServiceError, WebhookService};
+
+/// Webhook subscription management and delivery
+///
+/// Authorization is left to callers.
+#[async_trait]
+pub trait WebhookSubscriptionService: Send + Sync {
+    /// Register an endpoint; the generated secret is only returned here
+    async fn create_subscription(
+        &self,
+        request: CreateWebhookSubscriptionRequest,
+        created_by: UserId,
+    ) -> Result<CreatedWebhookSubscription, ServiceError>;
+    async fn get_subscription(&self, id: WebhookSubscriptionId) -> Result<WebhookSubscription, ServiceError>;
+    async fn list_subscriptions(&self, limit: i64, offset: i64) -> Result<Page<WebhookSubscription>, ServiceError>;
+    async fn update_subscription(
+        &self,
+        id: WebhookSubscriptionId,
+        request: UpdateWebhookSubscriptionRequest,
+    ) -> Result<WebhookSubscription, ServiceError>;
+    async fn delete_subscription(&self, id: WebhookSubscriptionId) -> Result<(), ServiceError>;
+
+    /// Replace a subscription's secret; the old one keeps signing alongside it
+    /// for the requested or configured overlap
+    async fn rotate_secret(
+        &self,
+        id: WebhookSubscriptionId,
+        request: RotateWebhookSecretRequest,
+    ) -> Result<CreatedWebhookSubscription, ServiceError>;
+
+    /// Send a `webhook.ping` event to a subscription, active or not, and log the attempt
+    async fn ping(&self, id: WebhookSubscriptionId) -> Result<WebhookDelivery, ServiceError>;
+
+    /// Send an envelope to one active subscription again and log the attempt,
+    /// as when replaying a dead letter
+    async fn redeliver(
+        &self,
+        id: WebhookSubscriptionId,
+        event_id: Uuid,
+        event_type: &str,
+        envelope: &serde_json::Value,
+        attempt: i32,
+    ) -> Result<WebhookDelivery, ServiceError>;
+
+    /// A subscription's delivery attempts, newest first
+    async fn list_deliveries(
+        &self,
+        id: WebhookSubscriptionId,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Page<WebhookDelivery>, ServiceError>;
+
+    /// Deliver an outbox event to every matching subscription that has not received it yet
+    ///
+    /// Subscriptions created after the event are skipped. Returns the attempts
+    /// made; the caller retries the event while any of them failed.
+    async fn dispatch(&self, event: &OutboxEvent) -> Result<Vec<WebhookDelivery>, ServiceError>;
+
+    /// Delete delivery attempts older than the configured retention
+    async fn purge_deliveries(&self) -> Result<u64, ServiceError>;
+}
+
+/// Webhook subscription service implementation
+pub struct WebhookSubscriptionServiceImpl {
+    repository: Arc<dyn WebhookRepository>,
+    webhooks: WebhookService,
+    config: WebhookConfig,
+}
+
+impl WebhookSubscriptionServiceImpl {
+    pub fn new(repository: Arc<dyn WebhookRepository>, webhooks: WebhookService, config: WebhookConfig) -> Self {
+        Self {
+            repository,
+            webhooks,
+            config,
+        }
+    }
+
+    async fn require_subscription(&self, id: WebhookSubscriptionId) -> Result<WebhookSubscription, ServiceError> {
+        self.repository.find_by_id(id).await?.ok_or(ServiceError::NotFound)
+    }
+
+    /// Check that a subscription URL is well formed and leads to public addresses
+    async fn validate_url(&self, url: &str) -> Result<(), ServiceError> {
+        validate_webhook_url(url, self.config.require_https).map_err(ServiceError::Validation)?;
+        self.webhooks
+            .check_endpoint(url)
+            .await
+            .map_err(|e| ServiceError::Validation(format!("Webhook URL is not allowed: {}", e)))
+    }
+
+    /// Post an envelope to a subscription and log the attempt, keeping at
+    /// most `max_body_bytes` of the response
+    async fn deliver(
+        &self,
+        subscription: &WebhookSubscription,
+        event_id: Uuid,
+        event_type: &str,
+        attempt: i32,
+        envelope: &serde_json::Value,
+        max_body_bytes: usize,
+    ) -> Result<WebhookDelivery, ServiceError> {
+        let started = Instant::now();
+        let result = self
+            .webhooks
+            .deliver(
+                &subscription.url,
+                &event_id.to_string(),
+                envelope,
+                &subscription.signing_secrets(Utc::now()),
+                max_body_bytes,
+            )
+            .await;
+        let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+        let delivery = match result {
+            Ok(response) => NewWebhookDelivery {
+                subscription_id: subscription.id,
+                event_id,
+                event_type: event_type.to_string(),
+                attempt,
+                success: response.is_success(),
+                response_status: Some(response.status as i32),
+                error: (!response.is_success()).then(|| format!("Endpoint responded with status {}", response.status)),
+                response_body: Some(response.body).filter(|body| !body.is_empty()),
+                duration_ms,
+            },
+            Err(e) => NewWebhookDelivery {
+                subscription_id: subscription.id,
+                event_id,
+                event_type: event_type.to_string(),
+                attempt,
+                success: false,
+                response_status: None,
+                response_body: None,
+                error: Some(e.to_string()),
+                duration_ms,
+            },
+        };
+
+        if !delivery.success {
+            tracing::info!(
+                "Webhook delivery of {} to subscription {} failed: {}",
+                event_id,
+                subscription.id,
+                delivery.error.as_deref().unwrap_or_default()
+            );
+        }
+
+        Ok(self.repository.record_delivery(&delivery).await?)
+    }
+}
+
+fn subscription_error(error: RepositoryError) -> ServiceError {
+    match error {
+        RepositoryError::NotFound => ServiceError::NotFound,
+        e => ServiceError::Repository(e),
+    }
+}
+
+#[async_trait]
+impl WebhookSubscriptionService for WebhookSubscriptionServiceImpl {
+    #[tracing::instrument(skip(self, request))]
+    async fn create_subscription(
+        &self,
+        request: CreateWebhookSubscriptionRequest,
+        created_by: UserId,
+    ) -> Result<CreatedWebhookSubscription, ServiceError> {
+        let request = request.normalize();
+        request.validate().map_err(|e| ServiceError::Validation(e.to_string()))?;
+        self.validate_url(&request.url).await?;
+        validate_webhook_event_types(&request.event_types).map_err(ServiceError::Validation)?;
+
+        let secret = generate_webhook_secret();
+        let subscription = self
+            .repository
+            .create(
+                &request.url,
+                &request.event_types,
+                &secret,
+                request.description.as_deref(),
+                request.is_active,
+                created_by,
+            )
+            .await?;
+
+        tracing::info!("User {} subscribed {} to {:?}", created_by, subscription.url, subscription.event_types);
+        Ok(CreatedWebhookSubscription { subscription, secret })
+    }
+
+    async fn get_subscription(&self, id: WebhookSubscriptionId) -> Result<WebhookSubscription, ServiceError> {
+        self.require_subscription(id).await
+    }
+
+    async fn list_subscriptions(&self, limit: i64, offset: i64) -> Result<Page<WebhookSubscription>, ServiceError> {
+        let (subscriptions, total) = self.repository.list(limit, offset).await?;
+        Ok(Page::new(subscriptions, total, limit, offset))
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn update_subscription(
+        &self,
+        id: WebhookSubscriptionId,
+        request: UpdateWebhookSubscriptionRequest,
+    ) -> Result<WebhookSubscription, ServiceError> {
+        let request = request.normalize();
+        request.validate().map_err(|e| ServiceError::Validation(e.to_string()))?;
+        if !request.has_updates() {
+            return Err(ServiceError::Validation("No updates provided".to_string()));
+        }
+        if let Some(url) = &request.url {
+            self.validate_url(url).await?;
+        }
+        if let Some(event_types) = &request.event_types {
+            validate_webhook_event_types(event_types).map_err(ServiceError::Validation)?;
+        }
+
+        self.repository
+            .update(
+                id,
+                request.url.as_deref(),
+                request.event_types.as_deref(),
+                request.description.as_deref(),
+                request.is_active,
+            )
+            .await
+            .map_err(subscription_error)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_subscription(&self, id: WebhookSubscriptionId) -> Result<(), ServiceError> {
+        self.repository.delete(id).await.map_err(subscription_error)
+    }
+
+    #[tracing::instrument(skip(self, request))]
+    async fn rotate_secret(
+        &self,
+        id: WebhookSubscriptionId,
+        request: RotateWebhookSecretRequest,
+    ) -> Result<CreatedWebhookSubscription, ServiceError> {
+        request.validate().map_err(|e| ServiceError::Validation(e.to_string()))?;
+
+        let overlap_hours = request.overlap_hours.unwrap_or(self.config.secret_rotation_overlap_hours);
+        let previous_expires_at = (overlap_hours > 0).then(|| Utc::now() + chrono::Duration::hours(overlap_hours as i64));
+
+        let secret = generate_webhook_secret();
+        let subscription = self
+            .repository
+            .rotate_secret(id, &secret, previous_expires_at)
+            .await
+            .map_err(subscription_error)?;
+
+        tracing::info!("Rotated the secret of webhook subscription {} with {} hours of overlap", id, overlap_hours);
+        Ok(CreatedWebhookSubscription { subscription, secret })
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn ping(&self, id: WebhookSubscriptionId) -> Result<WebhookDelivery, ServiceError> {
+        let subscription = self.require_subscription(id).await?;
+
+        // Same envelope as outbox events
+        let event_id = Uuid::new_v4();
+        let envelope = serde_json::json!({
+            "id": event_id,
+            "type": WEBHOOK_PING_EVENT,
+            "timestamp": Utc::now(),
+            "data": { "subscription_id": subscription.id },
+        });
+
+        // Only the status is kept, so pings cannot be used to read other services' responses
+        self.deliver(&subscription, event_id, WEBHOOK_PING_EVENT, 1, &envelope, 0).await
+    }
+
+    #[tracing::instrument(skip(self, envelope))]
+    async fn redeliver(
+        &self,
+        id: WebhookSubscriptionId,
+        event_id: Uuid,
+        event_type: &str,
+        envelope: &serde_json::Value,
+        attempt: i32,
+    ) -> Result<WebhookDelivery, ServiceError> {
+        let subscription = self.require_subscription(id).await?;
+        if !subscription.is_active {
+            return Err(ServiceError::Conflict("Webhook subscription is inactive".to_string()));
+        }
+
+        self.deliver(&subscription, event_id, event_type, attempt, envelope, MAX_DELIVERY_RESPONSE_BYTES).await
+    }
+
+    async fn list_deliveries(
+        &self,
+        id: WebhookSubscriptionId,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Page<WebhookDelivery>, ServiceError> {
+        self.require_subscription(id).await?;
+
+        let (deliveries, total) = self.repository.list_deliveries(id, limit, offset).await?;
+        Ok(Page::new(deliveries, total, limit, offset))
+    }
+
+    async fn dispatch(&self, event: &OutboxEvent) -> Result<Vec<WebhookDelivery>, ServiceError> {
+        let subscriptions = self
+            .repository
+            .find_undelivered(event.id, &event.event_type, event.created_at)
+            .await?;
+        if subscriptions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let envelope = event.envelope();
+        let deliveries = futures::future::join_all(
+            subscriptions
+                .iter()
+                .map(|subscription| self.deliver(subscription, event.id, &event.event_type, event.attempts, &envelope, MAX_DELIVERY_RESPONSE_BYTES)),
+        )
+        .await;
+
+        deliveries.into_iter().collect()
+    }
+
+    async fn purge_deliveries(&self) -> Result<u64, ServiceError> {
+        let retention = chrono::Duration::days(self.config.delivery_log_retention_days as i64);
+        let purged = self.repository.purge_deliveries(Utc::now() - retention).await?;
+        if purged > 0 {
+            tracing::info!("Purged {} webhook delivery log entries", purged);
+        }
+
+        Ok(purged)
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+use crate::config::WebSocketConfig;
+use crate::models::{ClientMessage, DomainEventKind, ServerMessage, StreamEvent, WebSocketErrorCode};
+
+/// Open WebSocket sessions of this instance
+///
+/// Caps how many sockets are open at once and lets shutdown ask every
+/// session to close and wait for them to finish.
+pub struct WebSocketSessions {
+    config: WebSocketConfig,
+    open: watch::Sender<usize>,
+    closing: watch::Sender<bool>,
+}
+
+impl WebSocketSessions {
+    pub fn new(config: WebSocketConfig) -> Self {
+        Self {
+            config,
+            open: watch::channel(0).0,
+            closing: watch::channel(false).0,
+        }
+    }
+
+    pub fn config(&self) -> &WebSocketConfig {
+        &self.config
+    }
+
+    /// Start a session, unless the connection limit is reached or sessions are closing
+    pub fn open(self: &Arc<Self>) -> Option<WebSocketSession> {
+        if *self.closing.borrow() {
+            return None;
+        }
+
+        let max_connections = self.config.max_connections;
+        let opened = self.open.send_if_modified(|open| {
+            if *open < max_connections {
+                *open += 1;
+                true
+            } else {
+                false
+            }
+        });
+
+        opened.then(|| WebSocketSession {
+            sessions: self.clone(),
+            subscriptions: BTreeMap::new(),
+            closing: self.closing.subscribe(),
+        })
+    }
+
+    /// Number of sessions currently open
+    pub fn open_count(&self) -> usize {
+        *self.open.borrow()
+    }
+
+    /// Ask every session to close and wait up to `timeout` for them to finish
+    ///
+    /// Returns the number of sessions still open.
+    pub async fn close_all(&self, timeout: Duration) -> usize {
+        self.closing.send_replace(true);
+
+        let mut open = self.open.subscribe();
+        let _ = tokio::time::timeout(timeout, open.wait_for(|open| *open == 0)).await;
+        self.open_count()
+    }
+}
+
+/// Subscriptions and protocol state of one open socket
+///
+/// Dropping the session frees its connection slot.
+pub struct WebSocketSession {
+    sessions: Arc<WebSocketSessions>,
+    /// Event kinds per subscription ID; empty for every kind
+    subscriptions: BTreeMap<String, Vec<DomainEventKind>>,
+    closing: watch::Receiver<bool>,
+}
+
+impl WebSocketSession {
+    /// Apply a text message from the client and return the reply
+    pub fn handle(&mut self, text: &str) -> ServerMessage {
+        let message = match serde_json::from_str::<ClientMessage>(text) {
+            Ok(message) => message,
+            Err(e) => {
+                return ServerMessage::error(None, WebSocketErrorCode::InvalidMessage, format!("Invalid message: {}", e))
+            }
+        };
+
+        match message {
+            ClientMessage::Subscribe { id, types } => self.subscribe(id, types),
+            ClientMessage::Unsubscribe { id } => match self.subscriptions.remove(&id) {
+                Some(_) => ServerMessage::Unsubscribed { id },
+                None => ServerMessage::error(
+                    Some(id),
+                    WebSocketErrorCode::SubscriptionNotFound,
+                    "No subscription with this ID",
+                ),
+            },
+            ClientMessage::Ping { nonce } => ServerMessage::Pong { nonce },
+        }
+    }
+
+    fn subscribe(&mut self, id: String, types: Vec<String>) -> ServerMessage {
+        if self.subscriptions.contains_key(&id) {
+            return ServerMessage::error(
+                Some(id),
+                WebSocketErrorCode::SubscriptionExists,
+                "A subscription with this ID already exists",
+            );
+        }
+
+        let max_subscriptions = self.sessions.config.max_subscriptions;
+        if self.subscriptions.len() >= max_subscriptions {
+            return ServerMessage::error(
+                Some(id),
+                WebSocketErrorCode::TooManySubscriptions,
+                format!("At most {} subscriptions are allowed per connection", max_subscriptions),
+            );
+        }
+
+        let mut kinds = Vec::with_capacity(types.len());
+        for name in &types {
+            match DomainEventKind::parse(name) {
+                Some(kind) if !kinds.contains(&kind) => kinds.push(kind),
+                Some(_) => {}
+                None => {
+                    return ServerMessage::error(
+                        Some(id),
+                        WebSocketErrorCode::UnknownEventType,
+                        format!("Unknown event type '{}'", name),
+                    )
+                }
+            }
+        }
+
+        let types = kinds.iter().map(|kind| kind.as_str().to_string()).collect();
+        self.subscriptions.insert(id.clone(), kinds);
+        ServerMessage::Subscribed { id, types }
+    }
+
+    /// Message delivering an event to the subscriptions that want it, if any
+    pub fn event_message(&self, event: &StreamEvent) -> Option<ServerMessage> {
+        let subscriptions: Vec<String> = self
+            .subscriptions
+            .iter()
+            .filter(|(_, kinds)| kinds.is_empty() || kinds.iter().any(|kind| kind.as_str() == event.event_type))
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        (!subscriptions.is_empty()).then(|| ServerMessage::Event {
+            subscriptions,
+            event: event.clone(),
+        })
+    }
+
+    /// Wait until the session is asked to close for shutdown
+    pub async fn closing(&mut self) {
+        // The sender lives as long as the sessions this one holds on to
+        let _ = self.closing.wait_for(|closing| *closing).await;
+    }
+}
+
+impl Drop for WebSocketSession {
+    fn drop(&mut self) {
+        self.sessions.open.send_modify(|open| *open -= 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AuditContext, DomainEvent, EventEnvelope, User};
+    use chrono::Utc;
+
+    fn sessions(max_connections: usize, max_subscriptions: usize) -> Arc<WebSocketSessions> {
+        Arc::new(WebSocketSessions::new(WebSocketConfig {
+            max_connections,
+            max_subscriptions,
+            ..WebSocketConfig::default()
+        }))
+    }
+
+    fn event(event: fn(User) -> DomainEvent) -> StreamEvent {
+        let user = User {
+            id: uuid::Uuid::new_v4(),
+            name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+            metadata: serde_json::json!({}),
+            avatar_key: None,
+            avatar_url: None,
+        };
+        StreamEvent::from_envelope(&EventEnvelope::new(event(user), &AuditContext::system()))
+    }
+
+    #[test]
+    fn test_events_go_to_matching_subscriptions() {
+        let mut session = sessions(1, 2).open().unwrap();
+        assert_eq!(
+            session.handle(r#"{"type": "subscribe", "id": "created", "types": ["user.created"]}"#),
+            ServerMessage::Subscribed {
+                id: "created".to_string(),
+                types: vec!["user.created".to_string()],
+            }
+        );
+        session.handle(r#"{"type": "subscribe", "id": "all"}"#);
+
+        let created = event(|user| DomainEvent::UserCreated { user });
+        let Some(ServerMessage::Event { subscriptions, .. }) = session.event_message(&created) else {
+            panic!("expected an event message");
+        };
+        assert_eq!(subscriptions, vec!["all", "created"]);
+
+        session.handle(r#"{"type": "unsubscribe", "id": "all"}"#);
+        assert!(session.event_message(&event(|user| DomainEvent::UserDeleted { user })).is_none());
+    }
+
+    #[test]
+    fn test_invalid_commands_are_rejected() {
+        let mut session = sessions(1, 1).open().unwrap();
+        let code = |message: ServerMessage| match message {
+            ServerMessage::Error { code, .. } => Some(code),
+            _ => None,
+        };
+
+        assert_eq!(code(session.handle("not json")), Some(WebSocketErrorCode::InvalidMessage));
+        assert_eq!(
+            code(session.handle(r#"{"type": "subscribe", "id": "a", "types": ["group.created"]}"#)),
+            Some(WebSocketErrorCode::UnknownEventType)
+        );
+        assert_eq!(code(session.handle(r#"{"type": "subscribe", "id": "a"}"#)), None);
+        assert_eq!(
+            code(session.handle(r#"{"type": "subscribe", "id": "a"}"#)),
+            Some(WebSocketErrorCode::SubscriptionExists)
+        );
+        assert_eq!(
+            code(session.handle(r#"{"type": "subscribe", "id": "b"}"#)),
+            Some(WebSocketErrorCode::TooManySubscriptions)
+        );
+        assert_eq!(
+            code(session.handle(r#"{"type": "unsubscribe", "id": "b"}"#)),
+            Some(WebSocketErrorCode::SubscriptionNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connection_limit_and_close_all() {
+        let sessions = sessions(1, 1);
+        let mut session = sessions.open().unwrap();
+        assert!(sessions.open().is_none());
+
+        let task = tokio::spawn(async move {
+            session.closing().await;
+        });
+        assert_eq!(sessions.close_all(Duration::from_secs(5)).await, 0);
+        task.await.unwrap();
+
+        // Closed sessions free their slot, but no new ones open during shutdown
+        assert_eq!(sessions.open_count(), 0);
+        assert!(sessions.open().is_none());
+    }
+}
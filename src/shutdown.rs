@@ -88,6 +88,9 @@ pub enum ShutdownError {
     
     #[error("Resource cleanup error: {0}")]
     ResourceCleanup(String),
+
+    #[error("Background task shutdown error: {0}")]
+    BackgroundTask(String),
 }
 
 /// Shutdown coordinator that manages the shutdown sequence for all application components
@@ -391,6 +394,262 @@ impl ShutdownComponent for TracingShutdown {
     }
 }
 
+/// Periodic background task that stops cleanly during shutdown
+pub struct BackgroundTask {
+    name: String,
+    stop: tokio::sync::watch::Sender<bool>,
+    handle: Option<tokio::task::JoinHandle<()>>,
+    stop_timeout: Duration,
+}
+
+impl BackgroundTask {
+    /// Run `tick` every `period`, starting one period from now
+    ///
+    /// A tick in progress when shutdown starts is allowed to finish.
+    pub fn spawn_periodic<F, Fut>(name: impl Into<String>, period: Duration, mut tick: F) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        let (stop, mut stopped) = tokio::sync::watch::channel(false);
+        let task_name = name.clone();
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately
+            interval.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => tick().await,
+                    _ = stopped.changed() => break,
+                }
+            }
+            info!("Background task '{}' stopped", task_name);
+        });
+
+        info!("Started background task '{}' every {:?}", name, period);
+        Self {
+            name,
+            stop,
+            handle: Some(handle),
+            stop_timeout: Duration::from_secs(10), // Default 10 second timeout for the current tick
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = timeout;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl ShutdownComponent for BackgroundTask {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn shutdown(&mut self) -> Result<(), ShutdownError> {
+        let Some(mut handle) = self.handle.take() else {
+            warn!("Background task '{}' already stopped", self.name);
+            return Ok(());
+        };
+
+        // The task may already have exited, in which case nobody is listening
+        let _ = self.stop.send(true);
+
+        match tokio::time::timeout(self.stop_timeout, &mut handle).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(ShutdownError::BackgroundTask(format!("{} failed: {}", self.name, e))),
+            Err(_) => {
+                warn!("Background task '{}' did not stop within {:?}, aborting it", self.name, self.stop_timeout);
+                handle.abort();
+                Err(ShutdownError::BackgroundTask(format!("{} did not stop in time", self.name)))
+            }
+        }
+    }
+}
+
+/// Domain event bus shutdown component
+///
+/// Stops handing out new events and lets handlers finish the queued ones.
+pub struct EventBusShutdown {
+    event_bus: Option<std::sync::Arc<crate::services::EventBus>>,
+    drain_timeout: Duration,
+}
+
+impl EventBusShutdown {
+    pub fn new(event_bus: std::sync::Arc<crate::services::EventBus>) -> Self {
+        Self {
+            event_bus: Some(event_bus),
+            drain_timeout: Duration::from_secs(10), // Default 10 second timeout for queued events
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl ShutdownComponent for EventBusShutdown {
+    fn name(&self) -> &str {
+        "Event Bus"
+    }
+
+    async fn shutdown(&mut self) -> Result<(), ShutdownError> {
+        let Some(event_bus) = self.event_bus.take() else {
+            warn!("Event bus already shut down");
+            return Ok(());
+        };
+
+        info!("Draining event handler queues with timeout of {:?}", self.drain_timeout);
+        let aborted = event_bus.shutdown(self.drain_timeout).await;
+        if aborted.is_empty() {
+            Ok(())
+        } else {
+            Err(ShutdownError::BackgroundTask(format!(
+                "Event handlers did not finish in time: {}",
+                aborted.join(", ")
+            )))
+        }
+    }
+}
+
+/// Domain event stream shutdown component
+///
+/// Ends open event streams so their connections do not hold up the HTTP
+/// server drain, then stops listening for events.
+pub struct EventStreamShutdown {
+    event_stream: Option<std::sync::Arc<crate::services::EventStream>>,
+    close_timeout: Duration,
+}
+
+impl EventStreamShutdown {
+    pub fn new(event_stream: std::sync::Arc<crate::services::EventStream>) -> Self {
+        Self {
+            event_stream: Some(event_stream),
+            close_timeout: Duration::from_secs(5), // Default 5 second timeout for the listener to stop
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.close_timeout = timeout;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl ShutdownComponent for EventStreamShutdown {
+    fn name(&self) -> &str {
+        "Event Stream"
+    }
+
+    async fn shutdown(&mut self) -> Result<(), ShutdownError> {
+        let Some(event_stream) = self.event_stream.take() else {
+            warn!("Event stream already closed");
+            return Ok(());
+        };
+
+        info!("Closing {} event stream subscriptions", event_stream.subscriber_count());
+        tokio::time::timeout(self.close_timeout, event_stream.close())
+            .await
+            .map_err(|_| ShutdownError::BackgroundTask("Event stream listener did not stop in time".to_string()))
+    }
+}
+
+/// WebSocket sessions shutdown component
+///
+/// Sends every open socket a close frame with the "going away" code so
+/// clients know to reconnect elsewhere, and waits for them to close.
+pub struct WebSocketShutdown {
+    sessions: Option<std::sync::Arc<crate::services::WebSocketSessions>>,
+    close_timeout: Duration,
+}
+
+impl WebSocketShutdown {
+    pub fn new(sessions: std::sync::Arc<crate::services::WebSocketSessions>) -> Self {
+        Self {
+            sessions: Some(sessions),
+            close_timeout: Duration::from_secs(5), // Default 5 second timeout for sockets to close
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.close_timeout = timeout;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl ShutdownComponent for WebSocketShutdown {
+    fn name(&self) -> &str {
+        "WebSocket Sessions"
+    }
+
+    async fn shutdown(&mut self) -> Result<(), ShutdownError> {
+        let Some(sessions) = self.sessions.take() else {
+            warn!("WebSocket sessions already closed");
+            return Ok(());
+        };
+
+        info!("Closing {} WebSocket sessions with timeout of {:?}", sessions.open_count(), self.close_timeout);
+        match sessions.close_all(self.close_timeout).await {
+            0 => Ok(()),
+            open => Err(ShutdownError::BackgroundTask(format!(
+                "{} WebSocket sessions did not close in time",
+                open
+            ))),
+        }
+    }
+}
+
+/// User import jobs shutdown component
+///
+/// Interrupts background imports after their current batch and marks the
+/// jobs failed, so they do not stay `running` once the instance is gone.
+pub struct UserImportShutdown {
+    import_service: Option<std::sync::Arc<dyn crate::services::UserImportService>>,
+    stop_timeout: Duration,
+}
+
+impl UserImportShutdown {
+    pub fn new(import_service: std::sync::Arc<dyn crate::services::UserImportService>) -> Self {
+        Self {
+            import_service: Some(import_service),
+            stop_timeout: Duration::from_secs(10), // Default 10 second timeout for the current batches
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = timeout;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl ShutdownComponent for UserImportShutdown {
+    fn name(&self) -> &str {
+        "User Imports"
+    }
+
+    async fn shutdown(&mut self) -> Result<(), ShutdownError> {
+        let Some(import_service) = self.import_service.take() else {
+            warn!("User imports already stopped");
+            return Ok(());
+        };
+
+        info!("Stopping user import jobs with timeout of {:?}", self.stop_timeout);
+        let interrupted = import_service.shutdown(self.stop_timeout).await;
+        if !interrupted.is_empty() {
+            warn!("Interrupted {} user import jobs", interrupted.len());
+        }
+        Ok(())
+    }
+}
+
 /// Resource cleanup utilities for proper resource disposal
 pub struct ResourceCleanup;
 
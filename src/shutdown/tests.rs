@@ -1,5 +1,5 @@
 use super::*;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::time::Duration;
 
@@ -172,4 +172,27 @@ async fn test_shutdown_component_timeouts() {
     let result = coordinator.shutdown_all().await;
     assert!(result.is_ok());
     assert!(shutdown_called.load(Ordering::SeqCst));
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_background_task_runs_until_shutdown() {
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let counter = ticks.clone();
+    let mut task = BackgroundTask::spawn_periodic("ticker", Duration::from_millis(10), move || {
+        let counter = counter.clone();
+        async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    tokio::time::sleep(Duration::from_millis(55)).await;
+    assert!(task.shutdown().await.is_ok());
+
+    let stopped_at = ticks.load(Ordering::SeqCst);
+    assert!(stopped_at > 0);
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), stopped_at);
+
+    // A second shutdown is a no-op
+    assert!(task.shutdown().await.is_ok());
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use super::{check_key, BlobStore, BlobStoreError};
+
+/// Blob store backed by a directory on the local filesystem
+pub struct LocalBlobStore {
+    root: PathBuf,
+    public_base_url: String,
+}
+
+impl LocalBlobStore {
+    pub fn new<P: AsRef<Path>>(root: P, public_base_url: String) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            public_base_url,
+        }
+    }
+
+    /// Directory blobs are written to
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, BlobStoreError> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> Result<(), BlobStoreError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&path, data).await?;
+        debug!("Stored blob {} at {}", key, path.display());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobStoreError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => {
+                debug!("Deleted blob {}", key);
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_base_url.trim_end_matches('/'), key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalBlobStore::new(dir.path(), "/avatars/".to_string());
+
+        store.put("1/a.png", b"png".to_vec(), "image/png").await.unwrap();
+        assert_eq!(std::fs::read(dir.path().join("1/a.png")).unwrap(), b"png");
+        assert_eq!(store.url("1/a.png"), "/avatars/1/a.png");
+
+        store.delete("1/a.png").await.unwrap();
+        assert!(!dir.path().join("1/a.png").exists());
+        store.delete("1/a.png").await.unwrap();
+
+        assert!(store.put("../escape", Vec::new(), "image/png").await.is_err());
+    }
+}
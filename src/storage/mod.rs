@@ -0,0 +1,95 @@
+//! Blob storage for user-uploaded files
+//!
+//! Files are addressed by slash-separated keys such as
+//! `avatars/<user id>/<file>` and stored through a [`BlobStore`].
+
+pub mod local;
+pub mod s3;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::config::BlobStoreConfig;
+
+pub use local::LocalBlobStore;
+pub use s3::S3BlobStore;
+
+/// Blob storage errors
+#[derive(Debug, thiserror::Error)]
+pub enum BlobStoreError {
+    #[error("Invalid blob key: {0}")]
+    InvalidKey(String),
+
+    #[error("Blob storage I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Blob storage request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Blob storage returned {status}: {message}")]
+    Status { status: u16, message: String },
+}
+
+/// Storage backend for uploaded files
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Store a blob, replacing any blob with the same key
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<(), BlobStoreError>;
+
+    /// Delete a blob; deleting a missing blob is not an error
+    async fn delete(&self, key: &str) -> Result<(), BlobStoreError>;
+
+    /// Public URL a stored blob is served from
+    fn url(&self, key: &str) -> String;
+}
+
+/// Create the blob store described by the configuration
+pub fn from_config(config: &BlobStoreConfig) -> Arc<dyn BlobStore> {
+    match config {
+        BlobStoreConfig::Local { root, public_base_url } => {
+            Arc::new(LocalBlobStore::new(root, public_base_url.clone()))
+        }
+        BlobStoreConfig::S3 {
+            endpoint,
+            bucket,
+            region,
+            access_key_id,
+            secret_access_key,
+            public_base_url,
+        } => Arc::new(S3BlobStore::new(
+            endpoint.clone(),
+            bucket.clone(),
+            region.clone(),
+            access_key_id.clone(),
+            secret_access_key.clone(),
+            public_base_url.clone(),
+        )),
+    }
+}
+
+/// Reject keys that are empty or could escape the store's namespace
+fn check_key(key: &str) -> Result<(), BlobStoreError> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+        && !key.contains('\\');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(BlobStoreError::InvalidKey(key.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_key() {
+        assert!(check_key("avatars/1/a.png").is_ok());
+        assert!(check_key("").is_err());
+        assert!(check_key("/avatars/a.png").is_err());
+        assert!(check_key("avatars/../secret").is_err());
+        assert!(check_key("avatars//a.png").is_err());
+    }
+}
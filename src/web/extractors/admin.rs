@@ -0,0 +1,32 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+
+use super::group_permission::{authenticated_user, require_group};
+use crate::models::CurrentUser;
+use crate::web::{responses::AppError, router::AppState};
+
+/// Extractor for an authenticated administrator
+///
+/// Administrators are the members of the group set as `admin.group_id`.
+pub struct Admin(pub CurrentUser);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        require_group(authenticated_user(parts)?, state.config.admin.group_id, "administrator").map(Admin)
+    }
+}
+
+/// Whether a user is an administrator, for handlers that also serve other callers
+pub fn is_admin(current_user: &CurrentUser, state: &AppState) -> bool {
+    state
+        .config
+        .admin
+        .group_id
+        .is_some_and(|group_id| current_user.is_group_member(group_id))
+}
@@ -0,0 +1,27 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+use std::convert::Infallible;
+
+use crate::models::{AuditContext, CurrentUser};
+
+/// Extractor for the actor and correlation ID recorded with audited changes
+///
+/// The correlation ID is set by the request ID middleware; both parts are
+/// left empty when it or authentication did not run.
+#[async_trait]
+impl<S> FromRequestParts<S> for AuditContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(AuditContext::new(
+            parts.extensions.get::<CurrentUser>().map(|user| user.id),
+            parts.extensions.get::<String>().cloned(),
+        ))
+    }
+}
@@ -0,0 +1,23 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+
+use super::group_permission::{authenticated_user, require_group};
+use crate::models::CurrentUser;
+use crate::web::{responses::AppError, router::AppState};
+
+/// Extractor for an authenticated user allowed to read the audit log
+///
+/// Auditors are the members of the group set as `audit.auditor_group_id`.
+pub struct Auditor(pub CurrentUser);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Auditor {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        require_group(authenticated_user(parts)?, state.config.audit.auditor_group_id, "auditor").map(Auditor)
+    }
+}
@@ -0,0 +1,31 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+
+use super::group_permission::{authenticated_user, require_group};
+use crate::models::CurrentUser;
+use crate::web::{responses::AppError, router::AppState};
+
+/// Extractor for an authenticated user allowed to subscribe to domain events
+///
+/// Subscribers are the members of the group set as `event_stream.subscriber_group_id`.
+pub struct EventSubscriber(pub CurrentUser);
+
+#[async_trait]
+impl FromRequestParts<AppState> for EventSubscriber {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        EventSubscriber::authorize(authenticated_user(parts)?, state)
+    }
+}
+
+impl EventSubscriber {
+    /// Check that an authenticated user may subscribe to domain events
+    pub fn authorize(current_user: CurrentUser, state: &AppState) -> Result<Self, AppError> {
+        require_group(current_user, state.config.event_stream.subscriber_group_id, "event subscriber")
+            .map(EventSubscriber)
+    }
+}
@@ -0,0 +1,70 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::marker::PhantomData;
+
+use crate::models::{ApiResponse, Fieldset, Included, Projectable, Resource};
+use crate::web::responses::AppError;
+
+#[derive(Debug, Deserialize)]
+struct FieldsQuery {
+    fields: Option<String>,
+    include: Option<String>,
+}
+
+/// Extractor for the `fields` and `include` query parameters of resource `R`
+///
+/// Unknown field and relation names are rejected with a validation error.
+#[derive(Debug, Clone)]
+pub struct Fields<R> {
+    pub fieldset: Fieldset,
+    resource: PhantomData<fn() -> R>,
+}
+
+impl<R> std::ops::Deref for Fields<R> {
+    type Target = Fieldset;
+
+    fn deref(&self) -> &Fieldset {
+        &self.fieldset
+    }
+}
+
+#[async_trait]
+impl<S, R> FromRequestParts<S> for Fields<R>
+where
+    S: Send + Sync,
+    R: Resource,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<FieldsQuery>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| AppError::validation(e.body_text()))?;
+        let fieldset = Fieldset::parse::<R>(query.fields.as_deref(), query.include.as_deref())
+            .map_err(AppError::Validation)?;
+
+        Ok(Self {
+            fieldset,
+            resource: PhantomData,
+        })
+    }
+}
+
+impl<R: Resource> Fields<R> {
+    /// Shape a response's data to the requested fieldset
+    pub fn project<T>(&self, response: ApiResponse<T>, included: &Included) -> Result<Json<ApiResponse<Value>>, AppError>
+    where
+        T: Projectable<Resource = R>,
+    {
+        response
+            .project(&self.fieldset, included)
+            .map(Json)
+            .map_err(|e| AppError::generic(format!("Failed to serialize response: {}", e)))
+    }
+}
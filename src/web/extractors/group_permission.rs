@@ -0,0 +1,31 @@
+use axum::http::request::Parts;
+
+use crate::models::{CurrentUser, GroupId};
+use crate::web::responses::AppError;
+
+/// The caller identified by the authentication middleware
+pub(crate) fn authenticated_user(parts: &Parts) -> Result<CurrentUser, AppError> {
+    parts
+        .extensions
+        .get::<CurrentUser>()
+        .cloned()
+        .ok_or_else(|| AppError::authentication("Authentication required"))
+}
+
+/// Check that a user holds a permission granted by membership of a group
+///
+/// `group_id` is the configured group, if any; without one nobody holds the
+/// permission. `permission` names it in the error, such as "auditor".
+pub(crate) fn require_group(
+    current_user: CurrentUser,
+    group_id: Option<GroupId>,
+    permission: &str,
+) -> Result<CurrentUser, AppError> {
+    match group_id {
+        Some(group_id) if current_user.is_group_member(group_id) => Ok(current_user),
+        _ => {
+            tracing::warn!("User {} does not have the {} permission", current_user.id, permission);
+            Err(AppError::authorization(format!("This operation requires the {} permission", permission)))
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+
+use std::future::Future;
+
+use crate::web::responses::AppError;
+
+/// Format a resource version as a strong entity tag
+pub fn version_etag(version: i64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Format a resource version as a weak entity tag, for partial representations
+///
+/// `If-Match` never accepts weak tags, so they cannot be used for updates.
+pub fn weak_version_etag(version: i64) -> String {
+    format!("W/{}", version_etag(version))
+}
+
+/// Extractor for the `If-Match` request header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    /// No `If-Match` header was sent
+    Absent,
+    /// `If-Match: *`
+    Any,
+    /// Strong entity tags that parsed as resource versions
+    Versions(Vec<i64>),
+}
+
+impl IfMatch {
+    /// Parse an `If-Match` header value
+    ///
+    /// Weak and malformed tags are dropped since they can never match under
+    /// the strong comparison `If-Match` requires.
+    pub fn parse(value: &str) -> Self {
+        if value.trim() == "*" {
+            return IfMatch::Any;
+        }
+
+        let versions = value
+            .split(',')
+            .map(str::trim)
+            .filter_map(|tag| tag.strip_prefix('"')?.strip_suffix('"'))
+            .filter_map(|tag| tag.parse::<i64>().ok())
+            .collect();
+
+        IfMatch::Versions(versions)
+    }
+
+    /// Resolve the version an update or delete must match
+    ///
+    /// Returns `None` when any version is acceptable. A list of tags is
+    /// checked against `current`, which is only loaded in that case, and
+    /// resolves to the current version when one of them matches it so the
+    /// write still fails if the resource changes in the meantime.
+    pub async fn expected_version<F, Fut>(&self, required: bool, current: F) -> Result<Option<i64>, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<i64, AppError>>,
+    {
+        match self {
+            IfMatch::Absent if required => Err(AppError::precondition_required(
+                "This request must include an If-Match header",
+            )),
+            IfMatch::Absent | IfMatch::Any => Ok(None),
+            IfMatch::Versions(versions) => match versions.as_slice() {
+                [] => Err(version_mismatch()),
+                [version] => Ok(Some(*version)),
+                _ => {
+                    let current = current().await?;
+                    if versions.contains(&current) {
+                        Ok(Some(current))
+                    } else {
+                        Err(version_mismatch())
+                    }
+                }
+            },
+        }
+    }
+}
+
+fn version_mismatch() -> AppError {
+    AppError::precondition_failed("If-Match does not match the current version")
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IfMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match parts.headers.get(header::IF_MATCH) {
+            Some(value) => value
+                .to_str()
+                .map(IfMatch::parse)
+                .map_err(|_| AppError::validation("If-Match header is not valid ASCII")),
+            None => Ok(IfMatch::Absent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_if_match() {
+        assert_eq!(IfMatch::parse("*"), IfMatch::Any);
+        assert_eq!(IfMatch::parse("\"3\""), IfMatch::Versions(vec![3]));
+        assert_eq!(IfMatch::parse("W/\"3\", \"4\""), IfMatch::Versions(vec![4]));
+        assert_eq!(IfMatch::parse("\"abc\""), IfMatch::Versions(vec![]));
+    }
+
+    async fn current() -> Result<i64, AppError> {
+        Ok(8)
+    }
+
+    async fn unused() -> Result<i64, AppError> {
+        panic!("a single tag must not load the current version")
+    }
+
+    #[tokio::test]
+    async fn test_expected_version() {
+        assert_eq!(IfMatch::Absent.expected_version(false, unused).await.unwrap(), None);
+        assert!(matches!(IfMatch::Absent.expected_version(true, unused).await, Err(AppError::PreconditionRequired(_))));
+        assert_eq!(IfMatch::Any.expected_version(true, unused).await.unwrap(), None);
+        assert_eq!(IfMatch::Versions(vec![7]).expected_version(true, unused).await.unwrap(), Some(7));
+        assert!(matches!(IfMatch::Versions(vec![]).expected_version(false, unused).await, Err(AppError::PreconditionFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_expected_version_accepts_any_listed_tag() {
+        let if_match = IfMatch::parse("\"7\", \"8\"");
+        assert_eq!(if_match.expected_version(true, current).await.unwrap(), Some(8));
+
+        let if_match = IfMatch::parse("\"6\", \"7\"");
+        assert!(matches!(if_match.expected_version(true, current).await, Err(AppError::PreconditionFailed(_))));
+    }
+
+    #[test]
+    fn test_version_etag_is_strong() {
+        assert_eq!(version_etag(12), "\"12\"");
+        assert_eq!(IfMatch::parse(&version_etag(12)), IfMatch::Versions(vec![12]));
+        assert_eq!(IfMatch::parse(&weak_version_etag(12)), IfMatch::Versions(vec![]));
+    }
+}
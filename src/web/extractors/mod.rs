@@ -1,6 +1,20 @@
+pub mod admin;
+pub mod audit_context;
+pub mod auditor;
 pub mod current_user;
 pub mod error_context;
+pub mod event_subscriber;
+pub mod fields;
+mod group_permission;
+pub mod if_match;
+pub mod webhook_manager;
 
 pub use error_context::*;
 
+pub use admin::*;
+pub use auditor::*;
 pub use current_user::*;
+pub use event_subscriber::*;
+pub use fields::*;
+pub use if_match::*;
+pub use webhook_manager::*;
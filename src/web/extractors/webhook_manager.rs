@@ -0,0 +1,24 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+
+use super::group_permission::{authenticated_user, require_group};
+use crate::models::CurrentUser;
+use crate::web::{responses::AppError, router::AppState};
+
+/// Extractor for an authenticated user allowed to manage webhook subscriptions
+///
+/// Managers are the members of the group set as `webhooks.manager_group_id`.
+pub struct WebhookManager(pub CurrentUser);
+
+#[async_trait]
+impl FromRequestParts<AppState> for WebhookManager {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        require_group(authenticated_user(parts)?, state.config.webhooks.manager_group_id, "webhook manager")
+            .map(WebhookManager)
+    }
+}
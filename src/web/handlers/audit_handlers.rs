@@ -0,0 +1,158 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt};
+use serde::Deserialize;
+
+use crate::models::{ApiResponse, AuditChainReport, AuditCheckpoint, AuditLog, AuditLogFilter, AuditLogPage, DataFormat, UserId};
+use crate::web::{extractors::Auditor, responses::AppError, router::AppState};
+
+/// Query parameters for searching the audit log
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    /// User who performed the action
+    pub actor: Option<UserId>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub action: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+/// Query parameters for a resource's history
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub cursor: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+impl AuditLogQuery {
+    fn filter(&self) -> AuditLogFilter {
+        AuditLogFilter {
+            actor: self.actor,
+            resource_type: self.resource_type.clone(),
+            resource_id: self.resource_id.clone(),
+            action: self.action.clone(),
+            from: self.from,
+            to: self.to,
+        }
+    }
+}
+
+/// Search the audit log, newest first, with keyset pagination
+pub async fn list_audit_logs(
+    State(app_state): State<AppState>,
+    Auditor(auditor): Auditor,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<ApiResponse<AuditLogPage>>, AppError> {
+    tracing::debug!("User {} is searching the audit log", auditor.id);
+
+    let page = app_state
+        .audit_service()
+        .query_logs(&query.filter(), query.cursor.as_deref(), query.limit)
+        .await?;
+
+    Ok(Json(ApiResponse::new(page)))
+}
+
+/// Stream the audit log entries matching the filters as CSV
+pub async fn export_audit_logs(
+    State(app_state): State<AppState>,
+    Auditor(auditor): Auditor,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Response, AppError> {
+    let header_row = AuditLog::csv_header()
+        .map_err(|e| AppError::generic(format!("Failed to encode export header: {}", e)))?;
+    let logs = app_state
+        .audit_service()
+        .export_logs(query.filter(), Some(auditor.id))
+        .await?;
+
+    let rows = logs.map(|log| {
+        let log = log.map_err(|e| {
+            tracing::error!("Audit log export aborted: {}", e);
+            std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        })?;
+        log.to_csv_row()
+            .map(Bytes::from)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+    });
+    let body = stream::once(async move { Ok::<_, std::io::Error>(Bytes::from(header_row)) })
+        .chain(rows);
+
+    let filename = format!("audit-logs-{}.csv", Utc::now().format("%Y%m%dT%H%M%SZ"));
+
+    tracing::info!("User {} is exporting the audit log as {}", auditor.id, filename);
+    Ok((
+        [
+            (header::CONTENT_TYPE, DataFormat::Csv.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+/// Walk the hash chain since the latest signed checkpoint and report the first broken link, if any
+///
+/// The whole chain is walked by a daily background job instead, which could
+/// outlast the request timeout.
+pub async fn verify_audit_chain(
+    State(app_state): State<AppState>,
+    Auditor(auditor): Auditor,
+) -> Result<Json<ApiResponse<AuditChainReport>>, AppError> {
+    tracing::info!("User {} is verifying the audit chain", auditor.id);
+
+    let report = app_state.audit_service().verify_chain(false).await?;
+
+    Ok(Json(ApiResponse::new(report)))
+}
+
+/// Download the signed chain checkpoints, to keep a copy outside the database
+pub async fn export_audit_checkpoints(
+    State(app_state): State<AppState>,
+    Auditor(auditor): Auditor,
+) -> Result<([(header::HeaderName, String); 1], Json<Vec<AuditCheckpoint>>), AppError> {
+    let checkpoints = app_state.audit_service().list_checkpoints().await?;
+    let filename = format!("audit-checkpoints-{}.json", Utc::now().format("%Y%m%dT%H%M%SZ"));
+
+    tracing::info!("User {} is exporting {} audit checkpoints", auditor.id, checkpoints.len());
+    Ok((
+        [(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))],
+        Json(checkpoints),
+    ))
+}
+
+/// Audit trail of changes to one user, newest first
+pub async fn get_user_history(
+    State(app_state): State<AppState>,
+    Auditor(_auditor): Auditor,
+    Path(id): Path<UserId>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<ApiResponse<AuditLogPage>>, AppError> {
+    let filter = AuditLogFilter {
+        resource_type: Some("user".to_string()),
+        resource_id: Some(id.to_string()),
+        ..AuditLogFilter::default()
+    };
+
+    let page = app_state
+        .audit_service()
+        .query_logs(&filter, query.cursor.as_deref(), query.limit)
+        .await?;
+
+    Ok(Json(ApiResponse::new(page)))
+}
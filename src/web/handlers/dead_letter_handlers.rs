@@ -0,0 +1,115 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::models::{
+    ApiResponse, DeadLetter, DeadLetterFilter, DeadLetterId, DeadLetterReplay, DeadLetterReplayQueue, Page,
+    ReplayDeadLettersRequest, WebhookSubscriptionId,
+};
+use crate::web::{extractors::WebhookManager, responses::AppError, router::AppState};
+
+/// Query parameters for listing dead letters
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    pub subscription_id: Option<WebhookSubscriptionId>,
+    pub event_type: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+impl DeadLetterQuery {
+    /// Validate query parameters
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.limit < 1 || self.limit > 100 {
+            return Err(AppError::Validation("Limit must be between 1 and 100".to_string()));
+        }
+
+        if self.offset < 0 {
+            return Err(AppError::Validation("Offset must be non-negative".to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn filter(&self) -> DeadLetterFilter {
+        DeadLetterFilter {
+            ids: None,
+            subscription_id: self.subscription_id,
+            event_type: self.event_type.as_ref().map(|t| t.trim().to_lowercase()),
+        }
+    }
+}
+
+/// List dead letters, newest first
+pub async fn list_dead_letters(
+    State(app_state): State<AppState>,
+    WebhookManager(_): WebhookManager,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<ApiResponse<Page<DeadLetter>>>, AppError> {
+    query.validate()?;
+    let page = app_state
+        .dead_letter_service()
+        .list_dead_letters(query.filter(), query.limit, query.offset)
+        .await?;
+
+    Ok(Json(ApiResponse::new(page)))
+}
+
+/// Get a dead letter by ID, including its payload
+pub async fn get_dead_letter(
+    State(app_state): State<AppState>,
+    WebhookManager(_): WebhookManager,
+    Path(id): Path<DeadLetterId>,
+) -> Result<Json<ApiResponse<DeadLetter>>, AppError> {
+    let dead_letter = app_state.dead_letter_service().get_dead_letter(id).await?;
+
+    Ok(Json(ApiResponse::new(dead_letter)))
+}
+
+/// Replay a single dead letter
+pub async fn replay_dead_letter(
+    State(app_state): State<AppState>,
+    WebhookManager(current_user): WebhookManager,
+    Path(id): Path<DeadLetterId>,
+) -> Result<Json<ApiResponse<DeadLetterReplay>>, AppError> {
+    let replay = app_state.dead_letter_service().replay(id).await?;
+
+    tracing::info!("User {} replayed dead letter {}: success={}", current_user.id, id, replay.success);
+    Ok(Json(ApiResponse::new(replay)))
+}
+
+/// Queue the oldest dead letters matching the request for replay
+///
+/// Answered with `202 Accepted` and the queued IDs; replayed letters
+/// disappear from the list, and letters that fail again show the new error.
+pub async fn replay_dead_letters(
+    State(app_state): State<AppState>,
+    WebhookManager(current_user): WebhookManager,
+    Json(request): Json<ReplayDeadLettersRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<DeadLetterReplayQueue>>), AppError> {
+    let queue = app_state.dead_letter_service().queue_replay(request).await?;
+
+    tracing::info!("User {} queued {} dead letters for replay", current_user.id, queue.queued);
+    Ok((StatusCode::ACCEPTED, Json(ApiResponse::new(queue))))
+}
+
+/// Discard a dead letter without replaying it
+pub async fn discard_dead_letter(
+    State(app_state): State<AppState>,
+    WebhookManager(current_user): WebhookManager,
+    Path(id): Path<DeadLetterId>,
+) -> Result<StatusCode, AppError> {
+    app_state.dead_letter_service().discard(id).await?;
+
+    tracing::info!("User {} discarded dead letter {}", current_user.id, id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,124 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderName},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures::stream;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::models::{parse_event_types, DomainEventKind, StreamEvent, EVENT_STREAM_RESET_EVENT};
+use crate::services::EventSubscription;
+use crate::web::{extractors::EventSubscriber, responses::AppError, router::AppState};
+
+/// Query parameters for streaming events
+#[derive(Debug, Deserialize)]
+pub struct EventStreamQuery {
+    /// Comma-separated event types to stream; all of them when absent
+    pub types: Option<String>,
+}
+
+/// Events still to be sent to one stream client
+struct ClientStream {
+    subscription: EventSubscription,
+    replay: VecDeque<Arc<StreamEvent>>,
+    kinds: Vec<DomainEventKind>,
+    reset: bool,
+}
+
+impl ClientStream {
+    fn wants(&self, event: &StreamEvent) -> bool {
+        self.kinds.is_empty() || self.kinds.iter().any(|kind| kind.as_str() == event.event_type)
+    }
+
+    /// Next event for the client, or `None` once the stream should end
+    async fn next(&mut self) -> Option<Arc<StreamEvent>> {
+        loop {
+            let event = match self.replay.pop_front() {
+                Some(event) => event,
+                None => tokio::select! {
+                    _ = self.subscription.closed.wait_for(|closed| *closed) => return None,
+                    received = self.subscription.events.recv() => match received {
+                        Ok(event) => event,
+                        // Ending the stream makes the client reconnect and resume from the buffer
+                        Err(RecvError::Lagged(missed)) => {
+                            tracing::warn!("Ending event stream that fell {} events behind", missed);
+                            return None;
+                        }
+                        Err(RecvError::Closed) => return None,
+                    },
+                },
+            };
+
+            if self.wants(&event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+/// Stream user change events as Server-Sent Events
+///
+/// A client reconnecting with `Last-Event-ID` first gets the buffered events
+/// it missed; if that event is no longer buffered, it gets a `stream.reset`
+/// event instead and should reload the users it shows.
+pub async fn stream_events(
+    State(app_state): State<AppState>,
+    EventSubscriber(current_user): EventSubscriber,
+    Query(query): Query<EventStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let kinds = parse_event_types(query.types.as_deref().unwrap_or_default()).map_err(AppError::Validation)?;
+    let last_event_id = headers
+        .get("last-event-id")
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| Uuid::parse_str(value.trim()).ok())
+                .ok_or_else(|| AppError::Validation("Last-Event-ID must be an event ID".to_string()))
+        })
+        .transpose()?;
+
+    let event_stream = app_state.event_stream();
+    let subscription = event_stream.subscribe(last_event_id);
+    tracing::info!(
+        "User {} subscribed to events, replaying {} (resumed: {})",
+        current_user.id,
+        subscription.replay.len(),
+        subscription.resumed
+    );
+
+    let client = ClientStream {
+        replay: subscription.replay.iter().cloned().collect(),
+        reset: !subscription.resumed,
+        subscription,
+        kinds,
+    };
+    let events = stream::unfold(client, |mut client| async move {
+        if client.reset {
+            client.reset = false;
+            return Some((Ok(Event::default().event(EVENT_STREAM_RESET_EVENT).data("{}")), client));
+        }
+
+        let event = client.next().await?;
+        let sse = Event::default()
+            .id(event.id.to_string())
+            .event(&event.event_type)
+            .json_data(&*event);
+        Some((sse, client))
+    });
+
+    Ok((
+        // Keep reverse proxies from buffering events
+        [(HeaderName::from_static("x-accel-buffering"), "no")],
+        Sse::new(events).keep_alive(KeepAlive::new().interval(event_stream.heartbeat_interval())),
+    )
+        .into_response())
+}
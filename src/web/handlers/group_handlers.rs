@@ -0,0 +1,186 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::models::{
+    ApiResponse, Included, CreateGroupRequest, CurrentUser, Group, GroupId, GroupMember, Page, SetGroupMemberRequest,
+    UpdateGroupRequest, UserGroup, UserId,
+};
+use crate::web::{
+    extractors::{is_admin, Fields},
+    responses::AppError,
+    router::AppState,
+};
+
+/// Query parameters for paginated group listings
+#[derive(Debug, Deserialize)]
+pub struct GroupPageQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+impl GroupPageQuery {
+    /// Validate query parameters
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.limit < 1 || self.limit > 100 {
+            return Err(AppError::Validation("Limit must be between 1 and 100".to_string()));
+        }
+
+        if self.offset < 0 {
+            return Err(AppError::Validation("Offset must be non-negative".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Only owners of the group may manage it
+fn require_owner(current_user: &CurrentUser, group_id: GroupId) -> Result<(), AppError> {
+    if current_user.is_group_owner(group_id) {
+        Ok(())
+    } else {
+        tracing::warn!("User {} is not an owner of group {}", current_user.id, group_id);
+        Err(AppError::authorization("Only group owners can manage this group"))
+    }
+}
+
+/// Create a group owned by the caller
+pub async fn create_group(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Json(request): Json<CreateGroupRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<Group>>), AppError> {
+    let group = app_state.group_service().create_group(request, current_user.id).await?;
+
+    tracing::info!("User {} created group {}", current_user.id, group.id);
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::with_message(group, "Group created successfully".to_string())),
+    ))
+}
+
+/// List groups with pagination
+pub async fn list_groups(
+    State(app_state): State<AppState>,
+    Query(query): Query<GroupPageQuery>,
+    fields: Fields<Group>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    query.validate()?;
+    let page = app_state.group_service().list_groups(query.limit, query.offset).await?;
+
+    fields.project(ApiResponse::new(page), &Included::new())
+}
+
+/// Get a group by ID
+pub async fn get_group(
+    State(app_state): State<AppState>,
+    Path(group_id): Path<GroupId>,
+    fields: Fields<Group>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    let group = app_state.group_service().get_group(group_id).await?;
+
+    fields.project(ApiResponse::new(group), &Included::new())
+}
+
+/// Rename or describe a group
+pub async fn update_group(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path(group_id): Path<GroupId>,
+    Json(request): Json<UpdateGroupRequest>,
+) -> Result<Json<ApiResponse<Group>>, AppError> {
+    require_owner(&current_user, group_id)?;
+    let group = app_state.group_service().update_group(group_id, request).await?;
+
+    tracing::info!("User {} updated group {}", current_user.id, group_id);
+    Ok(Json(ApiResponse::with_message(group, "Group updated successfully".to_string())))
+}
+
+/// Delete a group and its memberships
+pub async fn delete_group(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path(group_id): Path<GroupId>,
+) -> Result<StatusCode, AppError> {
+    require_owner(&current_user, group_id)?;
+    app_state.group_service().delete_group(group_id).await?;
+
+    tracing::info!("User {} deleted group {}", current_user.id, group_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List a group's members with pagination
+pub async fn list_group_members(
+    State(app_state): State<AppState>,
+    Path(group_id): Path<GroupId>,
+    Query(query): Query<GroupPageQuery>,
+) -> Result<Json<ApiResponse<Page<GroupMember>>>, AppError> {
+    query.validate()?;
+    let page = app_state
+        .group_service()
+        .list_members(group_id, query.limit, query.offset)
+        .await?;
+
+    Ok(Json(ApiResponse::new(page)))
+}
+
+/// Add a member to a group or change their role
+pub async fn set_group_member(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path((group_id, user_id)): Path<(GroupId, UserId)>,
+    Json(request): Json<SetGroupMemberRequest>,
+) -> Result<Json<ApiResponse<GroupMember>>, AppError> {
+    require_owner(&current_user, group_id)?;
+    let member = app_state
+        .group_service()
+        .set_member(group_id, user_id, request.role)
+        .await?;
+
+    tracing::info!("User {} set user {} as {:?} of group {}", current_user.id, user_id, member.role, group_id);
+    Ok(Json(ApiResponse::new(member)))
+}
+
+/// Remove a member from a group; members may also leave on their own
+pub async fn remove_group_member(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path((group_id, user_id)): Path<(GroupId, UserId)>,
+) -> Result<StatusCode, AppError> {
+    if current_user.id != user_id {
+        require_owner(&current_user, group_id)?;
+    }
+    app_state.group_service().remove_member(group_id, user_id).await?;
+
+    tracing::info!("User {} removed user {} from group {}", current_user.id, user_id, group_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the groups a user belongs to with pagination
+///
+/// The user and administrators see all of them; anyone else only the groups
+/// they are a member of too.
+pub async fn list_user_groups(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path(user_id): Path<UserId>,
+    Query(query): Query<GroupPageQuery>,
+) -> Result<Json<ApiResponse<Page<UserGroup>>>, AppError> {
+    query.validate()?;
+    let visible_to = (current_user.id != user_id && !is_admin(&current_user, &app_state)).then_some(current_user.id);
+    let page = app_state
+        .group_service()
+        .list_user_groups(user_id, visible_to, query.limit, query.offset)
+        .await?;
+
+    Ok(Json(ApiResponse::new(page)))
+}
@@ -0,0 +1,142 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{encode_import_report, ApiResponse, DataFormat, ImportJob, ImportMode, UserImportOptions};
+use crate::services::UserImportOutcome;
+use crate::web::{
+    extractors::Admin,
+    responses::AppError,
+    router::AppState,
+};
+
+/// Query parameters for importing users
+#[derive(Debug, Deserialize)]
+pub struct ImportUsersQuery {
+    pub format: DataFormat,
+    /// How to treat emails that already exist; defaults to skipping them
+    #[serde(default)]
+    pub mode: ImportMode,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Query parameters for downloading an import report
+#[derive(Debug, Deserialize)]
+pub struct ImportReportQuery {
+    pub format: Option<DataFormat>,
+}
+
+/// Read the uploaded file, refusing bodies over `limit` bytes
+async fn read_upload(body: Body, limit: usize) -> Result<Vec<u8>, AppError> {
+    let mut chunks = body.into_data_stream();
+    let mut data = Vec::new();
+
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|e| AppError::validation(format!("Failed to read upload: {}", e)))?;
+        if data.len() + chunk.len() > limit {
+            return Err(AppError::payload_too_large(format!("Import file must not exceed {} bytes", limit)));
+        }
+        data.extend_from_slice(&chunk);
+    }
+
+    if data.is_empty() {
+        return Err(AppError::validation("Import file is empty"));
+    }
+    Ok(data)
+}
+
+/// Import users from a CSV or NDJSON upload
+///
+/// Small files are imported inline and answered with the full report; larger
+/// ones are queued and answered with `202 Accepted` and the job to poll.
+/// Limited to administrators, as imports can create and overwrite any user.
+pub async fn import_users(
+    State(app_state): State<AppState>,
+    Admin(current_user): Admin,
+    Query(query): Query<ImportUsersQuery>,
+    body: Body,
+) -> Result<Response, AppError> {
+    let data = read_upload(body, app_state.config.user_import.max_body_bytes).await?;
+    let options = UserImportOptions {
+        format: query.format,
+        mode: query.mode,
+        dry_run: query.dry_run,
+    };
+
+    tracing::info!(
+        "User {} is importing users from a {} byte {:?} file",
+        current_user.id,
+        data.len(),
+        options.format
+    );
+    let outcome = app_state
+        .user_import_service()
+        .import_users(&data, options, Some(current_user.id))
+        .await?;
+
+    Ok(match outcome {
+        UserImportOutcome::Completed(result) => {
+            let message = if result.job.dry_run { "Import validated (dry run)" } else { "Import completed" };
+            Json(ApiResponse::with_message(result, message.to_string())).into_response()
+        }
+        UserImportOutcome::Queued(job) => (
+            StatusCode::ACCEPTED,
+            [(header::LOCATION, format!("/api/v1/users/imports/{}", job.id))],
+            Json(ApiResponse::with_message(job, "Import queued".to_string())),
+        )
+            .into_response(),
+    })
+}
+
+/// Get the status of an import job
+pub async fn get_import_job(
+    State(app_state): State<AppState>,
+    Admin(_): Admin,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ImportJob>>, AppError> {
+    let job = app_state
+        .user_import_service()
+        .get_import_job(job_id)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("Import job {} not found", job_id)))?;
+
+    Ok(Json(ApiResponse::new(job)))
+}
+
+/// Download the per-row report of a finished import job
+pub async fn get_import_report(
+    State(app_state): State<AppState>,
+    Admin(_): Admin,
+    Path(job_id): Path<Uuid>,
+    Query(query): Query<ImportReportQuery>,
+) -> Result<Response, AppError> {
+    let service = app_state.user_import_service();
+    if service.get_import_job(job_id).await?.is_none() {
+        return Err(AppError::not_found(format!("Import job {} not found", job_id)));
+    }
+    let report = service
+        .get_import_report(job_id)
+        .await?
+        .ok_or_else(|| AppError::conflict(format!("Import job {} has not finished yet", job_id)))?;
+
+    let format = query.format.unwrap_or(DataFormat::Csv);
+    let body = encode_import_report(format, &report)
+        .map_err(|e| AppError::generic(format!("Failed to encode import report: {}", e)))?;
+    let filename = format!("user-import-{}.{}", job_id, format.extension());
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    )
+        .into_response())
+}
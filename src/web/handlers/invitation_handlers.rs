@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::models::{
+    AcceptInvitationRequest, ApiResponse, AuditContext, CreateInvitationRequest, Invitation, InvitationId, InvitationStatus, Page,
+    User,
+};
+use crate::web::{extractors::Admin, responses::AppError, router::AppState};
+
+/// Query parameters for listing invitations
+#[derive(Debug, Deserialize)]
+pub struct ListInvitationsQuery {
+    pub status: Option<InvitationStatus>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+impl ListInvitationsQuery {
+    /// Validate query parameters
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.limit < 1 || self.limit > 100 {
+            return Err(AppError::Validation("Limit must be between 1 and 100".to_string()));
+        }
+
+        if self.offset < 0 {
+            return Err(AppError::Validation("Offset must be non-negative".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Invite someone by email, optionally into groups the caller owns
+///
+/// Managing invitations is limited to administrators.
+pub async fn create_invitation(
+    State(app_state): State<AppState>,
+    Admin(current_user): Admin,
+    Json(request): Json<CreateInvitationRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<Invitation>>), AppError> {
+    if let Some(group) = request.groups.iter().find(|group| !current_user.is_group_owner(group.group_id)) {
+        tracing::warn!("User {} cannot invite into group {} they do not own", current_user.id, group.group_id);
+        return Err(AppError::authorization("Only group owners can invite people into a group"));
+    }
+
+    let invitation = app_state
+        .invitation_service()
+        .invite(request, current_user.id)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::with_message(invitation, "Invitation sent".to_string())),
+    ))
+}
+
+/// List invitations with pagination
+pub async fn list_invitations(
+    State(app_state): State<AppState>,
+    Admin(_): Admin,
+    Query(query): Query<ListInvitationsQuery>,
+) -> Result<Json<ApiResponse<Page<Invitation>>>, AppError> {
+    query.validate()?;
+    let page = app_state
+        .invitation_service()
+        .list_invitations(query.status, query.limit, query.offset)
+        .await?;
+
+    Ok(Json(ApiResponse::new(page)))
+}
+
+/// Get an invitation by ID
+pub async fn get_invitation(
+    State(app_state): State<AppState>,
+    Admin(_): Admin,
+    Path(id): Path<InvitationId>,
+) -> Result<Json<ApiResponse<Invitation>>, AppError> {
+    let invitation = app_state.invitation_service().get_invitation(id).await?;
+
+    Ok(Json(ApiResponse::new(invitation)))
+}
+
+/// Revoke a pending invitation
+pub async fn revoke_invitation(
+    State(app_state): State<AppState>,
+    Admin(current_user): Admin,
+    Path(id): Path<InvitationId>,
+) -> Result<StatusCode, AppError> {
+    app_state.invitation_service().revoke_invitation(id).await?;
+
+    tracing::info!("User {} revoked invitation {}", current_user.id, id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Accept an invitation with the mailed token, creating the invitee's account
+pub async fn accept_invitation(
+    State(app_state): State<AppState>,
+    audit_context: AuditContext,
+    Json(request): Json<AcceptInvitationRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<User>>), AppError> {
+    let user = app_state.invitation_service().accept_invitation(request, &audit_context).await?;
+
+    tracing::info!("Created user {} from an invitation", user.id);
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::with_message(user, "Invitation accepted".to_string())),
+    ))
+}
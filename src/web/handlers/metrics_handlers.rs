@@ -30,6 +30,12 @@ pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
             );
         }
 
+        match state.dead_letter_service().count().await {
+            Ok(count) => metrics.update_dead_letter_metrics(count),
+            Err(e) => warn!("Failed to count dead letters: {}", e),
+        }
+        metrics.update_event_bus_metrics(&state.services.event_bus().stats());
+
         // Gather all metrics
         let metrics_output = metrics.gather();
         let gather_duration = start_time.elapsed();
@@ -102,6 +108,17 @@ pub async fn metrics_json(State(state): State<AppState>) -> impl IntoResponse {
             })
         };
 
+        let dead_letters = match state.dead_letter_service().count().await {
+            Ok(count) => {
+                metrics.update_dead_letter_metrics(count);
+                serde_json::json!(count)
+            }
+            Err(_) => serde_json::json!("unavailable"),
+        };
+
+        let event_bus = state.services.event_bus().stats();
+        metrics.update_event_bus_metrics(&event_bus);
+
         metrics_data["metrics"] = serde_json::json!({
             "http": {
                 "requests_total": http_requests,
@@ -113,6 +130,10 @@ pub async fn metrics_json(State(state): State<AppState>) -> impl IntoResponse {
                 "errors_total": external_errors,
                 "circuit_breaker_state": circuit_breaker_state
             },
+            "webhooks": {
+                "dead_letters": dead_letters
+            },
+            "events": event_bus,
             "system": {
                 "memory_usage_bytes": metrics.memory_usage_bytes.get(),
                 "cpu_usage_percent": metrics.cpu_usage_percent.get()
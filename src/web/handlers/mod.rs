@@ -1,7 +1,25 @@
 pub mod user_handlers;
+pub mod import_handlers;
+pub mod privacy_handlers;
+pub mod group_handlers;
+pub mod invitation_handlers;
+pub mod audit_handlers;
+pub mod webhook_handlers;
+pub mod websocket_handlers;
+pub mod dead_letter_handlers;
+pub mod event_handlers;
 pub mod health_handlers;
 pub mod metrics_handlers;
 
 pub use user_handlers::*;
+pub use import_handlers::*;
+pub use privacy_handlers::*;
+pub use group_handlers::*;
+pub use invitation_handlers::*;
+pub use audit_handlers::*;
+pub use webhook_handlers::*;
+pub use websocket_handlers::*;
+pub use dead_letter_handlers::*;
+pub use event_handlers::*;
 pub use health_handlers::*;
 pub use metrics_handlers::*;
@@ -0,0 +1,68 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use validator::Validate;
+
+use crate::models::{ApiResponse, CurrentUser, EraseUserRequest, ErasureRequest, UserId};
+use crate::web::{
+    extractors::{is_admin, Admin},
+    responses::AppError,
+    router::AppState,
+};
+
+/// Download everything held about a user as a JSON archive
+///
+/// Users may export their own data; administrators may export anyone's.
+pub async fn export_user_data(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path(id): Path<UserId>,
+) -> Result<Response, AppError> {
+    if current_user.id != id && !is_admin(&current_user, &app_state) {
+        tracing::warn!("User {} may not export the data of user {}", current_user.id, id);
+        return Err(AppError::authorization("Only administrators may export other users' data"));
+    }
+    tracing::info!("User {} requested a personal data export of user {}", current_user.id, id);
+
+    let archive = app_state
+        .privacy_service()
+        .export_user_data(id, Some(current_user.id))
+        .await?;
+
+    let body = serde_json::to_vec_pretty(&archive)
+        .map_err(|e| AppError::generic(format!("Failed to encode data export: {}", e)))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"user-{}-data.json\"", id)),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Anonymize a user's personal data, returning the record of the erasure
+///
+/// Limited to administrators.
+pub async fn erase_user(
+    State(app_state): State<AppState>,
+    Admin(current_user): Admin,
+    Path(id): Path<UserId>,
+    Json(request): Json<EraseUserRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<ErasureRequest>>), AppError> {
+    request.validate()?;
+    tracing::info!("User {} requested erasure of user {}", current_user.id, id);
+
+    let erasure = app_state
+        .privacy_service()
+        .erase_user(id, Some(current_user.id), request.reason)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::with_message(erasure, "User data erased".to_string())),
+    ))
+}
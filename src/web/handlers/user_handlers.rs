@@ -1,12 +1,78 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    body::{Body, Bytes},
+    extract::{multipart::MultipartRejection, Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
+use futures::{stream, StreamExt};
 use serde::Deserialize;
+use validator::Validate;
+use std::collections::HashMap;
 
-use crate::models::{User, CreateUserRequest, UpdateUserRequest, UserId, ApiResponse};
-use crate::web::{responses::AppError, router::AppState};
+use crate::models::{
+    User, CreateUserRequest, UpdateUserRequest, UserId, UserPatch, UserSearchResult, ApiResponse,
+    DataFormat, UserColumn, UserExportRequest, EmailChange, EmailChangeTokenRequest, Fieldset, Included,
+    AuditContext, CurrentUser, UserStatusRequest,
+};
+use crate::web::{
+    extractors::{is_admin, version_etag, weak_version_etag, Admin, Fields, IfMatch, OptionalCurrentUser},
+    middleware::http_date,
+    responses::AppError,
+    router::AppState,
+};
+
+/// User response carrying the user's version as a strong `ETag` and its `Last-Modified` date
+type VersionedUserResponse = ([(HeaderName, String); 2], Json<ApiResponse<User>>);
+
+fn versioned(response: ApiResponse<User>) -> VersionedUserResponse {
+    (version_headers(&response.data), Json(response))
+}
+
+fn version_headers(user: &User) -> [(HeaderName, String); 2] {
+    [
+        (header::ETAG, version_etag(user.version)),
+        (header::LAST_MODIFIED, http_date(user.updated_at)),
+    ]
+}
+
+/// The user's current version, to check an `If-Match` list of tags against
+async fn current_version(app_state: &AppState, user_id: UserId) -> Result<i64, AppError> {
+    Ok(app_state.user_service().get_user(user_id).await?.version)
+}
+
+/// Most groups embedded per user by `?include=groups`; the full list is
+/// paginated under `/users/:id/groups`
+const MAX_INCLUDED_GROUPS: i64 = 100;
+
+/// Load the relations a client asked to embed in user responses
+///
+/// Groups are only embedded for authenticated callers; administrators see
+/// every group and everyone else only the groups they are a member of too.
+async fn include_user_relations(
+    app_state: &AppState,
+    caller: Option<&CurrentUser>,
+    fieldset: &Fieldset,
+    users: &[&User],
+) -> Result<Included, AppError> {
+    let mut included = Included::new();
+
+    if fieldset.includes("groups") {
+        let caller = caller.ok_or_else(|| AppError::authentication("Authentication is required to include groups"))?;
+        let visible_to = (!is_admin(caller, app_state)).then_some(caller.id);
+        let user_ids: Vec<UserId> = users.iter().map(|user| user.id).collect();
+        let mut groups = app_state
+            .group_service()
+            .groups_of_users(&user_ids, visible_to, MAX_INCLUDED_GROUPS)
+            .await?;
+        for user_id in user_ids {
+            let groups = serde_json::to_value(groups.remove(&user_id).unwrap_or_default())
+                .map_err(|e| AppError::generic(format!("Failed to serialize groups: {}", e)))?;
+            included.insert("groups", user_id, groups);
+        }
+    }
+
+    Ok(included)
+}
 
 /// Query parameters for listing users
 #[derive(Debug, Deserialize)]
@@ -24,6 +90,23 @@ fn default_limit() -> i64 {
     20
 }
 
+/// Query parameters for exporting users
+#[derive(Debug, Deserialize)]
+pub struct ExportUsersQuery {
+    pub format: DataFormat,
+    /// Comma-separated column list; all columns when omitted
+    pub columns: Option<String>,
+}
+
+/// Query parameters for searching users
+#[derive(Debug, Deserialize)]
+pub struct SearchUsersQuery {
+    pub q: String,
+    pub is_active: Option<bool>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
 impl ListUsersQuery {
     /// Validate query parameters
     pub fn validate(&self) -> Result<(), String> {
@@ -60,6 +143,7 @@ impl ListUsersQuery {
 /// Create a new user
 pub async fn create_user(
     State(app_state): State<AppState>,
+    audit_context: AuditContext,
     Json(request): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<ApiResponse<User>>), AppError> {
     tracing::info!("Creating new user with email: {}", request.email);
@@ -78,7 +162,7 @@ pub async fn create_user(
         }
     };
 
-    let user = app_state.user_service().create_user(validated_request).await?;
+    let user = app_state.user_service().create_user(validated_request, &audit_context).await?;
 
     tracing::info!("Successfully created user with ID: {}", user.id);
     Ok((
@@ -90,24 +174,40 @@ pub async fn create_user(
 /// Get a user by ID
 pub async fn get_user(
     State(app_state): State<AppState>,
+    OptionalCurrentUser(current_user): OptionalCurrentUser,
     Path(user_id): Path<UserId>,
-) -> Result<Json<ApiResponse<User>>, AppError> {
+    fields: Fields<User>,
+) -> Result<([(HeaderName, String); 2], Json<ApiResponse<serde_json::Value>>), AppError> {
     tracing::debug!("Getting user with ID: {}", user_id);
 
     let user = app_state.user_service().get_user(user_id).await?;
+    let included = include_user_relations(&app_state, current_user.as_ref(), &fields, &[&user]).await?;
+
+    // A projection or included relations change the body without changing the
+    // version, so only the full representation gets a strong tag for If-Match
+    let mut headers = version_headers(&user);
+    if !fields.is_full() {
+        headers[0].1 = weak_version_etag(user.version);
+    }
 
     tracing::info!("Successfully retrieved user: {}", user_id);
-    Ok(Json(ApiResponse::new(user)))
+    Ok((headers, fields.project(ApiResponse::new(user), &included)?))
 }
 
 /// Update a user
 pub async fn update_user(
     State(app_state): State<AppState>,
     Path(user_id): Path<UserId>,
+    if_match: IfMatch,
+    audit_context: AuditContext,
     Json(request): Json<UpdateUserRequest>,
-) -> Result<Json<ApiResponse<User>>, AppError> {
+) -> Result<VersionedUserResponse, AppError> {
     tracing::info!("Updating user with ID: {}", user_id);
 
+    let expected_version = if_match
+        .expected_version(app_state.config.server.require_if_match, || current_version(&app_state, user_id))
+        .await?;
+
     // Validate and normalize the request
     let validated_request = match request.validate_and_normalize() {
         Ok(req) => req,
@@ -128,30 +228,228 @@ pub async fn update_user(
         return Err(AppError::Validation("No updates provided".to_string()));
     }
 
-    let user = app_state.user_service().update_user(user_id, validated_request).await?;
+    let requested_email = validated_request.email.clone();
+    let user = app_state.user_service().update_user(user_id, validated_request, expected_version, &audit_context).await?;
 
     tracing::info!("Successfully updated user: {}", user_id);
-    Ok(Json(ApiResponse::with_message(user, "User updated successfully".to_string())))
+    let message = match requested_email {
+        Some(email) if email != user.email => {
+            format!("User updated successfully; a confirmation link was sent to {}", email)
+        }
+        _ => "User updated successfully".to_string(),
+    };
+    Ok(versioned(ApiResponse::with_message(user, message)))
+}
+
+/// Partially update a user with a JSON Merge Patch or JSON Patch document
+pub async fn patch_user(
+    State(app_state): State<AppState>,
+    Path(user_id): Path<UserId>,
+    if_match: IfMatch,
+    audit_context: AuditContext,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<VersionedUserResponse, AppError> {
+    tracing::info!("Patching user with ID: {}", user_id);
+
+    let expected_version = if_match
+        .expected_version(app_state.config.server.require_if_match, || current_version(&app_state, user_id))
+        .await?;
+    let patch = parse_user_patch(&headers, &body)?;
+
+    let user = app_state.user_service().patch_user(user_id, patch, expected_version, &audit_context).await?;
+
+    tracing::info!("Successfully patched user: {}", user_id);
+    Ok(versioned(ApiResponse::with_message(user, "User updated successfully".to_string())))
+}
+
+/// Parse a patch body according to its Content-Type header
+fn parse_user_patch(headers: &HeaderMap, body: &[u8]) -> Result<UserPatch, AppError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_lowercase())
+        .unwrap_or_default();
+
+    match content_type.as_str() {
+        UserPatch::MERGE_PATCH_CONTENT_TYPE => serde_json::from_slice(body)
+            .map(UserPatch::Merge)
+            .map_err(|e| AppError::Validation(format!("Invalid merge patch document: {}", e))),
+        UserPatch::JSON_PATCH_CONTENT_TYPE => serde_json::from_slice(body)
+            .map(UserPatch::Json)
+            .map_err(|e| AppError::Validation(format!("Invalid JSON patch document: {}", e))),
+        other => {
+            tracing::warn!("Unsupported patch content type: {}", other);
+            Err(AppError::UnsupportedMediaType(format!(
+                "Expected {} or {}",
+                UserPatch::MERGE_PATCH_CONTENT_TYPE,
+                UserPatch::JSON_PATCH_CONTENT_TYPE
+            )))
+        }
+    }
 }
 
 /// Delete a user
 pub async fn delete_user(
     State(app_state): State<AppState>,
     Path(user_id): Path<UserId>,
+    if_match: IfMatch,
+    audit_context: AuditContext,
 ) -> Result<StatusCode, AppError> {
     tracing::info!("Deleting user with ID: {}", user_id);
 
-    app_state.user_service().delete_user(user_id).await?;
+    let expected_version = if_match
+        .expected_version(app_state.config.server.require_if_match, || current_version(&app_state, user_id))
+        .await?;
+    app_state.user_service().delete_user(user_id, expected_version, &audit_context).await?;
 
     tracing::info!("Successfully deleted user: {}", user_id);
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Activate or deactivate a user
+pub async fn set_user_status(
+    State(app_state): State<AppState>,
+    Path(user_id): Path<UserId>,
+    if_match: IfMatch,
+    audit_context: AuditContext,
+    Json(request): Json<UserStatusRequest>,
+) -> Result<VersionedUserResponse, AppError> {
+    let expected_version = if_match
+        .expected_version(app_state.config.server.require_if_match, || current_version(&app_state, user_id))
+        .await?;
+    let message = if request.is_active { "User activated" } else { "User deactivated" };
+
+    let user = app_state
+        .user_service()
+        .set_user_status(user_id, request, expected_version, &audit_context)
+        .await?;
+
+    tracing::info!("Set is_active = {} for user {}", user.is_active, user_id);
+    Ok(versioned(ApiResponse::with_message(user, message.to_string())))
+}
+
+/// Get the user's email change awaiting confirmation; the user or an administrator only
+pub async fn get_email_change(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path(user_id): Path<UserId>,
+) -> Result<Json<ApiResponse<EmailChange>>, AppError> {
+    require_self_or_admin(&current_user, &app_state, user_id, "email changes")?;
+    let change = app_state
+        .email_change_service()
+        .pending_change(user_id)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("No pending email change for user {}", user_id)))?;
+
+    Ok(Json(ApiResponse::new(change)))
+}
+
+/// Withdraw the user's email change awaiting confirmation; the user or an administrator only
+pub async fn cancel_email_change(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path(user_id): Path<UserId>,
+) -> Result<StatusCode, AppError> {
+    require_self_or_admin(&current_user, &app_state, user_id, "email changes")?;
+    app_state.email_change_service().cancel_change(user_id).await?;
+
+    tracing::info!("User {} cancelled pending email change for user: {}", current_user.id, user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Some parts of a user, such as pending email changes and the avatar, are
+/// managed by the user themselves and by administrators only
+fn require_self_or_admin(current_user: &CurrentUser, app_state: &AppState, user_id: UserId, what: &str) -> Result<(), AppError> {
+    if current_user.id != user_id && !is_admin(current_user, app_state) {
+        tracing::warn!("User {} may not manage the {} of user {}", current_user.id, what, user_id);
+        return Err(AppError::authorization(format!("Only administrators may manage other users' {}", what)));
+    }
+
+    Ok(())
+}
+
+/// Apply an email change with the token mailed to the new address
+pub async fn confirm_email_change(
+    State(app_state): State<AppState>,
+    audit_context: AuditContext,
+    Json(request): Json<EmailChangeTokenRequest>,
+) -> Result<VersionedUserResponse, AppError> {
+    request.validate()?;
+    let user = app_state.email_change_service().confirm_change(&request.token, &audit_context).await?;
+
+    tracing::info!("Confirmed email change for user: {}", user.id);
+    Ok(versioned(ApiResponse::with_message(user, "Email address changed".to_string())))
+}
+
+/// Cancel or undo an email change with the token mailed to the old address
+pub async fn revert_email_change(
+    State(app_state): State<AppState>,
+    audit_context: AuditContext,
+    Json(request): Json<EmailChangeTokenRequest>,
+) -> Result<VersionedUserResponse, AppError> {
+    request.validate()?;
+    let user = app_state.email_change_service().revert_change(&request.token, &audit_context).await?;
+
+    tracing::info!("Reverted email change for user: {}", user.id);
+    Ok(versioned(ApiResponse::with_message(user, "Email address change reverted".to_string())))
+}
+
+/// Prefix for query parameters that filter on user metadata, e.g. `metadata.plan=pro`
+const METADATA_FILTER_PREFIX: &str = "metadata.";
+
+/// Build a JSON containment filter from `metadata.*` query parameters
+///
+/// Dotted keys nest (`metadata.billing.plan`). Values that parse as JSON
+/// scalars (numbers, booleans, quoted strings) are matched as such; anything
+/// else is matched as a plain string.
+fn metadata_filter(params: &HashMap<String, String>) -> Result<Option<serde_json::Value>, AppError> {
+    let mut filter = serde_json::Map::new();
+
+    for (key, value) in params {
+        let Some(path) = key.strip_prefix(METADATA_FILTER_PREFIX) else {
+            continue;
+        };
+
+        let segments: Vec<&str> = path.split('.').collect();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return Err(AppError::Validation(format!("Invalid metadata filter: {}", key)));
+        }
+
+        let value = match serde_json::from_str::<serde_json::Value>(value) {
+            Ok(parsed) if !parsed.is_object() && !parsed.is_array() => parsed,
+            _ => serde_json::Value::String(value.clone()),
+        };
+
+        let (leaf, parents) = segments.split_last().expect("split always yields a segment");
+        let mut target = &mut filter;
+        for segment in parents {
+            target = match target
+                .entry(segment.to_string())
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+            {
+                Some(object) => object,
+                None => return Err(AppError::Validation(format!("Conflicting metadata filter: {}", key))),
+            };
+        }
+        if target.insert(leaf.to_string(), value).is_some() {
+            return Err(AppError::Validation(format!("Conflicting metadata filter: {}", key)));
+        }
+    }
+
+    Ok((!filter.is_empty()).then_some(serde_json::Value::Object(filter)))
+}
+
 /// List users with pagination
 pub async fn list_users(
     State(app_state): State<AppState>,
+    OptionalCurrentUser(current_user): OptionalCurrentUser,
     Query(query): Query<ListUsersQuery>,
-) -> Result<Json<ApiResponse<Vec<User>>>, AppError> {
+    Query(params): Query<HashMap<String, String>>,
+    fields: Fields<User>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
     tracing::debug!("Listing users with limit: {}, offset: {}", query.limit, query.offset);
 
     // Validate query parameters
@@ -160,8 +458,176 @@ pub async fn list_users(
         return Err(AppError::Validation(validation_error));
     }
 
-    let users = app_state.user_service().list_users(query.limit, query.offset).await?;
+    let metadata = metadata_filter(&params)?;
+    let users = app_state.user_service().list_users(query.limit, query.offset, metadata).await?;
 
     tracing::info!("Successfully retrieved {} users", users.len());
-    Ok(Json(ApiResponse::new(users)))
+
+    let included = include_user_relations(&app_state, current_user.as_ref(), &fields, &users.iter().collect::<Vec<_>>()).await?;
+    fields.project(ApiResponse::new(users), &included)
+}
+
+/// Search users by name or email, tolerating partial and misspelled terms
+pub async fn search_users(
+    State(app_state): State<AppState>,
+    Query(query): Query<SearchUsersQuery>,
+) -> Result<Json<ApiResponse<Vec<UserSearchResult>>>, AppError> {
+    tracing::debug!("Searching users for: {}", query.q);
+
+    let results = app_state
+        .user_service()
+        .search_users(&query.q, query.is_active, query.limit)
+        .await?;
+
+    tracing::info!("User search returned {} results", results.len());
+    Ok(Json(ApiResponse::new(results)))
+}
+
+/// Stream active users as CSV or NDJSON
+///
+/// Honors the same `metadata.*` filters as listing. Rows are written as they
+/// are read from the database cursor, so the table is never held in memory.
+/// Limited to administrators, as the export holds every user's personal data.
+pub async fn export_users(
+    State(app_state): State<AppState>,
+    Admin(current_user): Admin,
+    audit_context: AuditContext,
+    Query(query): Query<ExportUsersQuery>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, AppError> {
+    let columns = match &query.columns {
+        Some(list) => UserColumn::parse_list(list).map_err(AppError::Validation)?,
+        None => UserColumn::ALL.to_vec(),
+    };
+    let request = UserExportRequest {
+        format: query.format,
+        columns,
+        metadata_filter: metadata_filter(&params)?,
+    };
+
+    let header_row = request
+        .encode_header()
+        .map_err(|e| AppError::generic(format!("Failed to encode export header: {}", e)))?;
+    let users = app_state
+        .user_service()
+        .export_users(&request, &audit_context)
+        .await?;
+
+    let rows = users.map(move |user| {
+        let user = user.map_err(|e| {
+            tracing::error!("User export aborted: {}", e);
+            std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        })?;
+        request
+            .encode_user(&user)
+            .map(Bytes::from)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+    });
+    let body = stream::once(async move { Ok::<_, std::io::Error>(Bytes::from(header_row)) })
+        .chain(rows);
+
+    let filename = format!(
+        "users-{}.{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ"),
+        query.format.extension()
+    );
+
+    tracing::info!("Streaming user export as {} to user {}", filename, current_user.id);
+    Ok((
+        [
+            (header::CONTENT_TYPE, query.format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+/// Upload a user's avatar, sent as the `avatar` field of a multipart form
+pub async fn upload_avatar(
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    Path(id): Path<UserId>,
+    if_match: IfMatch,
+    audit_context: AuditContext,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<VersionedUserResponse, AppError> {
+    require_self_or_admin(&current_user, &app_state, id, "avatars")?;
+    let expected_version = if_match
+        .expected_version(app_state.config.server.require_if_match, || current_version(&app_state, id))
+        .await?;
+    let mut multipart = multipart.map_err(|e| AppError::unsupported_media_type(e.body_text()))?;
+    let max_bytes = app_state.config.avatar.max_bytes;
+    let invalid_body = |e: axum::extract::multipart::MultipartError| match e.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => {
+            AppError::payload_too_large(format!("Avatar image must not exceed {} bytes", max_bytes))
+        }
+        _ => AppError::validation(format!("Invalid multipart body: {}", e.body_text())),
+    };
+
+    let mut field = multipart
+        .next_field()
+        .await
+        .map_err(invalid_body)?
+        .ok_or_else(|| AppError::validation("Multipart body must include an avatar field"))?;
+    // Other fields are rejected rather than skipped, as skipping a field still reads it
+    if field.name() != Some("avatar") {
+        return Err(AppError::validation(format!(
+            "Unexpected multipart field '{}'; only 'avatar' is accepted",
+            field.name().unwrap_or_default()
+        )));
+    }
+
+    tracing::debug!("Receiving avatar for user {} (declared type: {:?})", id, field.content_type());
+    let mut image = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(invalid_body)? {
+        if image.len() + chunk.len() > max_bytes {
+            return Err(AppError::payload_too_large(format!("Avatar image must not exceed {} bytes", max_bytes)));
+        }
+        image.extend_from_slice(&chunk);
+    }
+
+    let user = app_state.user_service().set_avatar(id, image, expected_version, &audit_context).await?;
+
+    tracing::info!("Successfully updated avatar for user {}", id);
+    Ok(versioned(ApiResponse::with_message(user, "Avatar updated successfully".to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_metadata_filter_from_query() {
+        let filter = metadata_filter(&params(&[
+            ("limit", "10"),
+            ("metadata.plan", "pro"),
+            ("metadata.seats", "5"),
+            ("metadata.billing.annual", "true"),
+            ("metadata.code", "\"007\""),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            filter,
+            Some(json!({
+                "plan": "pro",
+                "seats": 5,
+                "billing": { "annual": true },
+                "code": "007"
+            }))
+        );
+    }
+
+    #[test]
+    fn test_metadata_filter_absent_or_invalid() {
+        assert_eq!(metadata_filter(&params(&[("limit", "10")])).unwrap(), None);
+        assert!(metadata_filter(&params(&[("metadata.", "x")])).is_err());
+        assert!(metadata_filter(&params(&[("metadata.a", "1"), ("metadata.a.b", "2")])).is_err());
+    }
 }
@@ -0,0 +1,159 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::models::{
+    ApiResponse, CreateWebhookSubscriptionRequest, CreatedWebhookSubscription, Page, RotateWebhookSecretRequest,
+    UpdateWebhookSubscriptionRequest,
+    WebhookDelivery, WebhookSubscription, WebhookSubscriptionId,
+};
+use crate::web::{extractors::WebhookManager, responses::AppError, router::AppState};
+
+/// Query parameters for paginated webhook listings
+#[derive(Debug, Deserialize)]
+pub struct WebhookPageQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+impl WebhookPageQuery {
+    /// Validate query parameters
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.limit < 1 || self.limit > 100 {
+            return Err(AppError::Validation("Limit must be between 1 and 100".to_string()));
+        }
+
+        if self.offset < 0 {
+            return Err(AppError::Validation("Offset must be non-negative".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Register a webhook endpoint; the response carries its signing secret
+pub async fn create_webhook(
+    State(app_state): State<AppState>,
+    WebhookManager(current_user): WebhookManager,
+    Json(request): Json<CreateWebhookSubscriptionRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<CreatedWebhookSubscription>>), AppError> {
+    let created = app_state
+        .webhook_subscription_service()
+        .create_subscription(request, current_user.id)
+        .await?;
+
+    tracing::info!("User {} created webhook subscription {}", current_user.id, created.subscription.id);
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::with_message(
+            created,
+            "Webhook subscription created; store the secret, it is not shown again".to_string(),
+        )),
+    ))
+}
+
+/// List webhook subscriptions with pagination
+pub async fn list_webhooks(
+    State(app_state): State<AppState>,
+    WebhookManager(_): WebhookManager,
+    Query(query): Query<WebhookPageQuery>,
+) -> Result<Json<ApiResponse<Page<WebhookSubscription>>>, AppError> {
+    query.validate()?;
+    let page = app_state
+        .webhook_subscription_service()
+        .list_subscriptions(query.limit, query.offset)
+        .await?;
+
+    Ok(Json(ApiResponse::new(page)))
+}
+
+/// Get a webhook subscription by ID
+pub async fn get_webhook(
+    State(app_state): State<AppState>,
+    WebhookManager(_): WebhookManager,
+    Path(id): Path<WebhookSubscriptionId>,
+) -> Result<Json<ApiResponse<WebhookSubscription>>, AppError> {
+    let subscription = app_state.webhook_subscription_service().get_subscription(id).await?;
+
+    Ok(Json(ApiResponse::new(subscription)))
+}
+
+/// Change a webhook subscription's URL, event types, description or active flag
+pub async fn update_webhook(
+    State(app_state): State<AppState>,
+    WebhookManager(current_user): WebhookManager,
+    Path(id): Path<WebhookSubscriptionId>,
+    Json(request): Json<UpdateWebhookSubscriptionRequest>,
+) -> Result<Json<ApiResponse<WebhookSubscription>>, AppError> {
+    let subscription = app_state.webhook_subscription_service().update_subscription(id, request).await?;
+
+    tracing::info!("User {} updated webhook subscription {}", current_user.id, id);
+    Ok(Json(ApiResponse::with_message(
+        subscription,
+        "Webhook subscription updated successfully".to_string(),
+    )))
+}
+
+/// Delete a webhook subscription and its delivery log
+pub async fn delete_webhook(
+    State(app_state): State<AppState>,
+    WebhookManager(current_user): WebhookManager,
+    Path(id): Path<WebhookSubscriptionId>,
+) -> Result<StatusCode, AppError> {
+    app_state.webhook_subscription_service().delete_subscription(id).await?;
+
+    tracing::info!("User {} deleted webhook subscription {}", current_user.id, id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Replace a webhook subscription's secret; the response carries the new one
+pub async fn rotate_webhook_secret(
+    State(app_state): State<AppState>,
+    WebhookManager(current_user): WebhookManager,
+    Path(id): Path<WebhookSubscriptionId>,
+    Json(request): Json<RotateWebhookSecretRequest>,
+) -> Result<Json<ApiResponse<CreatedWebhookSubscription>>, AppError> {
+    let rotated = app_state.webhook_subscription_service().rotate_secret(id, request).await?;
+
+    tracing::info!("User {} rotated the secret of webhook subscription {}", current_user.id, id);
+    Ok(Json(ApiResponse::with_message(
+        rotated,
+        "Webhook secret rotated; store the new secret, it is not shown again".to_string(),
+    )))
+}
+
+/// Send a test ping to a webhook subscription and return the logged attempt
+pub async fn ping_webhook(
+    State(app_state): State<AppState>,
+    WebhookManager(_): WebhookManager,
+    Path(id): Path<WebhookSubscriptionId>,
+) -> Result<Json<ApiResponse<WebhookDelivery>>, AppError> {
+    let delivery = app_state.webhook_subscription_service().ping(id).await?;
+
+    Ok(Json(ApiResponse::new(delivery)))
+}
+
+/// List a webhook subscription's delivery attempts, newest first
+pub async fn list_webhook_deliveries(
+    State(app_state): State<AppState>,
+    WebhookManager(_): WebhookManager,
+    Path(id): Path<WebhookSubscriptionId>,
+    Query(query): Query<WebhookPageQuery>,
+) -> Result<Json<ApiResponse<Page<WebhookDelivery>>>, AppError> {
+    query.validate()?;
+    let page = app_state
+        .webhook_subscription_service()
+        .list_deliveries(id, query.limit, query.offset)
+        .await?;
+
+    Ok(Json(ApiResponse::new(page)))
+}
@@ -0,0 +1,234 @@
+use axum::{
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::{header, HeaderMap},
+    response::Response,
+};
+use futures::{SinkExt, StreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio::time::Instant;
+
+use crate::models::{CurrentUser, ServerMessage, WebSocketErrorCode};
+use crate::services::{AuthError, AuthService, EventSubscription, WebSocketSession};
+use crate::web::{extractors::EventSubscriber, middleware::extract_bearer_token, responses::AppError, router::AppState};
+
+/// Subprotocol that carries the access token as the protocol after it
+pub const BEARER_PROTOCOL: &str = "bearer";
+
+/// Close code sent when the connect token expires or is revoked
+pub const TOKEN_EXPIRED_CLOSE_CODE: u16 = 4401;
+
+/// Open a WebSocket for subscribing to user change events
+///
+/// The token is checked before upgrading, so failures are plain HTTP errors.
+/// Browsers, which cannot set the Authorization header, send it as
+/// `Sec-WebSocket-Protocol: bearer, <token>` so it stays out of the URL.
+pub async fn websocket(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let token = extract_bearer_token(&headers)
+        .or_else(|| protocol_token(&headers))
+        .ok_or_else(|| AppError::authentication("Authentication required"))?
+        .to_string();
+    let current_user = authenticate(&app_state, &token).await?;
+    let EventSubscriber(current_user) = EventSubscriber::authorize(current_user, &app_state)?;
+
+    let sessions = app_state.websocket_sessions();
+    let session = sessions
+        .open()
+        .ok_or_else(|| AppError::RateLimit("Too many open WebSocket connections".to_string()))?;
+    let events = app_state.event_stream().subscribe(None);
+
+    let config = sessions.config().clone();
+    let connection = Connection {
+        auth_service: app_state.auth_service(),
+        token,
+        ping_interval: Duration::from_secs(config.ping_interval_seconds),
+        pong_timeout: Duration::from_secs(config.pong_timeout_seconds),
+        close_timeout: Duration::from_secs(config.close_timeout_seconds),
+        send_queue_capacity: config.send_queue_capacity,
+    };
+
+    tracing::info!("User {} opened a WebSocket ({} open)", current_user.id, sessions.open_count());
+    Ok(upgrade
+        .protocols([BEARER_PROTOCOL])
+        .max_message_size(config.max_message_bytes)
+        .max_frame_size(config.max_message_bytes)
+        .on_upgrade(move |socket| async move {
+            connection.run(socket, session, events).await;
+            tracing::info!("User {} closed a WebSocket", current_user.id);
+        }))
+}
+
+/// The token offered after the `bearer` subprotocol, if any
+fn protocol_token(headers: &HeaderMap) -> Option<&str> {
+    let mut protocols = headers
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim);
+
+    protocols.find(|protocol| *protocol == BEARER_PROTOCOL)?;
+    protocols.next().filter(|token| !token.is_empty())
+}
+
+/// Resolve the user of the connect token
+async fn authenticate(app_state: &AppState, token: &str) -> Result<CurrentUser, AppError> {
+    let mut current_user = app_state.auth_service().validate_token(token).await.map_err(|e| match e {
+        AuthError::Internal(msg) => AppError::generic(format!("Authentication service error: {}", msg)),
+        _ => AppError::authentication("Invalid or expired token"),
+    })?;
+    current_user.groups = app_state.group_service().memberships(current_user.id).await?;
+
+    Ok(current_user)
+}
+
+/// Limits applied to one open socket
+struct Connection {
+    /// Checks the connect token again at every ping
+    auth_service: Arc<dyn AuthService>,
+    token: String,
+    ping_interval: Duration,
+    pong_timeout: Duration,
+    close_timeout: Duration,
+    send_queue_capacity: usize,
+}
+
+impl Connection {
+    /// Whether the connect token is still accepted; the socket stays open
+    /// when the auth service cannot tell
+    async fn token_valid(&self) -> bool {
+        match self.auth_service.validate_token(&self.token).await {
+            Ok(_) => true,
+            Err(AuthError::Internal(msg)) => {
+                tracing::warn!("Failed to check WebSocket token: {}", msg);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Serve a socket until either side closes it
+    ///
+    /// Messages go through a bounded queue to a writer task. A client that
+    /// does not read fast enough to keep it from filling up, that falls
+    /// behind the event stream, or that does not answer a ping within the
+    /// pong timeout is disconnected, and so is one whose token has expired.
+    async fn run(self, socket: WebSocket, mut session: WebSocketSession, mut events: EventSubscription) {
+        let (mut sink, mut incoming) = socket.split();
+        let (outgoing, mut queue) = mpsc::channel::<Message>(self.send_queue_capacity);
+        let mut writer = tokio::spawn(async move {
+            while let Some(message) = queue.recv().await {
+                let closing = matches!(message, Message::Close(_));
+                if sink.send(message).await.is_err() || closing {
+                    break;
+                }
+            }
+        });
+
+        let mut ping = tokio::time::interval_at(Instant::now() + self.ping_interval, self.ping_interval);
+        // Set while a ping is unanswered
+        let mut pong_deadline: Option<Instant> = None;
+        let close = loop {
+            let message = tokio::select! {
+                _ = session.closing() => break Some((close_code::AWAY, "Server is shutting down")),
+                _ = events.closed.wait_for(|closed| *closed) => break Some((close_code::AWAY, "Server is shutting down")),
+                _ = tokio::time::sleep_until(pong_deadline.unwrap_or_else(Instant::now)), if pong_deadline.is_some() => {
+                    tracing::debug!("Closing WebSocket that did not answer a ping");
+                    break Some((close_code::POLICY, "Ping not answered"));
+                }
+                _ = ping.tick() => Message::Ping(Vec::new()),
+                received = incoming.next() => match received {
+                    Some(Ok(Message::Text(text))) => Message::Text(session.handle(&text).to_text()),
+                    Some(Ok(Message::Binary(_))) => Message::Text(
+                        ServerMessage::error(None, WebSocketErrorCode::InvalidMessage, "Messages must be JSON text")
+                            .to_text(),
+                    ),
+                    Some(Ok(Message::Pong(_))) => {
+                        pong_deadline = None;
+                        continue;
+                    }
+                    // Pings are answered by the socket itself
+                    Some(Ok(Message::Ping(_))) => continue,
+                    // A close from the client is answered by the socket itself
+                    Some(Ok(Message::Close(_))) | None => break None,
+                    Some(Err(e)) => {
+                        tracing::debug!("WebSocket receive failed: {}", e);
+                        break None;
+                    }
+                },
+                received = events.events.recv() => match received {
+                    Ok(event) => match session.event_message(&event) {
+                        Some(message) => Message::Text(message.to_text()),
+                        None => continue,
+                    },
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!("Closing WebSocket that fell {} events behind", missed);
+                        break Some((close_code::AGAIN, "Too far behind the event stream"));
+                    }
+                    Err(RecvError::Closed) => break Some((close_code::AWAY, "Server is shutting down")),
+                },
+            };
+
+            if matches!(message, Message::Ping(_)) {
+                if !self.token_valid().await {
+                    break Some((TOKEN_EXPIRED_CLOSE_CODE, "Token expired"));
+                }
+                pong_deadline.get_or_insert_with(|| Instant::now() + self.pong_timeout);
+            }
+
+            if outgoing.try_send(message).is_err() {
+                tracing::warn!("Closing WebSocket whose send queue is full");
+                // The queue has no room left for a close frame
+                writer.abort();
+                return;
+            }
+        };
+
+        if let Some((code, reason)) = close {
+            let frame = CloseFrame {
+                code,
+                reason: reason.into(),
+            };
+            if outgoing.try_send(Message::Close(Some(frame))).is_err() {
+                writer.abort();
+                return;
+            }
+        }
+
+        // The writer stops once it has sent what is queued
+        drop(outgoing);
+        if tokio::time::timeout(self.close_timeout, &mut writer).await.is_err() {
+            writer.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_token_is_taken_from_the_bearer_protocol() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(protocol_token(&headers), None);
+
+        headers.insert(header::SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static("bearer, abc.def"));
+        assert_eq!(protocol_token(&headers), Some("abc.def"));
+
+        headers.insert(header::SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static("chat, bearer"));
+        assert_eq!(protocol_token(&headers), None);
+
+        headers.insert(header::SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static("chat"));
+        headers.append(header::SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static("bearer,abc.def"));
+        assert_eq!(protocol_token(&headers), Some("abc.def"));
+    }
+}
@@ -31,7 +31,7 @@ pub async fn auth_middleware(
     };
 
     // Validate token and get current user
-    let current_user = match app_state.auth_service().validate_token(token).await {
+    let mut current_user = match app_state.auth_service().validate_token(token).await {
         Ok(user) => {
             tracing::debug!("Authentication successful for user: {} [correlation_id: {}]", user.id, correlation_id);
             user
@@ -54,6 +54,15 @@ pub async fn auth_middleware(
         }
     };
 
+    // Load group memberships for authorization checks
+    current_user.groups = match app_state.group_service().memberships(current_user.id).await {
+        Ok(groups) => groups,
+        Err(e) => {
+            tracing::error!("Failed to load group memberships for user {}: {} [correlation_id: {}]", current_user.id, e, correlation_id);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
     // Add current user to request extensions for use in handlers
     request.extensions_mut().insert(current_user);
 
@@ -77,8 +86,12 @@ pub async fn optional_auth_middleware(
     // Try to extract and validate authorization header
     if let Some(token) = extract_bearer_token(request.headers()) {
         match app_state.auth_service().validate_token(token).await {
-            Ok(current_user) => {
+            Ok(mut current_user) => {
                 tracing::debug!("Optional authentication successful for user: {} [correlation_id: {}]", current_user.id, correlation_id);
+                match app_state.group_service().memberships(current_user.id).await {
+                    Ok(groups) => current_user.groups = groups,
+                    Err(e) => tracing::warn!("Failed to load group memberships for user {}: {} [correlation_id: {}]", current_user.id, e, correlation_id),
+                }
                 request.extensions_mut().insert(current_user);
             }
             Err(AuthError::InvalidToken) => {
@@ -98,7 +111,7 @@ pub async fn optional_auth_middleware(
 }
 
 /// Extract Bearer token from Authorization header
-fn extract_bearer_token(headers: &axum::http::HeaderMap) -> Option<&str> {
+pub(crate) fn extract_bearer_token(headers: &axum::http::HeaderMap) -> Option<&str> {
     headers
         .get("authorization")
         .and_then(|header| header.to_str().ok())
@@ -0,0 +1,270 @@
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::web::router::AppState;
+
+/// Headers kept on a `304 Not Modified` response
+const NOT_MODIFIED_HEADERS: [header::HeaderName; 5] = [
+    header::CACHE_CONTROL,
+    header::ETAG,
+    header::EXPIRES,
+    header::LAST_MODIFIED,
+    header::VARY,
+];
+
+/// Probes and scrapes that must always see a fresh response
+const UNCACHED_PREFIXES: [&str; 2] = ["/health", "/metrics"];
+
+/// Conditional GET middleware for read endpoints
+///
+/// Adds a weak content-hash `ETag` when the handler did not set one, applies
+/// the configured `Cache-Control` policy for the matched route, and answers
+/// `If-None-Match` / `If-Modified-Since` with `304 Not Modified`.
+pub async fn caching_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let cache = &state.config.cache;
+    if !cache.enabled
+        || !matches!(*request.method(), Method::GET | Method::HEAD)
+        || is_uncached(request.uri().path())
+    {
+        return next.run(request).await;
+    }
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let conditions = request.headers().clone();
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+
+    let body = if parts.headers.contains_key(header::ETAG) {
+        body
+    } else {
+        match body.size_hint().upper() {
+            Some(size) if size as usize <= cache.max_body_bytes => {
+                let bytes = match to_bytes(body, cache.max_body_bytes).await {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        warn!("Failed to buffer response body for ETag on {}: {}", route, e);
+                        return Response::builder()
+                            .status(StatusCode::INTERNAL_SERVER_ERROR)
+                            .body(Body::empty())
+                            .unwrap_or_default();
+                    }
+                };
+                if let Ok(etag) = HeaderValue::from_str(&content_etag(&parts.headers, &bytes)) {
+                    parts.headers.insert(header::ETAG, etag);
+                }
+                Body::from(bytes)
+            }
+            // Streaming or oversized bodies are passed through without an ETag
+            _ => body,
+        }
+    };
+
+    if !parts.headers.contains_key(header::CACHE_CONTROL) {
+        if let Ok(policy) = HeaderValue::from_str(cache.cache_control_for(&route)) {
+            parts.headers.insert(header::CACHE_CONTROL, policy);
+        }
+    }
+
+    if is_not_modified(&conditions, &parts.headers) {
+        debug!("Returning 304 Not Modified for {}", route);
+        return not_modified(&parts.headers);
+    }
+
+    Response::from_parts(parts, body)
+}
+
+/// Whether a path is served without ETags, Cache-Control or 304 responses
+fn is_uncached(path: &str) -> bool {
+    UNCACHED_PREFIXES.iter().any(|prefix| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Format a timestamp as an HTTP date for `Last-Modified`
+pub fn http_date(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parse an HTTP date as sent in `If-Modified-Since`
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Build a weak `ETag` from a hash of the response body
+///
+/// The `timestamp` envelope field of JSON responses changes on every request,
+/// so it is left out of the hash.
+fn content_etag(headers: &HeaderMap, body: &[u8]) -> String {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+
+    let mut hasher = Sha256::new();
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(serde_json::Value::Object(mut fields)) if is_json => {
+            fields.remove("timestamp");
+            hasher.update(serde_json::Value::Object(fields).to_string().as_bytes());
+        }
+        _ => hasher.update(body),
+    }
+
+    let digest = hasher.finalize();
+    let hash: String = digest[..16].iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("W/\"{}\"", hash)
+}
+
+/// Decide whether the request's validators match the response
+///
+/// `If-None-Match` takes precedence over `If-Modified-Since` (RFC 9110 13.2.2).
+fn is_not_modified(request_headers: &HeaderMap, response_headers: &HeaderMap) -> bool {
+    if let Some(if_none_match) = request_headers.get(header::IF_NONE_MATCH) {
+        let etag = response_headers
+            .get(header::ETAG)
+            .and_then(|value| value.to_str().ok());
+        return match (if_none_match.to_str(), etag) {
+            (Ok(candidates), Some(etag)) => etag_matches_weak(candidates, etag),
+            _ => false,
+        };
+    }
+
+    let if_modified_since = request_headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_http_date);
+    let last_modified = response_headers
+        .get(header::LAST_MODIFIED)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_http_date);
+
+    match (if_modified_since, last_modified) {
+        (Some(since), Some(modified)) => modified <= since,
+        _ => false,
+    }
+}
+
+/// Weak comparison of an `If-None-Match` list against an entity tag
+fn etag_matches_weak(candidates: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let current = opaque(etag);
+
+    candidates
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == current)
+}
+
+fn not_modified(headers: &HeaderMap) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::NOT_MODIFIED;
+
+    for name in NOT_MODIFIED_HEADERS {
+        if let Some(value) = headers.get(&name) {
+            response.headers_mut().insert(name, value.clone());
+        }
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_health_and_metrics_are_uncached() {
+        assert!(is_uncached("/health"));
+        assert!(is_uncached("/health/ready"));
+        assert!(is_uncached("/metrics"));
+        assert!(!is_uncached("/metricsfoo"));
+        assert!(!is_uncached("/api/v1/users"));
+    }
+
+    #[test]
+    fn test_etag_weak_comparison() {
+        assert!(etag_matches_weak("W/\"abc\"", "\"abc\""));
+        assert!(etag_matches_weak("\"x\", W/\"abc\"", "W/\"abc\""));
+        assert!(etag_matches_weak("*", "\"abc\""));
+        assert!(!etag_matches_weak("\"abd\"", "\"abc\""));
+    }
+
+    #[test]
+    fn test_content_etag_ignores_envelope_timestamp() {
+        let json = headers(&[(header::CONTENT_TYPE, "application/json")]);
+        let first = content_etag(&json, br#"{"data":[1,2],"timestamp":"2024-01-01T00:00:00Z"}"#);
+        let second = content_etag(&json, br#"{"data":[1,2],"timestamp":"2024-01-01T00:00:05Z"}"#);
+        let changed = content_etag(&json, br#"{"data":[1,3],"timestamp":"2024-01-01T00:00:05Z"}"#);
+
+        assert!(first.starts_with("W/\""));
+        assert_eq!(first, second);
+        assert_ne!(first, changed);
+    }
+
+    #[test]
+    fn test_if_none_match_takes_precedence() {
+        let response = headers(&[
+            (header::ETAG, "W/\"abc\""),
+            (header::LAST_MODIFIED, "Mon, 01 Jan 2024 00:00:00 GMT"),
+        ]);
+
+        let matching = headers(&[(header::IF_NONE_MATCH, "W/\"abc\"")]);
+        assert!(is_not_modified(&matching, &response));
+
+        let stale = headers(&[
+            (header::IF_NONE_MATCH, "W/\"old\""),
+            (header::IF_MODIFIED_SINCE, "Tue, 02 Jan 2024 00:00:00 GMT"),
+        ]);
+        assert!(!is_not_modified(&stale, &response));
+    }
+
+    #[test]
+    fn test_if_modified_since() {
+        let response = headers(&[(header::LAST_MODIFIED, "Mon, 01 Jan 2024 12:00:00 GMT")]);
+
+        let later = headers(&[(header::IF_MODIFIED_SINCE, "Mon, 01 Jan 2024 12:00:00 GMT")]);
+        assert!(is_not_modified(&later, &response));
+
+        let earlier = headers(&[(header::IF_MODIFIED_SINCE, "Mon, 01 Jan 2024 11:59:59 GMT")]);
+        assert!(!is_not_modified(&earlier, &response));
+    }
+
+    #[test]
+    fn test_http_date_round_trip() {
+        let timestamp = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let formatted = http_date(timestamp);
+
+        assert_eq!(formatted, "Mon, 01 Jan 2024 12:00:00 GMT");
+        assert_eq!(parse_http_date(&formatted), Some(timestamp));
+    }
+}
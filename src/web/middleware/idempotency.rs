@@ -0,0 +1,299 @@
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Duration;
+use sha2::{Digest, Sha256};
+use tracing::{debug, error, info, warn};
+
+use crate::models::{request_fingerprint, IdempotencyClaim, StoredResponse};
+use crate::web::{middleware::auth::extract_bearer_token, responses::AppError, router::AppState};
+
+/// Request header carrying the client's idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header marking a replayed response
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Scope of keys sent without credentials
+const ANONYMOUS_SCOPE: &str = "anonymous";
+
+/// Route whose uploads are limited by `user_import.max_body_bytes`
+const IMPORT_ROUTE: &str = "/api/v1/users/import";
+
+/// Headers that describe a single transfer rather than the response itself
+const TRANSFER_HEADERS: [header::HeaderName; 3] = [
+    header::CONNECTION,
+    header::CONTENT_LENGTH,
+    header::TRANSFER_ENCODING,
+];
+
+/// `Idempotency-Key` middleware for POST requests
+///
+/// The first request under a key is processed and its response stored for
+/// `idempotency.ttl_hours`; retries with the same method, path and body get
+/// the stored response back. Keys are scoped to the caller, so the same key
+/// sent by different users never collides. Reusing a key for a different
+/// request is rejected with `422`, and a retry arriving while the first
+/// request is still running gets `409` until it completes. Server errors are
+/// not stored, so the request can be retried under the same key. Multipart
+/// uploads are passed through without idempotency handling.
+pub async fn idempotency_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = &state.config.idempotency;
+    if !config.enabled || request.method() != Method::POST || is_multipart(request.headers()) {
+        return next.run(request).await;
+    }
+
+    let key = match request.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        Some(value) => match parse_key(value) {
+            Ok(key) => key,
+            Err(e) => return e.into_response(),
+        },
+        None => return next.run(request).await,
+    };
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    // Uploads are buffered up to their own route's limit rather than refused here
+    let body_limit = if route == IMPORT_ROUTE {
+        config.max_body_bytes.max(state.config.user_import.max_body_bytes)
+    } else {
+        config.max_body_bytes
+    };
+
+    let scope = request_scope(&state, request.headers()).await;
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, body_limit).await {
+        Ok(body) => body,
+        Err(_) => {
+            return AppError::payload_too_large(format!(
+                "Requests with an Idempotency-Key are limited to {} bytes",
+                body_limit
+            ))
+            .into_response();
+        }
+    };
+
+    let path = parts.uri.path_and_query().map(|path| path.as_str()).unwrap_or("/");
+    let fingerprint = request_fingerprint(parts.method.as_str(), path, &body);
+
+    let repository = state.services.idempotency_repository();
+    let claim = repository
+        .claim(
+            &scope,
+            &key,
+            &fingerprint,
+            Duration::hours(config.ttl_hours as i64),
+            Duration::seconds(config.lock_timeout_seconds as i64),
+        )
+        .await;
+
+    let locked_until = match claim {
+        Ok(IdempotencyClaim::Acquired { locked_until }) => locked_until,
+        Ok(IdempotencyClaim::Replay(stored)) => {
+            debug!("Replaying stored response for idempotency key {}", key);
+            return replay(stored);
+        }
+        Ok(IdempotencyClaim::InFlight) => {
+            debug!("Idempotency key {} is still being processed", key);
+            let mut response =
+                AppError::conflict("A request with this Idempotency-Key is still being processed").into_response();
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+            return response;
+        }
+        Ok(IdempotencyClaim::Mismatch) => {
+            warn!("Idempotency key {} reused for a different request", key);
+            return AppError::unprocessable_entity("Idempotency-Key was already used for a different request")
+                .into_response();
+        }
+        Err(e) => {
+            error!("Failed to claim idempotency key {}: {}", key, e);
+            return AppError::Repository(e).into_response();
+        }
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    if !is_storable(response.status()) {
+        if let Err(e) = repository.release(&scope, &key, locked_until).await {
+            error!("Failed to release idempotency key {}: {}", key, e);
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let fits = body
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size as usize <= config.max_body_bytes);
+    if !fits {
+        // Streaming or oversized responses cannot be replayed
+        warn!("Response for idempotency key {} is too large to store", key);
+        if let Err(e) = repository.release(&scope, &key, locked_until).await {
+            error!("Failed to release idempotency key {}: {}", key, e);
+        }
+        return Response::from_parts(parts, body);
+    }
+
+    let body = match to_bytes(body, config.max_body_bytes).await {
+        Ok(body) => body,
+        Err(e) => {
+            error!("Failed to buffer response for idempotency key {}: {}", key, e);
+            if let Err(e) = repository.release(&scope, &key, locked_until).await {
+                error!("Failed to release idempotency key {}: {}", key, e);
+            }
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        headers: stored_headers(&parts.headers),
+        body: body.to_vec(),
+    };
+    // If this fails the key stays locked until the lock lapses, after which a retry runs again
+    match repository.complete(&scope, &key, locked_until, &stored).await {
+        Ok(true) => info!("Stored response {} for idempotency key {}", stored.status, key),
+        Ok(false) => warn!("Lock on idempotency key {} lapsed and it was taken over; not storing the response", key),
+        Err(e) => error!("Failed to store response for idempotency key {}: {}", key, e),
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Check that the key is a non-empty string of visible ASCII characters
+fn parse_key(value: &HeaderValue) -> Result<String, AppError> {
+    let key = value.to_str().unwrap_or_default();
+
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH || !key.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(AppError::Validation(format!(
+            "Idempotency-Key must be 1 to {} visible ASCII characters",
+            MAX_KEY_LENGTH
+        )));
+    }
+
+    Ok(key.to_string())
+}
+
+/// Scope keys to the authenticated user, or to the credential presented when
+/// it does not identify one, so callers cannot collide on a key
+async fn request_scope(state: &AppState, headers: &HeaderMap) -> String {
+    if let Some(token) = extract_bearer_token(headers) {
+        if let Ok(user) = state.auth_service().validate_token(token).await {
+            return format!("user:{}", user.id);
+        }
+    }
+
+    match headers.get(header::AUTHORIZATION) {
+        Some(credential) => format!("credential:{:x}", Sha256::digest(credential.as_bytes())),
+        None => ANONYMOUS_SCOPE.to_string(),
+    }
+}
+
+fn is_multipart(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim_start().to_ascii_lowercase().starts_with("multipart/"))
+}
+
+/// Responses worth replaying; failures the client may retry are not kept
+fn is_storable(status: StatusCode) -> bool {
+    !(status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT || status == StatusCode::TOO_MANY_REQUESTS)
+}
+
+fn stored_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| !TRANSFER_HEADERS.contains(name))
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let mut response = Response::new(Body::from(stored.body));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+
+    for (name, value) in &stored.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key(&HeaderValue::from_static("abc-123_XYZ")).unwrap(), "abc-123_XYZ");
+        assert!(parse_key(&HeaderValue::from_static("")).is_err());
+        assert!(parse_key(&HeaderValue::from_static("has space")).is_err());
+        assert!(parse_key(&HeaderValue::from_str(&"k".repeat(MAX_KEY_LENGTH + 1)).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_multipart_requests_are_detected() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_multipart(&headers));
+
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        assert!(!is_multipart(&headers));
+
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("Multipart/Form-Data; boundary=x"));
+        assert!(is_multipart(&headers));
+    }
+
+    #[test]
+    fn test_server_errors_are_not_stored() {
+        assert!(is_storable(StatusCode::CREATED));
+        assert!(is_storable(StatusCode::CONFLICT));
+        assert!(!is_storable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_storable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_storable(StatusCode::REQUEST_TIMEOUT));
+        assert!(!is_storable(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[tokio::test]
+    async fn test_replay_restores_stored_response() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("11"));
+        headers.insert(header::ETAG, HeaderValue::from_static("\"1\""));
+
+        let stored = StoredResponse {
+            status: 201,
+            headers: stored_headers(&headers),
+            body: br#"{"id":"a"}"#.to_vec(),
+        };
+        assert_eq!(stored.headers.len(), 2);
+
+        let response = replay(stored);
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::ETAG], "\"1\"");
+        assert_eq!(response.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert!(!response.headers().contains_key(header::CONTENT_LENGTH));
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], br#"{"id":"a"}"#);
+    }
+}
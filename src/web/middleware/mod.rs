@@ -2,8 +2,12 @@ pub mod request_id;
 pub mod auth;
 pub mod logging;
 pub mod metrics;
+pub mod caching;
+pub mod idempotency;
 
 pub use request_id::*;
 pub use auth::*;
 pub use logging::*;
 pub use metrics::*;
+pub use caching::*;
+pub use idempotency::*;
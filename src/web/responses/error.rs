@@ -72,6 +72,25 @@ pub enum AppError {
     #[error("Rate limit exceeded: {0}")]
     RateLimit(String),
 
+    // Unsupported request body media type
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+
+    // Request body over the allowed size
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    // Well-formed request that cannot be processed
+    #[error("Unprocessable entity: {0}")]
+    UnprocessableEntity(String),
+
+    // Conditional request errors
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
+    #[error("Precondition required: {0}")]
+    PreconditionRequired(String),
+
     // Internal server errors
     #[error("Internal server error")]
     Internal,
@@ -105,6 +124,9 @@ impl IntoResponse for AppError {
             AppError::Repository(RepositoryError::Validation(ref msg)) => {
                 (StatusCode::BAD_REQUEST, "Validation error".to_string(), Some(msg.clone()), false)
             }
+            AppError::Repository(RepositoryError::VersionMismatch) => {
+                (StatusCode::PRECONDITION_FAILED, "Precondition failed".to_string(), None, false)
+            }
             AppError::Repository(ref e) => {
                 tracing::error!("Repository error: {:?}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string(), None, true)
@@ -120,6 +142,12 @@ impl IntoResponse for AppError {
             AppError::Service(ServiceError::Validation(ref msg)) => {
                 (StatusCode::BAD_REQUEST, "Validation failed".to_string(), Some(msg.clone()), false)
             }
+            AppError::Service(ServiceError::Conflict(ref msg)) => {
+                (StatusCode::CONFLICT, "Conflict".to_string(), Some(msg.clone()), false)
+            }
+            AppError::Service(ServiceError::PreconditionFailed(ref msg)) => {
+                (StatusCode::PRECONDITION_FAILED, "Precondition failed".to_string(), Some(msg.clone()), false)
+            }
             AppError::Service(ServiceError::Repository(ref e)) => {
                 tracing::error!("Service repository error: {:?}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string(), None, true)
@@ -128,6 +156,10 @@ impl IntoResponse for AppError {
                 tracing::warn!("External service error: {}", msg);
                 (StatusCode::BAD_GATEWAY, "External service unavailable".to_string(), Some(msg.clone()), false)
             }
+            AppError::Service(ServiceError::Storage(ref msg)) => {
+                tracing::error!("Storage error: {}", msg);
+                (StatusCode::SERVICE_UNAVAILABLE, "Storage unavailable".to_string(), None, true)
+            }
 
             // Validation errors - client errors
             AppError::Validation(ref msg) => {
@@ -189,6 +221,25 @@ impl IntoResponse for AppError {
                 (StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded".to_string(), Some(msg.clone()), false)
             }
 
+            // Unsupported media type errors - client errors
+            AppError::UnsupportedMediaType(ref msg) => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, "Unsupported media type".to_string(), Some(msg.clone()), false)
+            }
+            AppError::PayloadTooLarge(ref msg) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "Payload too large".to_string(), Some(msg.clone()), false)
+            }
+            AppError::UnprocessableEntity(ref msg) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "Unprocessable entity".to_string(), Some(msg.clone()), false)
+            }
+
+            // Conditional request errors - client errors
+            AppError::PreconditionFailed(ref msg) => {
+                (StatusCode::PRECONDITION_FAILED, "Precondition failed".to_string(), Some(msg.clone()), false)
+            }
+            AppError::PreconditionRequired(ref msg) => {
+                (StatusCode::PRECONDITION_REQUIRED, "Precondition required".to_string(), Some(msg.clone()), false)
+            }
+
             // Internal server errors - log and capture
             AppError::Internal => {
                 tracing::error!("Internal server error: {:?}", self);
@@ -290,6 +341,31 @@ impl AppError {
         AppError::RateLimit(message.into())
     }
 
+    /// Create an unsupported media type error with a custom message
+    pub fn unsupported_media_type<S: Into<String>>(message: S) -> Self {
+        AppError::UnsupportedMediaType(message.into())
+    }
+
+    /// Create a payload too large error with a custom message
+    pub fn payload_too_large<S: Into<String>>(message: S) -> Self {
+        AppError::PayloadTooLarge(message.into())
+    }
+
+    /// Create an unprocessable entity error with a custom message
+    pub fn unprocessable_entity<S: Into<String>>(message: S) -> Self {
+        AppError::UnprocessableEntity(message.into())
+    }
+
+    /// Create a precondition failed error with a custom message
+    pub fn precondition_failed<S: Into<String>>(message: S) -> Self {
+        AppError::PreconditionFailed(message.into())
+    }
+
+    /// Create a precondition required error with a custom message
+    pub fn precondition_required<S: Into<String>>(message: S) -> Self {
+        AppError::PreconditionRequired(message.into())
+    }
+
     /// Create a generic error with a custom message
     pub fn generic<S: Into<String>>(message: S) -> Self {
         AppError::Generic {
@@ -307,10 +383,18 @@ impl AppError {
                 | AppError::NotFound(_)
                 | AppError::Conflict(_)
                 | AppError::RateLimit(_)
+                | AppError::UnsupportedMediaType(_)
+                | AppError::PayloadTooLarge(_)
+                | AppError::UnprocessableEntity(_)
+                | AppError::PreconditionFailed(_)
+                | AppError::PreconditionRequired(_)
                 | AppError::Service(ServiceError::NotFound)
                 | AppError::Service(ServiceError::AlreadyExists)
                 | AppError::Service(ServiceError::Validation(_))
+                | AppError::Service(ServiceError::Conflict(_))
+                | AppError::Service(ServiceError::PreconditionFailed(_))
                 | AppError::Repository(RepositoryError::NotFound)
+                | AppError::Repository(RepositoryError::VersionMismatch)
                 | AppError::Repository(RepositoryError::DuplicateEmail(_))
                 | AppError::Repository(RepositoryError::Validation(_))
         )
@@ -339,6 +423,11 @@ impl AppError {
             AppError::Io(_) => "io",
             AppError::Timeout(_) => "timeout",
             AppError::RateLimit(_) => "rate_limit",
+            AppError::UnsupportedMediaType(_) => "unsupported_media_type",
+            AppError::PayloadTooLarge(_) => "payload_too_large",
+            AppError::UnprocessableEntity(_) => "unprocessable_entity",
+            AppError::PreconditionFailed(_) => "precondition_failed",
+            AppError::PreconditionRequired(_) => "precondition_required",
             AppError::Internal => "internal",
             AppError::Generic { .. } => "generic",
         }
@@ -367,6 +456,9 @@ impl AppError {
             AppError::Repository(RepositoryError::Validation(ref msg)) => {
                 (StatusCode::BAD_REQUEST, "Validation error".to_string(), Some(msg.clone()))
             }
+            AppError::Repository(RepositoryError::VersionMismatch) => {
+                (StatusCode::PRECONDITION_FAILED, "Precondition failed".to_string(), None)
+            }
             AppError::Repository(_) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string(), None)
             }
@@ -381,12 +473,21 @@ impl AppError {
             AppError::Service(ServiceError::Validation(ref msg)) => {
                 (StatusCode::BAD_REQUEST, "Validation failed".to_string(), Some(msg.clone()))
             }
+            AppError::Service(ServiceError::Conflict(ref msg)) => {
+                (StatusCode::CONFLICT, "Conflict".to_string(), Some(msg.clone()))
+            }
+            AppError::Service(ServiceError::PreconditionFailed(ref msg)) => {
+                (StatusCode::PRECONDITION_FAILED, "Precondition failed".to_string(), Some(msg.clone()))
+            }
             AppError::Service(ServiceError::Repository(_)) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string(), None)
             }
             AppError::Service(ServiceError::ExternalService(ref msg)) => {
                 (StatusCode::BAD_GATEWAY, "External service unavailable".to_string(), Some(msg.clone()))
             }
+            AppError::Service(ServiceError::Storage(_)) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "Storage unavailable".to_string(), None)
+            }
 
             // Validation errors - client errors
             AppError::Validation(ref msg) => {
@@ -443,6 +544,25 @@ impl AppError {
                 (StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded".to_string(), Some(msg.clone()))
             }
 
+            // Unsupported media type errors - client errors
+            AppError::UnsupportedMediaType(ref msg) => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, "Unsupported media type".to_string(), Some(msg.clone()))
+            }
+            AppError::PayloadTooLarge(ref msg) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "Payload too large".to_string(), Some(msg.clone()))
+            }
+            AppError::UnprocessableEntity(ref msg) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "Unprocessable entity".to_string(), Some(msg.clone()))
+            }
+
+            // Conditional request errors - client errors
+            AppError::PreconditionFailed(ref msg) => {
+                (StatusCode::PRECONDITION_FAILED, "Precondition failed".to_string(), Some(msg.clone()))
+            }
+            AppError::PreconditionRequired(ref msg) => {
+                (StatusCode::PRECONDITION_REQUIRED, "Precondition required".to_string(), Some(msg.clone()))
+            }
+
             // Internal server errors - log and capture
             AppError::Internal => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string(), None)
@@ -1,8 +1,9 @@
 use axum::{
+    extract::DefaultBodyLimit,
     http::StatusCode,
     middleware,
     response::{IntoResponse, Json},
-    routing::{get, post, put, delete},
+    routing::{get, post, put, patch, delete},
     Router,
 };
 use serde_json::json;
@@ -13,17 +14,28 @@ use tower_http::{
     trace::TraceLayer,
     timeout::TimeoutLayer,
     compression::CompressionLayer,
+    services::ServeDir,
     request_id::{MakeRequestId, PropagateRequestIdLayer, RequestId, SetRequestIdLayer},
 };
 use uuid::Uuid;
 
 use crate::{
-    config::AppConfig,
+    config::{AppConfig, BlobStoreConfig},
     metrics::AppMetrics,
-    services::{container::ServiceContainer, AuthService, UserService},
+    services::{
+        container::ServiceContainer, AuditService, AuthService, EmailChangeService, GroupService, InvitationService, PrivacyService,
+        UserImportService, UserService, WebhookSubscriptionService, DeadLetterService, EventStream,
+        WebSocketSessions,
+    },
     web::{
-        handlers::{health_handlers, metrics_handlers, user_handlers},
-        middleware::{metrics_middleware, request_id_middleware},
+        handlers::{
+            audit_handlers, dead_letter_handlers, event_handlers, group_handlers, health_handlers, import_handlers, invitation_handlers, metrics_handlers, privacy_handlers,
+            user_handlers, webhook_handlers, websocket_handlers,
+        },
+        middleware::{
+            auth_middleware, caching_middleware, idempotency_middleware, metrics_middleware, optional_auth_middleware,
+            request_id_middleware,
+        },
     },
 };
 
@@ -57,6 +69,56 @@ impl AppState {
         self.services.user_service()
     }
 
+    /// Get user import service
+    pub fn user_import_service(&self) -> Arc<dyn UserImportService> {
+        self.services.user_import_service()
+    }
+
+    /// Get email change service
+    pub fn email_change_service(&self) -> Arc<dyn EmailChangeService> {
+        self.services.email_change_service()
+    }
+
+    /// Get group service
+    pub fn group_service(&self) -> Arc<dyn GroupService> {
+        self.services.group_service()
+    }
+
+    /// Get invitation service
+    pub fn invitation_service(&self) -> Arc<dyn InvitationService> {
+        self.services.invitation_service()
+    }
+
+    /// Get audit log search service
+    pub fn audit_service(&self) -> Arc<dyn AuditService> {
+        self.services.audit_service()
+    }
+
+    /// Get webhook subscription service
+    pub fn webhook_subscription_service(&self) -> Arc<dyn WebhookSubscriptionService> {
+        self.services.webhook_subscription_service()
+    }
+
+    /// Get dead letter service
+    pub fn dead_letter_service(&self) -> Arc<dyn DeadLetterService> {
+        self.services.dead_letter_service()
+    }
+
+    /// Get the domain event stream
+    pub fn event_stream(&self) -> Arc<EventStream> {
+        self.services.event_stream()
+    }
+
+    /// Get the registry of open WebSocket sessions
+    pub fn websocket_sessions(&self) -> Arc<WebSocketSessions> {
+        self.services.websocket_sessions()
+    }
+
+    /// Get privacy service
+    pub fn privacy_service(&self) -> Arc<dyn PrivacyService> {
+        self.services.privacy_service()
+    }
+
     /// Get auth service
     pub fn auth_service(&self) -> Arc<dyn AuthService> {
         self.services.auth_service()
@@ -77,7 +139,7 @@ impl MakeRequestId for UuidMakeRequestId {
 /// Create the main application router with middleware stack
 pub fn create_router(state: AppState) -> Router {
     // Create API routes
-    let api_routes = create_api_routes();
+    let api_routes = create_api_routes(&state);
 
     // Create health check routes
     let health_routes = create_health_routes();
@@ -85,11 +147,20 @@ pub fn create_router(state: AppState) -> Router {
     // Create metrics routes
     let metrics_routes = create_metrics_routes();
 
-    // Build the main router with nested routes and middleware
-    Router::new()
+    let mut router = Router::new()
         .nest("/api/v1", api_routes)
         .nest("/health", health_routes)
-        .nest("/metrics", metrics_routes)
+        .nest("/metrics", metrics_routes);
+
+    // Serve locally stored avatars; other backends serve their own URLs
+    if let BlobStoreConfig::Local { root, public_base_url } = &state.config.avatar.storage {
+        if public_base_url.starts_with('/') {
+            router = router.nest_service(public_base_url, ServeDir::new(root));
+        }
+    }
+
+    // Apply the middleware stack
+    router
         .layer(
             ServiceBuilder::new()
                 // Request ID generation and propagation (outermost)
@@ -108,6 +179,12 @@ pub fn create_router(state: AppState) -> Router {
                 // Response compression
                 .layer(CompressionLayer::new())
 
+                // Conditional GET handling and per-route Cache-Control policies
+                .layer(middleware::from_fn_with_state(state.clone(), caching_middleware))
+
+                // Replay of POST responses sent with an Idempotency-Key
+                .layer(middleware::from_fn_with_state(state.clone(), idempotency_middleware))
+
                 // Request timeout (30 seconds)
                 .layer(TimeoutLayer::new(std::time::Duration::from_secs(30)))
 
@@ -126,22 +203,139 @@ pub fn create_router(state: AppState) -> Router {
 }
 
 /// Create API v1 routes
-fn create_api_routes() -> Router<AppState> {
+///
+/// Routes that act for a user require a Bearer token; the others identify
+/// the caller when one is sent. The WebSocket checks its token itself.
+fn create_api_routes(state: &AppState) -> Router<AppState> {
+    let authenticated = || middleware::from_fn_with_state(state.clone(), auth_middleware);
+    let optionally_authenticated = || middleware::from_fn_with_state(state.clone(), optional_auth_middleware);
+
     Router::new()
-        .nest("/users", create_user_routes())
+        .nest(
+            "/users",
+            create_public_user_routes()
+                .route_layer(optionally_authenticated())
+                .merge(create_protected_user_routes(state).route_layer(authenticated())),
+        )
+        .nest("/groups", create_group_routes().route_layer(authenticated()))
+        .nest(
+            "/invitations",
+            create_public_invitation_routes()
+                .route_layer(optionally_authenticated())
+                .merge(create_protected_invitation_routes().route_layer(authenticated())),
+        )
+        .nest("/audit-logs", create_audit_routes().route_layer(authenticated()))
+        .nest("/webhooks", create_webhook_routes().route_layer(authenticated()))
+        .nest("/admin", create_admin_routes().route_layer(authenticated()))
+        .nest("/events", create_event_routes().route_layer(authenticated()))
+        .route("/ws", get(websocket_handlers::websocket))
         // Add more API route groups here as needed
 }
 
-/// Create user management routes
-fn create_user_routes() -> Router<AppState> {
+/// Room for the multipart boundaries and part headers around an avatar image
+const AVATAR_MULTIPART_OVERHEAD_BYTES: usize = 16 * 1024;
+
+/// Create user management routes open to anonymous callers
+fn create_public_user_routes() -> Router<AppState> {
     Router::new()
         .route("/", post(user_handlers::create_user))
         .route("/", get(user_handlers::list_users))
+        .route("/search", get(user_handlers::search_users))
+        .route("/email-change/confirm", post(user_handlers::confirm_email_change))
+        .route("/email-change/revert", post(user_handlers::revert_email_change))
         .route("/:id", get(user_handlers::get_user))
         .route("/:id", put(user_handlers::update_user))
+        .route("/:id", patch(user_handlers::patch_user))
         .route("/:id", delete(user_handlers::delete_user))
-        // Note: Authentication middleware will be applied at the router level
-        // Individual routes can use the CurrentUser extractor to require authentication
+        .route("/:id/status", put(user_handlers::set_user_status))
+}
+
+/// Create user management routes that require authentication
+fn create_protected_user_routes(state: &AppState) -> Router<AppState> {
+    let avatar_body_limit = state.config.avatar.max_bytes + AVATAR_MULTIPART_OVERHEAD_BYTES;
+
+    Router::new()
+        .route("/export", get(user_handlers::export_users))
+        .route("/import", post(import_handlers::import_users))
+        .route("/imports/:job_id", get(import_handlers::get_import_job))
+        .route("/imports/:job_id/report", get(import_handlers::get_import_report))
+        .route("/:id/email-change", get(user_handlers::get_email_change))
+        .route("/:id/email-change", delete(user_handlers::cancel_email_change))
+        .route("/:id/history", get(audit_handlers::get_user_history))
+        .route("/:id/groups", get(group_handlers::list_user_groups))
+        .route(
+            "/:id/avatar",
+            // The handler checks the image itself against the configured size
+            put(user_handlers::upload_avatar).layer(DefaultBodyLimit::max(avatar_body_limit)),
+        )
+}
+
+/// Create group management routes
+fn create_group_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(group_handlers::create_group))
+        .route("/", get(group_handlers::list_groups))
+        .route("/:id", get(group_handlers::get_group))
+        .route("/:id", put(group_handlers::update_group))
+        .route("/:id", delete(group_handlers::delete_group))
+        .route("/:id/members", get(group_handlers::list_group_members))
+        .route("/:id/members/:user_id", put(group_handlers::set_group_member))
+        .route("/:id/members/:user_id", delete(group_handlers::remove_group_member))
+}
+
+/// Create invitation routes open to invitees, who may not have an account yet
+fn create_public_invitation_routes() -> Router<AppState> {
+    Router::new()
+        .route("/accept", post(invitation_handlers::accept_invitation))
+}
+
+/// Create invitation management routes
+fn create_protected_invitation_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(invitation_handlers::create_invitation))
+        .route("/", get(invitation_handlers::list_invitations))
+        .route("/:id", get(invitation_handlers::get_invitation))
+        .route("/:id", delete(invitation_handlers::revoke_invitation))
+}
+
+/// Create audit log search routes
+fn create_audit_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(audit_handlers::list_audit_logs))
+        .route("/export", get(audit_handlers::export_audit_logs))
+        .route("/verify", get(audit_handlers::verify_audit_chain))
+        .route("/checkpoints", get(audit_handlers::export_audit_checkpoints))
+}
+
+/// Create webhook subscription routes
+fn create_webhook_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(webhook_handlers::create_webhook))
+        .route("/", get(webhook_handlers::list_webhooks))
+        .route("/:id", get(webhook_handlers::get_webhook))
+        .route("/:id", put(webhook_handlers::update_webhook))
+        .route("/:id", delete(webhook_handlers::delete_webhook))
+        .route("/:id/rotate-secret", post(webhook_handlers::rotate_webhook_secret))
+        .route("/:id/ping", post(webhook_handlers::ping_webhook))
+        .route("/:id/deliveries", get(webhook_handlers::list_webhook_deliveries))
+}
+
+/// Create domain event stream routes
+fn create_event_routes() -> Router<AppState> {
+    Router::new()
+        .route("/stream", get(event_handlers::stream_events))
+}
+
+/// Create administrative routes
+fn create_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/users/:id/data-export", get(privacy_handlers::export_user_data))
+        .route("/users/:id/erasure", post(privacy_handlers::erase_user))
+        .route("/dead-letters", get(dead_letter_handlers::list_dead_letters))
+        .route("/dead-letters/replay", post(dead_letter_handlers::replay_dead_letters))
+        .route("/dead-letters/:id", get(dead_letter_handlers::get_dead_letter))
+        .route("/dead-letters/:id", delete(dead_letter_handlers::discard_dead_letter))
+        .route("/dead-letters/:id/replay", post(dead_letter_handlers::replay_dead_letter))
 }
 
 /// Create health check routes
@@ -190,6 +384,10 @@ mod tests {
     use super::*;
     use axum_test::TestServer;
     use std::sync::Arc;
+    use tower::ServiceExt;
+
+    use crate::models::{AuthRequest, AuthResponse, CurrentUser, GroupMembership, GroupRole};
+    use crate::services::{AuthError, MockGroupService};
 
     // TODO: Implement mock services for testing
     // For now, we'll skip the tests that require services
@@ -200,4 +398,66 @@ mod tests {
         // This test doesn't require the full router setup
         // Just testing the handler function directly
     }
+    /// Accepts the token "valid" and nothing else
+    struct StaticAuthService;
+
+    #[async_trait::async_trait]
+    impl AuthService for StaticAuthService {
+        async fn authenticate(&self, _request: AuthRequest) -> Result<AuthResponse, AuthError> {
+            Err(AuthError::InvalidCredentials)
+        }
+
+        async fn validate_token(&self, token: &str) -> Result<CurrentUser, AuthError> {
+            match token {
+                "valid" => Ok(CurrentUser {
+                    id: Uuid::new_v4(),
+                    email: "ada@example.com".to_string(),
+                    name: "Ada".to_string(),
+                    groups: Vec::new(),
+                }),
+                _ => Err(AuthError::InvalidToken),
+            }
+        }
+
+        async fn refresh_token(&self, _token: &str) -> Result<AuthResponse, AuthError> {
+            Err(AuthError::InvalidToken)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_valid_token_reaches_protected_handler() {
+        let group_id = Uuid::new_v4();
+        let mut config = AppConfig::default();
+        config.event_stream.subscriber_group_id = Some(group_id);
+        // Every user is in the subscriber group
+        let mut groups = MockGroupService::new();
+        groups
+            .expect_memberships()
+            .returning(move |_| Ok(vec![GroupMembership { group_id, role: GroupRole::Member }]));
+        // Nothing on this path touches the database
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let services = ServiceContainer::new(pool, 5)
+            .with_auth_service(Arc::new(StaticAuthService))
+            .with_group_service(Arc::new(groups));
+        let app = create_router(AppState::new(config, services));
+
+        let request = |token: Option<&str>| {
+            let mut request = axum::http::Request::builder().uri("/api/v1/events/stream");
+            if let Some(token) = token {
+                request = request.header("authorization", format!("Bearer {}", token));
+            }
+            request.body(axum::body::Body::empty()).unwrap()
+        };
+
+        let response = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.clone().oneshot(request(Some("forged"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.oneshot(request(Some("valid"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+    }
 }